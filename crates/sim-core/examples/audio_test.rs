@@ -29,11 +29,11 @@ fn main() {
 
     // 2. Create and configure the audio pipeline.
     let mut pipeline = AudioPipeline::new();
-    pipeline.set_pump_params(params.rpm, params.num_valves, params.duty_cycle);
+    pipeline.configure_pump(&params);
     pipeline.set_volume(0.3);
 
     // 3. Hot-swap in the computed impulse response.
-    pipeline.swap_ir(result.impulse_response);
+    pipeline.swap_ir(result.impulse_response, result.sample_rate);
     println!("IR loaded into pipeline.");
 
     // 4. Start playback.
@@ -0,0 +1,169 @@
+use crate::constants::{GRAVITY, WATER_DENSITY};
+use crate::elements::StraightDuct;
+use crate::numerics::WallLossModel;
+
+/// Surface tension of water against air in N/m.
+const WATER_SURFACE_TENSION: f64 = 0.072;
+
+/// An air stone submerged in the aquarium, acting as the load at the end
+/// of the air line.
+///
+/// Its linearised resistance has two parts: the flow resistance of the
+/// porous stone itself, and a bubble-formation term. Each pore only
+/// releases a bubble once the line pressure exceeds the hydrostatic head
+/// plus the Laplace pressure 4σ/d of the pore, so the stone behaves like a
+/// pressure-dependent valve whose secant resistance is that opening
+/// pressure divided by the mean flow. Deeper stones therefore load the
+/// pump harder.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AirStone {
+    /// Flow resistance of the porous stone in Pa·s/m³.
+    pub pore_resistance: f64,
+    /// Mean pore diameter in metres.
+    pub pore_diameter: f64,
+    /// Depth below the water surface in metres.
+    pub depth: f64,
+    /// Mean air flow through the stone in m³/s.
+    pub flow_rate: f64,
+}
+
+impl Default for AirStone {
+    fn default() -> Self {
+        Self {
+            pore_resistance: 6e7,      // ~2 kPa drop at 2 L/min
+            pore_diameter: 100e-6,     // 100 µm
+            depth: 0.3,                // 30 cm
+            flow_rate: 2.0 / 60_000.0, // 2 L/min
+        }
+    }
+}
+
+impl AirStone {
+    /// Hydrostatic back-pressure of the water column in Pa.
+    pub fn back_pressure(&self) -> f64 {
+        WATER_DENSITY * GRAVITY * self.depth
+    }
+
+    /// Laplace pressure needed to grow a bubble at a pore, in Pa.
+    pub fn bubble_pressure(&self) -> f64 {
+        4.0 * WATER_SURFACE_TENSION / self.pore_diameter
+    }
+
+    /// Estimated resistance from bubble formation at depth, in Pa·s/m³.
+    pub fn bubble_resistance(&self) -> f64 {
+        (self.back_pressure() + self.bubble_pressure()) / self.flow_rate
+    }
+
+    /// Load impedance presented by the stone (acoustic ohms).
+    pub fn impedance(&self) -> f64 {
+        self.pore_resistance + self.bubble_resistance()
+    }
+}
+
+/// The installed system downstream of the muffler: a supply hose running to
+/// an air stone submerged in the aquarium.
+///
+/// The stone terminates the line, and the water column above it
+/// pressurises the whole line, raising the air density (and so every
+/// characteristic impedance) upstream of it.
+#[derive(Debug, Clone)]
+pub struct AirLine {
+    /// Supply hose length in metres.
+    pub hose_length: f64,
+    /// Supply hose inner diameter in metres.
+    pub hose_diameter: f64,
+    /// The submerged air stone at the end of the hose.
+    pub stone: AirStone,
+}
+
+impl Default for AirLine {
+    fn default() -> Self {
+        Self {
+            hose_length: 1.5,    // 1.5 m
+            hose_diameter: 4e-3, // standard 4/6 mm airline tubing
+            stone: AirStone::default(),
+        }
+    }
+}
+
+impl AirLine {
+    /// Ratio of line density to ambient density at static pressure
+    /// `ambient` Pa. The line sits at ambient plus the back-pressure; the
+    /// adiabatic sound speed is unchanged.
+    pub fn density_ratio(&self, ambient: f64) -> f64 {
+        (ambient + self.stone.back_pressure()) / ambient
+    }
+
+    /// The supply hose as a smooth-walled duct element with the given wall
+    /// loss model.
+    pub fn hose(&self, losses: WallLossModel) -> StraightDuct {
+        StraightDuct::new(self.hose_length, self.hose_diameter).with_losses(losses)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::STANDARD_PRESSURE;
+    use crate::{compute, SimParams};
+
+    #[test]
+    fn test_air_line_back_pressure_and_effect_on_tl() {
+        let line = AirLine {
+            stone: AirStone {
+                depth: 0.5,
+                ..AirStone::default()
+            },
+            ..AirLine::default()
+        };
+        let back_pressure = line.stone.back_pressure();
+        assert!((back_pressure - 4895.1).abs() < 1.0, "back pressure = {back_pressure}");
+        assert!((1.04..1.05).contains(&line.density_ratio(STANDARD_PRESSURE)));
+
+        let bare = compute(&SimParams::default()).expect("default params valid");
+        let installed = compute(&SimParams {
+            air_line: Some(line),
+            ..SimParams::default()
+        })
+        .expect("air line params valid");
+
+        assert!(installed.transmission_loss.iter().all(|tl| tl.is_finite()));
+        let max_diff = bare
+            .transmission_loss
+            .iter()
+            .zip(&installed.transmission_loss)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(max_diff > 1.0, "air line should change the response, max diff = {max_diff} dB");
+    }
+
+    #[test]
+    fn test_deeper_stone_loads_harder() {
+        let at_depth = |depth: f64| AirLine {
+            stone: AirStone {
+                depth,
+                ..AirStone::default()
+            },
+            ..AirLine::default()
+        };
+        let shallow = at_depth(0.2);
+        let deep = at_depth(0.6);
+        assert!(deep.stone.impedance() > shallow.stone.impedance());
+
+        let tl = |line: AirLine| {
+            compute(&SimParams {
+                air_line: Some(line),
+                ..SimParams::default()
+            })
+            .expect("air line params valid")
+            .transmission_loss
+        };
+        let (tl_shallow, tl_deep) = (tl(shallow), tl(deep));
+        let max_diff = tl_shallow
+            .iter()
+            .zip(&tl_deep)
+            .map(|(a, b)| (a - b).abs())
+            .fold(0.0, f64::max);
+        assert!(max_diff > 0.1, "20 cm vs 60 cm should differ, max diff = {max_diff} dB");
+    }
+}
@@ -2,107 +2,1216 @@ use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
+use std::time::{Duration, Instant};
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
 use cpal::{SampleFormat, Stream};
 
-use crate::pump::PumpSource;
+use realfft::num_complex::Complex;
+use realfft::{ComplexToReal, RealFftPlanner, RealToComplex};
+
+use crate::impulse_response;
+use crate::pump::{
+    HarmonicSeries, MotorNoise, PistonCrank, PumpSource, ReedValve, SampleSource, SecondPump, SpeedWander,
+    StrokeTiming, ValveLift, ValveVariation, SECOND_PUMP_SEED,
+};
+use crate::recording::{Recording, RecordingPlayer};
+use crate::rpm_profile::RpmProfile;
+use crate::test_signal::{SignalGenerator, TestSignal};
+use crate::SimParams;
 
 // ---------------------------------------------------------------------------
 // ConvolutionEngine
 // ---------------------------------------------------------------------------
 
-/// Overlap-add convolution engine.
+/// Blocks over which a swapped-in impulse response is crossfaded in.
+const CROSSFADE_BLOCKS: usize = 4;
+
+/// Input kept to give a swapped-in impulse response its tail, in samples;
+/// a longer response loses the oldest part of its tail at the swap.
+const HISTORY_LENGTH: usize = 8192;
+
+/// Block convolution engine.
 ///
 /// Processes audio in fixed-size blocks, convolving with a hot-swappable
-/// impulse response. The engine maintains an overlap buffer so that block
-/// boundaries are seamless (no clicks).
+/// impulse response through a [`PartitionedConvolver`], so that block
+/// boundaries are seamless (no clicks) and a response tens of thousands
+/// of taps long costs little more per block than a short one. A
+/// swapped-in impulse response is crossfaded in over a few blocks so
+/// that dragging a slider during playback does not click or zipper.
 pub struct ConvolutionEngine {
-    /// Current impulse response (time domain), shared for hot-swap.
-    impulse_response: Arc<Mutex<Vec<f64>>>,
-    /// Block size for overlap-add processing (informational; `process()` works
-    /// with any input length).
-    #[allow(dead_code)]
+    /// Current impulse response, shared for hot-swap.
+    impulse_response: IrHandle,
+    /// Block size; `process()` takes input in blocks of this many samples.
     block_size: usize,
-    /// Overlap buffer (tail from previous convolution that must be added to
-    /// the beginning of the next block's output).
-    overlap: Vec<f64>,
+    /// Generation of the shared impulse response in use.
+    generation: u64,
+    /// Convolver holding the input history and the response in use.
+    convolver: PartitionedConvolver,
+    /// Whether any input has been processed yet.
+    started: bool,
+    /// Crossfade away from the previous impulse response, while one runs.
+    fade: Option<Fade>,
+}
+
+/// The impulse response being faded out and how far the fade has got.
+struct Fade {
+    /// Partition spectra of the previous response.
+    partitions: Arc<Vec<Spectrum>>,
+    /// Samples since the fade started.
+    position: usize,
 }
 
 impl ConvolutionEngine {
     pub fn new(block_size: usize) -> Self {
         // Start with a unit impulse (delta) so pass-through works immediately.
+        let mut convolver = PartitionedConvolver::new(&[1.0], block_size);
+        convolver.keep_history(HISTORY_LENGTH.div_ceil(block_size.max(1)));
         Self {
-            impulse_response: Arc::new(Mutex::new(vec![1.0])),
+            impulse_response: IrHandle::new(block_size),
             block_size,
-            overlap: Vec::new(),
+            generation: 0,
+            convolver,
+            started: false,
+            fade: None,
         }
     }
 
     /// Get a handle to the impulse response for hot-swapping from another
     /// thread (e.g. the simulation thread calls `swap_ir` via this handle).
-    pub fn ir_handle(&self) -> Arc<Mutex<Vec<f64>>> {
-        Arc::clone(&self.impulse_response)
+    pub fn ir_handle(&self) -> IrHandle {
+        self.impulse_response.clone()
     }
 
-    /// Process a block of input samples through overlap-add convolution.
+    /// Convolve a block of `block_size` input samples.
     ///
-    /// The returned vector always has exactly `input.len()` samples; any
-    /// excess (the "tail") is stored internally and added to the next block.
+    /// The returned vector always has exactly `input.len()` samples; the
+    /// input is kept to carry the tail into the following blocks.
     pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
-        let ir = self.impulse_response.lock().unwrap_or_else(|e| e.into_inner()).clone();
+        if input.is_empty() {
+            return Vec::new();
+        }
+        if let Some((generation, next)) = self.impulse_response.newer_than(self.generation) {
+            self.generation = generation;
+            self.switch_to(next);
+        }
+
+        self.convolver.push(input);
+        self.started = true;
+        let mut output = self.convolver.convolve(&self.convolver.partitions);
+        let length = self.fade_length();
+        if let Some(fade) = &mut self.fade {
+            let previous = self.convolver.convolve(&fade.partitions);
+            for (i, (out, old)) in output.iter_mut().zip(previous).enumerate() {
+                let t = ((fade.position + i) as f64 / length).min(1.0);
+                *out = old + (*out - old) * t;
+            }
+            fade.position += input.len();
+            if fade.position as f64 >= length {
+                self.fade = None;
+            }
+        }
+        output
+    }
+
+    /// Length of a crossfade in samples.
+    fn fade_length(&self) -> f64 {
+        (CROSSFADE_BLOCKS * self.block_size).max(1) as f64
+    }
+
+    /// Start crossfading from the impulse response in use to the one
+    /// partitioned into `partitions`.
+    fn switch_to(&mut self, partitions: Arc<Vec<Spectrum>>) {
+        if !self.started || partitions.is_empty() {
+            // Nothing has played yet to click, or the output is muted
+            self.convolver.set_partitions(partitions);
+            self.fade = None;
+            return;
+        }
+        // Both responses convolve the same input spectra, so the new one
+        // starts with its full tail. Convolution is linear in the impulse
+        // response, so a fade in progress continues from the blend of its
+        // two responses.
+        let t = self.fade.as_ref().map(|fade| (fade.position as f64 / self.fade_length()).min(1.0));
+        let current = self.convolver.set_partitions(partitions);
+        let previous = match (self.fade.take(), t) {
+            (Some(fade), Some(t)) => Arc::new(blend(&fade.partitions, &current, t)),
+            _ => current,
+        };
+        self.fade = Some(Fade {
+            partitions: previous,
+            position: 0,
+        });
+    }
+}
+
+/// `(1 - t)·a + t·b` partition by partition, the shorter response padded
+/// with zeros.
+fn blend(a: &[Spectrum], b: &[Spectrum], t: f64) -> Vec<Spectrum> {
+    (0..a.len().max(b.len()))
+        .map(|i| match (a.get(i), b.get(i)) {
+            (Some(a), Some(b)) => a.iter().zip(b).map(|(x, y)| x * (1.0 - t) + y * t).collect(),
+            (Some(a), None) => a.iter().map(|x| x * (1.0 - t)).collect(),
+            (None, Some(b)) => b.iter().map(|y| y * t).collect(),
+            (None, None) => unreachable!(),
+        })
+        .collect()
+}
+
+/// Handle to a [`ConvolutionEngine`]'s impulse response, for hot-swapping
+/// it from another thread.
+///
+/// Setting a response partitions it on the setting thread and counts up
+/// a generation, so the engine notices the swap without comparing the
+/// responses or transforming them in the audio path.
+#[derive(Clone)]
+pub struct IrHandle {
+    block_size: usize,
+    shared: Arc<Mutex<SharedIr>>,
+}
+
+/// An impulse response shared through an [`IrHandle`].
+struct SharedIr {
+    /// Behind its own `Arc`, so it can be compared without holding the
+    /// lock the feeder polls.
+    impulse_response: Arc<Vec<f64>>,
+    /// Spectra of its partitions, ready for the convolver.
+    partitions: Arc<Vec<Spectrum>>,
+    /// Counts the swaps, so the engine can tell a new response by it.
+    generation: u64,
+}
+
+impl IrHandle {
+    /// A handle holding a unit impulse, for an engine with `block_size`.
+    fn new(block_size: usize) -> Self {
+        let shared = SharedIr {
+            impulse_response: Arc::new(vec![1.0]),
+            partitions: Arc::new(partition(&[1.0], block_size)),
+            generation: 0,
+        };
+        Self {
+            block_size,
+            shared: Arc::new(Mutex::new(shared)),
+        }
+    }
+
+    /// Swap in `impulse_response`; setting the one already in use does
+    /// nothing.
+    pub fn set(&self, impulse_response: Vec<f64>) {
+        if *self.current() == impulse_response {
+            return;
+        }
+        let partitions = Arc::new(partition(&impulse_response, self.block_size));
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        shared.impulse_response = Arc::new(impulse_response);
+        shared.partitions = partitions;
+        shared.generation += 1;
+    }
+
+    /// The impulse response last set.
+    pub fn get(&self) -> Vec<f64> {
+        self.current().to_vec()
+    }
+
+    /// The impulse response last set, without copying it.
+    fn current(&self) -> Arc<Vec<f64>> {
+        Arc::clone(&self.shared.lock().unwrap_or_else(|e| e.into_inner()).impulse_response)
+    }
+
+    /// The generation and partitions of the response, if it has been
+    /// swapped since `generation`.
+    fn newer_than(&self, generation: u64) -> Option<(u64, Arc<Vec<Spectrum>>)> {
+        let shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+        (shared.generation != generation).then(|| (shared.generation, Arc::clone(&shared.partitions)))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// PartitionedConvolver
+// ---------------------------------------------------------------------------
+
+/// Half-spectrum of a real signal two blocks long.
+type Spectrum = Vec<Complex<f64>>;
+
+/// FFT convolution for long impulse responses, such as a muffler response
+/// tens of thousands of taps long, a room response or a headphone
+/// correction.
+///
+/// Uniformly partitioned overlap-save: the response is cut into
+/// block-sized partitions, and each block of output sums the spectrum of
+/// every recent input block times the partition as old as it. The
+/// output has no latency beyond the block, and the cost per block grows
+/// with the number of partitions, not with their length.
+pub struct PartitionedConvolver {
+    block_size: usize,
+    forward: Arc<dyn RealToComplex<f64>>,
+    inverse: Arc<dyn ComplexToReal<f64>>,
+    /// Spectrum of each partition of the impulse response, zero-padded to
+    /// two blocks, earliest first.
+    partitions: Arc<Vec<Spectrum>>,
+    /// Spectra of the most recent inputs, newest first, at least one per
+    /// partition.
+    spectra: VecDeque<Spectrum>,
+    /// The previous input block followed by the current one.
+    window: Vec<f64>,
+}
+
+impl PartitionedConvolver {
+    /// A convolver with `impulse_response` for input in blocks of
+    /// `block_size` samples.
+    pub fn new(impulse_response: &[f64], block_size: usize) -> Self {
+        let size = 2 * block_size;
+        let mut planner = RealFftPlanner::<f64>::new();
+        let mut convolver = Self {
+            block_size,
+            forward: planner.plan_fft_forward(size),
+            inverse: planner.plan_fft_inverse(size),
+            partitions: Arc::new(Vec::new()),
+            spectra: VecDeque::new(),
+            window: vec![0.0; size],
+        };
+        convolver.set_partitions(Arc::new(partition(impulse_response, block_size)));
+        convolver
+    }
+
+    /// Convolve one block of `block_size` input samples, carrying the
+    /// history into the next block.
+    pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        self.push(input);
+        self.convolve(&self.partitions)
+    }
+
+    /// Convolve with `partitions` from the next block on, returning the
+    /// ones replaced. The input history is kept, so the new response
+    /// starts with its full tail.
+    fn set_partitions(&mut self, partitions: Arc<Vec<Spectrum>>) -> Arc<Vec<Spectrum>> {
+        self.keep_history(partitions.len());
+        std::mem::replace(&mut self.partitions, partitions)
+    }
+
+    /// Keep the spectra of at least `blocks` past input blocks.
+    fn keep_history(&mut self, blocks: usize) {
+        while self.spectra.len() < blocks {
+            self.spectra.push_back(self.forward.make_output_vec());
+        }
+    }
+
+    /// Take in the next block of input.
+    fn push(&mut self, input: &[f64]) {
+        debug_assert_eq!(input.len(), self.block_size, "input must come in whole blocks");
+        let n = self.block_size;
+        self.window.copy_within(n.., 0);
+        let length = input.len().min(n);
+        self.window[n..n + length].copy_from_slice(&input[..length]);
+        self.window[n + length..].fill(0.0);
+        if self.spectra.is_empty() {
+            return;
+        }
+        let mut scratch = self.window.clone();
+        let mut spectrum = self.spectra.pop_back().unwrap_or_else(|| self.forward.make_output_vec());
+        self.forward.process(&mut scratch, &mut spectrum).expect("FFT failed");
+        self.spectra.push_front(spectrum);
+    }
+
+    /// The block of output for the input taken in last, convolved with
+    /// `partitions`.
+    fn convolve(&self, partitions: &[Spectrum]) -> Vec<f64> {
+        let n = self.block_size;
+        if partitions.is_empty() {
+            return vec![0.0; n];
+        }
+        let mut sum = self.inverse.make_input_vec();
+        for (input, partition) in self.spectra.iter().zip(partitions) {
+            for ((total, x), h) in sum.iter_mut().zip(input).zip(partition) {
+                *total += x * h;
+            }
+        }
+        // realfft requires DC and Nyquist bins to be purely real.
+        let last = sum.len() - 1;
+        sum[0].im = 0.0;
+        sum[last].im = 0.0;
+        let mut output = self.inverse.make_output_vec();
+        self.inverse.process(&mut sum, &mut output).expect("IFFT failed");
+        // The first half wraps around; the second is the linear convolution
+        let scale = 1.0 / (2 * n) as f64;
+        output[n..].iter().map(|y| y * scale).collect()
+    }
+}
+
+/// Spectra of the block-sized partitions of `impulse_response`, each
+/// zero-padded to two blocks.
+fn partition(impulse_response: &[f64], block_size: usize) -> Vec<Spectrum> {
+    let forward = RealFftPlanner::<f64>::new().plan_fft_forward(2 * block_size);
+    impulse_response
+        .chunks(block_size.max(1))
+        .map(|part| {
+            let mut padded = vec![0.0; 2 * block_size];
+            padded[..part.len()].copy_from_slice(part);
+            let mut spectrum = forward.make_output_vec();
+            forward.process(&mut padded, &mut spectrum).expect("FFT failed");
+            spectrum
+        })
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// NotchFilter
+// ---------------------------------------------------------------------------
+
+/// A band muted from playback, to judge by ear how much a tone contributes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Notch {
+    /// Centre frequency in Hz.
+    pub frequency: f64,
+    /// Width between the -3 dB points in Hz.
+    pub bandwidth: f64,
+}
+
+/// Second-order notch filter (RBJ cookbook biquad, direct form I).
+///
+/// Coefficients can change between blocks without resetting the filter
+/// history, so moving the notch while audio plays does not click.
+pub struct NotchFilter {
+    sample_rate: f64,
+    notch: Option<Notch>,
+    /// Normalised `[b0, b1, b2, a1, a2]`.
+    coefficients: [f64; 5],
+    /// Previous two inputs and outputs.
+    x: [f64; 2],
+    y: [f64; 2],
+}
+
+impl NotchFilter {
+    /// A filter that passes everything until [`set_notch`](Self::set_notch).
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            notch: None,
+            coefficients: [1.0, 0.0, 0.0, 0.0, 0.0],
+            x: [0.0; 2],
+            y: [0.0; 2],
+        }
+    }
+
+    /// Mute `notch`, or pass everything with `None`. A notch outside
+    /// (0, Nyquist) or without a positive bandwidth also passes everything.
+    pub fn set_notch(&mut self, notch: Option<Notch>) {
+        if notch == self.notch {
+            return;
+        }
+        self.notch = notch;
+        let nyquist = self.sample_rate / 2.0;
+        match notch {
+            Some(Notch { frequency, bandwidth })
+                if frequency > 0.0 && frequency < nyquist && bandwidth > 0.0 =>
+            {
+                let w0 = 2.0 * std::f64::consts::PI * frequency / self.sample_rate;
+                let q = frequency / bandwidth;
+                let alpha = w0.sin() / (2.0 * q);
+                let a0 = 1.0 + alpha;
+                let cos = -2.0 * w0.cos() / a0;
+                self.coefficients = [1.0 / a0, cos, 1.0 / a0, cos, (1.0 - alpha) / a0];
+            }
+            _ => {
+                self.coefficients = [1.0, 0.0, 0.0, 0.0, 0.0];
+                self.x = [0.0; 2];
+                self.y = [0.0; 2];
+            }
+        }
+    }
+
+    /// Filter a block of samples, carrying the history into the next block.
+    pub fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        let [b0, b1, b2, a1, a2] = self.coefficients;
+        input
+            .iter()
+            .map(|&x| {
+                let y = b0 * x + b1 * self.x[0] + b2 * self.x[1] - a1 * self.y[0] - a2 * self.y[1];
+                self.x = [x, self.x[0]];
+                self.y = [y, self.y[0]];
+                y
+            })
+            .collect()
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Spatializer
+// ---------------------------------------------------------------------------
+
+/// Radius in metres of the spherical head of the [`Spatializer`].
+const HEAD_RADIUS: f64 = 0.0875;
+
+/// Speed of sound in m/s around the listener's head.
+const HEAD_SPEED_OF_SOUND: f64 = 343.0;
+
+/// Angle in degrees between the source and an ear at which the head
+/// shadows that ear most.
+const DEEPEST_SHADOW: f64 = 150.0;
+
+/// High-frequency gain of the head shadow at [`DEEPEST_SHADOW`].
+const SHADOW_GAIN: f64 = 0.1;
+
+/// Samples of input the [`Spatializer`] keeps for its interaural delay,
+/// more than the longest delay (0.66 ms) at up to 384 kHz.
+const EAR_HISTORY: usize = 256;
+
+/// Where the muffler outlet sits around the listener.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SourcePosition {
+    /// Direction in degrees: 0 straight ahead, positive to the right,
+    /// ±180 behind.
+    pub azimuth: f64,
+    /// Distance in metres from the centre of the head. The level falls
+    /// as 1/r from its unpositioned level at 1 m.
+    pub distance: f64,
+}
+
+impl Default for SourcePosition {
+    fn default() -> Self {
+        Self {
+            azimuth: 30.0,
+            distance: 1.0,
+        }
+    }
+}
+
+impl SourcePosition {
+    /// Check that the azimuth is finite and the distance positive.
+    pub fn validate(&self) -> Result<(), String> {
+        if !self.azimuth.is_finite() {
+            return Err(format!("azimuth must be finite, got {}", self.azimuth));
+        }
+        if !(self.distance > 0.0 && self.distance.is_finite()) {
+            return Err(format!("distance must be > 0, got {}", self.distance));
+        }
+        Ok(())
+    }
+
+    /// Angle in degrees, 0 to 180, between the source and the ear on
+    /// the `right` or left side.
+    fn ear_angle(&self, right: bool) -> f64 {
+        let ear = if right { 90.0 } else { -90.0 };
+        ((self.azimuth - ear + 180.0).rem_euclid(360.0) - 180.0).abs()
+    }
+}
+
+/// Delay line and head-shadow filter state of one ear.
+#[derive(Default)]
+struct Ear {
+    /// Delay in samples at the end of the last block.
+    delay: Option<f64>,
+    /// Previous input and output of the shadow filter.
+    x: f64,
+    y: f64,
+}
+
+/// Places a mono signal around the listener's head for headphones.
+///
+/// Spherical-head model after Brown and Duda: each ear hears the source
+/// delayed by the path around the head and through a first-order shelf
+/// that lifts the treble facing the source and cuts it in the head's
+/// shadow, giving both the time and the level difference between the
+/// ears. The level falls as 1/r with distance. Without a position the
+/// signal goes to both channels unchanged.
+pub struct Spatializer {
+    sample_rate: f64,
+    position: Option<SourcePosition>,
+    /// The last [`EAR_HISTORY`] input samples.
+    history: Vec<f64>,
+    /// Left and right ear.
+    ears: [Ear; 2],
+}
+
+impl Spatializer {
+    /// A spatializer that duplicates the signal until
+    /// [`set_position`](Self::set_position).
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate,
+            position: None,
+            history: vec![0.0; EAR_HISTORY],
+            ears: Default::default(),
+        }
+    }
+
+    /// Place the source at `position`, or duplicate the signal to both
+    /// channels with `None`. A move glides the delays over the next block.
+    pub fn set_position(&mut self, position: Option<SourcePosition>) {
+        if position == self.position {
+            return;
+        }
+        if position.is_none() {
+            self.history = vec![0.0; EAR_HISTORY];
+            self.ears = Default::default();
+        }
+        self.position = position;
+    }
+
+    /// Turn a block of mono samples into `[left, right]` frames, carrying
+    /// the history into the next block.
+    pub fn process(&mut self, input: &[f64]) -> Vec<[f64; 2]> {
+        let Some(position) = self.position else {
+            return input.iter().map(|&x| [x, x]).collect();
+        };
+        let gain = 1.0 / position.distance;
+        let head = HEAD_RADIUS / HEAD_SPEED_OF_SOUND;
+        let right_angle = std::f64::consts::FRAC_PI_2;
+        let mut signal = std::mem::take(&mut self.history);
+        signal.extend_from_slice(input);
+        let mut output = vec![[0.0; 2]; input.len()];
+        for (channel, ear) in self.ears.iter_mut().enumerate() {
+            let angle = position.ear_angle(channel == 1).to_radians();
+            // Path around the head, relative to the ear nearest the source
+            let path = if angle < right_angle { 1.0 - angle.cos() } else { 1.0 + angle - right_angle };
+            let target = path * head * self.sample_rate;
+            let start = ear.delay.unwrap_or(target);
+            // Shelf (1 + α·s/2ω₀) / (1 + s/2ω₀), bilinear transformed
+            let alpha = (1.0 + SHADOW_GAIN / 2.0)
+                + (1.0 - SHADOW_GAIN / 2.0) * (angle / DEEPEST_SHADOW.to_radians() * std::f64::consts::PI).cos();
+            let k = self.sample_rate * head;
+            let (b0, b1, a0, a1) = (1.0 + alpha * k, 1.0 - alpha * k, 1.0 + k, 1.0 - k);
+            for (i, frame) in output.iter_mut().enumerate() {
+                let delay = start + (target - start) * (i + 1) as f64 / input.len() as f64;
+                let at = (EAR_HISTORY + i) as f64 - delay;
+                let (j, frac) = (at.floor() as usize, at.fract());
+                let x = signal[j] * (1.0 - frac) + signal.get(j + 1).unwrap_or(&0.0) * frac;
+                let y = (b0 * x + b1 * ear.x - a1 * ear.y) / a0;
+                (ear.x, ear.y) = (x, y);
+                frame[channel] = gain * y;
+            }
+            ear.delay = Some(target);
+        }
+        self.history = signal.split_off(signal.len() - EAR_HISTORY);
+        output
+    }
+}
+
+// ---------------------------------------------------------------------------
+// DryWetMix
+// ---------------------------------------------------------------------------
+
+/// Time constant in seconds over which [`DryWetMix`] follows the levels.
+const LEVEL_TIME: f64 = 0.3;
+
+/// Blend of the unmuffled (dry) and muffled (wet) signals for A/B
+/// listening.
+///
+/// The dry signal is brought to the wet signal's running RMS level, so
+/// that switching compares how the muffler shapes the sound rather than
+/// how much quieter it makes it. A change of mix ramps over one block.
+pub struct DryWetMix {
+    /// Per-sample weight of the newest sample in the running levels.
+    weight: f64,
+    /// Running mean squares of the dry and wet signals.
+    dry_power: f64,
+    wet_power: f64,
+    /// Mix at the end of the last block, 0 for dry and 1 for wet.
+    mix: f64,
+}
+
+impl DryWetMix {
+    /// A fully wet mix for signals at `sample_rate` Hz.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            weight: 1.0 - (-1.0 / (LEVEL_TIME * sample_rate)).exp(),
+            dry_power: 0.0,
+            wet_power: 0.0,
+            mix: 1.0,
+        }
+    }
+
+    /// Blend a block of `dry` and `wet` samples, moving to `mix` (clamped
+    /// to 0–1) over the block.
+    pub fn process(&mut self, dry: &[f64], wet: &[f64], mix: f64) -> Vec<f64> {
+        let mix = mix.clamp(0.0, 1.0);
+        let start = self.mix;
+        self.mix = mix;
+        let n = dry.len().min(wet.len());
+        (0..n)
+            .map(|i| {
+                self.dry_power += self.weight * (dry[i] * dry[i] - self.dry_power);
+                self.wet_power += self.weight * (wet[i] * wet[i] - self.wet_power);
+                let gain = if self.dry_power > 0.0 { (self.wet_power / self.dry_power).sqrt() } else { 1.0 };
+                let m = start + (mix - start) * (i + 1) as f64 / n as f64;
+                (1.0 - m) * gain * dry[i] + m * wet[i]
+            })
+            .collect()
+    }
+}
 
-        // Handle degenerate cases
-        if ir.is_empty() || input.is_empty() {
-            return vec![0.0; input.len()];
+// ---------------------------------------------------------------------------
+// Limiter
+// ---------------------------------------------------------------------------
+
+/// Peak level the [`Limiter`] holds the output to, just under full scale.
+const LIMIT: f64 = 0.9;
+
+/// Time constant in seconds of the [`Limiter`]'s recovery after a peak.
+const LIMITER_RELEASE: f64 = 0.1;
+
+/// Peak limiter in front of the output device.
+///
+/// A resonant muffler can have well over unity gain, and the device would
+/// hard-clip it harshly at full volume. The limiter turns its gain down
+/// at once on a peak above [`LIMIT`] and lets it recover smoothly, so the
+/// output never exceeds the limit.
+pub struct Limiter {
+    /// Per-sample decay of the peak envelope.
+    release: f64,
+    /// Peak envelope of the input.
+    envelope: f64,
+    /// Deepest gain reduction in dB since it was last taken.
+    reduction: f64,
+}
+
+impl Limiter {
+    /// A limiter for a signal at `sample_rate` Hz.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            release: (-1.0 / (LIMITER_RELEASE * sample_rate)).exp(),
+            envelope: 0.0,
+            reduction: 0.0,
+        }
+    }
+
+    /// Limit one sample.
+    pub fn process(&mut self, x: f64) -> f64 {
+        x * self.gain(x.abs())
+    }
+
+    /// Limit a `[left, right]` frame, turning both channels down together
+    /// so the image does not shift.
+    pub fn process_frame(&mut self, [left, right]: [f64; 2]) -> [f64; 2] {
+        let gain = self.gain(left.abs().max(right.abs()));
+        [left * gain, right * gain]
+    }
+
+    /// Gain for a sample of magnitude `peak`.
+    fn gain(&mut self, peak: f64) -> f64 {
+        self.envelope = peak.max(self.envelope * self.release);
+        if self.envelope <= LIMIT {
+            return 1.0;
         }
+        let gain = LIMIT / self.envelope;
+        self.reduction = self.reduction.max(-20.0 * gain.log10());
+        gain
+    }
+
+    /// Deepest gain reduction in dB (0 when not limiting) since the last
+    /// call.
+    pub fn take_reduction(&mut self) -> f64 {
+        std::mem::take(&mut self.reduction)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Fades
+// ---------------------------------------------------------------------------
+
+/// Time in seconds over which playback fades in and out.
+const FADE_TIME: f64 = 0.02;
+
+/// Gain ramp at the start and end of playback, so the output neither
+/// begins nor ends on a step from a pump sample to silence.
+///
+/// The gain follows a raised cosine from 0 to 1 over [`FADE_TIME`].
+pub struct OutputFade {
+    /// Ramp position advanced per sample.
+    step: f64,
+    /// Position along the ramp, 0 (silent) to 1 (full level).
+    position: f64,
+}
+
+impl OutputFade {
+    /// A fade for a signal at `sample_rate` Hz, starting silent.
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            step: 1.0 / (FADE_TIME * sample_rate),
+            position: 0.0,
+        }
+    }
+
+    /// Gain for the next sample, rising towards full level or, while
+    /// `fading_out`, falling towards silence.
+    pub fn next(&mut self, fading_out: bool) -> f64 {
+        let direction = if fading_out { -1.0 } else { 1.0 };
+        self.position = (self.position + direction * self.step).clamp(0.0, 1.0);
+        0.5 - 0.5 * (std::f64::consts::PI * self.position).cos()
+    }
+
+    /// Drop back to silence, to fade in again from there.
+    pub fn restart(&mut self) {
+        self.position = 0.0;
+    }
+
+    /// Whether the fade has reached silence.
+    pub fn is_silent(&self) -> bool {
+        self.position == 0.0
+    }
+}
+
+/// Per-frame work of the device callback, whatever the sample format:
+/// fading, volume, limiting and metering.
+struct OutputStage {
+    fade: OutputFade,
+    limiter: Limiter,
+    meter: BlockMeter,
+}
+
+impl OutputStage {
+    fn new(sample_rate: f64) -> Self {
+        Self {
+            fade: OutputFade::new(sample_rate),
+            limiter: Limiter::new(sample_rate),
+            meter: BlockMeter::default(),
+        }
+    }
+
+    /// The next frame to play from `buf` at volume `vol`, fading out while
+    /// `fading_out`. Running out of samples leaves silence and fades back
+    /// in once the feeder produces again.
+    fn next_frame(&mut self, buf: &mut VecDeque<[f64; 2]>, vol: f64, fading_out: bool) -> [f64; 2] {
+        let Some([left, right]) = buf.pop_front() else {
+            self.fade.restart();
+            self.meter.add_frame([0.0; 2], [0.0; 2]);
+            return [0.0; 2];
+        };
+        let gain = vol * self.fade.next(fading_out);
+        let unlimited = [left * gain, right * gain];
+        let sample = self.limiter.process_frame(unlimited);
+        self.meter.add_frame(unlimited, sample);
+        sample
+    }
+
+    /// Store the levels of the callback in `levels`.
+    fn publish(&mut self, levels: &Mutex<OutputLevels>) {
+        self.meter.publish(levels, self.limiter.take_reduction());
+    }
+}
+
+/// Sample of `frame` for output `channel` of `channels`: left and right
+/// on the first two, their average on a mono device and on any further
+/// channels.
+fn channel_sample([left, right]: [f64; 2], channel: usize, channels: usize) -> f64 {
+    match channel {
+        0 if channels > 1 => left,
+        1 => right,
+        _ => 0.5 * (left + right),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Output level metering
+// ---------------------------------------------------------------------------
+
+/// Level of the signal sent to the output device over the last device
+/// callback, for a VU meter and a clip indicator.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct OutputLevels {
+    /// Largest absolute sample after the limiter, 1 at full scale.
+    pub peak: f64,
+    /// RMS after the limiter.
+    pub rms: f64,
+    /// Deepest gain reduction in dB of the [`Limiter`].
+    pub gain_reduction: f64,
+    /// Whether a sample would have clipped without the limiter since the
+    /// indicator was last reset.
+    pub clipped: bool,
+}
+
+impl OutputLevels {
+    /// Peak level in dB relative to full scale.
+    pub fn peak_db(&self) -> f64 {
+        20.0 * self.peak.log10()
+    }
+
+    /// RMS level in dB relative to full scale.
+    pub fn rms_db(&self) -> f64 {
+        20.0 * self.rms.log10()
+    }
+}
+
+/// Accumulates [`OutputLevels`] over one device callback.
+#[derive(Default)]
+struct BlockMeter {
+    peak: f64,
+    sum_squares: f64,
+    count: usize,
+    over: bool,
+}
+
+impl BlockMeter {
+    /// Measure one sample, `unlimited` going into the limiter and
+    /// `limited` coming out.
+    fn add(&mut self, unlimited: f64, limited: f64) {
+        self.peak = self.peak.max(limited.abs());
+        self.sum_squares += limited * limited;
+        self.count += 1;
+        self.over |= unlimited.abs() > 1.0;
+    }
+
+    /// Measure both channels of a frame.
+    fn add_frame(&mut self, unlimited: [f64; 2], limited: [f64; 2]) {
+        for (unlimited, limited) in unlimited.into_iter().zip(limited) {
+            self.add(unlimited, limited);
+        }
+    }
+
+    /// Store the levels of the block in `levels`, keeping the clip
+    /// indicator latched, and start a new block.
+    fn publish(&mut self, levels: &Mutex<OutputLevels>, gain_reduction: f64) {
+        let mut levels = levels.lock().unwrap_or_else(|e| e.into_inner());
+        levels.peak = self.peak;
+        levels.rms = (self.sum_squares / self.count.max(1) as f64).sqrt();
+        levels.gain_reduction = gain_reduction;
+        levels.clipped |= self.over;
+        *self = Self::default();
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Pipeline health
+// ---------------------------------------------------------------------------
+
+/// Health of the playback pipeline since playback started, to tell a
+/// feeder thread too slow for real time from a ring buffer sized too
+/// small when the audio glitches.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PipelineStats {
+    /// Device callbacks served.
+    pub callbacks: u64,
+    /// Device callbacks that found the ring buffer short and played
+    /// silence for the rest of their buffer.
+    pub underruns: u64,
+    /// Frames of silence played in those callbacks.
+    pub missing_samples: u64,
+    /// Frames in the ring buffer at the start of the last callback.
+    pub buffered: usize,
+    /// Frames the feeder keeps the ring buffer filled to.
+    pub buffer_target: usize,
+    /// Audio duration in seconds of the last callback's buffer.
+    pub callback_duration: f64,
+    /// Longest time in seconds a callback took to run.
+    pub callback_time_max: f64,
+    /// Blocks the feeder produced.
+    pub blocks: u64,
+    /// Audio duration in seconds of one feeder block.
+    pub block_duration: f64,
+    /// Total time in seconds the feeder spent producing blocks.
+    pub block_time_total: f64,
+    /// Longest time in seconds the feeder took to produce a block.
+    pub block_time_max: f64,
+}
+
+impl PipelineStats {
+    /// Average time the feeder takes per block as a share of the block's
+    /// audio duration. Above 1 it cannot keep up in real time.
+    pub fn feeder_load(&self) -> f64 {
+        if self.blocks == 0 || self.block_duration <= 0.0 {
+            return 0.0;
+        }
+        self.block_time_total / self.blocks as f64 / self.block_duration
+    }
+
+    /// Like [`feeder_load`](Self::feeder_load) for the slowest block. A
+    /// load above 1 here only glitches when the buffer is too small to
+    /// ride it out.
+    pub fn peak_feeder_load(&self) -> f64 {
+        if self.block_duration <= 0.0 {
+            return 0.0;
+        }
+        self.block_time_max / self.block_duration
+    }
+
+    /// Record a feeder block that took `elapsed` to produce.
+    fn record_block(&mut self, elapsed: Duration) {
+        let seconds = elapsed.as_secs_f64();
+        self.blocks += 1;
+        self.block_time_total += seconds;
+        self.block_time_max = self.block_time_max.max(seconds);
+    }
+
+    /// Record a device callback that wanted `frames` frames at
+    /// `sample_rate` Hz, found `buffered` in the ring buffer and ran for
+    /// `elapsed`. Before the feeder's first block a short buffer is the
+    /// start-up, not an underrun.
+    fn record_callback(&mut self, buffered: usize, frames: usize, sample_rate: f64, elapsed: Duration) {
+        self.callbacks += 1;
+        self.buffered = buffered;
+        self.callback_duration = frames as f64 / sample_rate;
+        self.callback_time_max = self.callback_time_max.max(elapsed.as_secs_f64());
+        if buffered < frames && self.blocks > 0 {
+            self.underruns += 1;
+            self.missing_samples += (frames - buffered) as u64;
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Live input
+// ---------------------------------------------------------------------------
+
+/// Most input in seconds held waiting for the feeder; older samples are
+/// dropped so the live input does not lag further and further behind.
+const MAX_INPUT_LATENCY: f64 = 0.1;
+
+/// Samples from a live input device, such as a microphone next to a real
+/// pump or a loopback of a recording, resampled to the output rate.
+///
+/// The input stream itself is not `Send`, so the [`AudioPipeline`] holds
+/// it and this source only reads the ring buffer it fills.
+struct LiveInput {
+    /// Mono input samples, oldest first.
+    ring: Arc<Mutex<VecDeque<f64>>>,
+    /// Sample rate of the input device in Hz.
+    input_rate: f64,
+    /// Output sample rate in Hz.
+    sample_rate: f64,
+    /// Read position into `ring` in input samples.
+    position: f64,
+}
+
+impl SampleSource for LiveInput {
+    /// Interpolate linearly between input samples, playing silence while
+    /// the input has not caught up.
+    fn generate(&mut self, count: usize) -> Vec<f64> {
+        let ratio = self.input_rate / self.sample_rate;
+        let mut buf = self.ring.lock().unwrap_or_else(|e| e.into_inner());
+        let mut output = Vec::with_capacity(count);
+        for _ in 0..count {
+            let i = self.position as usize;
+            if i + 1 >= buf.len() {
+                output.push(0.0);
+                continue;
+            }
+            let t = self.position - i as f64;
+            output.push(buf[i] + (buf[i + 1] - buf[i]) * t);
+            self.position += ratio;
+        }
+        let used = (self.position as usize).min(buf.len());
+        buf.drain(..used);
+        self.position -= used as f64;
+        output
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+}
+
+/// Mix interleaved input `data` of `channels` channels down to mono onto
+/// `ring`, keeping at most `max_buffered` samples.
+fn push_input<T: Copy>(
+    ring: &Mutex<VecDeque<f64>>,
+    data: &[T],
+    channels: usize,
+    max_buffered: usize,
+    convert: impl Fn(T) -> f64,
+) {
+    let mut buf = ring.lock().unwrap_or_else(|e| e.into_inner());
+    for frame in data.chunks(channels) {
+        buf.push_back(frame.iter().map(|&s| convert(s)).sum::<f64>() / channels as f64);
+    }
+    let excess = buf.len().saturating_sub(max_buffered);
+    buf.drain(..excess);
+}
+
+/// Build a cpal input stream of samples of type `T`, mixed down to mono
+/// onto `ring` as `f64` (see [`push_input`]).
+fn build_input_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    ring: Arc<Mutex<VecDeque<f64>>>,
+    max_buffered: usize,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample,
+    f64: cpal::FromSample<T>,
+{
+    let channels = config.channels as usize;
+    device.build_input_stream(
+        config,
+        move |data: &[T], _: &cpal::InputCallbackInfo| {
+            push_input(&ring, data, channels, max_buffered, cpal::Sample::to_sample::<f64>)
+        },
+        |err: cpal::StreamError| {
+            eprintln!("cpal input stream error: {err}");
+        },
+        None,
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Output backends
+// ---------------------------------------------------------------------------
+
+/// Fills a buffer of interleaved `f32` output samples, called by the
+/// backend whenever the output needs more.
+pub type RenderCallback = Box<dyn FnMut(&mut [f32]) + Send>;
+
+/// Where the [`AudioPipeline`]'s output goes: the sound card through
+/// [`CpalBackend`], or memory through [`NullBackend`].
+pub trait AudioBackend {
+    /// Open the output and return its sample rate in Hz and channel count.
+    fn open(&mut self) -> Result<(f64, usize), String>;
+
+    /// Start pulling output from `render`, as often and in buffers as
+    /// large as the output wants.
+    fn start(&mut self, render: RenderCallback) -> Result<(), String>;
+
+    /// Stop pulling output and drop `render`.
+    fn stop(&mut self);
+}
+
+/// The default output device through cpal.
+#[derive(Default)]
+pub struct CpalBackend {
+    /// Device, its config and its sample format, once opened.
+    device: Option<(cpal::Device, cpal::StreamConfig, SampleFormat)>,
+    /// cpal stream (held to keep it alive; dropped on stop).
+    stream: Option<Stream>,
+}
+
+impl AudioBackend for CpalBackend {
+    fn open(&mut self) -> Result<(f64, usize), String> {
+        let host = cpal::default_host();
+        let device = host
+            .default_output_device()
+            .ok_or_else(|| "No default audio output device found".to_string())?;
+        let supported_config = device
+            .default_output_config()
+            .map_err(|e| format!("No default output config: {e}"))?;
+        let sample_format = supported_config.sample_format();
+        let config: cpal::StreamConfig = supported_config.into();
+        let format = (config.sample_rate.0 as f64, config.channels as usize);
+        self.device = Some((device, config, sample_format));
+        Ok(format)
+    }
+
+    fn start(&mut self, render: RenderCallback) -> Result<(), String> {
+        let (device, config, sample_format) = self
+            .device
+            .as_ref()
+            .ok_or_else(|| "The output device is not open".to_string())?;
+        let stream = match sample_format {
+            SampleFormat::I8 => build_output_stream::<i8>(device, config, render),
+            SampleFormat::I16 => build_output_stream::<i16>(device, config, render),
+            SampleFormat::I32 => build_output_stream::<i32>(device, config, render),
+            SampleFormat::I64 => build_output_stream::<i64>(device, config, render),
+            SampleFormat::U8 => build_output_stream::<u8>(device, config, render),
+            SampleFormat::U16 => build_output_stream::<u16>(device, config, render),
+            SampleFormat::U32 => build_output_stream::<u32>(device, config, render),
+            SampleFormat::U64 => build_output_stream::<u64>(device, config, render),
+            SampleFormat::F32 => build_output_stream::<f32>(device, config, render),
+            SampleFormat::F64 => build_output_stream::<f64>(device, config, render),
+            _ => return Err(format!("Unsupported sample format: {sample_format:?}")),
+        }
+        .map_err(|e| format!("Failed to build output stream: {e}"))?;
+        stream.play().map_err(|e| format!("Failed to start cpal stream: {e}"))?;
+        self.stream = Some(stream);
+        Ok(())
+    }
+
+    fn stop(&mut self) {
+        self.stream.take();
+    }
+}
+
+/// Build a cpal output stream of samples of type `T`, rendered as `f32`
+/// into a scratch buffer and converted.
+fn build_output_stream<T>(
+    device: &cpal::Device,
+    config: &cpal::StreamConfig,
+    mut render: RenderCallback,
+) -> Result<Stream, cpal::BuildStreamError>
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    let mut scratch: Vec<f32> = Vec::new();
+    device.build_output_stream(
+        config,
+        move |data: &mut [T], _: &cpal::OutputCallbackInfo| {
+            scratch.resize(data.len(), 0.0);
+            render(&mut scratch);
+            convert_samples(&scratch, data);
+        },
+        |err: cpal::StreamError| {
+            eprintln!("cpal stream error: {err}");
+        },
+        None,
+    )
+}
+
+/// Convert `rendered` samples into the device's sample type.
+fn convert_samples<T>(rendered: &[f32], data: &mut [T])
+where
+    T: cpal::SizedSample + cpal::FromSample<f32>,
+{
+    for (out, &sample) in data.iter_mut().zip(rendered) {
+        *out = <T as cpal::Sample>::from_sample(sample);
+    }
+}
+
+/// An output without a device, for tests: nothing plays until the
+/// [`NullSink`] pulls samples into memory.
+pub struct NullBackend {
+    sample_rate: f64,
+    channels: usize,
+    render: Arc<Mutex<Option<RenderCallback>>>,
+}
+
+impl NullBackend {
+    /// An output at `sample_rate` Hz with `channels` channels.
+    pub fn new(sample_rate: f64, channels: usize) -> Self {
+        Self {
+            sample_rate,
+            channels,
+            render: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Handle to pull the output with, kept after the backend moves into
+    /// the pipeline.
+    pub fn sink(&self) -> NullSink {
+        NullSink {
+            channels: self.channels,
+            render: Arc::clone(&self.render),
+        }
+    }
+}
+
+impl AudioBackend for NullBackend {
+    fn open(&mut self) -> Result<(f64, usize), String> {
+        Ok((self.sample_rate, self.channels))
+    }
 
-        let conv_len = input.len() + ir.len() - 1;
-        let mut convolved = vec![0.0; conv_len];
+    fn start(&mut self, render: RenderCallback) -> Result<(), String> {
+        *self.render.lock().unwrap_or_else(|e| e.into_inner()) = Some(render);
+        Ok(())
+    }
 
-        // Direct (time-domain) convolution.
-        // Fine for block_size = 512 and IR length up to ~2048.
-        for (i, &x) in input.iter().enumerate() {
-            for (j, &h) in ir.iter().enumerate() {
-                convolved[i + j] += x * h;
-            }
-        }
+    fn stop(&mut self) {
+        self.render.lock().unwrap_or_else(|e| e.into_inner()).take();
+    }
+}
 
-        // Add the overlap (tail) from the *previous* block.
-        let overlap_add_len = self.overlap.len().min(conv_len);
-        for i in 0..overlap_add_len {
-            convolved[i] += self.overlap[i];
-        }
-        // If the old overlap was longer than the new convolved result (can
-        // happen when IR shrinks via hot-swap), carry the remainder forward.
-        if self.overlap.len() > conv_len {
-            // This case is unusual but handled for correctness.
-            let leftover = self.overlap[conv_len..].to_vec();
-            self.overlap = leftover;
-        } else {
-            self.overlap.clear();
-        }
-
-        // Split: first `input.len()` samples are the output; the rest become
-        // the new overlap for the next block.
-        let n = input.len();
-        let output = convolved[..n].to_vec();
-        if conv_len > n {
-            // Merge any remaining old overlap that extends beyond our output
-            let new_tail = &convolved[n..];
-            // Extend existing overlap (which may have leftover from above)
-            let needed = new_tail.len().max(self.overlap.len());
-            let mut merged = vec![0.0; needed];
-            for (i, &v) in self.overlap.iter().enumerate() {
-                merged[i] += v;
-            }
-            for (i, &v) in new_tail.iter().enumerate() {
-                merged[i] += v;
-            }
-            self.overlap = merged;
-        }
+/// The memory end of a [`NullBackend`].
+#[derive(Clone)]
+pub struct NullSink {
+    channels: usize,
+    render: Arc<Mutex<Option<RenderCallback>>>,
+}
 
-        output
+impl NullSink {
+    /// Pull `frames` interleaved frames as a device callback would, or
+    /// `None` while the pipeline is not playing.
+    pub fn pull(&self, frames: usize) -> Option<Vec<f32>> {
+        let mut render = self.render.lock().unwrap_or_else(|e| e.into_inner());
+        let render = render.as_mut()?;
+        let mut data = vec![0.0; frames * self.channels];
+        render(&mut data);
+        Some(data)
     }
 }
 
@@ -110,37 +1219,81 @@ impl ConvolutionEngine {
 // AudioPipeline
 // ---------------------------------------------------------------------------
 
-/// Shared ring buffer between the feeder thread and the cpal callback.
-type RingBuffer = Arc<Mutex<VecDeque<f64>>>;
+/// Default time constant in seconds over which the feeder glides the pump
+/// speed and duty cycle to new settings.
+const GLIDE_TIME: f64 = 0.05;
+
+/// Shared ring buffer of `[left, right]` frames between the feeder thread
+/// and the cpal callback.
+type RingBuffer = Arc<Mutex<VecDeque<[f64; 2]>>>;
+
+/// A custom source shared with the feeder thread.
+type SharedSource = Arc<Mutex<Box<dyn SampleSource + Send>>>;
 
 /// Audio output pipeline managing pump generation, convolution, and cpal output.
 ///
 /// Architecture:
-///   - A *feeder thread* generates pump samples in 512-sample blocks,
-///     convolves them through the `ConvolutionEngine`, and pushes results
-///     into a ring buffer (`VecDeque<f64>` behind `Arc<Mutex<_>>`).
-///   - The cpal stream callback pulls samples from the ring buffer,
-///     multiplies by the volume scalar, and writes them to the output.
+///   - A *feeder thread* generates pump samples (or a [`TestSignal`], a
+///     custom [`SampleSource`] such as a live input device, or a looped
+///     [`Recording`] of a real pump,
+///     in that order of precedence) in 512-sample blocks,
+///     convolves them through the `ConvolutionEngine`, mixing in a
+///     [`SecondPump`] through the same muffler or a muffler of its own,
+///     blends in the unmuffled signal through the [`DryWetMix`],
+///     runs them through the [`NotchFilter`] (if a band is muted),
+///     through a room or headphone response with a third
+///     `ConvolutionEngine` (a unit impulse while none is loaded),
+///     places them around the listener with the [`Spatializer`],
+///     and pushes `[left, right]` frames into a ring buffer (`VecDeque<[f64; 2]>`
+///     behind `Arc<Mutex<_>>`).
+///   - The callback of the [`AudioBackend`] (the cpal stream, or memory
+///     in tests) pulls frames from the ring buffer,
+///     fades them in and out with the [`OutputFade`] on play and stop,
+///     multiplies by the volume scalar, runs them through the [`Limiter`],
+///     meters their [`OutputLevels`], and writes them to the output.
+///   - Both sides record underruns and their timing in [`PipelineStats`].
 ///   - If the ring buffer is empty the callback outputs silence.
 pub struct AudioPipeline {
     /// Whether audio is currently playing.
     playing: Arc<AtomicBool>,
     /// Output volume (0.0 to 1.0).
     volume: Arc<Mutex<f64>>,
+    /// Output levels during the last device callback.
+    levels: Arc<Mutex<OutputLevels>>,
+    /// Underruns and timing of the feeder and the device callback.
+    stats: Arc<Mutex<PipelineStats>>,
     /// Handle into the ConvolutionEngine's IR for hot-swap.
-    ir_handle: Arc<Mutex<Vec<f64>>>,
+    ir_handle: IrHandle,
+    /// The last IR swapped in and its sample rate, before resampling to
+    /// the device rate.
+    source_ir: Mutex<(Vec<f64>, f64)>,
+    /// Handle into the IR of the room or headphone response.
+    room_ir_handle: IrHandle,
+    /// The room or headphone IR and its sample rate, before normalizing
+    /// and resampling to the device rate.
+    room_source_ir: Mutex<Option<(Vec<f64>, f64)>>,
+    /// Handle into the IR of the second pump's own muffler.
+    second_ir_handle: IrHandle,
+    /// The second pump's own muffler IR and its sample rate, if it has one.
+    second_source_ir: Mutex<Option<(Vec<f64>, f64)>>,
     /// Handle into the PumpSource parameters.
     pump_params: Arc<Mutex<PumpParams>>,
     /// Sample rate used by the pipeline.
     sample_rate: f64,
     /// Block size used by the feeder.
     block_size: usize,
-    /// cpal stream (held to keep it alive; dropped on stop).
-    stream: Option<Stream>,
+    /// Output the device callback plays through.
+    backend: Box<dyn AudioBackend>,
+    /// cpal input stream feeding the [`LiveInput`] source, while one plays.
+    input_stream: Option<Stream>,
     /// Join handle for the feeder thread.
     feeder_handle: Option<thread::JoinHandle<()>>,
     /// Signal the feeder thread to shut down.
     feeder_running: Arc<AtomicBool>,
+    /// Signal the device callback to fade out ahead of a stop.
+    fading_out: Arc<AtomicBool>,
+    /// Set by the device callback once the fade out has reached silence.
+    faded_out: Arc<AtomicBool>,
 }
 
 /// Snapshot of pump parameters, shared between the main thread and the feeder.
@@ -149,11 +1302,106 @@ struct PumpParams {
     rpm: f64,
     num_valves: u32,
     duty_cycle: f64,
+    timing: StrokeTiming,
+    stroke: f64,
+    variation: ValveVariation,
+    piston: Option<PistonCrank>,
+    reed: Option<ReedValve>,
+    lift: Option<ValveLift>,
+    series: Option<HarmonicSeries>,
+    motor: Option<MotorNoise>,
+    wander: Option<SpeedWander>,
+    /// Pump mixed in beside the first, if any.
+    second: Option<SecondPump>,
+    /// Whether the second pump has a muffler of its own rather than
+    /// sharing the first pump's.
+    separate_muffler: bool,
+    /// Test signal played instead of the pump, if any.
+    test_signal: Option<TestSignal>,
+    /// Custom source played instead of the pump, if any.
+    source: Option<SharedSource>,
+    /// Recording looped instead of the synthesized pump, if any.
+    recording: Option<Arc<Recording>>,
+    /// RPM the recording was made at, when its pitch follows `rpm`.
+    recording_rpm: Option<f64>,
+    /// Speed profile the pump follows instead of `rpm`, if any.
+    profile: Option<Arc<RpmProfile>>,
+    /// RPM the feeder last played from `profile`.
+    profile_rpm: Option<f64>,
+    /// Band muted from playback, if any.
+    notch: Option<Notch>,
+    /// Linear make-up gain applied after the muffler, e.g. to match the
+    /// loudness of another design.
+    gain: f64,
+    /// Share of the muffled signal in the output, 0 (dry) to 1 (wet).
+    mix: f64,
+    /// Play the unmuffled signal alone, whatever the mix.
+    bypass: bool,
+    /// Where the outlet is placed around the listener, if anywhere.
+    position: Option<SourcePosition>,
+    /// Time constant in seconds over which the pumps glide to a new
+    /// speed and duty cycle; 0 steps at once.
+    glide_time: f64,
+}
+
+impl PumpParams {
+    /// Bring `pump` up to every setting but its speed, valves and duty
+    /// cycle, without resetting phase.
+    fn configure(&self, pump: &mut PumpSource) {
+        pump.set_timing(self.timing);
+        pump.set_stroke(self.stroke);
+        pump.set_piston(self.piston);
+        pump.set_reed(self.reed);
+        if pump.lift != self.lift {
+            pump.set_lift(self.lift.clone());
+        }
+        if pump.series != self.series {
+            pump.set_series(self.series.clone());
+        }
+        pump.set_motor(self.motor);
+        pump.set_wander(self.wander);
+        if pump.variation != self.variation {
+            pump.set_variation(self.variation.clone());
+        }
+    }
+}
+
+/// A pump's speed and duty cycle following their settings with a
+/// first-order lag, so dragging a slider sweeps the pitch instead of
+/// stepping it.
+#[derive(Default)]
+struct Glide {
+    /// Current RPM and duty cycle, once set.
+    current: Option<(f64, f64)>,
+}
+
+impl Glide {
+    /// Move `factor` of the way from the current RPM and duty cycle to
+    /// `rpm` and `duty_cycle` (jumping there the first time) and return
+    /// the new values.
+    fn follow(&mut self, rpm: f64, duty_cycle: f64, factor: f64) -> (f64, f64) {
+        let next = match self.current {
+            Some((from_rpm, from_duty)) => (
+                from_rpm + (rpm - from_rpm) * factor,
+                from_duty + (duty_cycle - from_duty) * factor,
+            ),
+            None => (rpm, duty_cycle),
+        };
+        self.current = Some(next);
+        next
+    }
 }
 
 impl AudioPipeline {
-    /// Create a new audio pipeline.  Does *not* start playback.
+    /// Create a new audio pipeline playing through the default output
+    /// device.  Does *not* start playback.
     pub fn new() -> Self {
+        Self::with_backend(Box::new(CpalBackend::default()))
+    }
+
+    /// Create a new audio pipeline playing through `backend`, such as a
+    /// [`NullBackend`] in tests.
+    pub fn with_backend(backend: Box<dyn AudioBackend>) -> Self {
         let block_size = 512;
         let sample_rate = 44_100.0;
 
@@ -161,38 +1409,142 @@ impl AudioPipeline {
         // clone of its IR handle so the outside world can hot-swap.
         let engine = ConvolutionEngine::new(block_size);
         let ir_handle = engine.ir_handle();
+        let second_ir_handle = ConvolutionEngine::new(block_size).ir_handle();
+        let room_ir_handle = ConvolutionEngine::new(block_size).ir_handle();
 
         let pump_params = PumpParams {
             rpm: 3000.0,
             num_valves: 3,
             duty_cycle: 0.5,
+            timing: StrokeTiming::default(),
+            stroke: 1.0,
+            variation: ValveVariation::default(),
+            piston: None,
+            reed: None,
+            lift: None,
+            series: None,
+            motor: None,
+            wander: None,
+            second: None,
+            separate_muffler: false,
+            test_signal: None,
+            source: None,
+            recording: None,
+            recording_rpm: None,
+            profile: None,
+            profile_rpm: None,
+            notch: None,
+            gain: 1.0,
+            mix: 1.0,
+            bypass: false,
+            position: None,
+            glide_time: GLIDE_TIME,
         };
 
         Self {
             playing: Arc::new(AtomicBool::new(false)),
             volume: Arc::new(Mutex::new(0.5)),
+            levels: Arc::new(Mutex::new(OutputLevels::default())),
+            stats: Arc::new(Mutex::new(PipelineStats::default())),
             ir_handle,
+            source_ir: Mutex::new((vec![1.0], sample_rate)),
+            second_ir_handle,
+            second_source_ir: Mutex::new(None),
+            room_ir_handle,
+            room_source_ir: Mutex::new(None),
             pump_params: Arc::new(Mutex::new(pump_params)),
             sample_rate,
             block_size,
-            stream: None,
+            backend,
+            input_stream: None,
             feeder_handle: None,
             feeder_running: Arc::new(AtomicBool::new(false)),
+            fading_out: Arc::new(AtomicBool::new(false)),
+            faded_out: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    /// Replace the impulse response used by the convolution engine.
+    /// Replace the impulse response used by the convolution engine with
+    /// `ir`, sampled at `sample_rate` Hz. It is resampled to the output
+    /// device's rate so the muffler is heard at the right pitch, and
+    /// crossfaded in during playback.
     ///
     /// This is thread-safe and can be called from the simulation thread
     /// while audio is playing.
-    pub fn swap_ir(&self, ir: Vec<f64>) {
+    pub fn swap_ir(&self, ir: Vec<f64>, sample_rate: f64) {
         // Reject IR containing non-finite values (NaN, inf).
         if !ir.iter().all(|v| v.is_finite()) {
             eprintln!("swap_ir: rejected IR with non-finite values; keeping previous IR");
             return;
         }
-        let mut guard = self.ir_handle.lock().unwrap_or_else(|e| e.into_inner());
-        *guard = ir;
+        let mut source = self.source_ir.lock().unwrap_or_else(|e| e.into_inner());
+        *source = (ir, sample_rate);
+        drop(source);
+        self.install_ir();
+    }
+
+    /// Run the second pump through a muffler of its own, with impulse
+    /// response `ir` sampled at `sample_rate` Hz, or back through the first
+    /// pump's muffler with `None`.
+    pub fn swap_second_ir(&self, ir: Option<(Vec<f64>, f64)>) {
+        if let Some((ir, _)) = &ir {
+            if !ir.iter().all(|v| v.is_finite()) {
+                eprintln!("swap_second_ir: rejected IR with non-finite values; keeping previous IR");
+                return;
+            }
+        }
+        let separate = ir.is_some();
+        *self.second_source_ir.lock().unwrap_or_else(|e| e.into_inner()) = ir;
+        self.install_ir();
+        self.pump_params.lock().unwrap_or_else(|e| e.into_inner()).separate_muffler = separate;
+    }
+
+    /// Convolve the output with a room response or headphone correction
+    /// `ir` sampled at `sample_rate` Hz after the muffler, or stop with
+    /// `None`. The response is scaled to unit energy, so switching it in
+    /// keeps broadband sound about as loud, and crossfaded in or out like
+    /// a muffler IR.
+    pub fn swap_room_ir(&self, ir: Option<(Vec<f64>, f64)>) -> Result<(), String> {
+        if let Some((ir, sample_rate)) = &ir {
+            if !ir.iter().all(|v| v.is_finite()) {
+                return Err("room IR contains non-finite samples".to_string());
+            }
+            if !ir.iter().any(|&v| v != 0.0) {
+                return Err("room IR is silent".to_string());
+            }
+            if !(*sample_rate > 0.0 && sample_rate.is_finite()) {
+                return Err(format!("sample_rate must be > 0, got {sample_rate}"));
+            }
+        }
+        *self.room_source_ir.lock().unwrap_or_else(|e| e.into_inner()) = ir;
+        self.install_ir();
+        Ok(())
+    }
+
+    /// Resample the last swapped-in IRs to the pipeline's sample rate and
+    /// hand them to the convolution engines.
+    fn install_ir(&self) {
+        let source = self.source_ir.lock().unwrap_or_else(|e| e.into_inner());
+        let (ir, rate) = &*source;
+        let resampled = impulse_response::resample(ir, *rate, self.sample_rate);
+        self.ir_handle.set(resampled);
+
+        let second = self.second_source_ir.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((ir, rate)) = &*second {
+            let resampled = impulse_response::resample(ir, *rate, self.sample_rate);
+            self.second_ir_handle.set(resampled);
+        }
+
+        let room = self.room_source_ir.lock().unwrap_or_else(|e| e.into_inner());
+        let room = room.as_ref().map_or_else(
+            || vec![1.0],
+            |(ir, rate)| {
+                let resampled = impulse_response::resample(ir, *rate, self.sample_rate);
+                let norm = resampled.iter().map(|v| v * v).sum::<f64>().sqrt();
+                resampled.into_iter().map(|v| v / norm).collect()
+            },
+        );
+        self.room_ir_handle.set(room);
     }
 
     /// Update the pump source parameters without restarting the stream.
@@ -203,12 +1555,199 @@ impl AudioPipeline {
         guard.duty_cycle = duty_cycle;
     }
 
+    /// Update the pump stroke timing without restarting the stream.
+    pub fn set_pump_timing(&self, timing: StrokeTiming) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.timing = timing;
+    }
+
+    /// Take every pump setting (drive, valves, timing, stroke, variation,
+    /// piston, reed valves, valve lift, harmonic series, motor noise,
+    /// speed wander, second pump) from the simulation parameters.
+    pub fn configure_pump(&self, params: &SimParams) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.rpm = params.pump_rpm();
+        guard.num_valves = params.num_valves;
+        guard.duty_cycle = params.duty_cycle;
+        guard.timing = params.valve_timing;
+        guard.stroke = params.pump_stroke();
+        guard.variation = params.valve_variation.clone();
+        guard.piston = params.piston;
+        guard.reed = params.reed_valve;
+        guard.lift = params.valve_lift.clone();
+        guard.series = params.harmonic_series.clone();
+        guard.motor = params.motor();
+        guard.wander = params.speed_wander;
+        guard.second = params.second_pump;
+    }
+
+    /// Play a test signal through the muffler instead of the pump, or
+    /// return to the pump with `None`.
+    pub fn set_test_signal(&self, signal: Option<TestSignal>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.test_signal = signal;
+    }
+
+    /// Play a custom source through the muffler instead of the pump, or
+    /// return to the pump with `None`. The source is told the pump RPM
+    /// every block and the output sample rate when playback picks it up.
+    pub fn set_source(&self, source: Option<Box<dyn SampleSource + Send>>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.source = source.map(|source| Arc::new(Mutex::new(source)));
+    }
+
+    /// Play the default input device, a microphone or a loopback, through
+    /// the muffler instead of the pump, in place of any custom source.
+    /// Returns the device's name.
+    pub fn start_live_input(&mut self) -> Result<String, String> {
+        let host = cpal::default_host();
+        let device = host
+            .default_input_device()
+            .ok_or_else(|| "No default audio input device found".to_string())?;
+        let name = device.name().unwrap_or_else(|_| "input device".to_string());
+        let supported_config = device
+            .default_input_config()
+            .map_err(|e| format!("No default input config: {e}"))?;
+        let sample_format = supported_config.sample_format();
+        let config: cpal::StreamConfig = supported_config.into();
+        let input_rate = config.sample_rate.0 as f64;
+
+        let ring = Arc::new(Mutex::new(VecDeque::new()));
+        let cb_ring = Arc::clone(&ring);
+        let max_buffered = (MAX_INPUT_LATENCY * input_rate) as usize;
+        let stream = match sample_format {
+            SampleFormat::I8 => build_input_stream::<i8>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::I16 => build_input_stream::<i16>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::I32 => build_input_stream::<i32>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::I64 => build_input_stream::<i64>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::U8 => build_input_stream::<u8>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::U16 => build_input_stream::<u16>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::U32 => build_input_stream::<u32>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::U64 => build_input_stream::<u64>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::F32 => build_input_stream::<f32>(&device, &config, cb_ring, max_buffered),
+            SampleFormat::F64 => build_input_stream::<f64>(&device, &config, cb_ring, max_buffered),
+            _ => return Err(format!("Unsupported input sample format: {sample_format:?}")),
+        }
+        .map_err(|e| format!("Failed to build input stream: {e}"))?;
+        stream.play().map_err(|e| format!("Failed to start input stream: {e}"))?;
+
+        self.input_stream = Some(stream);
+        self.set_source(Some(Box::new(LiveInput {
+            ring,
+            input_rate,
+            sample_rate: self.sample_rate,
+            position: 0.0,
+        })));
+        Ok(name)
+    }
+
+    /// Close the input device and return to the pump.
+    pub fn stop_live_input(&mut self) {
+        if self.input_stream.take().is_some() {
+            self.set_source(None);
+        }
+    }
+
+    /// Whether a live input device is playing through the muffler.
+    pub fn is_live_input(&self) -> bool {
+        self.input_stream.is_some()
+    }
+
+    /// Loop `recording` instead of the synthesized pump, or return to the
+    /// synthesized pump with `None`. With a `recorded_rpm` the recording
+    /// is sped up or slowed down as the pump RPM moves away from it.
+    pub fn set_recording(&self, recording: Option<Arc<Recording>>, recorded_rpm: Option<f64>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.recording = recording;
+        guard.recording_rpm = recorded_rpm;
+    }
+
+    /// Drive the pump (and a recording or custom source following it) from
+    /// `profile`, starting from its beginning, or return to the fixed RPM
+    /// with `None`. Setting the profile already playing leaves it running.
+    pub fn set_rpm_profile(&self, profile: Option<RpmProfile>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        if guard.profile.as_deref() != profile.as_ref() {
+            guard.profile = profile.map(Arc::new);
+            guard.profile_rpm = None;
+        }
+    }
+
+    /// RPM the pump is playing at from its speed profile, once playback
+    /// has started it.
+    pub fn profile_rpm(&self) -> Option<f64> {
+        self.pump_params.lock().unwrap_or_else(|e| e.into_inner()).profile_rpm
+    }
+
+    /// Mute a band of the playback signal, or stop muting with `None`.
+    pub fn set_notch(&self, notch: Option<Notch>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.notch = notch;
+    }
+
+    /// Offset the playback level by `offset_db` after the muffler, on top
+    /// of the volume.
+    pub fn set_gain_offset(&self, offset_db: f64) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.gain = 10f64.powf(offset_db / 20.0);
+    }
+
+    /// Blend the unmuffled signal, at the muffled signal's level, into the
+    /// output: 0 plays it alone, 1 (the default) only the muffled signal.
+    pub fn set_dry_wet(&self, mix: f64) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.mix = mix.clamp(0.0, 1.0);
+    }
+
+    /// Bypass the muffler (at a matched level) until released, whatever
+    /// the dry/wet mix, for instant A/B comparison.
+    pub fn set_bypass(&self, bypass: bool) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.bypass = bypass;
+    }
+
+    /// Place the muffler outlet around the listener for headphones, or
+    /// play the same signal on every channel with `None`.
+    pub fn set_position(&self, position: Option<SourcePosition>) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.position = position.filter(|position| position.validate().is_ok());
+    }
+
+    /// Glide the pump speed and duty cycle to new settings with a time
+    /// constant of `seconds` (0 to step at once).
+    pub fn set_glide_time(&self, seconds: f64) {
+        let mut guard = self.pump_params.lock().unwrap_or_else(|e| e.into_inner());
+        guard.glide_time = if seconds.is_finite() { seconds.max(0.0) } else { GLIDE_TIME };
+    }
+
     /// Set output volume (clamped to 0.0..=1.0).
     pub fn set_volume(&self, vol: f64) {
         let mut guard = self.volume.lock().unwrap_or_else(|e| e.into_inner());
         *guard = vol.clamp(0.0, 1.0);
     }
 
+    /// How far in dB the output limiter is turning the level down to keep
+    /// it from clipping, 0 when it is not.
+    pub fn gain_reduction_db(&self) -> f64 {
+        self.output_levels().gain_reduction
+    }
+
+    /// Peak and RMS level of the output during the last device callback,
+    /// and whether it has clipped.
+    pub fn output_levels(&self) -> OutputLevels {
+        *self.levels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Underruns and timing of the pipeline since playback last started.
+    pub fn stats(&self) -> PipelineStats {
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Clear the clip indicator.
+    pub fn reset_clip(&self) {
+        self.levels.lock().unwrap_or_else(|e| e.into_inner()).clipped = false;
+    }
+
     /// Returns true if the pipeline is currently playing.
     pub fn is_playing(&self) -> bool {
         self.playing.load(Ordering::Relaxed)
@@ -221,31 +1760,19 @@ impl AudioPipeline {
             return; // already playing
         }
 
-        // -- cpal device setup ------------------------------------------------
-        let host = cpal::default_host();
-        let device = match host.default_output_device() {
-            Some(d) => d,
-            None => {
-                eprintln!("No default audio output device found; audio will not play");
-                return;
-            }
-        };
-
-        let supported_config = match device.default_output_config() {
-            Ok(c) => c,
+        // -- Output setup -----------------------------------------------------
+        let (actual_sample_rate, channels) = match self.backend.open() {
+            Ok(format) => format,
             Err(e) => {
-                eprintln!("No default output config: {e}; audio will not play");
+                eprintln!("{e}; audio will not play");
                 return;
             }
         };
-        let sample_format = supported_config.sample_format();
-        let config: cpal::StreamConfig = supported_config.into();
-
-        let actual_sample_rate = config.sample_rate.0 as f64;
-        let channels = config.channels as usize;
 
-        // Update our record of the sample rate (the device may differ from 44100)
+        // Update our record of the sample rate (the device may differ from
+        // 44100) and bring the IR to it
         self.sample_rate = actual_sample_rate;
+        self.install_ir();
 
         // -- Shared ring buffer -----------------------------------------------
         // Pre-allocate capacity for ~100 ms of audio as headroom.
@@ -254,11 +1781,23 @@ impl AudioPipeline {
 
         // -- Feeder thread ----------------------------------------------------
         let feeder_ring = Arc::clone(&ring);
-        let feeder_ir = Arc::clone(&self.ir_handle);
+        let feeder_ir = self.ir_handle.clone();
+        let feeder_second_ir = self.second_ir_handle.clone();
+        let feeder_room_ir = self.room_ir_handle.clone();
         let feeder_pump = Arc::clone(&self.pump_params);
         let feeder_running = Arc::clone(&self.feeder_running);
+        let feeder_stats = Arc::clone(&self.stats);
         let block_size = self.block_size;
 
+        // Maximum ring buffer occupancy before the feeder sleeps (avoid
+        // unbounded growth).
+        let max_buffered = block_size * 8;
+        *self.stats.lock().unwrap_or_else(|e| e.into_inner()) = PipelineStats {
+            buffer_target: max_buffered,
+            block_duration: block_size as f64 / actual_sample_rate,
+            ..PipelineStats::default()
+        };
+
         self.feeder_running.store(true, Ordering::Relaxed);
 
         let feeder_handle = thread::spawn(move || {
@@ -266,6 +1805,10 @@ impl AudioPipeline {
             let mut engine = ConvolutionEngine::new(block_size);
             // Point the engine's IR at the shared handle so hot-swaps are visible.
             engine.impulse_response = feeder_ir;
+            let mut second_engine = ConvolutionEngine::new(block_size);
+            second_engine.impulse_response = feeder_second_ir;
+            let mut room_engine = ConvolutionEngine::new(block_size);
+            room_engine.impulse_response = feeder_room_ir;
 
             let params = feeder_pump.lock().unwrap_or_else(|e| e.into_inner()).clone();
             let mut pump = PumpSource::new(
@@ -275,37 +1818,166 @@ impl AudioPipeline {
                 actual_sample_rate,
             );
 
-            // Maximum ring buffer occupancy before we sleep (avoid unbounded growth).
-            let max_buffered = block_size * 8;
+            let (mut glide, mut second_glide) = (Glide::default(), Glide::default());
+            // Second pump and its level, while one is set
+            let mut second: Option<(PumpSource, f64)> = None;
+            let mut generator: Option<SignalGenerator> = None;
+            let mut player: Option<RecordingPlayer> = None;
+            let mut custom: Option<SharedSource> = None;
+            // Profile being played and how far into it playback is, in seconds
+            let mut profile: Option<(Arc<RpmProfile>, f64)> = None;
+            let mut notch = NotchFilter::new(actual_sample_rate);
+            let mut dry_wet = DryWetMix::new(actual_sample_rate);
+            let mut spatializer = Spatializer::new(actual_sample_rate);
 
             while feeder_running.load(Ordering::Relaxed) {
                 // Refresh pump parameters each block (cheap lock).
-                {
-                    let p = feeder_pump.lock().unwrap_or_else(|e| e.into_inner());
-                    pump.set_params(p.rpm, p.num_valves, p.duty_cycle);
-                }
+                let (gain, separate_muffler, mix) = {
+                    let mut p = feeder_pump.lock().unwrap_or_else(|e| e.into_inner());
+                    match (&p.profile, &mut profile) {
+                        (Some(next), Some((current, _))) if Arc::ptr_eq(next, current) => {}
+                        (Some(next), _) => profile = Some((Arc::clone(next), 0.0)),
+                        (None, _) => profile = None,
+                    }
+                    let rpm = profile.as_ref().map_or(p.rpm, |(profile, time)| profile.rpm_at(*time));
+                    p.profile_rpm = profile.is_some().then_some(rpm);
+                    let factor = if p.glide_time > 0.0 {
+                        1.0 - (-(block_size as f64) / (p.glide_time * actual_sample_rate)).exp()
+                    } else {
+                        1.0
+                    };
+                    let (rpm, duty_cycle) = glide.follow(rpm, p.duty_cycle, factor);
+                    pump.set_params(rpm, p.num_valves, duty_cycle);
+                    p.configure(&mut pump);
+                    match (p.second, &mut second) {
+                        (Some(settings), Some((other, level))) => {
+                            let (rpm, duty_cycle) = second_glide.follow(settings.rpm, settings.duty_cycle, factor);
+                            other.set_params(rpm, settings.num_valves, duty_cycle);
+                            p.configure(other);
+                            *level = settings.level;
+                        }
+                        (Some(settings), None) => {
+                            let mut other = PumpSource::new(
+                                settings.rpm,
+                                settings.num_valves,
+                                settings.duty_cycle,
+                                actual_sample_rate,
+                            );
+                            other.reseed(SECOND_PUMP_SEED);
+                            p.configure(&mut other);
+                            second_glide.follow(settings.rpm, settings.duty_cycle, factor);
+                            second = Some((other, settings.level));
+                        }
+                        (None, _) => {
+                            second = None;
+                            second_glide = Glide::default();
+                        }
+                    }
+                    notch.set_notch(p.notch);
+                    spatializer.set_position(p.position);
+                    match (p.test_signal, &mut generator) {
+                        (Some(signal), Some(gen)) => gen.set_signal(signal),
+                        (Some(signal), None) => {
+                            generator = Some(SignalGenerator::new(signal, actual_sample_rate))
+                        }
+                        (None, _) => generator = None,
+                    }
+                    match (&p.recording, &mut player) {
+                        (Some(recording), Some(current)) if Arc::ptr_eq(recording, current.recording()) => {}
+                        (Some(recording), _) => {
+                            player = Some(RecordingPlayer::new(Arc::clone(recording), actual_sample_rate))
+                        }
+                        (None, _) => player = None,
+                    }
+                    if let Some(player) = &mut player {
+                        player.set_pitch(p.recording_rpm.map_or(1.0, |recorded| rpm / recorded));
+                    }
+                    match (&p.source, &custom) {
+                        (Some(source), Some(current)) if Arc::ptr_eq(source, current) => {}
+                        (Some(source), _) => {
+                            let mut guard = source.lock().unwrap_or_else(|e| e.into_inner());
+                            guard.set_sample_rate(actual_sample_rate);
+                            drop(guard);
+                            custom = Some(Arc::clone(source));
+                        }
+                        (None, _) => custom = None,
+                    }
+                    (p.gain, p.separate_muffler, if p.bypass { 0.0 } else { p.mix })
+                };
 
                 // Check ring buffer level; if already full enough, sleep briefly.
                 {
                     let buf = feeder_ring.lock().unwrap_or_else(|e| e.into_inner());
                     if buf.len() >= max_buffered {
                         drop(buf);
-                        thread::sleep(std::time::Duration::from_millis(5));
+                        thread::sleep(Duration::from_millis(5));
                         continue;
                     }
                 }
 
                 // Generate and convolve a block.
-                let raw = pump.generate(block_size);
-                let processed = engine.process(&raw);
+                let started = Instant::now();
+                let pumping = generator.is_none() && custom.is_none() && player.is_none();
+                let mut custom_guard = custom
+                    .as_ref()
+                    .map(|source| source.lock().unwrap_or_else(|e| e.into_inner()));
+                let source: &mut dyn SampleSource = match (&mut generator, &mut custom_guard, &mut player) {
+                    (Some(gen), _, _) => gen,
+                    (None, Some(custom), _) => {
+                        custom.set_rpm(pump.rpm);
+                        custom.as_mut()
+                    }
+                    (None, None, Some(player)) => player,
+                    (None, None, None) => &mut pump,
+                };
+                let raw = source.generate(block_size);
+                drop(custom_guard);
+                // The second pump joins in only while the pump itself plays
+                let second_raw = second.as_mut().filter(|_| pumping).map(|(other, level)| {
+                    other.generate(block_size).into_iter().map(|x| x * *level).collect::<Vec<f64>>()
+                });
+                let mut dry = raw.clone();
+                if let Some(second_raw) = &second_raw {
+                    for (sample, other) in dry.iter_mut().zip(second_raw) {
+                        *sample += other;
+                    }
+                }
+                let mut muffled = match second_raw {
+                    Some(second_raw) if separate_muffler => {
+                        let mut muffled = engine.process(&raw);
+                        for (sample, other) in muffled.iter_mut().zip(second_engine.process(&second_raw)) {
+                            *sample += other;
+                        }
+                        muffled
+                    }
+                    Some(_) => engine.process(&dry),
+                    None => engine.process(&raw),
+                };
+                // Motor noise through the housing skips the muffler
+                for ((sample, unmuffled), direct) in muffled.iter_mut().zip(&mut dry).zip(pump.take_bypass()) {
+                    *sample += direct;
+                    *unmuffled += direct;
+                }
+                if let Some((other, level)) = second.as_mut().filter(|_| pumping) {
+                    for ((sample, unmuffled), direct) in muffled.iter_mut().zip(&mut dry).zip(other.take_bypass()) {
+                        *sample += direct * *level;
+                        *unmuffled += direct * *level;
+                    }
+                }
+                if let Some((_, time)) = &mut profile {
+                    *time += block_size as f64 / actual_sample_rate;
+                }
+                let heard = room_engine.process(&notch.process(&dry_wet.process(&dry, &muffled, mix)));
+                let processed = spatializer.process(&heard);
 
                 // Push into ring buffer.
                 {
                     let mut buf = feeder_ring.lock().unwrap_or_else(|e| e.into_inner());
-                    for &s in &processed {
-                        buf.push_back(s);
+                    for &[left, right] in &processed {
+                        buf.push_back([left * gain, right * gain]);
                     }
                 }
+                feeder_stats.lock().unwrap_or_else(|e| e.into_inner()).record_block(started.elapsed());
             }
         });
         self.feeder_handle = Some(feeder_handle);
@@ -313,80 +1985,45 @@ impl AudioPipeline {
         // -- cpal stream callback ---------------------------------------------
         let cb_ring = Arc::clone(&ring);
         let cb_volume = Arc::clone(&self.volume);
-
-        let err_fn = |err: cpal::StreamError| {
-            eprintln!("cpal stream error: {err}");
-        };
-
-        let stream = match sample_format {
-            SampleFormat::F32 => device
-                .build_output_stream(
-                    &config,
-                    move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
-                        let vol = *cb_volume.lock().unwrap_or_else(|e| e.into_inner());
-                        let mut buf = cb_ring.lock().unwrap_or_else(|e| e.into_inner());
-                        for frame in data.chunks_mut(channels) {
-                            let sample = buf.pop_front().unwrap_or(0.0) * vol;
-                            let out = sample as f32;
-                            for s in frame.iter_mut() {
-                                *s = out;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .expect("Failed to build f32 output stream"),
-            SampleFormat::I16 => device
-                .build_output_stream(
-                    &config,
-                    move |data: &mut [i16], _: &cpal::OutputCallbackInfo| {
-                        let vol = *cb_volume.lock().unwrap_or_else(|e| e.into_inner());
-                        let mut buf = cb_ring.lock().unwrap_or_else(|e| e.into_inner());
-                        for frame in data.chunks_mut(channels) {
-                            let sample = buf.pop_front().unwrap_or(0.0) * vol;
-                            let out = (sample * i16::MAX as f64) as i16;
-                            for s in frame.iter_mut() {
-                                *s = out;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .expect("Failed to build i16 output stream"),
-            SampleFormat::U16 => device
-                .build_output_stream(
-                    &config,
-                    move |data: &mut [u16], _: &cpal::OutputCallbackInfo| {
-                        let vol = *cb_volume.lock().unwrap_or_else(|e| e.into_inner());
-                        let mut buf = cb_ring.lock().unwrap_or_else(|e| e.into_inner());
-                        for frame in data.chunks_mut(channels) {
-                            let sample = buf.pop_front().unwrap_or(0.0) * vol;
-                            let out =
-                                ((sample * 0.5 + 0.5) * u16::MAX as f64) as u16;
-                            for s in frame.iter_mut() {
-                                *s = out;
-                            }
-                        }
-                    },
-                    err_fn,
-                    None,
-                )
-                .expect("Failed to build u16 output stream"),
-            _ => {
-                eprintln!("Unsupported sample format: {sample_format:?}; audio will not play");
-                // Stop the feeder thread we just spawned since we can't play.
-                self.feeder_running.store(false, Ordering::Relaxed);
-                if let Some(handle) = self.feeder_handle.take() {
-                    let _ = handle.join();
+        let cb_levels = Arc::clone(&self.levels);
+        let cb_stats = Arc::clone(&self.stats);
+        let cb_fading_out = Arc::clone(&self.fading_out);
+        let cb_faded_out = Arc::clone(&self.faded_out);
+        let mut stage = OutputStage::new(actual_sample_rate);
+        self.fading_out.store(false, Ordering::Relaxed);
+        self.faded_out.store(false, Ordering::Relaxed);
+
+        let render = move |data: &mut [f32]| {
+            let started = Instant::now();
+            let vol = *cb_volume.lock().unwrap_or_else(|e| e.into_inner());
+            let mut buf = cb_ring.lock().unwrap_or_else(|e| e.into_inner());
+            let buffered = buf.len();
+            let fading_out = cb_fading_out.load(Ordering::Relaxed);
+            for frame in data.chunks_mut(channels) {
+                let sample = stage.next_frame(&mut buf, vol, fading_out);
+                for (channel, s) in frame.iter_mut().enumerate() {
+                    *s = channel_sample(sample, channel, channels) as f32;
                 }
-                return;
             }
+            stage.publish(&cb_levels);
+            cb_faded_out.store(fading_out && stage.fade.is_silent(), Ordering::Relaxed);
+            cb_stats.lock().unwrap_or_else(|e| e.into_inner()).record_callback(
+                buffered,
+                data.len() / channels,
+                actual_sample_rate,
+                started.elapsed(),
+            );
         };
 
-        stream.play().expect("Failed to start cpal stream");
-        self.stream = Some(stream);
+        if let Err(e) = self.backend.start(Box::new(render)) {
+            eprintln!("{e}; audio will not play");
+            // Stop the feeder thread we just spawned since we can't play.
+            self.feeder_running.store(false, Ordering::Relaxed);
+            if let Some(handle) = self.feeder_handle.take() {
+                let _ = handle.join();
+            }
+            return;
+        }
         self.playing.store(true, Ordering::Relaxed);
     }
 
@@ -396,17 +2033,26 @@ impl AudioPipeline {
             return;
         }
 
+        // Fade out before the stream stops, waiting no longer than a
+        // device would plausibly take to ask for the fade.
+        self.fading_out.store(true, Ordering::Relaxed);
+        let deadline = Instant::now() + Duration::from_secs_f64(FADE_TIME) + Duration::from_millis(200);
+        while !self.faded_out.load(Ordering::Relaxed) && Instant::now() < deadline {
+            thread::sleep(Duration::from_millis(2));
+        }
+
         // Signal feeder to exit.
         self.feeder_running.store(false, Ordering::Relaxed);
 
-        // Drop the stream first (stops the callback).
-        self.stream.take();
+        // Stop the output first (stops the callback).
+        self.backend.stop();
 
         // Join the feeder thread.
         if let Some(handle) = self.feeder_handle.take() {
             let _ = handle.join();
         }
 
+        *self.levels.lock().unwrap_or_else(|e| e.into_inner()) = OutputLevels::default();
         self.playing.store(false, Ordering::Relaxed);
     }
 }
@@ -446,10 +2092,7 @@ mod tests {
         // Use a two-sample IR [0.5, 0.5] (simple low-pass) and process two
         // consecutive blocks.  Verify that the boundary sample is correct.
         let mut engine = ConvolutionEngine::new(4);
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![0.5, 0.5];
-        }
+        engine.impulse_response.set(vec![0.5, 0.5]);
 
         let block1 = vec![1.0, 0.0, 0.0, 0.0];
         let out1 = engine.process(&block1);
@@ -476,10 +2119,7 @@ mod tests {
     #[test]
     fn test_convolution_empty_ir() {
         let mut engine = ConvolutionEngine::new(4);
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![];
-        }
+        engine.impulse_response.set(vec![]);
         let input = vec![1.0, 2.0, 3.0, 4.0];
         let output = engine.process(&input);
         assert_eq!(output.len(), 4);
@@ -488,14 +2128,42 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_convolution_long_ir() {
+        // A response tens of thousands of taps long, here a delayed echo,
+        // still comes out exact through the partitioned convolution
+        let mut engine = ConvolutionEngine::new(512);
+        let mut ir = vec![0.0; 32_768];
+        ir[0] = 1.0;
+        ir[20_000] = 0.5;
+        engine.impulse_response.set(ir);
+        let input: Vec<f64> = (0..48 * 512).map(|i| if i % 3000 == 7 { 1.0 } else { 0.0 }).collect();
+        let output: Vec<f64> = input.chunks(512).flat_map(|block| engine.process(block)).collect();
+        for (n, y) in output.iter().enumerate() {
+            let expected = input[n] + if n >= 20_000 { 0.5 * input[n - 20_000] } else { 0.0 };
+            assert!((y - expected).abs() < 1e-9, "sample {n}: {y} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_ir_handle_counts_swaps() {
+        // A swap is partitioned as it is set and picked up by generation;
+        // setting the same response again is no swap
+        let engine = ConvolutionEngine::new(8);
+        let handle = engine.ir_handle();
+        assert!(handle.newer_than(0).is_none());
+        handle.set(vec![0.5; 20]);
+        let (generation, partitions) = handle.newer_than(0).unwrap();
+        assert_eq!(partitions.len(), 3);
+        handle.set(vec![0.5; 20]);
+        assert!(handle.newer_than(generation).is_none());
+    }
+
     #[test]
     fn test_convolution_single_sample_ir() {
         // IR = [2.0] should scale input by 2.
         let mut engine = ConvolutionEngine::new(4);
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![2.0];
-        }
+        engine.impulse_response.set(vec![2.0]);
         let input = vec![1.0, 2.0, 3.0, 4.0];
         let output = engine.process(&input);
         assert_eq!(output.len(), 4);
@@ -531,11 +2199,168 @@ mod tests {
     fn test_pipeline_swap_ir() {
         let pipeline = AudioPipeline::new();
         let new_ir = vec![0.5, 0.3, 0.1];
-        pipeline.swap_ir(new_ir.clone());
-        let stored = pipeline.ir_handle.lock().unwrap().clone();
+        pipeline.swap_ir(new_ir.clone(), 44_100.0);
+        let stored = pipeline.ir_handle.get();
         assert_eq!(stored, new_ir);
     }
 
+    #[test]
+    fn test_pipeline_resamples_ir_to_device_rate() {
+        // On a 48 kHz device a 44.1 kHz IR is stretched by 48/44.1 in
+        // samples and re-resampled when the device rate changes
+        let mut pipeline = AudioPipeline::new();
+        pipeline.sample_rate = 48_000.0;
+        pipeline.swap_ir(vec![0.01; 441], 44_100.0);
+        assert_eq!(pipeline.ir_handle.get().len(), 480);
+        pipeline.sample_rate = 96_000.0;
+        pipeline.install_ir();
+        assert_eq!(pipeline.ir_handle.get().len(), 960);
+    }
+
+    #[test]
+    fn test_pipeline_set_source() {
+        struct Silence;
+        impl SampleSource for Silence {
+            fn generate(&mut self, count: usize) -> Vec<f64> {
+                vec![0.0; count]
+            }
+        }
+        let pipeline = AudioPipeline::new();
+        pipeline.set_source(Some(Box::new(Silence)));
+        let source = pipeline.pump_params.lock().unwrap().source.clone().unwrap();
+        assert_eq!(source.lock().unwrap().generate(3), vec![0.0; 3]);
+        pipeline.set_source(None);
+        assert!(pipeline.pump_params.lock().unwrap().source.is_none());
+    }
+
+    #[test]
+    fn test_live_input_resamples_and_waits_for_input() {
+        // Stereo input mixed down to mono, keeping the newest 8 samples
+        let ring = Arc::new(Mutex::new(VecDeque::new()));
+        let stereo: Vec<f32> = (0..10).flat_map(|i| [i as f32, i as f32 + 1.0]).collect();
+        push_input(&ring, &stereo, 2, 8, |s| s as f64);
+        assert_eq!(*ring.lock().unwrap(), (2..10).map(|i| i as f64 + 0.5).collect::<VecDeque<f64>>());
+
+        // Input at twice the output rate: every other sample, then silence
+        // until more input arrives
+        let mut input = LiveInput {
+            ring: Arc::clone(&ring),
+            input_rate: 1000.0,
+            sample_rate: 2000.0,
+            position: 0.0,
+        };
+        input.set_sample_rate(500.0);
+        assert_eq!(input.generate(5), vec![2.5, 4.5, 6.5, 8.5, 0.0]);
+        assert_eq!(ring.lock().unwrap().len(), 0);
+        push_input(&ring, &[10.0f32, 11.0, 12.0], 1, 8, |s| s as f64);
+        assert_eq!(input.generate(1), vec![10.0]);
+    }
+
+    #[test]
+    fn test_sample_format_conversion() {
+        let rendered = [0.5f32, -1.0, 0.0];
+        let mut wide = [0i32; 3];
+        convert_samples(&rendered, &mut wide);
+        assert_eq!(wide, [1 << 30, i32::MIN, 0]);
+        let mut narrow = [0u8; 3];
+        convert_samples(&rendered, &mut narrow);
+        assert_eq!(narrow, [192, 0, 128]);
+        let mut double = [0f64; 3];
+        convert_samples(&rendered, &mut double);
+        assert_eq!(double, [0.5, -1.0, 0.0]);
+
+        // Input of any format reaches the live input ring as f64
+        let ring = Mutex::new(VecDeque::new());
+        push_input(&ring, &[1i32 << 30, i32::MIN], 1, 8, cpal::Sample::to_sample::<f64>);
+        assert_eq!(*ring.lock().unwrap(), vec![0.5, -1.0]);
+    }
+
+    #[test]
+    fn test_pipeline_plays_into_null_backend() {
+        let backend = NullBackend::new(8000.0, 2);
+        let sink = backend.sink();
+        let mut pipeline = AudioPipeline::with_backend(Box::new(backend));
+        assert!(sink.pull(16).is_none());
+        pipeline.play();
+        assert!(pipeline.is_playing());
+
+        // Pull at most a few blocks at a time, well behind the feeder
+        let pull = || {
+            thread::sleep(Duration::from_millis(20));
+            sink.pull(256).unwrap()
+        };
+        let rms = |volume: f64| {
+            pipeline.set_volume(volume);
+            // Let the fade in and any volume change pass
+            let samples: Vec<f32> = (0..20).flat_map(|_| pull()).skip(2 * 800).collect();
+            assert!(samples.chunks(2).all(|frame| frame[0] == frame[1]));
+            (samples.iter().map(|&s| (s as f64).powi(2)).sum::<f64>() / samples.len() as f64).sqrt()
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while pull().iter().all(|&s| s == 0.0) {
+            assert!(Instant::now() < deadline, "the feeder never produced output");
+        }
+        let (loud, quiet) = (rms(0.4), rms(0.2));
+        assert!(loud > 0.01);
+        assert!((loud / quiet - 2.0).abs() < 0.05, "{loud} vs {quiet}");
+        assert!(pipeline.output_levels().peak <= LIMIT);
+
+        pipeline.stop();
+        assert!(!pipeline.is_playing());
+        assert!(sink.pull(16).is_none());
+    }
+
+    #[test]
+    fn test_pipeline_set_rpm_profile() {
+        let pipeline = AudioPipeline::new();
+        let ramp = RpmProfile::ramp(1000.0, 5000.0, 4.0, 1.0, 4.0);
+        pipeline.set_rpm_profile(Some(ramp.clone()));
+        let first = pipeline.pump_params.lock().unwrap().profile.clone().unwrap();
+        // The same profile keeps running; a new one starts over
+        pipeline.set_rpm_profile(Some(ramp.clone()));
+        assert!(Arc::ptr_eq(&first, pipeline.pump_params.lock().unwrap().profile.as_ref().unwrap()));
+        pipeline.set_rpm_profile(Some(RpmProfile { looped: true, ..ramp }));
+        assert!(!Arc::ptr_eq(&first, pipeline.pump_params.lock().unwrap().profile.as_ref().unwrap()));
+        pipeline.set_rpm_profile(None);
+        assert!(pipeline.pump_params.lock().unwrap().profile.is_none());
+        assert_eq!(pipeline.profile_rpm(), None);
+    }
+
+    #[test]
+    fn test_pipeline_second_pump() {
+        let pipeline = AudioPipeline::new();
+        let second = SecondPump::default();
+        pipeline.configure_pump(&SimParams {
+            second_pump: Some(second),
+            ..SimParams::default()
+        });
+        assert_eq!(pipeline.pump_params.lock().unwrap().second, Some(second));
+
+        // A muffler of its own is resampled to the output rate; dropping it
+        // shares the first pump's again
+        pipeline.swap_second_ir(Some((vec![1.0, 0.5], 44_100.0)));
+        assert!(pipeline.pump_params.lock().unwrap().separate_muffler);
+        assert_eq!(pipeline.second_ir_handle.get(), vec![1.0, 0.5]);
+        pipeline.swap_second_ir(Some((vec![f64::NAN], 44_100.0)));
+        assert_eq!(pipeline.second_ir_handle.get(), vec![1.0, 0.5]);
+        pipeline.swap_second_ir(None);
+        assert!(!pipeline.pump_params.lock().unwrap().separate_muffler);
+    }
+
+    #[test]
+    fn test_glide_follows_settings() {
+        let mut glide = Glide::default();
+        assert_eq!(glide.follow(1000.0, 0.5, 0.5), (1000.0, 0.5));
+        let (rpm, duty_cycle) = glide.follow(2000.0, 0.3, 0.5);
+        assert!((rpm - 1500.0).abs() < 1e-9 && (duty_cycle - 0.4).abs() < 1e-12);
+        assert_eq!(glide.follow(3000.0, 0.3, 1.0), (3000.0, 0.3));
+
+        let pipeline = AudioPipeline::new();
+        assert_eq!(pipeline.pump_params.lock().unwrap().glide_time, GLIDE_TIME);
+        pipeline.set_glide_time(-1.0);
+        assert_eq!(pipeline.pump_params.lock().unwrap().glide_time, 0.0);
+    }
+
     #[test]
     fn test_pipeline_set_pump_params() {
         let pipeline = AudioPipeline::new();
@@ -546,6 +2371,240 @@ mod tests {
         assert!((p.duty_cycle - 0.3).abs() < 1e-12);
     }
 
+    #[test]
+    fn test_dry_wet_mix_matches_levels() {
+        // A loud dry tone and a quiet wet one of another frequency
+        let sample_rate = 8000.0;
+        let tone = |amplitude: f64, frequency: f64| -> Vec<f64> {
+            (0..8000)
+                .map(|i| amplitude * (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin())
+                .collect()
+        };
+        let (dry, wet) = (tone(1.0, 100.0), tone(0.1, 300.0));
+        let rms = |samples: &[f64]| (samples.iter().map(|x| x * x).sum::<f64>() / samples.len() as f64).sqrt();
+        let mut mix = DryWetMix::new(sample_rate);
+        assert_eq!(mix.process(&dry, &wet, 1.0), wet);
+
+        // Bypassed, the dry tone settles at the wet tone's level
+        let mut mix = DryWetMix::new(sample_rate);
+        mix.process(&dry, &wet, 0.0);
+        let bypassed = mix.process(&dry, &wet, 0.0);
+        assert!((rms(&bypassed) / rms(&wet) - 1.0).abs() < 0.02, "{}", rms(&bypassed));
+
+        // Switching ramps over the block rather than jumping
+        let switched = mix.process(&dry[..100], &wet[..100], 1.0);
+        assert!((switched[99] - wet[99]).abs() < 1e-12);
+        assert!(switched.windows(2).all(|pair| (pair[1] - pair[0]).abs() < 0.05));
+    }
+
+    #[test]
+    fn test_limiter_holds_peaks_below_full_scale() {
+        let sample_rate = 8000.0;
+        let tone = |amplitude: f64| -> Vec<f64> {
+            (0..4000)
+                .map(|i| amplitude * (2.0 * std::f64::consts::PI * 200.0 * i as f64 / sample_rate).sin())
+                .collect()
+        };
+        let mut limiter = Limiter::new(sample_rate);
+        // A quiet signal passes untouched
+        let quiet = tone(0.5);
+        assert_eq!(quiet.iter().map(|&x| limiter.process(x)).collect::<Vec<f64>>(), quiet);
+        assert_eq!(limiter.take_reduction(), 0.0);
+
+        // A signal twice full scale is held at the limit, about 7 dB down
+        let limited: Vec<f64> = tone(2.0).iter().map(|&x| limiter.process(x)).collect();
+        assert!(limited.iter().all(|x| x.abs() <= LIMIT + 1e-12));
+        assert!(limited.iter().any(|x| x.abs() > 0.99 * LIMIT));
+        let reduction = limiter.take_reduction();
+        assert!((reduction - 20.0 * (2.0 / LIMIT).log10()).abs() < 0.01, "{reduction}");
+
+        // and the gain recovers once the peaks are gone
+        let recovered: Vec<f64> = tone(0.5).iter().map(|&x| limiter.process(x)).collect();
+        assert!((recovered[3999] - quiet[3999]).abs() < 1e-9);
+
+        // A stereo frame is turned down as a whole, keeping its balance
+        let mut limiter = Limiter::new(sample_rate);
+        assert_eq!(limiter.process_frame([1.8, 0.3]), [LIMIT, LIMIT / 6.0]);
+    }
+
+    #[test]
+    fn test_output_fades_in_and_out() {
+        // 20 ms at 1 kHz is 20 samples
+        let mut fade = OutputFade::new(1000.0);
+        assert!(fade.is_silent());
+        let rising: Vec<f64> = (0..25).map(|_| fade.next(false)).collect();
+        assert!(rising.windows(2).all(|pair| pair[1] >= pair[0]));
+        assert!(rising[0] < 0.01 && (rising[9] - 0.5).abs() < 1e-9);
+        assert_eq!(rising[19..], [1.0; 6]);
+
+        let falling: Vec<f64> = (0..20).map(|_| fade.next(true)).collect();
+        assert!(falling.windows(2).all(|pair| pair[1] <= pair[0]));
+        assert!(fade.is_silent());
+
+        // The output stage fades in again after running dry
+        let mut stage = OutputStage::new(1000.0);
+        let mut buf: VecDeque<[f64; 2]> = vec![[0.5, 0.5]; 40].into();
+        let played: Vec<f64> = (0..30).map(|_| stage.next_frame(&mut buf, 1.0, false)[0]).collect();
+        assert_eq!(played[29], 0.5);
+        buf.clear();
+        assert_eq!(stage.next_frame(&mut buf, 1.0, false), [0.0; 2]);
+        buf.push_back([0.5, 0.5]);
+        assert!(stage.next_frame(&mut buf, 1.0, false)[0] < 0.01);
+    }
+
+    #[test]
+    fn test_spatializer_places_source_to_the_side() {
+        let sample_rate = 48_000.0;
+        let mut spatializer = Spatializer::new(sample_rate);
+        assert_eq!(spatializer.process(&[1.0, 0.0]), vec![[1.0, 1.0], [0.0, 0.0]]);
+
+        // Hard right: the left ear hears the click 0.66 ms later
+        let position = SourcePosition { azimuth: 90.0, distance: 1.0 };
+        spatializer.set_position(Some(position));
+        let mut click = vec![0.0; 512];
+        click[0] = 1.0;
+        let output = spatializer.process(&click);
+        assert!(output[0][1] > 0.0);
+        let first = |channel: usize| output.iter().position(|frame| frame[channel] != 0.0).unwrap();
+        assert_eq!((first(0), first(1)), (31, 0));
+
+        // The head shadows the treble but passes the bass
+        let level_difference = |frequency: f64, distance: f64| {
+            let mut spatializer = Spatializer::new(sample_rate);
+            spatializer.set_position(Some(SourcePosition { distance, ..position }));
+            let tone: Vec<f64> = (0..9600)
+                .map(|i| (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin())
+                .collect();
+            let output = spatializer.process(&tone);
+            let rms = |channel: usize| {
+                let tail = &output[4800..];
+                (tail.iter().map(|frame| frame[channel].powi(2)).sum::<f64>() / tail.len() as f64).sqrt()
+            };
+            (20.0 * (rms(1) / rms(0)).log10(), rms(1))
+        };
+        let (bass, near) = level_difference(100.0, 1.0);
+        let (treble, _) = level_difference(8000.0, 1.0);
+        assert!(bass.abs() < 1.0, "{bass}");
+        assert!(treble > 10.0, "{treble}");
+        // Twice as far is 6 dB quieter
+        let (_, far) = level_difference(100.0, 2.0);
+        assert!((far / near - 0.5).abs() < 1e-9);
+
+        assert!(SourcePosition { distance: 0.0, ..position }.validate().is_err());
+    }
+
+    #[test]
+    fn test_block_meter_levels_and_clip() {
+        let levels = Mutex::new(OutputLevels::default());
+        let mut meter = BlockMeter::default();
+        // A full-scale square wave measured after a limiter at half gain
+        for i in 0..100 {
+            let x = if i % 2 == 0 { 1.0 } else { -1.0 };
+            meter.add(x, 0.5 * x);
+        }
+        meter.publish(&levels, 6.0);
+        let measured = *levels.lock().unwrap();
+        assert_eq!(measured.peak, 0.5);
+        assert!((measured.rms - 0.5).abs() < 1e-12);
+        assert!((measured.peak_db() + 6.02).abs() < 0.01);
+        assert_eq!(measured.gain_reduction, 6.0);
+        assert!(!measured.clipped);
+
+        // An over stays latched through quieter blocks
+        meter.add(1.5, 0.9);
+        meter.publish(&levels, 4.0);
+        meter.add(0.1, 0.1);
+        meter.publish(&levels, 0.0);
+        let measured = *levels.lock().unwrap();
+        assert!(measured.clipped);
+        assert_eq!(measured.peak, 0.1);
+        assert_eq!(measured.gain_reduction, 0.0);
+    }
+
+    #[test]
+    fn test_pipeline_stats_count_underruns_and_load() {
+        let mut stats = PipelineStats {
+            block_duration: 0.01,
+            ..PipelineStats::default()
+        };
+        // A short buffer before the first block is the start-up
+        stats.record_callback(0, 256, 25_600.0, Duration::from_micros(50));
+        assert_eq!(stats.underruns, 0);
+
+        stats.record_block(Duration::from_millis(4));
+        stats.record_block(Duration::from_millis(8));
+        assert!((stats.feeder_load() - 0.6).abs() < 1e-9);
+        assert!((stats.peak_feeder_load() - 0.8).abs() < 1e-9);
+
+        stats.record_callback(1000, 256, 25_600.0, Duration::from_micros(20));
+        stats.record_callback(100, 256, 25_600.0, Duration::from_micros(80));
+        assert_eq!((stats.callbacks, stats.underruns, stats.missing_samples), (3, 1, 156));
+        assert_eq!(stats.buffered, 100);
+        assert!((stats.callback_duration - 0.01).abs() < 1e-12);
+        assert!((stats.callback_time_max - 80e-6).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_partitioned_convolution_matches_direct() {
+        // A response over three and a bit blocks, through the partitioned
+        // convolver and a direct sum
+        let block_size = 64;
+        let ir: Vec<f64> = (0..200).map(|i| (-(i as f64) / 50.0).exp() * ((i * 7 % 11) as f64 - 5.0)).collect();
+        let input: Vec<f64> = (0..6 * block_size).map(|i| ((i * 13 % 17) as f64 - 8.0) / 8.0).collect();
+        let mut partitioned = PartitionedConvolver::new(&ir, block_size);
+        let output: Vec<f64> = input.chunks(block_size).flat_map(|block| partitioned.process(block)).collect();
+        for (n, y) in output.iter().enumerate() {
+            let expected: f64 = ir.iter().take(n + 1).enumerate().map(|(k, h)| h * input[n - k]).sum();
+            assert!((y - expected).abs() < 1e-9, "sample {n}: {y} != {expected}");
+        }
+    }
+
+    #[test]
+    fn test_pipeline_swap_room_ir() {
+        let pipeline = AudioPipeline::new();
+        assert!(pipeline.swap_room_ir(Some((vec![0.0; 4], 44_100.0))).is_err());
+        assert!(pipeline.swap_room_ir(Some((vec![1.0, f64::NAN], 44_100.0))).is_err());
+        assert_eq!(pipeline.room_ir_handle.get(), vec![1.0]);
+
+        // Scaled to unit energy at the output rate
+        pipeline.swap_room_ir(Some((vec![3.0, 4.0], 44_100.0))).unwrap();
+        let room = pipeline.room_ir_handle.get();
+        assert!((room[0] - 0.6).abs() < 1e-12 && (room[1] - 0.8).abs() < 1e-12);
+        pipeline.swap_room_ir(None).unwrap();
+        assert_eq!(pipeline.room_ir_handle.get(), vec![1.0]);
+    }
+
+    #[test]
+    fn test_notch_filter_mutes_only_its_band() {
+        let sample_rate = 44_100.0;
+        let rms_through = |notch: Option<Notch>, frequency: f64| {
+            let mut filter = NotchFilter::new(sample_rate);
+            filter.set_notch(notch);
+            let input: Vec<f64> = (0..22_050)
+                .map(|i| (2.0 * std::f64::consts::PI * frequency * i as f64 / sample_rate).sin())
+                .collect();
+            // Skip the first half while the filter settles
+            let output = filter.process(&input);
+            let tail = &output[11_025..];
+            (tail.iter().map(|s| s * s).sum::<f64>() / tail.len() as f64).sqrt()
+        };
+        let notch = Some(Notch {
+            frequency: 150.0,
+            bandwidth: 20.0,
+        });
+        let unity = rms_through(None, 150.0);
+        assert!((unity - 0.5f64.sqrt()).abs() < 1e-3);
+        assert!(rms_through(notch, 150.0) < 0.01 * unity);
+        assert!(rms_through(notch, 300.0) > 0.95 * unity);
+        assert!(rms_through(notch, 75.0) > 0.95 * unity);
+        // Out of range notches pass everything
+        let beyond = Some(Notch {
+            frequency: 30_000.0,
+            bandwidth: 20.0,
+        });
+        assert!((rms_through(beyond, 150.0) - unity).abs() < 1e-9);
+    }
+
     // -----------------------------------------------------------------------
     // Test Group 3: Convolution engine edge cases
     // -----------------------------------------------------------------------
@@ -574,10 +2633,7 @@ mod tests {
         assert_eq!(out1.len(), 8);
 
         // Hot-swap to a longer IR
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![0.25, 0.25, 0.25, 0.25];
-        }
+        engine.impulse_response.set(vec![0.25, 0.25, 0.25, 0.25]);
 
         // Block 2 after swap
         let block2 = vec![1.0; 8];
@@ -585,10 +2641,7 @@ mod tests {
         assert_eq!(out2.len(), 8);
 
         // Hot-swap to a very short IR
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![0.5];
-        }
+        engine.impulse_response.set(vec![0.5]);
 
         // Block 3 after another swap
         let block3 = vec![2.0; 8];
@@ -596,10 +2649,7 @@ mod tests {
         assert_eq!(out3.len(), 8);
 
         // Hot-swap to empty IR
-        {
-            let mut ir = engine.impulse_response.lock().unwrap();
-            *ir = vec![];
-        }
+        engine.impulse_response.set(vec![]);
 
         // Block 4 with empty IR
         let block4 = vec![1.0; 8];
@@ -610,6 +2660,36 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_convolution_ir_swap_crossfades() {
+        // A swap mid-stream ramps from the old gain to the new one over
+        // four blocks instead of jumping
+        let mut engine = ConvolutionEngine::new(8);
+        engine.process(&[1.0; 8]);
+        engine.impulse_response.set(vec![0.5]);
+        let ramp: Vec<f64> = (0..4).flat_map(|_| engine.process(&[1.0; 8])).collect();
+        assert!(ramp[0] > 0.98);
+        assert!(ramp.windows(2).all(|pair| pair[1] <= pair[0] && pair[0] - pair[1] < 0.02), "{ramp:?}");
+        assert!((engine.process(&[1.0; 8])[0] - 0.5).abs() < 1e-12);
+
+        // Swapping again mid-fade carries on from the blend, and once the
+        // fade is over the output is the full convolution with the new IR,
+        // tail of the earlier input included
+        let input: Vec<f64> = (0..64).map(|i| ((i * 7) % 5) as f64 - 2.0).collect();
+        let mut engine = ConvolutionEngine::new(8);
+        let mut output = engine.process(&input[..8]);
+        engine.impulse_response.set(vec![0.25; 4]);
+        output.extend(engine.process(&input[8..16]));
+        engine.impulse_response.set(vec![0.5, -0.5, 0.25]);
+        for block in input[16..].chunks(8) {
+            output.extend(engine.process(block));
+        }
+        for i in 56..64 {
+            let expected = 0.5 * input[i] - 0.5 * input[i - 1] + 0.25 * input[i - 2];
+            assert!((output[i] - expected).abs() < 1e-12, "sample {i}: {} != {expected}", output[i]);
+        }
+    }
+
     #[test]
     fn test_convolution_output_length_matches_input_per_block() {
         // Verify that for various block sizes and IR lengths, the output
@@ -620,10 +2700,7 @@ mod tests {
         for &bs in &block_sizes {
             for &ir_len in &ir_lengths {
                 let mut engine = ConvolutionEngine::new(bs);
-                {
-                    let mut ir = engine.impulse_response.lock().unwrap();
-                    *ir = vec![1.0 / ir_len as f64; ir_len];
-                }
+                engine.impulse_response.set(vec![1.0 / ir_len as f64; ir_len]);
 
                 // Process multiple blocks
                 for block_num in 0..3 {
@@ -0,0 +1,250 @@
+use crate::elements::CrossSection;
+use crate::muffler::Muffler;
+use crate::SimParams;
+use num_complex::Complex64;
+use std::f64::consts::PI;
+
+/// Poisson's ratio assumed for every shell material.
+const POISSON_RATIO: f64 = 0.35;
+
+/// Wall material of the chamber shell. Printed parts are taken as solid
+/// (100 % infill) walls.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShellMaterial {
+    Pla,
+    Petg,
+    Abs,
+    Nylon,
+    Aluminium,
+    Brass,
+}
+
+impl ShellMaterial {
+    /// Every material, for selectors.
+    pub const ALL: [ShellMaterial; 6] = [
+        ShellMaterial::Pla,
+        ShellMaterial::Petg,
+        ShellMaterial::Abs,
+        ShellMaterial::Nylon,
+        ShellMaterial::Aluminium,
+        ShellMaterial::Brass,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            ShellMaterial::Pla => "PLA",
+            ShellMaterial::Petg => "PETG",
+            ShellMaterial::Abs => "ABS",
+            ShellMaterial::Nylon => "Nylon",
+            ShellMaterial::Aluminium => "Aluminium",
+            ShellMaterial::Brass => "Brass",
+        }
+    }
+
+    /// Density in kg/m³.
+    pub fn density(self) -> f64 {
+        match self {
+            ShellMaterial::Pla => 1240.0,
+            ShellMaterial::Petg => 1270.0,
+            ShellMaterial::Abs => 1040.0,
+            ShellMaterial::Nylon => 1010.0,
+            ShellMaterial::Aluminium => 2700.0,
+            ShellMaterial::Brass => 8500.0,
+        }
+    }
+
+    /// Young's modulus in Pa.
+    pub fn youngs_modulus(self) -> f64 {
+        match self {
+            ShellMaterial::Pla => 3.5e9,
+            ShellMaterial::Petg => 2.1e9,
+            ShellMaterial::Abs => 2.2e9,
+            ShellMaterial::Nylon => 1.7e9,
+            ShellMaterial::Aluminium => 69e9,
+            ShellMaterial::Brass => 100e9,
+        }
+    }
+
+    /// Structural damping loss factor.
+    pub fn loss_factor(self) -> f64 {
+        match self {
+            ShellMaterial::Pla | ShellMaterial::Petg | ShellMaterial::Abs => 0.03,
+            ShellMaterial::Nylon => 0.05,
+            ShellMaterial::Aluminium | ShellMaterial::Brass => 0.002,
+        }
+    }
+}
+
+/// The chamber's outer wall, through which sound breaks out of the duct
+/// and radiates directly.
+///
+/// The wall is a single-degree-of-freedom impedance per unit area,
+/// z_w = jωm·(1 − (f_s/f)²·(1 + jη)): mass-controlled above the stiffness
+/// frequency f_s and stiffness-controlled below it, where f_s is the ring
+/// frequency of a cylinder or the fundamental plate mode of a flat wall.
+/// The chamber's mean-square pressure drives the wall as a diffuse field
+/// would, so the result is an engineering estimate of a few dB.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Shell {
+    pub material: ShellMaterial,
+    /// Wall thickness in metres.
+    pub thickness: f64,
+}
+
+impl Shell {
+    /// Mass per unit wall area in kg/m².
+    pub fn surface_mass(&self) -> f64 {
+        self.material.density() * self.thickness
+    }
+
+    /// Stiffness frequency f_s in Hz of a chamber with the given section
+    /// and length: the ring frequency c_L/(πD) of a circular shell, or the
+    /// (1,1) mode of the widest flat wall, simply supported.
+    pub fn stiffness_frequency(&self, section: &CrossSection, length: f64) -> f64 {
+        let material = self.material;
+        let plate_speed = (material.youngs_modulus() / (material.density() * (1.0 - POISSON_RATIO.powi(2)))).sqrt();
+        match *section {
+            CrossSection::Circular { diameter } => plate_speed / (PI * diameter),
+            CrossSection::Annular { outer_diameter, .. } => plate_speed / (PI * outer_diameter),
+            CrossSection::Rectangular { width, height } => {
+                let side = width.max(height);
+                let bending = self.thickness * plate_speed / 12f64.sqrt();
+                PI / 2.0 * bending * (1.0 / side.powi(2) + 1.0 / length.powi(2))
+            }
+        }
+    }
+
+    /// Wall impedance per unit area in Pa·s/m at `frequency` Hz.
+    pub fn wall_impedance(&self, frequency: f64, stiffness_frequency: f64) -> Complex64 {
+        let omega = 2.0 * PI * frequency;
+        let stiffness = (stiffness_frequency / frequency).powi(2) * Complex64::new(1.0, self.material.loss_factor());
+        Complex64::new(0.0, omega * self.surface_mass()) * (1.0 - stiffness)
+    }
+
+    /// Transmission coefficient τ = 1/|1 + z_w/(2ρc)|² of the wall.
+    pub fn transmission_coefficient(&self, frequency: f64, stiffness_frequency: f64, c: f64, rho: f64) -> f64 {
+        if frequency <= 0.0 {
+            return 0.0;
+        }
+        let ratio = 1.0 + self.wall_impedance(frequency, stiffness_frequency) / (2.0 * rho * c);
+        1.0 / ratio.norm_sqr()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.thickness > 0.0 && self.thickness.is_finite()) {
+            return Err(format!("shell.thickness must be > 0, got {}", self.thickness));
+        }
+        Ok(())
+    }
+}
+
+/// Fraction of the incident sound power that breaks out through the shell
+/// of the muffler built from `params`, at `omega`. Zero without a shell.
+///
+/// The mean-square pressure along each chamber section follows from its
+/// plane-wave state, and the whole outer surface (side wall and both end
+/// plates) radiates τ·⟨|p|²⟩/(4ρc) per unit area.
+pub fn breakout_ratio(muffler: &Muffler, params: &SimParams, omega: f64, c: f64, rho: f64) -> f64 {
+    let Some(shell) = &params.shell else {
+        return 0.0;
+    };
+    let sections = muffler.chamber_sections();
+    if sections.is_empty() || omega <= 0.0 {
+        return 0.0;
+    }
+    let section = params.chamber_section();
+    let states = muffler.node_states(omega, c, rho);
+    let z_source = muffler.z_source;
+    let incident = (states[0].0 + z_source * states[0].1) / 2.0;
+    let incident_power = incident.norm_sqr() / z_source;
+
+    let k = omega / c;
+    let z_chamber = rho * c / section.area();
+    let (mut weighted, mut total_length) = (0.0, 0.0);
+    for &(index, length) in sections {
+        let (p, u) = states[index];
+        // Forward and backward waves, averaged over the section length
+        let forward = (p + z_chamber * u) / 2.0;
+        let backward = (p - z_chamber * u) / 2.0;
+        let j = Complex64::new(0.0, 1.0);
+        let kl = 2.0 * k * length;
+        let interference = (1.0 - (-j * kl).exp()) / (j * kl);
+        let mean_square =
+            forward.norm_sqr() + backward.norm_sqr() + 2.0 * (forward * backward.conj() * interference).re;
+        weighted += mean_square * length;
+        total_length += length;
+    }
+    let mean_square = weighted / total_length;
+
+    let frequency = omega / (2.0 * PI);
+    let tau = shell.transmission_coefficient(frequency, shell.stiffness_frequency(&section, params.chamber_length), c, rho);
+    let area = section.perimeter() * params.chamber_length + 2.0 * section.area();
+    tau * mean_square * area / (4.0 * rho * c) / incident_power
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute;
+
+    #[test]
+    fn test_breakout_limits_thin_walled_box() {
+        // A flat-walled printed box leaks far more than a brass tube
+        let side = 40e-3;
+        let box_params = |material, thickness| SimParams {
+            chamber_shape: Some(CrossSection::Rectangular {
+                width: side,
+                height: side,
+            }),
+            shell: Some(Shell { material, thickness }),
+            ..SimParams::default()
+        };
+        let plain = compute(&SimParams {
+            shell: None,
+            ..box_params(ShellMaterial::Pla, 1e-3)
+        })
+        .unwrap();
+        let thin = compute(&box_params(ShellMaterial::Pla, 0.6e-3)).unwrap();
+        let brass = compute(&box_params(ShellMaterial::Brass, 1e-3)).unwrap();
+        assert!(plain.breakout_loss.is_none());
+
+        let thin_breakout = thin.breakout_loss.as_ref().unwrap();
+        let brass_breakout = brass.breakout_loss.as_ref().unwrap();
+        let bin = thin.frequencies.iter().position(|&f| f > 5000.0).unwrap();
+        assert!(brass_breakout[bin] > thin_breakout[bin] + 15.0);
+
+        // Parallel paths: the combined TL never exceeds either one
+        let paths = plain.transmission_loss.iter().zip(thin_breakout);
+        for (combined, (duct, shell)) in thin.transmission_loss.iter().zip(paths).skip(1) {
+            assert!(*combined <= duct + 1e-9 && *combined <= shell + 1e-9);
+        }
+        // and the thin shell caps the first duct-borne peak
+        let first_peak = |result: &crate::SimResult| {
+            (1..result.frequencies.len())
+                .filter(|&i| result.frequencies[i] < 1600.0)
+                .map(|i| result.transmission_loss[i])
+                .fold(f64::MIN, f64::max)
+        };
+        assert!(first_peak(&thin) < first_peak(&plain) - 3.0);
+        assert!(compute(&box_params(ShellMaterial::Pla, 0.0)).is_err());
+    }
+
+    #[test]
+    fn test_shell_wall_regimes() {
+        let shell = Shell {
+            material: ShellMaterial::Pla,
+            thickness: 2e-3,
+        };
+        let tube = CrossSection::Circular { diameter: 40e-3 };
+        let ring = shell.stiffness_frequency(&tube, 0.08);
+        assert!((10e3..16e3).contains(&ring), "{ring}");
+
+        // Mass law well above f_s: +6 dB per doubling of frequency
+        let (c, rho) = (343.0, 1.2);
+        let tl = |f: f64| -10.0 * shell.transmission_coefficient(f, 100.0, c, rho).log10();
+        assert!((tl(8000.0) - tl(4000.0) - 6.0).abs() < 0.2);
+        // The stiff ring holds far more than mass law alone below f_s
+        let stiff = -10.0 * shell.transmission_coefficient(1000.0, ring, c, rho).log10();
+        assert!(stiff > tl(1000.0) + 30.0);
+    }
+}
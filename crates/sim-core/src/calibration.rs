@@ -0,0 +1,242 @@
+use crate::muffler::Muffler;
+use crate::SimParams;
+
+/// Empirical corrections fitted to a measurement of a built muffler, so
+/// that later simulations of the same printer/material workflow land
+/// closer to reality. The default changes nothing.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    /// Extra end correction in metres added at both chamber junctions.
+    pub end_correction: f64,
+    /// Extra loss factor η applied to every duct of the muffler (see
+    /// [`StraightDuct::loss_factor`](crate::elements::StraightDuct::loss_factor)).
+    pub loss_factor: f64,
+    /// Multiplier on the free chamber length.
+    pub length_scale: f64,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Self {
+            end_correction: 0.0,
+            loss_factor: 0.0,
+            length_scale: 1.0,
+        }
+    }
+}
+
+impl Calibration {
+    /// Fitted extra end correction range in metres.
+    pub const END_CORRECTION_RANGE: (f64, f64) = (0.0, 0.02);
+    /// Fitted loss factor range.
+    pub const LOSS_FACTOR_RANGE: (f64, f64) = (0.0, 1.0);
+    /// Fitted chamber length multiplier range.
+    pub const LENGTH_SCALE_RANGE: (f64, f64) = (0.8, 1.25);
+
+    /// Check the corrections against their supported ranges.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value, (min, max)) in [
+            ("end_correction", self.end_correction, Self::END_CORRECTION_RANGE),
+            ("loss_factor", self.loss_factor, Self::LOSS_FACTOR_RANGE),
+            ("length_scale", self.length_scale, Self::LENGTH_SCALE_RANGE),
+        ] {
+            if !(min..=max).contains(&value) {
+                return Err(format!("calibration.{name} must be in [{min}, {max}], got {value}"));
+            }
+        }
+        Ok(())
+    }
+
+    /// Parameter vector scaled to roughly unit sensitivity for the fit.
+    fn to_vector(self) -> [f64; 3] {
+        [self.end_correction * 1e3, self.loss_factor * 10.0, self.length_scale * 10.0]
+    }
+
+    /// Inverse of [`to_vector`](Self::to_vector), clamped into range.
+    fn from_vector(x: [f64; 3]) -> Self {
+        let clamp = |value: f64, (min, max): (f64, f64)| value.clamp(min, max);
+        Self {
+            end_correction: clamp(x[0] / 1e3, Self::END_CORRECTION_RANGE),
+            loss_factor: clamp(x[1] / 10.0, Self::LOSS_FACTOR_RANGE),
+            length_scale: clamp(x[2] / 10.0, Self::LENGTH_SCALE_RANGE),
+        }
+    }
+}
+
+/// Result of [`fit`].
+#[derive(Debug, Clone)]
+pub struct CalibrationFit {
+    /// Best corrections found; store them in
+    /// [`SimParams::calibration`] to apply them.
+    pub calibration: Calibration,
+    /// RMS TL mismatch in dB with the starting corrections.
+    pub rms_before: f64,
+    /// RMS TL mismatch in dB with the fitted corrections.
+    pub rms_after: f64,
+}
+
+/// Fit [`Calibration`] to measured transmission loss, given as
+/// `(frequency in Hz, TL in dB)` points of the design described by
+/// `params`.
+///
+/// Minimises the RMS TL mismatch with a Nelder–Mead search starting from
+/// `params.calibration`, keeping each correction within its range.
+pub fn fit(params: &SimParams, measured: &[(f64, f64)]) -> Result<CalibrationFit, String> {
+    let points: Vec<(f64, f64)> = measured
+        .iter()
+        .copied()
+        .filter(|&(f, tl)| f > 0.0 && f.is_finite() && tl.is_finite())
+        .collect();
+    if points.len() < 3 {
+        return Err(format!("need at least 3 valid measurement points, got {}", points.len()));
+    }
+    crate::validate_params(params)?;
+
+    let (c, rho) = params.medium();
+    let rms = |calibration: Calibration| {
+        let mut trial = params.clone();
+        trial.calibration = calibration;
+        let Ok(muffler) = Muffler::from_params(&trial) else {
+            return f64::INFINITY;
+        };
+        let sum: f64 = points
+            .iter()
+            .map(|&(f, tl)| {
+                let diff = muffler.transmission_loss(2.0 * std::f64::consts::PI * f, c, rho) - tl;
+                diff * diff
+            })
+            .sum();
+        (sum / points.len() as f64).sqrt()
+    };
+
+    let start = params.calibration;
+    let rms_before = rms(start);
+    let best = nelder_mead(|x| rms(Calibration::from_vector(x)), start.to_vector(), 0.5, 300);
+    let calibration = Calibration::from_vector(best);
+    let rms_after = rms(calibration);
+    // The search never makes things worse than where it started
+    Ok(if rms_after <= rms_before {
+        CalibrationFit {
+            calibration,
+            rms_before,
+            rms_after,
+        }
+    } else {
+        CalibrationFit {
+            calibration: start,
+            rms_before,
+            rms_after: rms_before,
+        }
+    })
+}
+
+/// Parse measured TL from text with one `frequency, TL` pair per line,
+/// separated by commas, semicolons or whitespace. Blank lines, `#`
+/// comments and a non-numeric header line are skipped.
+pub fn parse_tl_table(text: &str) -> Result<Vec<(f64, f64)>, String> {
+    crate::parse_pairs(text, "frequency, TL")
+}
+
+/// Minimise `f` with the Nelder–Mead simplex method from `start`, with
+/// initial simplex edges of `step`.
+fn nelder_mead(f: impl Fn([f64; 3]) -> f64, start: [f64; 3], step: f64, iterations: usize) -> [f64; 3] {
+    let mut simplex: Vec<([f64; 3], f64)> = (0..4)
+        .map(|i| {
+            let mut x = start;
+            if i > 0 {
+                x[i - 1] += step;
+            }
+            (x, f(x))
+        })
+        .collect();
+    let along = |from: [f64; 3], to: [f64; 3], t: f64| -> [f64; 3] {
+        std::array::from_fn(|i| from[i] + t * (to[i] - from[i]))
+    };
+
+    for _ in 0..iterations {
+        simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+        if simplex[3].1 - simplex[0].1 < 1e-9 {
+            break;
+        }
+        let centroid: [f64; 3] = std::array::from_fn(|i| simplex[..3].iter().map(|(x, _)| x[i]).sum::<f64>() / 3.0);
+        let worst = simplex[3];
+
+        let reflected = along(centroid, worst.0, -1.0);
+        let f_reflected = f(reflected);
+        if f_reflected < simplex[0].1 {
+            let expanded = along(centroid, worst.0, -2.0);
+            let f_expanded = f(expanded);
+            simplex[3] = if f_expanded < f_reflected {
+                (expanded, f_expanded)
+            } else {
+                (reflected, f_reflected)
+            };
+        } else if f_reflected < simplex[2].1 {
+            simplex[3] = (reflected, f_reflected);
+        } else {
+            let contracted = along(centroid, worst.0, 0.5);
+            let f_contracted = f(contracted);
+            if f_contracted < worst.1 {
+                simplex[3] = (contracted, f_contracted);
+            } else {
+                // Shrink towards the best vertex
+                let best = simplex[0].0;
+                for vertex in &mut simplex[1..] {
+                    let x = along(best, vertex.0, 0.5);
+                    *vertex = (x, f(x));
+                }
+            }
+        }
+    }
+    simplex.sort_by(|a, b| a.1.total_cmp(&b.1));
+    simplex[0].0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fit_recovers_known_corrections() {
+        let params = SimParams::default();
+        let truth = Calibration {
+            end_correction: 2e-3,
+            loss_factor: 0.05,
+            length_scale: 1.06,
+        };
+        let built = SimParams {
+            calibration: truth,
+            ..params.clone()
+        };
+        let (c, rho) = built.medium();
+        let muffler = Muffler::from_params(&built).unwrap();
+        let measured: Vec<(f64, f64)> = (1..=80)
+            .map(|i| {
+                let f = i as f64 * 100.0;
+                (f, muffler.transmission_loss(2.0 * std::f64::consts::PI * f, c, rho))
+            })
+            .collect();
+
+        let result = fit(&params, &measured).unwrap();
+        assert!(result.rms_before > 1.0, "{}", result.rms_before);
+        assert!(result.rms_after < 0.1 * result.rms_before, "{result:?}");
+        let fitted = result.calibration;
+        assert!((fitted.length_scale - truth.length_scale).abs() < 0.01, "{fitted:?}");
+        assert!((fitted.loss_factor - truth.loss_factor).abs() < 0.02, "{fitted:?}");
+
+        // Applying the fit brings the simulation onto the measurement
+        let corrected = SimParams {
+            calibration: fitted,
+            ..params
+        };
+        assert!(crate::compute(&corrected).is_ok());
+        assert!(fit(&corrected, &measured[..2]).is_err());
+    }
+
+    #[test]
+    fn test_parse_tl_table() {
+        let text = "frequency,tl\n# bench run 3\n100, 1.5\n200;2.5\n\n300 3.5 0.2\n";
+        assert_eq!(parse_tl_table(text).unwrap(), vec![(100.0, 1.5), (200.0, 2.5), (300.0, 3.5)]);
+        assert!(parse_tl_table("100, 1\nabc, 2\n").is_err());
+    }
+}
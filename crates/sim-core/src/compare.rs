@@ -0,0 +1,116 @@
+use crate::measurement::fft_convolve;
+use crate::SimResult;
+
+/// How design `b` differs from design `a`, for automated A/B checks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comparison {
+    /// RMS over the frequency bins of `a` (DC excluded) of the level of
+    /// `b` relative to `a`, 20·log₁₀|H_b/H_a|, in dB.
+    pub spectral_difference: f64,
+    /// Largest absolute level difference over the same bins, in dB.
+    pub max_spectral_difference: f64,
+    /// Peak of the normalised cross-correlation of the two impulse
+    /// responses, 1 for responses of the same shape.
+    pub correlation: f64,
+    /// Delay in samples of `b`'s impulse response against `a`'s at the
+    /// correlation peak.
+    pub lag: isize,
+    /// Frequency in Hz and level of `b` relative to `a` in dB at each
+    /// requested harmonic; negative where `b` is quieter.
+    pub harmonic_deltas: Vec<(f64, f64)>,
+}
+
+impl Comparison {
+    /// Whether `b` is within `tolerance` dB of `a` at every bin and every
+    /// harmonic.
+    pub fn within(&self, tolerance: f64) -> bool {
+        self.max_spectral_difference <= tolerance
+            && self.harmonic_deltas.iter().all(|&(_, delta)| delta.abs() <= tolerance)
+    }
+}
+
+/// Compare the results of two designs, reading `b`'s response at `a`'s
+/// frequencies and both at `harmonics` Hz (e.g. from
+/// [`SimParams::pump_harmonics`](crate::SimParams::pump_harmonics)).
+/// Both must share a sample rate for their impulse responses to line up.
+pub fn compare(a: &SimResult, b: &SimResult, harmonics: &[f64]) -> Result<Comparison, String> {
+    if a.sample_rate != b.sample_rate {
+        return Err(format!(
+            "results must share a sample rate, got {} Hz and {} Hz",
+            a.sample_rate, b.sample_rate
+        ));
+    }
+    let (response_a, response_b) = (a.response(), b.response());
+    let delta = |f: f64| response_b.magnitude_db_at(f) - response_a.magnitude_db_at(f);
+
+    let deltas: Vec<f64> = a.frequencies.iter().filter(|&&f| f > 0.0).map(|&f| delta(f)).collect();
+    if deltas.is_empty() {
+        return Err("results have no frequency bins above DC".to_string());
+    }
+    let spectral_difference = (deltas.iter().map(|d| d * d).sum::<f64>() / deltas.len() as f64).sqrt();
+    let max_spectral_difference = deltas.iter().fold(0.0f64, |m, d| m.max(d.abs()));
+
+    let (correlation, lag) = cross_correlation_peak(&a.impulse_response, &b.impulse_response);
+
+    Ok(Comparison {
+        spectral_difference,
+        max_spectral_difference,
+        correlation,
+        lag,
+        harmonic_deltas: harmonics.iter().map(|&f| (f, delta(f))).collect(),
+    })
+}
+
+/// Peak of the normalised cross-correlation of `a` and `b` and the lag in
+/// samples of `b` behind `a` where it occurs.
+fn cross_correlation_peak(a: &[f64], b: &[f64]) -> (f64, isize) {
+    let norm = |x: &[f64]| x.iter().map(|v| v * v).sum::<f64>().sqrt();
+    let scale = norm(a) * norm(b);
+    if scale == 0.0 {
+        return (0.0, 0);
+    }
+    // Convolving `b` with `a` reversed puts zero lag at index len(a) − 1
+    let reversed: Vec<f64> = a.iter().rev().copied().collect();
+    let correlation = fft_convolve(b, &reversed);
+    let (peak, value) = correlation
+        .iter()
+        .enumerate()
+        .fold((0, 0.0f64), |best, (i, &v)| if v.abs() > best.1.abs() { (i, v) } else { best });
+    (value.abs() / scale, peak as isize - (a.len() as isize - 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute, SimParams};
+
+    #[test]
+    fn test_compare_designs() {
+        let params = SimParams::default();
+        let a = compute(&params).unwrap();
+        let same = compare(&a, &a, &[150.0, 300.0]).unwrap();
+        assert!(same.spectral_difference < 1e-9);
+        assert!((same.correlation - 1.0).abs() < 1e-9);
+        assert_eq!(same.lag, 0);
+        assert!(same.within(1e-9));
+
+        // A longer chamber changes the response
+        let b = compute(&SimParams {
+            chamber_length: 2.0 * params.chamber_length,
+            ..params.clone()
+        })
+        .unwrap();
+        let diff = compare(&a, &b, &[150.0]).unwrap();
+        assert!(diff.spectral_difference > 1.0, "{}", diff.spectral_difference);
+        assert!(diff.max_spectral_difference >= diff.spectral_difference);
+        assert!(diff.correlation < 1.0);
+        let expected = b.response().magnitude_db_at(150.0) - a.response().magnitude_db_at(150.0);
+        assert_eq!(diff.harmonic_deltas, vec![(150.0, expected)]);
+        assert!(!diff.within(1.0));
+
+        // Delaying the impulse response shows up as a lag
+        let mut delayed = a.clone();
+        delayed.impulse_response.splice(0..0, [0.0; 5]);
+        assert_eq!(cross_correlation_peak(&a.impulse_response, &delayed.impulse_response).1, 5);
+    }
+}
@@ -1,27 +1,97 @@
-/// Speed of sound in air (m/s) and density (kg/m³) as a function of
-/// temperature in °C. Uses the ideal-gas approximation.
-pub fn speed_of_sound_and_density(temperature_c: f64) -> (f64, f64) {
+use crate::gas::Gas;
+
+/// Standard atmospheric pressure in Pa.
+pub const STANDARD_PRESSURE: f64 = 101325.0;
+/// Density of fresh water in kg/m³.
+pub const WATER_DENSITY: f64 = 998.0;
+/// Gravitational acceleration in m/s².
+pub const GRAVITY: f64 = 9.81;
+/// Dynamic viscosity of air at 20 °C in Pa·s.
+pub const AIR_VISCOSITY: f64 = 1.81e-5;
+/// Ratio of specific heats of air.
+pub const AIR_GAMMA: f64 = 1.4;
+/// Prandtl number of air.
+pub const AIR_PRANDTL: f64 = 0.71;
+
+/// Molar mass of dry air in kg/mol.
+pub const AIR_MOLAR_MASS: f64 = 28.965e-3;
+/// Molar mass of water in kg/mol.
+const WATER_MOLAR_MASS: f64 = 18.015e-3;
+
+/// Speed of sound (m/s) and density (kg/m³) of `gas` with water vapour as
+/// a function of temperature in °C, relative humidity (0–1) and static
+/// pressure in Pa. Uses the ideal-gas approximation for the mixture; the
+/// dry gas at any pressure has the sound speed of 101325 Pa.
+pub fn speed_of_sound_and_density(
+    gas: Gas,
+    temperature_c: f64,
+    relative_humidity: f64,
+    pressure: f64,
+) -> (f64, f64) {
     let t_kelvin = temperature_c + 273.15;
-    // c = 331.3 * sqrt(T/273.15)
-    let c = 331.3 * (t_kelvin / 273.15).sqrt();
-    // ρ = p / (R_specific * T), with p = 101325 Pa, R_specific = 287.05 J/(kg·K)
-    let rho = 101325.0 / (287.05 * t_kelvin);
+    // Mole fraction of water vapour, from the Magnus saturation pressure
+    let saturation = 610.94 * (17.625 * temperature_c / (temperature_c + 243.04)).exp();
+    let vapour = (relative_humidity * saturation / pressure).clamp(0.0, 1.0);
+    // Molar mass relative to dry air, and γ from the molar heat capacities
+    // (c_v = R/(γ−1) for the gas, 3·R for vapour)
+    let molar_mass = ((1.0 - vapour) * gas.molar_mass() + vapour * WATER_MOLAR_MASS) / AIR_MOLAR_MASS;
+    let cv = (1.0 - vapour) / (gas.gamma() - 1.0) + 3.0 * vapour;
+    let gamma = (cv + 1.0) / cv;
+    // c = 331.3 * sqrt(T/273.15) for dry air
+    let c = 331.3 * (t_kelvin / 273.15 * gamma / AIR_GAMMA / molar_mass).sqrt();
+    // ρ = p / (R_specific * T), R_specific = 287.05 J/(kg·K) for dry air
+    let rho = pressure * molar_mass / (287.05 * t_kelvin);
     (c, rho)
 }
 
+/// Static pressure in Pa at `altitude` metres above sea level in the
+/// International Standard Atmosphere (troposphere).
+pub fn pressure_at_altitude(altitude: f64) -> f64 {
+    STANDARD_PRESSURE * (1.0 - 2.25577e-5 * altitude).powf(5.25588)
+}
+
+/// Altitude in metres at which the standard atmosphere has `pressure` Pa;
+/// the inverse of [`pressure_at_altitude`].
+pub fn altitude_at_pressure(pressure: f64) -> f64 {
+    (1.0 - (pressure / STANDARD_PRESSURE).powf(1.0 / 5.25588)) / 2.25577e-5
+}
+
 /// Cross-sectional area from diameter (both in metres).
 pub fn area_from_diameter(diameter: f64) -> f64 {
     std::f64::consts::PI * (diameter / 2.0).powi(2)
 }
 
+/// Diameter of the circle with the given cross-sectional area (both SI).
+pub fn diameter_from_area(area: f64) -> f64 {
+    2.0 * (area / std::f64::consts::PI).sqrt()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn test_speed_of_sound_at_20c() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         assert!((c - 343.2).abs() < 0.5, "c = {c}");
         assert!((rho - 1.204).abs() < 0.01, "rho = {rho}");
     }
+
+    #[test]
+    fn test_humidity_and_altitude() {
+        let (dry_c, dry_rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
+        // Saturated air at 20 °C: about 0.35 % faster and 1 % lighter
+        let (humid_c, humid_rho) = speed_of_sound_and_density(Gas::Air, 20.0, 1.0, STANDARD_PRESSURE);
+        assert!((humid_c / dry_c - 1.0035).abs() < 0.0005, "{humid_c}");
+        assert!((humid_rho / dry_rho - 0.991).abs() < 0.001, "{humid_rho}");
+
+        // 3000 m: about 70 kPa, so the air is 30 % thinner but dry air
+        // carries sound at the same speed
+        let pressure = pressure_at_altitude(3000.0);
+        assert!((pressure - 70_120.0).abs() < 100.0, "{pressure}");
+        assert!((altitude_at_pressure(pressure) - 3000.0).abs() < 1e-6);
+        let (high_c, high_rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, pressure);
+        assert_eq!(high_c, dry_c);
+        assert!((high_rho / dry_rho - pressure / STANDARD_PRESSURE).abs() < 1e-12);
+    }
 }
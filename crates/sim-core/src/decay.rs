@@ -0,0 +1,108 @@
+/// Boundary in seconds between the early and late energy of an impulse
+/// response: the direct path and first reflections of a small muffler
+/// arrive well within it, chamber ringing outlasts it.
+pub const EARLY_TIME: f64 = 0.005;
+
+/// How fast an impulse response dies away, read off its Schroeder
+/// backward-integrated energy decay curve.
+///
+/// Decay times are extrapolated to a 60 dB fall and are `None` when the
+/// curve does not fall far enough within the response; a long-ringing,
+/// "boomy" design has long decay times and a low early-to-late ratio.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Decay {
+    /// Early decay time in seconds, from the fall from 0 to −10 dB.
+    pub edt: Option<f64>,
+    /// Decay time in seconds from the fall from −5 to −25 dB.
+    pub t20: Option<f64>,
+    /// Decay time in seconds from the fall from −5 to −35 dB.
+    pub t30: Option<f64>,
+    /// Energy arriving before `early_time` over the energy after it, in
+    /// dB; infinite when nothing arrives later.
+    pub early_to_late: f64,
+    /// Boundary in seconds between early and late energy.
+    pub early_time: f64,
+}
+
+/// Analyse the decay of `ir` sampled at `sample_rate` Hz, splitting early
+/// from late energy at `early_time` seconds.
+pub fn analyse(ir: &[f64], sample_rate: f64, early_time: f64) -> Decay {
+    let curve = schroeder_curve(ir);
+    let time = |from: f64, to: f64| decay_time(&curve, sample_rate, from, to);
+
+    let split = ((early_time * sample_rate).round() as usize).min(ir.len());
+    let energy = |samples: &[f64]| samples.iter().map(|h| h * h).sum::<f64>();
+    let (early, late) = (energy(&ir[..split]), energy(&ir[split..]));
+
+    Decay {
+        edt: time(0.0, -10.0),
+        t20: time(-5.0, -25.0),
+        t30: time(-5.0, -35.0),
+        early_to_late: if late > 0.0 { 10.0 * (early / late).log10() } else { f64::INFINITY },
+        early_time,
+    }
+}
+
+/// Schroeder energy decay curve of `ir`: the energy still to arrive after
+/// each sample, in dB relative to the total; `-inf` once none is left.
+pub fn schroeder_curve(ir: &[f64]) -> Vec<f64> {
+    let mut remaining = 0.0;
+    let mut curve: Vec<f64> = ir
+        .iter()
+        .rev()
+        .map(|h| {
+            remaining += h * h;
+            remaining
+        })
+        .collect();
+    curve.reverse();
+    let total = curve.first().copied().unwrap_or(0.0);
+    curve.iter().map(|&energy| 10.0 * (energy / total).log10()).collect()
+}
+
+/// Time in seconds for `curve` to fall 60 dB, from a least-squares line
+/// through its samples between `from` and `to` dB.
+fn decay_time(curve: &[f64], sample_rate: f64, from: f64, to: f64) -> Option<f64> {
+    let start = curve.iter().position(|&level| level <= from)?;
+    let end = curve.iter().position(|&level| level <= to)?;
+    if end <= start {
+        return None;
+    }
+    let n = (end - start + 1) as f64;
+    let (mut sum_t, mut sum_l, mut sum_tt, mut sum_tl) = (0.0, 0.0, 0.0, 0.0);
+    for (i, &level) in curve.iter().enumerate().take(end + 1).skip(start) {
+        let t = i as f64 / sample_rate;
+        sum_t += t;
+        sum_l += level;
+        sum_tt += t * t;
+        sum_tl += t * level;
+    }
+    let slope = (n * sum_tl - sum_t * sum_l) / (n * sum_tt - sum_t * sum_t);
+    (slope < 0.0).then(|| -60.0 / slope)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exponential_decay() {
+        // h = e^(−t/τ) falls 60 dB in 3τ·ln 10 seconds
+        let (sample_rate, tau) = (44_100.0, 0.01);
+        let ir: Vec<f64> = (0..44_100).map(|i| (-(i as f64) / sample_rate / tau).exp()).collect();
+        let decay = analyse(&ir, sample_rate, EARLY_TIME);
+        let t60 = 3.0 * tau * 10f64.ln();
+        for time in [decay.edt, decay.t20, decay.t30] {
+            assert!((time.unwrap() / t60 - 1.0).abs() < 1e-3, "{time:?} vs {t60}");
+        }
+        // Σ early / Σ late = e^(2N/(fs·τ)) − 1 for the first N samples early
+        let split = (EARLY_TIME * sample_rate).round();
+        let expected = 10.0 * ((2.0 * split / (sample_rate * tau)).exp() - 1.0).log10();
+        assert!((decay.early_to_late - expected).abs() < 0.01, "{} vs {expected}", decay.early_to_late);
+
+        // A single impulse has no decay to measure
+        let impulse = analyse(&[1.0, 0.0, 0.0], sample_rate, EARLY_TIME);
+        assert_eq!(impulse.t20, None);
+        assert_eq!(impulse.early_to_late, f64::INFINITY);
+    }
+}
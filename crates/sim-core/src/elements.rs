@@ -1,57 +1,2551 @@
-use crate::constants::area_from_diameter;
+use crate::breakout::Shell;
+use crate::constants::{area_from_diameter, diameter_from_area, AIR_GAMMA, AIR_PRANDTL, AIR_VISCOSITY};
+use crate::numerics::WallLossModel;
 use crate::transfer_matrix::TransferMatrix;
-use crate::AcousticElement;
+use crate::{AcousticElement, Connection};
 use num_complex::Complex64;
+use std::sync::Arc;
 
-/// A straight cylindrical duct.
+/// Shape and size of a duct's cross-section (all lengths in metres).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CrossSection {
+    Circular { diameter: f64 },
+    Rectangular { width: f64, height: f64 },
+    /// The gap between two coaxial tubes.
+    Annular { inner_diameter: f64, outer_diameter: f64 },
+}
+
+impl CrossSection {
+    /// Cross-sectional area in m².
+    pub fn area(&self) -> f64 {
+        match *self {
+            CrossSection::Circular { diameter } => area_from_diameter(diameter),
+            CrossSection::Rectangular { width, height } => width * height,
+            CrossSection::Annular {
+                inner_diameter,
+                outer_diameter,
+            } => area_from_diameter(outer_diameter) - area_from_diameter(inner_diameter),
+        }
+    }
+
+    /// Wetted perimeter in metres.
+    pub fn perimeter(&self) -> f64 {
+        use std::f64::consts::PI;
+        match *self {
+            CrossSection::Circular { diameter } => PI * diameter,
+            CrossSection::Rectangular { width, height } => 2.0 * (width + height),
+            CrossSection::Annular {
+                inner_diameter,
+                outer_diameter,
+            } => PI * (inner_diameter + outer_diameter),
+        }
+    }
+
+    /// Hydraulic diameter 4S/P, which sets friction and boundary-layer
+    /// losses. Equals the diameter of a circular section.
+    pub fn hydraulic_diameter(&self) -> f64 {
+        4.0 * self.area() / self.perimeter()
+    }
+
+    /// Diameter of the circle with the same area, for the lumped junction
+    /// models that only depend on area.
+    pub fn equivalent_diameter(&self) -> f64 {
+        diameter_from_area(self.area())
+    }
+
+    /// Cut-on frequency in Hz of the first non-planar mode, above which the
+    /// plane-wave transfer matrices no longer hold:
+    ///
+    /// - circular: f = 1.841·c/(π·D)
+    /// - rectangular: f = c/(2·a), a the longer side
+    /// - annular: f ≈ 2c/(π·(D_i + D_o)), the circumferential mode of a
+    ///   narrow gap
+    pub fn cutoff_frequency(&self, c: f64) -> f64 {
+        use std::f64::consts::PI;
+        match *self {
+            CrossSection::Circular { diameter } => 1.8412 * c / (PI * diameter),
+            CrossSection::Rectangular { width, height } => c / (2.0 * width.max(height)),
+            CrossSection::Annular {
+                inner_diameter,
+                outer_diameter,
+            } => 2.0 * c / (PI * (inner_diameter + outer_diameter)),
+        }
+    }
+
+    /// Check that every dimension is positive and an annulus has a gap.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            CrossSection::Circular { diameter } => require_positive(&[("diameter", diameter)]),
+            CrossSection::Rectangular { width, height } => {
+                require_positive(&[("width", width), ("height", height)])
+            }
+            CrossSection::Annular {
+                inner_diameter,
+                outer_diameter,
+            } => {
+                require_positive(&[("inner diameter", inner_diameter), ("outer diameter", outer_diameter)])?;
+                if inner_diameter >= outer_diameter {
+                    return Err(format!(
+                        "inner diameter {inner_diameter} must be smaller than the outer diameter {outer_diameter}"
+                    ));
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Short description in millimetres for labels.
+    pub fn describe(&self) -> String {
+        match *self {
+            CrossSection::Circular { diameter } => format!("Ø{:.1} mm", diameter * 1e3),
+            CrossSection::Rectangular { width, height } => {
+                format!("{:.1}×{:.1} mm", width * 1e3, height * 1e3)
+            }
+            CrossSection::Annular {
+                inner_diameter,
+                outer_diameter,
+            } => format!("Ø{:.1}/Ø{:.1} mm", inner_diameter * 1e3, outer_diameter * 1e3),
+        }
+    }
+}
+
+/// A straight duct of uniform cross-section.
+#[derive(Debug, Clone)]
+pub struct StraightDuct {
+    /// Length in metres.
+    pub length: f64,
+    /// Cross-section of the bore.
+    pub section: CrossSection,
+    /// Wall roughness height relative to the hydraulic diameter (ε/D);
+    /// 0 for a smooth (drawn brass) tube.
+    pub relative_roughness: f64,
+    /// Acoustic wall loss model.
+    pub losses: WallLossModel,
+    /// Extra loss factor η on top of the wall model: the wavenumber
+    /// becomes γ·(1 − jη/2). Lumps losses no model covers, e.g. from a
+    /// [`Calibration`](crate::calibration::Calibration) fit.
+    pub loss_factor: f64,
+    /// Mean-flow Mach number, positive for flow downstream. Convection
+    /// splits the wavenumber into k/(1 ± M), shifting resonances down
+    /// by (1 − M²).
+    pub mach: f64,
+    /// Elastic wall, or `None` for a rigid one. A yielding wall adds
+    /// compliance to the air and lowers the sound speed in the duct.
+    pub wall: Option<Shell>,
+}
+
+impl StraightDuct {
+    /// A circular duct.
+    pub fn new(length: f64, diameter: f64) -> Self {
+        Self::with_section(length, CrossSection::Circular { diameter })
+    }
+
+    /// A duct of any cross-section.
+    pub fn with_section(length: f64, section: CrossSection) -> Self {
+        Self {
+            length,
+            section,
+            relative_roughness: 0.0,
+            losses: WallLossModel::Lossless,
+            loss_factor: 0.0,
+            mach: 0.0,
+            wall: None,
+        }
+    }
+
+    /// Set the relative wall roughness ε/D.
+    pub fn with_roughness(mut self, relative_roughness: f64) -> Self {
+        self.relative_roughness = relative_roughness;
+        self
+    }
+
+    /// Set the acoustic wall loss model.
+    pub fn with_losses(mut self, losses: WallLossModel) -> Self {
+        self.losses = losses;
+        self
+    }
+
+    /// Set the extra loss factor η.
+    pub fn with_loss_factor(mut self, loss_factor: f64) -> Self {
+        self.loss_factor = loss_factor;
+        self
+    }
+
+    /// Set the mean-flow Mach number.
+    pub fn with_mach(mut self, mach: f64) -> Self {
+        self.mach = mach;
+        self
+    }
+
+    /// Give the duct an elastic wall.
+    pub fn with_wall(mut self, wall: Shell) -> Self {
+        self.wall = Some(wall);
+        self
+    }
+
+    /// Set the Mach number of a steady volume flow `flow_rate` (m³/s).
+    pub fn with_mean_flow(self, flow_rate: f64, c: f64) -> Self {
+        let mach = flow_rate / (self.area() * c);
+        self.with_mach(mach)
+    }
+
+    /// Cross-sectional area in m².
+    pub fn area(&self) -> f64 {
+        self.section.area()
+    }
+
+    /// Hydraulic diameter of the bore in metres.
+    pub fn diameter(&self) -> f64 {
+        self.section.hydraulic_diameter()
+    }
+
+    /// Characteristic impedance Z = ρc/S.
+    pub fn impedance(&self, c: f64, rho: f64) -> f64 {
+        rho * c / self.area()
+    }
+
+    /// Static pressure drop in Pa for a steady volume flow `flow_rate`
+    /// (m³/s), from the Darcy–Weisbach equation.
+    ///
+    /// Uses f = 64/Re for laminar flow and the Haaland approximation of
+    /// Colebrook–White above Re = 2300, so roughness only matters once the
+    /// flow is turbulent.
+    pub fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let velocity = flow_rate.abs() / self.area();
+        let reynolds = rho * velocity * self.diameter() / AIR_VISCOSITY;
+        if reynolds <= 0.0 {
+            return 0.0;
+        }
+        let friction = if reynolds < 2300.0 {
+            64.0 / reynolds
+        } else {
+            let inv_sqrt_f = -1.8 * ((self.relative_roughness / 3.7).powf(1.11) + 6.9 / reynolds).log10();
+            1.0 / (inv_sqrt_f * inv_sqrt_f)
+        };
+        friction * self.length / self.diameter() * 0.5 * rho * velocity * velocity
+    }
+
+    /// Hammerstad roughness factor 1 + (2/π)·atan(1.4·(ε/δ)²), which
+    /// doubles the wall loss once the roughness height ε exceeds the
+    /// viscous boundary-layer thickness δ = √(2ν/ω).
+    fn roughness_factor(&self, omega: f64, rho: f64) -> f64 {
+        let delta = (2.0 * AIR_VISCOSITY / (rho * omega)).sqrt();
+        let roughness = self.relative_roughness * self.diameter();
+        1.0 + 2.0 / std::f64::consts::PI * (1.4 * (roughness / delta).powi(2)).atan()
+    }
+
+    /// Complex propagation constant and characteristic impedance with
+    /// Kirchhoff's wide-duct viscothermal losses, scaled by the roughness
+    /// factor.
+    fn lossy_propagation(&self, omega: f64, c: f64, rho: f64) -> (Complex64, Complex64) {
+        let k = omega / c;
+        let nu = AIR_VISCOSITY / rho;
+        let factor = self.roughness_factor(omega, rho);
+
+        let base = (omega * nu / 2.0).sqrt() / (0.5 * self.diameter() * c) * factor;
+        let alpha_viscous = base;
+        let alpha_thermal = base * (AIR_GAMMA - 1.0) / AIR_PRANDTL.sqrt();
+
+        let one_minus_j = Complex64::new(1.0, -1.0);
+        let gamma = k + one_minus_j * (alpha_viscous + alpha_thermal);
+        let z = self.impedance(c, rho) * (1.0 + one_minus_j * (alpha_viscous - alpha_thermal) / k);
+        (gamma, z)
+    }
+
+    /// Complex propagation constant and characteristic impedance in the
+    /// low-reduced-frequency model of a circular tube of the hydraulic
+    /// diameter.
+    ///
+    /// The air behaves as a fluid of density ρ/(1 − F(κ_v·r)) and
+    /// compressibility (1 + (γ−1)·F(κ_t·r))/(ρc²), where
+    /// F(x) = 2·J₁(x)/(x·J₀(x)), κ_v = √(−jω/ν) and κ_t = κ_v·√Pr. The
+    /// departures from the lossless values are scaled by the roughness
+    /// factor, so wide ducts reduce to [`Self::lossy_propagation`].
+    fn low_reduced_frequency_propagation(&self, omega: f64, c: f64, rho: f64) -> (Complex64, Complex64) {
+        let nu = AIR_VISCOSITY / rho;
+        let radius = 0.5 * self.diameter();
+        let factor = self.roughness_factor(omega, rho);
+        let kappa = Complex64::new(1.0, -1.0) * (omega / (2.0 * nu)).sqrt();
+        let wall_function = |x: Complex64| 2.0 * bessel_j1_over_j0(x) / x;
+
+        let density = rho * (1.0 + factor * (1.0 / (1.0 - wall_function(kappa * radius)) - 1.0));
+        let shear = wall_function(kappa * AIR_PRANDTL.sqrt() * radius);
+        let compressibility = (1.0 + factor * (AIR_GAMMA - 1.0) * shear) / (rho * c * c);
+
+        let gamma = omega * (density * compressibility).sqrt();
+        let z = (density / compressibility).sqrt() / self.area();
+        (gamma, z)
+    }
+
+    /// Factor n by which a yielding wall scales the compressibility of the
+    /// air, or `None` for a rigid wall. The wavenumber becomes γ·√n and
+    /// the characteristic impedance Z/√n.
+    ///
+    /// The wall is the [`Shell`] impedance z_w spread along the perimeter
+    /// P, giving n = 1 + ρc²·P/(jωS·z_w). Well below the stiffness
+    /// frequency of a circular wall this is Korteweg's
+    /// 1 + ρc²·D/(E′t), negligible for air in any practical tube; flat
+    /// walls bend far more easily and lower the sound speed by several
+    /// percent at 1 mm thickness.
+    fn wall_compliance(&self, omega: f64, c: f64, rho: f64) -> Option<Complex64> {
+        let wall = self.wall.as_ref()?;
+        if omega <= 0.0 {
+            return None;
+        }
+        let frequency = omega / (2.0 * std::f64::consts::PI);
+        let z_wall = wall.wall_impedance(frequency, wall.stiffness_frequency(&self.section, self.length));
+        // A flat wall's (1,1) mode sweeps 64/π⁴ of the volume a piston would
+        let coupling = match self.section {
+            CrossSection::Rectangular { .. } => 64.0 / std::f64::consts::PI.powi(4),
+            _ => 1.0,
+        };
+        let admittance = coupling * self.section.perimeter() / (Complex64::new(0.0, omega * self.area()) * z_wall);
+        Some(1.0 + rho * c * c * admittance)
+    }
+
+    /// Lossy propagation constant and impedance of the wall model, or
+    /// `None` for lossless walls.
+    fn wall_propagation(&self, omega: f64, c: f64, rho: f64) -> Option<(Complex64, Complex64)> {
+        if omega <= 0.0 {
+            return None;
+        }
+        match self.losses {
+            WallLossModel::Lossless => None,
+            WallLossModel::Viscothermal => Some(self.lossy_propagation(omega, c, rho)),
+            WallLossModel::LowReducedFrequency => Some(self.low_reduced_frequency_propagation(omega, c, rho)),
+        }
+    }
+}
+
+/// J₁(x)/J₀(x) for complex `x`: by backward recurrence of the ratios
+/// J_n/J_{n−1} = 1/(2n/x − J_{n+1}/J_n) for moderate |x|, and by the
+/// Hankel asymptotic form ∓j + 1/(2x) far off the real axis.
+fn bessel_j1_over_j0(x: Complex64) -> Complex64 {
+    if x.norm() > 60.0 {
+        let dominant = if x.im < 0.0 { -1.0 } else { 1.0 };
+        return Complex64::new(0.0, dominant) + 0.5 / x;
+    }
+    let start = x.norm() as usize + 40;
+    (1..=start)
+        .rev()
+        .fold(Complex64::new(0.0, 0.0), |ratio, n| 1.0 / (2.0 * n as f64 / x - ratio))
+}
+
+impl AcousticElement for StraightDuct {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let damping = Complex64::new(1.0, -self.loss_factor / 2.0);
+        let mut lossy = self.wall_propagation(omega, c, rho);
+        if let Some(compliance) = self.wall_compliance(omega, c, rho) {
+            let (gamma, z) = lossy
+                .unwrap_or((Complex64::new(omega / c, 0.0), Complex64::new(self.impedance(c, rho), 0.0)));
+            let scale = compliance.sqrt();
+            lossy = Some((gamma * scale, z / scale));
+        }
+        if self.mach != 0.0 {
+            let (gamma, z) = lossy
+                .unwrap_or((Complex64::new(omega / c, 0.0), Complex64::new(self.impedance(c, rho), 0.0)));
+            return convected_duct_matrix(gamma * damping, z, self.length, self.mach);
+        }
+        if let Some((gamma, z)) = lossy {
+            return uniform_duct_matrix(gamma * damping, z, self.length);
+        }
+
+        let k = omega / c;
+        let z = self.impedance(c, rho);
+        if self.loss_factor > 0.0 {
+            return uniform_duct_matrix(k * damping, Complex64::new(z, 0.0), self.length);
+        }
+        let kl = k * self.length;
+
+        let cos_kl = Complex64::new(kl.cos(), 0.0);
+        let sin_kl = Complex64::new(kl.sin(), 0.0);
+        let j = Complex64::new(0.0, 1.0);
+
+        TransferMatrix::new(
+            cos_kl,
+            j * Complex64::new(z, 0.0) * sin_kl,
+            j * Complex64::new(1.0 / z, 0.0) * sin_kl,
+            cos_kl,
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[("length", self.length)])?;
+        self.section.validate()?;
+        require_non_negative(&[
+            ("relative roughness", self.relative_roughness),
+            ("loss factor", self.loss_factor),
+        ])?;
+        if !self.mach.is_finite() || self.mach.abs() >= 1.0 {
+            return Err(format!("Mach number must be subsonic, got {}", self.mach));
+        }
+        if let Some(wall) = &self.wall {
+            wall.validate()?;
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        match self.section {
+            CrossSection::Circular { diameter } => {
+                format!("Duct {:.0}×Ø{:.1} mm", self.length * 1e3, diameter * 1e3)
+            }
+            section => format!("Duct {:.0} mm, {}", self.length * 1e3, section.describe()),
+        }
+    }
+
+    fn cutoff_frequency(&self, c: f64) -> Option<f64> {
+        Some(self.section.cutoff_frequency(c))
+    }
+
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        StraightDuct::pressure_drop(self, flow_rate, rho)
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+
+    fn tail_matrix(&self, remaining: f64, omega: f64, c: f64, rho: f64) -> Option<TransferMatrix> {
+        let tail = StraightDuct {
+            length: remaining,
+            ..self.clone()
+        };
+        Some(tail.transfer_matrix(omega, c, rho))
+    }
+}
+
+/// A conical (tapered) duct between two diameters.
+///
+/// Uses the spherical-wave solution of the conical waveguide: with x the
+/// signed distance from the virtual apex, x·p(x) obeys the plane-wave
+/// equation, so the matrix follows from propagating x·p and its
+/// derivative over the length. A taper pointing downstream (contraction)
+/// simply puts the apex past the outlet.
+#[derive(Debug, Clone)]
+pub struct ConicalDuct {
+    /// Length in metres.
+    pub length: f64,
+    /// Diameter at the upstream end in metres.
+    pub inlet_diameter: f64,
+    /// Diameter at the downstream end in metres.
+    pub outlet_diameter: f64,
+}
+
+impl ConicalDuct {
+    pub fn new(length: f64, inlet_diameter: f64, outlet_diameter: f64) -> Self {
+        Self {
+            length,
+            inlet_diameter,
+            outlet_diameter,
+        }
+    }
+
+    /// Signed apex distances (x₁, x₂) of the inlet and outlet, or `None` if
+    /// the duct is effectively cylindrical.
+    fn apex_distances(&self) -> Option<(f64, f64)> {
+        let (r1, r2) = (self.inlet_diameter / 2.0, self.outlet_diameter / 2.0);
+        if (r2 - r1).abs() < 1e-9 * r1.max(r2) {
+            return None;
+        }
+        let x1 = r1 * self.length / (r2 - r1);
+        Some((x1, x1 + self.length))
+    }
+}
+
+impl AcousticElement for ConicalDuct {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let Some((x1, x2)) = self.apex_distances() else {
+            return StraightDuct::new(self.length, self.inlet_diameter).transfer_matrix(omega, c, rho);
+        };
+
+        let k = omega / c;
+        let j = Complex64::new(0.0, 1.0);
+        let (cos_kl, sin_kl) = ((k * self.length).cos(), (k * self.length).sin());
+        let s1 = area_from_diameter(self.inlet_diameter);
+        let s2 = area_from_diameter(self.outlet_diameter);
+
+        // Map a downstream state (p₂, U₂) back to the upstream (p₁, U₁).
+        // f = x·p, f' = p + x·p', and p' = −jωρ·U/S.
+        let upstream = |p2: Complex64, u2: Complex64| -> (Complex64, Complex64) {
+            let dp2 = -j * omega * rho * u2 / s2;
+            let f2 = p2 * x2;
+            let df2 = p2 + dp2 * x2;
+            let f1 = f2 * cos_kl - df2 * sin_kl / k;
+            let df1 = f2 * k * sin_kl + df2 * cos_kl;
+            let p1 = f1 / x1;
+            let dp1 = (df1 - p1) / x1;
+            (p1, j * s1 * dp1 / (omega * rho))
+        };
+
+        let (a, c21) = upstream(Complex64::new(1.0, 0.0), Complex64::new(0.0, 0.0));
+        let (b, d) = upstream(Complex64::new(0.0, 0.0), Complex64::new(1.0, 0.0));
+        TransferMatrix::new(a, b, c21, d)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("inlet diameter", self.inlet_diameter),
+            ("outlet diameter", self.outlet_diameter),
+        ])
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Cone {:.0}×Ø{:.1}→Ø{:.1} mm",
+            self.length * 1e3,
+            self.inlet_diameter * 1e3,
+            self.outlet_diameter * 1e3
+        )
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// A duct of varying bore given as a radius profile r(x), e.g. taken from
+/// the CAD model of a turned or printed body.
+///
+/// The profile is interpolated linearly between samples, so each interval
+/// is an exact [`ConicalDuct`] (or a straight one where the radius does not
+/// change) and the matrices are chained. A step in the bore can be given
+/// as two samples a fraction of a millimetre apart.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProfiledDuct {
+    /// `(position, radius)` samples in metres, positions increasing
+    /// downstream.
+    pub profile: Vec<(f64, f64)>,
+}
+
+impl ProfiledDuct {
+    pub fn new(profile: Vec<(f64, f64)>) -> Self {
+        Self { profile }
+    }
+
+    /// Parse a profile exported from CAD: one `position, radius` pair per
+    /// line in millimetres, in the format of
+    /// [`parse_tl_table`](crate::calibration::parse_tl_table).
+    pub fn from_table(text: &str) -> Result<Self, String> {
+        let samples = crate::parse_pairs(text, "position, radius")?;
+        Ok(Self::new(samples.into_iter().map(|(x, r)| (x * 1e-3, r * 1e-3)).collect()))
+    }
+
+    /// Length in metres from the first sample to the last.
+    pub fn length(&self) -> f64 {
+        match (self.profile.first(), self.profile.last()) {
+            (Some(first), Some(last)) => last.0 - first.0,
+            _ => 0.0,
+        }
+    }
+
+    /// Diameter at the upstream end in metres.
+    pub fn inlet_diameter(&self) -> f64 {
+        self.profile.first().map_or(0.0, |&(_, r)| 2.0 * r)
+    }
+
+    /// Diameter at the downstream end in metres.
+    pub fn outlet_diameter(&self) -> f64 {
+        self.profile.last().map_or(0.0, |&(_, r)| 2.0 * r)
+    }
+
+    /// Largest diameter along the profile in metres.
+    pub fn max_diameter(&self) -> f64 {
+        self.profile.iter().map(|&(_, r)| 2.0 * r).fold(0.0, f64::max)
+    }
+
+    /// The conical segments the profile is discretised into.
+    pub fn segments(&self) -> Vec<ConicalDuct> {
+        self.profile
+            .windows(2)
+            .map(|pair| ConicalDuct::new(pair[1].0 - pair[0].0, 2.0 * pair[0].1, 2.0 * pair[1].1))
+            .collect()
+    }
+}
+
+impl AcousticElement for ProfiledDuct {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        self.segments()
+            .iter()
+            .fold(TransferMatrix::identity(), |acc, segment| {
+                acc.chain(&segment.transfer_matrix(omega, c, rho))
+            })
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.profile.len() < 2 {
+            return Err(format!("profile needs at least 2 samples, got {}", self.profile.len()));
+        }
+        for (i, &(x, r)) in self.profile.iter().enumerate() {
+            if !(x.is_finite() && r > 0.0 && r.is_finite()) {
+                return Err(format!("profile sample {i} must have a finite position and a radius > 0, got ({x}, {r})"));
+            }
+        }
+        if let Some(pair) = self.profile.windows(2).find(|pair| pair[1].0 <= pair[0].0) {
+            return Err(format!(
+                "profile positions must increase, got {} after {}",
+                pair[1].0, pair[0].0
+            ));
+        }
+        Ok(())
+    }
+
+    fn cutoff_frequency(&self, c: f64) -> Option<f64> {
+        let diameter = self.max_diameter();
+        Some(CrossSection::Circular { diameter }.cutoff_frequency(c))
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Profile {:.0} mm, {} samples, Ø{:.1}→Ø{:.1} mm",
+            self.length() * 1e3,
+            self.profile.len(),
+            self.inlet_diameter() * 1e3,
+            self.outlet_diameter() * 1e3
+        )
+    }
+
+    fn length(&self) -> f64 {
+        ProfiledDuct::length(self)
+    }
+}
+
+/// A bend (elbow) turning the duct through `angle` at centreline radius
+/// `radius`, for chaining folded layouts.
+///
+/// In the plane-wave range a bend behaves as a straight duct along its
+/// centreline. Tight bends add inertance from the flow separating at the
+/// inner wall; this is estimated as an added length growing from zero at
+/// R = D to a quarter diameter for a 90° mitre. `radius = 0` is a mitred
+/// corner with no centreline length of its own.
+#[derive(Debug, Clone)]
+pub struct Bend {
+    /// Centreline radius in metres (0 for a mitred corner).
+    pub radius: f64,
+    /// Turning angle in radians.
+    pub angle: f64,
+    /// Inner diameter in metres.
+    pub diameter: f64,
+}
+
+impl Bend {
+    pub fn new(radius: f64, angle: f64, diameter: f64) -> Self {
+        Self {
+            radius,
+            angle,
+            diameter,
+        }
+    }
+
+    /// A 90° elbow.
+    pub fn elbow(radius: f64, diameter: f64) -> Self {
+        Self::new(radius, std::f64::consts::FRAC_PI_2, diameter)
+    }
+
+    /// Length along the centreline in metres.
+    pub fn centreline_length(&self) -> f64 {
+        self.radius * self.angle
+    }
+
+    /// Estimated added acoustic length of a tight bend in metres.
+    pub fn added_length(&self) -> f64 {
+        let tightness = (1.0 - self.radius / self.diameter).clamp(0.0, 1.0);
+        0.25 * self.diameter * tightness * self.angle / std::f64::consts::FRAC_PI_2
+    }
+
+    /// The straight duct the bend is acoustically equivalent to.
+    pub fn equivalent_duct(&self) -> StraightDuct {
+        StraightDuct::new(self.centreline_length() + self.added_length(), self.diameter)
+    }
+
+    /// Weisbach loss coefficient ζ (Δp = ζ·½ρv²): the smooth-bend formula
+    /// [0.131 + 1.847·(r/R)^3.5]·θ/90° while R exceeds the pipe radius r,
+    /// otherwise the mitre formula 0.946·sin²(θ/2) + 2.05·sin⁴(θ/2).
+    pub fn loss_coefficient(&self) -> f64 {
+        let r = self.diameter / 2.0;
+        if self.radius > r {
+            let turn = self.angle / std::f64::consts::FRAC_PI_2;
+            (0.131 + 1.847 * (r / self.radius).powf(3.5)) * turn
+        } else {
+            let s2 = (self.angle / 2.0).sin().powi(2);
+            0.946 * s2 + 2.05 * s2 * s2
+        }
+    }
+}
+
+impl AcousticElement for Bend {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        self.equivalent_duct().transfer_matrix(omega, c, rho)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[("angle", self.angle), ("diameter", self.diameter)])?;
+        require_non_negative(&[("radius", self.radius)])
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Bend {:.0}° R{:.0}×Ø{:.1} mm",
+            self.angle.to_degrees(),
+            self.radius * 1e3,
+            self.diameter * 1e3
+        )
+    }
+
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let velocity = flow_rate.abs() / area_from_diameter(self.diameter);
+        let friction = StraightDuct::new(self.centreline_length(), self.diameter)
+            .pressure_drop(flow_rate, rho);
+        friction + self.loss_coefficient() * 0.5 * rho * velocity * velocity
+    }
+}
+
+/// A hose segment with compliant walls (soft PVC, silicone).
+///
+/// The wall stretches under pressure, adding a distributed shunt
+/// compliance D/(E·t) per unit volume to the compressibility of the air
+/// (thin-walled Korteweg model). This slows the waves to
+/// c' = c/√(1 + ρc²·D/(E·t)) and lowers the characteristic impedance to
+/// ρc'/S. Wall damping enters through the complex modulus E·(1 + jη).
+#[derive(Debug, Clone)]
+pub struct CompliantHose {
+    /// Length in metres.
+    pub length: f64,
+    /// Inner diameter in metres.
+    pub diameter: f64,
+    /// Wall thickness in metres.
+    pub wall_thickness: f64,
+    /// Young's modulus of the wall material in Pa.
+    pub wall_modulus: f64,
+    /// Loss factor η of the wall material.
+    pub loss_factor: f64,
+}
+
+impl CompliantHose {
+    /// Create a hose with a loss factor of 0.1, typical of soft PVC.
+    pub fn new(length: f64, diameter: f64, wall_thickness: f64, wall_modulus: f64) -> Self {
+        Self {
+            length,
+            diameter,
+            wall_thickness,
+            wall_modulus,
+            loss_factor: 0.1,
+        }
+    }
+
+    /// Cross-sectional area in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.diameter)
+    }
+
+    /// Squared ratio (c/c')² = 1 + ρc²·D/(E·t) of the free to the
+    /// wall-loaded sound speed; complex when the wall is lossy.
+    pub fn wall_loading(&self, c: f64, rho: f64) -> Complex64 {
+        let modulus = Complex64::new(self.wall_modulus, self.wall_modulus * self.loss_factor);
+        1.0 + rho * c * c * self.diameter / (modulus * self.wall_thickness)
+    }
+}
+
+impl AcousticElement for CompliantHose {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let n = self.wall_loading(c, rho).sqrt();
+        let gamma = omega / c * n;
+        let z = rho * c / (self.area() * n);
+        uniform_duct_matrix(gamma, z, self.length)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("wall thickness", self.wall_thickness),
+            ("wall modulus", self.wall_modulus),
+        ])?;
+        require_non_negative(&[("loss factor", self.loss_factor)])
+    }
+
+    fn label(&self) -> String {
+        format!("Soft hose {:.0}×Ø{:.1} mm", self.length * 1e3, self.diameter * 1e3)
+    }
+
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        StraightDuct::new(self.length, self.diameter).pressure_drop(flow_rate, rho)
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// Check that every named dimension is positive and finite.
+fn require_positive(values: &[(&str, f64)]) -> Result<(), String> {
+    for &(name, value) in values {
+        if !(value > 0.0 && value.is_finite()) {
+            return Err(format!("{name} must be > 0, got {value}"));
+        }
+    }
+    Ok(())
+}
+
+/// Check that every named value is non-negative and finite.
+fn require_non_negative(values: &[(&str, f64)]) -> Result<(), String> {
+    for &(name, value) in values {
+        if !(value >= 0.0 && value.is_finite()) {
+            return Err(format!("{name} must be >= 0, got {value}"));
+        }
+    }
+    Ok(())
+}
+
+/// Transfer matrix of a uniform duct section with (possibly complex)
+/// propagation constant `gamma` and acoustic characteristic impedance `z`.
+///
+/// Reduces to the lossless `StraightDuct` matrix for γ = k, Z = ρc/S.
+pub(crate) fn uniform_duct_matrix(gamma: Complex64, z: Complex64, length: f64) -> TransferMatrix {
+    let j = Complex64::new(0.0, 1.0);
+    let gl = gamma * length;
+    let (cos_gl, sin_gl) = (gl.cos(), gl.sin());
+    TransferMatrix::new(cos_gl, j * z * sin_gl, j * sin_gl / z, cos_gl)
+}
+
+/// [`uniform_duct_matrix`] with a mean flow of Mach number `mach`.
+///
+/// The downstream and upstream waves travel with γ/(1 + M) and γ/(1 − M);
+/// in terms of γ_c = γ/(1 − M²) the matrix is the stationary one with
+/// γ_c, times the convective phase e^(−jMγ_cL).
+pub(crate) fn convected_duct_matrix(gamma: Complex64, z: Complex64, length: f64, mach: f64) -> TransferMatrix {
+    let j = Complex64::new(0.0, 1.0);
+    let gamma_c = gamma / (1.0 - mach * mach);
+    let phase = (-j * mach * gamma_c * length).exp();
+    let t = uniform_duct_matrix(gamma_c, z, length);
+    TransferMatrix::new(t.a * phase, t.b * phase, t.c * phase, t.d * phase)
+}
+
+/// End correction in metres for the evanescent higher-order modes excited
+/// where a pipe meets a coaxial chamber:
+///
+/// δ = 0.85·a·(1 − 1.25·a/R)
+///
+/// with a and R the pipe and chamber radii. The junction behaves as a
+/// slug of pipe air δ long; see [`AreaChange`].
+pub fn area_change_end_correction(pipe_diameter: f64, chamber_diameter: f64) -> f64 {
+    let a = pipe_diameter / 2.0;
+    let r = chamber_diameter / 2.0;
+    (0.85 * a * (1.0 - 1.25 * a / r)).max(0.0)
+}
+
+/// Which way the flow crosses an [`AreaChange`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AreaChangeDirection {
+    /// From the pipe into the wider chamber; without a measured loss
+    /// coefficient the Borda–Carnot value K = (1 − S_p/S_c)² is used.
+    Expansion,
+    /// From the chamber into the narrower pipe; without a measured loss
+    /// coefficient K = 0.5·(1 − S_p/S_c) is used.
+    Contraction,
+}
+
+/// Sudden expansion or contraction between a pipe and a wider chamber.
+///
+/// A lumped series element carrying the junction's end correction and,
+/// with mean flow, its jet loss, so the chamber keeps its geometric
+/// length: the end-correction inertance ρδ/S_p in series with the flow
+/// resistance K·ρ·Q̄/S_p² of the quasi-steady loss Δp = K·½ρv²,
+/// linearised about the mean flow Q̄.
+#[derive(Debug, Clone)]
+pub struct AreaChange {
+    /// Whether the flow expands into the chamber or contracts out of it.
+    pub direction: AreaChangeDirection,
+    /// Pipe diameter in metres.
+    pub pipe_diameter: f64,
+    /// Chamber diameter in metres.
+    pub chamber_diameter: f64,
+    /// End correction δ in metres; defaults to
+    /// [`area_change_end_correction`].
+    pub end_correction: f64,
+    /// Loss coefficient referred to the pipe velocity; `None` for the
+    /// textbook value.
+    pub loss_coefficient: Option<f64>,
+    /// Mean volume flow in m³/s that the acoustic resistance is linearised
+    /// about; 0 leaves the element lossless.
+    pub mean_flow: f64,
+}
+
+impl AreaChange {
+    pub fn new(direction: AreaChangeDirection, pipe_diameter: f64, chamber_diameter: f64) -> Self {
+        Self {
+            direction,
+            pipe_diameter,
+            chamber_diameter,
+            end_correction: area_change_end_correction(pipe_diameter, chamber_diameter),
+            loss_coefficient: None,
+            mean_flow: 0.0,
+        }
+    }
+
+    /// Sudden expansion from a pipe into a wider chamber.
+    pub fn expansion(pipe_diameter: f64, chamber_diameter: f64) -> Self {
+        Self::new(AreaChangeDirection::Expansion, pipe_diameter, chamber_diameter)
+    }
+
+    /// Sudden contraction from a chamber into a narrower pipe.
+    pub fn contraction(pipe_diameter: f64, chamber_diameter: f64) -> Self {
+        Self::new(AreaChangeDirection::Contraction, pipe_diameter, chamber_diameter)
+    }
+
+    /// Override the end correction, e.g. with a measured value or 0 to
+    /// leave it out.
+    pub fn with_end_correction(mut self, end_correction: f64) -> Self {
+        self.end_correction = end_correction;
+        self
+    }
+
+    /// Use a measured loss coefficient instead of the textbook value.
+    pub fn with_loss_coefficient(mut self, loss_coefficient: f64) -> Self {
+        self.loss_coefficient = Some(loss_coefficient);
+        self
+    }
+
+    /// Set the mean flow through the junction.
+    pub fn with_mean_flow(mut self, mean_flow: f64) -> Self {
+        self.mean_flow = mean_flow;
+        self
+    }
+
+    /// Effective loss coefficient K.
+    pub fn loss_coefficient(&self) -> f64 {
+        let ratio = (self.pipe_diameter / self.chamber_diameter).powi(2);
+        self.loss_coefficient.unwrap_or(match self.direction {
+            AreaChangeDirection::Expansion => (1.0 - ratio).powi(2),
+            AreaChangeDirection::Contraction => 0.5 * (1.0 - ratio),
+        })
+    }
+}
+
+impl AcousticElement for AreaChange {
+    fn transfer_matrix(&self, omega: f64, _c: f64, rho: f64) -> TransferMatrix {
+        let s_pipe = area_from_diameter(self.pipe_diameter);
+        let mass = rho * self.end_correction / s_pipe;
+        let resistance = self.loss_coefficient() * rho * self.mean_flow.abs() / (s_pipe * s_pipe);
+        TransferMatrix::new(
+            Complex64::new(1.0, 0.0),
+            Complex64::new(resistance, omega * mass),
+            Complex64::new(0.0, 0.0),
+            Complex64::new(1.0, 0.0),
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("pipe diameter", self.pipe_diameter),
+            ("chamber diameter", self.chamber_diameter),
+        ])?;
+        require_non_negative(&[
+            ("end correction", self.end_correction),
+            ("loss coefficient", self.loss_coefficient.unwrap_or(0.0)),
+            ("mean flow", self.mean_flow.abs()),
+        ])?;
+        if self.pipe_diameter >= self.chamber_diameter {
+            return Err(format!(
+                "pipe diameter {} must be smaller than the chamber diameter {}",
+                self.pipe_diameter, self.chamber_diameter
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        let (pipe, chamber) = (self.pipe_diameter * 1e3, self.chamber_diameter * 1e3);
+        match self.direction {
+            AreaChangeDirection::Expansion => format!("Expansion Ø{pipe:.1}→Ø{chamber:.1} mm"),
+            AreaChangeDirection::Contraction => format!("Contraction Ø{chamber:.1}→Ø{pipe:.1} mm"),
+        }
+    }
+
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let velocity = flow_rate.abs() / area_from_diameter(self.pipe_diameter);
+        self.loss_coefficient() * 0.5 * rho * velocity * velocity
+    }
+}
+
+/// A baffle plate across a chamber with a short connecting tube through
+/// it, splitting the chamber into two volumes.
+///
+/// The tube is a contraction from the chamber, a duct of `tube_length`
+/// and an expansion back into the chamber. Where the tube is longer than
+/// the plate is thick, it protrudes equally on both sides, and each closed
+/// annulus between the protrusion and the chamber wall becomes a
+/// quarter-wave side branch at the tube mouth. The chamber sections
+/// either side should therefore end at the tube mouths.
+#[derive(Debug, Clone)]
+pub struct Baffle {
+    /// Chamber inner diameter in metres.
+    pub chamber_diameter: f64,
+    /// Plate thickness in metres.
+    pub thickness: f64,
+    /// Connecting tube inner diameter in metres.
+    pub tube_diameter: f64,
+    /// Overall connecting tube length in metres, at least the plate
+    /// thickness.
+    pub tube_length: f64,
+    /// Include the end corrections at the tube mouths.
+    pub end_corrections: bool,
+}
+
+impl Baffle {
+    pub fn new(chamber_diameter: f64, thickness: f64, tube_diameter: f64, tube_length: f64) -> Self {
+        Self {
+            chamber_diameter,
+            thickness,
+            tube_diameter,
+            tube_length,
+            end_corrections: true,
+        }
+    }
+
+    /// Include or leave out the end corrections at the tube mouths.
+    pub fn with_end_corrections(mut self, end_corrections: bool) -> Self {
+        self.end_corrections = end_corrections;
+        self
+    }
+
+    /// How far the tube sticks out of each face of the plate, in metres.
+    pub fn protrusion(&self) -> f64 {
+        ((self.tube_length - self.thickness) / 2.0).max(0.0)
+    }
+
+    /// Mouth, tube and mouth, in chain order.
+    fn parts(&self) -> (AreaChange, StraightDuct, AreaChange) {
+        let mut contraction = AreaChange::contraction(self.tube_diameter, self.chamber_diameter);
+        let mut expansion = AreaChange::expansion(self.tube_diameter, self.chamber_diameter);
+        if !self.end_corrections {
+            contraction = contraction.with_end_correction(0.0);
+            expansion = expansion.with_end_correction(0.0);
+        }
+        (contraction, StraightDuct::new(self.tube_length, self.tube_diameter), expansion)
+    }
+
+    /// Closed annulus around a protrusion, if the tube protrudes.
+    fn annulus(&self) -> Option<QuarterWaveResonator> {
+        let area = area_from_diameter(self.chamber_diameter) - area_from_diameter(self.tube_diameter);
+        (self.protrusion() > 0.0)
+            .then(|| QuarterWaveResonator::new(self.protrusion(), diameter_from_area(area.max(0.0))))
+    }
+}
+
+impl AcousticElement for Baffle {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let (contraction, tube, expansion) = self.parts();
+        let through = contraction
+            .transfer_matrix(omega, c, rho)
+            .chain(&tube.transfer_matrix(omega, c, rho))
+            .chain(&expansion.transfer_matrix(omega, c, rho));
+        match self.annulus() {
+            Some(annulus) => {
+                let branch = annulus.transfer_matrix(omega, c, rho);
+                branch.chain(&through).chain(&branch)
+            }
+            None => through,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("chamber diameter", self.chamber_diameter),
+            ("thickness", self.thickness),
+            ("tube diameter", self.tube_diameter),
+            ("tube length", self.tube_length),
+        ])?;
+        if self.tube_diameter >= self.chamber_diameter {
+            return Err(format!(
+                "tube diameter {} must be smaller than the chamber diameter {}",
+                self.tube_diameter, self.chamber_diameter
+            ));
+        }
+        if self.tube_length < self.thickness {
+            return Err(format!(
+                "tube length {} must be at least the plate thickness {}",
+                self.tube_length, self.thickness
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!("Baffle, tube {:.0}×Ø{:.1} mm", self.tube_length * 1e3, self.tube_diameter * 1e3)
+    }
+
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let (contraction, tube, expansion) = self.parts();
+        contraction.pressure_drop(flow_rate, rho)
+            + tube.pressure_drop(flow_rate, rho)
+            + expansion.pressure_drop(flow_rate, rho)
+    }
+}
+
+/// Bessel function of the first kind J_n(x), from the trapezoidal rule
+/// on Bessel's integral J_n(x) = (1/2π)∫cos(nτ − x·sin τ)dτ, which
+/// converges geometrically once the point count exceeds n + |x|.
+fn bessel_j(n: u32, x: f64) -> f64 {
+    let points = 32 + 2 * (n as usize + x.abs().ceil() as usize);
+    let step = 2.0 * std::f64::consts::PI / points as f64;
+    (0..points)
+        .map(|i| {
+            let tau = i as f64 * step;
+            (n as f64 * tau - x * tau.sin()).cos()
+        })
+        .sum::<f64>()
+        / points as f64
+}
+
+/// Derivative J_n'(x).
+fn bessel_j_prime(n: u32, x: f64) -> f64 {
+    if n == 0 {
+        -bessel_j(1, x)
+    } else {
+        0.5 * (bessel_j(n - 1, x) - bessel_j(n + 1, x))
+    }
+}
+
+/// Largest transverse eigenvalue α = k_r·R kept in the modal sum of
+/// [`OffsetChamber`].
+const CHAMBER_MODE_LIMIT: f64 = 40.0;
+
+/// Rigid-wall modes (m, α_mn) of a circular duct, J_m'(α_mn) = 0, up to
+/// [`CHAMBER_MODE_LIMIT`], including the plane mode (0, 0).
+fn circular_duct_modes() -> &'static [(u32, f64)] {
+    static MODES: std::sync::OnceLock<Vec<(u32, f64)>> = std::sync::OnceLock::new();
+    MODES.get_or_init(|| {
+        let mut modes = vec![(0, 0.0)];
+        let step = 0.05;
+        // The first zero of J_m' lies above m; starting the scan there also
+        // skips the region where J_m is below rounding noise
+        for m in 0..CHAMBER_MODE_LIMIT as u32 {
+            let mut x = (m as f64).max(step);
+            let mut prev = bessel_j_prime(m, x);
+            while x + step <= CHAMBER_MODE_LIMIT {
+                let next = bessel_j_prime(m, x + step);
+                if prev * next < 0.0 {
+                    let (mut lo, mut hi) = (x, x + step);
+                    for _ in 0..50 {
+                        let mid = 0.5 * (lo + hi);
+                        if bessel_j_prime(m, lo) * bessel_j_prime(m, mid) <= 0.0 {
+                            hi = mid;
+                        } else {
+                            lo = mid;
+                        }
+                    }
+                    modes.push((m, 0.5 * (lo + hi)));
+                }
+                prev = next;
+                x += step;
+            }
+        }
+        modes
+    })
+}
+
+/// Port coupling of one chamber mode, precomputed for a geometry.
+#[derive(Debug, Clone)]
+struct ChamberMode {
+    /// Transverse wavenumber α/R in 1/m.
+    radial_wavenumber: f64,
+    /// Mode shape averaged over the inlet port, times the normalisation.
+    inlet: f64,
+    /// Mode shape averaged over the outlet port, times the normalisation.
+    outlet: f64,
+    /// cos(m·Δθ) between the ports.
+    azimuth: f64,
+}
+
+/// Expansion chamber with inlet and outlet ports off the axis.
+///
+/// A plane-wave chamber predicts the same TL wherever the ports sit.
+/// Here the chamber is a rigid cylinder described by its 3-D duct modes:
+/// with port-averaged mode shapes ψ̄ (normalised to a mean square of 1
+/// over the section) the port impedances are
+///
+/// ```text
+/// Z₁₁ = −j·ρω/S·Σ ψ̄₁²·cot(k_mn·L)/k_mn
+/// Z₁₂ = −j·ρω/S·Σ ψ̄₁ψ̄₂·cos(m·Δθ)/(k_mn·sin(k_mn·L))
+/// ```
+///
+/// with k_mn = √(k² − (α_mn/R)²), evanescent below cut-on. The plane mode
+/// alone reproduces a [`StraightDuct`]; the evanescent modes supply the
+/// port end corrections, and the propagating ones shift the TL nulls of
+/// off-axis layouts. Averaging over a circular port multiplies the mode at
+/// its centre by 2·J₁(k_r·a)/(k_r·a).
+///
+/// The mode table depends on the geometry, so the fields are set through
+/// the constructor and builder only.
+#[derive(Debug, Clone)]
+pub struct OffsetChamber {
+    length: f64,
+    diameter: f64,
+    inlet_diameter: f64,
+    outlet_diameter: f64,
+    inlet_offset: f64,
+    outlet_offset: f64,
+    outlet_angle: f64,
+    modes: Vec<ChamberMode>,
+}
+
+impl OffsetChamber {
+    /// A chamber with both ports on the axis.
+    pub fn new(length: f64, diameter: f64, inlet_diameter: f64, outlet_diameter: f64) -> Self {
+        let mut chamber = Self {
+            length,
+            diameter,
+            inlet_diameter,
+            outlet_diameter,
+            inlet_offset: 0.0,
+            outlet_offset: 0.0,
+            outlet_angle: 0.0,
+            modes: Vec::new(),
+        };
+        chamber.modes = chamber.compute_modes();
+        chamber
+    }
+
+    /// Move the ports `inlet_offset` and `outlet_offset` metres off the
+    /// axis, with the outlet turned `outlet_angle` radians around the axis
+    /// from the inlet (π puts them on opposite sides).
+    pub fn with_offsets(mut self, inlet_offset: f64, outlet_offset: f64, outlet_angle: f64) -> Self {
+        self.inlet_offset = inlet_offset;
+        self.outlet_offset = outlet_offset;
+        self.outlet_angle = outlet_angle;
+        self.modes = self.compute_modes();
+        self
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+
+    pub fn diameter(&self) -> f64 {
+        self.diameter
+    }
+
+    /// Radial offsets of the inlet and outlet port centres in metres.
+    pub fn offsets(&self) -> (f64, f64) {
+        (self.inlet_offset, self.outlet_offset)
+    }
+
+    /// Angle between the ports around the axis in radians.
+    pub fn outlet_angle(&self) -> f64 {
+        self.outlet_angle
+    }
+
+    fn compute_modes(&self) -> Vec<ChamberMode> {
+        let radius = self.diameter / 2.0;
+        if radius <= 0.0 || !radius.is_finite() {
+            return Vec::new();
+        }
+        let averaged = |m: u32, alpha: f64, offset: f64, port_diameter: f64| {
+            let kr = alpha / radius;
+            let x = kr * port_diameter / 2.0;
+            let piston = if x > 0.0 { 2.0 * bessel_j(1, x) / x } else { 1.0 };
+            bessel_j(m, kr * offset) * piston
+        };
+        circular_duct_modes()
+            .iter()
+            .map(|&(m, alpha)| {
+                let norm = if alpha == 0.0 {
+                    1.0
+                } else {
+                    let weight = if m == 0 { 1.0 } else { 2.0 };
+                    (weight / (1.0 - (m as f64 / alpha).powi(2))).sqrt() / bessel_j(m, alpha).abs()
+                };
+                ChamberMode {
+                    radial_wavenumber: alpha / radius,
+                    inlet: norm * averaged(m, alpha, self.inlet_offset, self.inlet_diameter),
+                    outlet: norm * averaged(m, alpha, self.outlet_offset, self.outlet_diameter),
+                    azimuth: (m as f64 * self.outlet_angle).cos(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl AcousticElement for OffsetChamber {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let k = omega / c;
+        let (mut z11, mut z22, mut z12) = (0.0, 0.0, Complex64::new(0.0, 0.0));
+        let mut accumulate = |mode: &ChamberMode, own: f64, cross: Complex64| {
+            z11 += mode.inlet * mode.inlet * own;
+            z22 += mode.outlet * mode.outlet * own;
+            z12 += mode.inlet * mode.outlet * mode.azimuth * cross;
+        };
+        // Reactance sums: propagating modes give cot/k and 1/(k·sin);
+        // evanescent ones (k_mn = −jκ) give −coth/κ and −1/(κ·sinh)
+        for mode in &self.modes {
+            let k_mn2 = k * k - mode.radial_wavenumber * mode.radial_wavenumber;
+            if k_mn2 > 0.0 {
+                let k_mn = k_mn2.sqrt();
+                let (sin, cos) = (k_mn * self.length).sin_cos();
+                accumulate(mode, cos / (sin * k_mn), Complex64::new(1.0 / (k_mn * sin), 0.0));
+            } else if k_mn2 < 0.0 {
+                let kappa = (-k_mn2).sqrt();
+                let kl = kappa * self.length;
+                accumulate(mode, -1.0 / (kl.tanh() * kappa), Complex64::new(-1.0 / (kappa * kl.sinh()), 0.0));
+            }
+        }
+        let factor = Complex64::new(0.0, -rho * omega / area_from_diameter(self.diameter));
+        let (z11, z22, z12) = (factor * z11, factor * z22, factor * z12);
+        TransferMatrix::new(z11 / z12, (z11 * z22 - z12 * z12) / z12, z12.inv(), z22 / z12)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("inlet diameter", self.inlet_diameter),
+            ("outlet diameter", self.outlet_diameter),
+        ])?;
+        require_non_negative(&[("inlet offset", self.inlet_offset), ("outlet offset", self.outlet_offset)])?;
+        let radius = self.diameter / 2.0;
+        for (side, offset, port) in [
+            ("inlet", self.inlet_offset, self.inlet_diameter),
+            ("outlet", self.outlet_offset, self.outlet_diameter),
+        ] {
+            if offset + port / 2.0 > radius {
+                return Err(format!(
+                    "{side} port Ø{port} at offset {offset} does not fit in the chamber radius {radius}"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Chamber {:.0}×Ø{:.1} mm, ports +{:.1}/+{:.1} mm",
+            self.length * 1e3,
+            self.diameter * 1e3,
+            self.inlet_offset * 1e3,
+            self.outlet_offset * 1e3
+        )
+    }
+
+    fn length(&self) -> f64 {
+        OffsetChamber::length(self)
+    }
+}
+
+/// A closed quarter-wave tube attached to the main duct as a side branch.
+///
+/// The branch contributes a shunt impedance Z_b = −j·(ρc/S_b)·cot(kL) at
+/// its junction, so attenuation peaks where kL = (2n−1)·π/2.
+#[derive(Debug, Clone)]
+pub struct QuarterWaveResonator {
+    /// Branch tube length in metres.
+    pub length: f64,
+    /// Branch tube inner diameter in metres.
+    pub diameter: f64,
+}
+
+impl QuarterWaveResonator {
+    pub fn new(length: f64, diameter: f64) -> Self {
+        Self { length, diameter }
+    }
+
+    /// Create a resonator tuned so its first TL peak lands on `freq` (Hz).
+    pub fn tuned(freq: f64, diameter: f64, c: f64) -> Self {
+        Self::new(c / (4.0 * freq), diameter)
+    }
+
+    /// Cross-sectional area of the branch tube in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.diameter)
+    }
+
+    /// Branch input admittance 1/Z_b = j·(S_b/ρc)·tan(kL).
+    ///
+    /// Working with the admittance avoids dividing by zero at the tuning
+    /// frequency, where Z_b → 0.
+    pub fn branch_admittance(&self, omega: f64, c: f64, rho: f64) -> Complex64 {
+        let k = omega / c;
+        let z = rho * c / self.area();
+        Complex64::new(0.0, (k * self.length).tan() / z)
+    }
+}
+
+impl AcousticElement for QuarterWaveResonator {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        TransferMatrix::new(
+            Complex64::new(1.0, 0.0),
+            Complex64::new(0.0, 0.0),
+            self.branch_admittance(omega, c, rho),
+            Complex64::new(1.0, 0.0),
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[("length", self.length), ("diameter", self.diameter)])
+    }
+
+    fn label(&self) -> String {
+        format!("λ/4 branch {:.0}×Ø{:.1} mm", self.length * 1e3, self.diameter * 1e3)
+    }
+
+    fn connection(&self) -> Connection {
+        Connection::Shunt
+    }
+}
+
+/// Discharge coefficient of a sharp-edged orifice.
+pub(crate) const ORIFICE_DISCHARGE_COEFFICIENT: f64 = 0.61;
+
+/// A thin plate with a single round hole, restricting the duct.
+///
+/// Acoustically it is a lumped series impedance Z = R + jωM: the air slug
+/// in the hole, lengthened by an end correction of 0.85·r on each face,
+/// gives M = ρ(t + 0.85·d)/S_o, and Ingard's viscous resistance
+/// R = ½·√(2μρω)·(2 + t/r)/S_o. Smaller holes attenuate more but also
+/// raise the static pressure drop the pump has to overcome.
+///
+/// At high amplitude the flow separates into a jet on every half cycle;
+/// with a velocity amplitude set, the jet loss adds its describing-function
+/// resistance (see [`Orifice::jet_resistance`]).
+#[derive(Debug, Clone)]
+pub struct Orifice {
+    /// Hole diameter in metres.
+    pub hole_diameter: f64,
+    /// Plate thickness in metres.
+    pub thickness: f64,
+    /// Peak acoustic particle velocity in the hole in m/s; 0 keeps the
+    /// orifice linear.
+    pub velocity_amplitude: f64,
+}
+
+impl Orifice {
+    pub fn new(hole_diameter: f64, thickness: f64) -> Self {
+        Self {
+            hole_diameter,
+            thickness,
+            velocity_amplitude: 0.0,
+        }
+    }
+
+    /// Set the peak particle velocity in the hole.
+    pub fn with_velocity_amplitude(mut self, velocity_amplitude: f64) -> Self {
+        self.velocity_amplitude = velocity_amplitude;
+        self
+    }
+
+    /// Open area of the hole in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.hole_diameter)
+    }
+
+    /// Acoustic mass M = ρ(t + 0.85·d)/S_o in kg/m⁴.
+    pub fn acoustic_mass(&self, rho: f64) -> f64 {
+        rho * (self.thickness + 0.85 * self.hole_diameter) / self.area()
+    }
+
+    /// Viscous acoustic resistance at angular frequency `omega`, in Pa·s/m³.
+    pub fn acoustic_resistance(&self, omega: f64, rho: f64) -> f64 {
+        let radius = self.hole_diameter / 2.0;
+        let wall_factor = 2.0 + self.thickness / radius;
+        0.5 * (2.0 * AIR_VISCOSITY * rho * omega).sqrt() * wall_factor / self.area()
+    }
+
+    /// Quasi-linear jet resistance in Pa·s/m³ at the velocity amplitude.
+    ///
+    /// The fundamental of the jet loss ½ρ·u|u|/C_d² for u = U·cos ωt is
+    /// (4/(3π))·ρ·U/C_d² times u, so R_jet = 4ρU/(3π·C_d²·S_o): the same
+    /// loss as [`AcousticElement::pressure_drop`], linearised about the
+    /// oscillation.
+    pub fn jet_resistance(&self, rho: f64) -> f64 {
+        4.0 * rho * self.velocity_amplitude
+            / (3.0 * std::f64::consts::PI * ORIFICE_DISCHARGE_COEFFICIENT.powi(2) * self.area())
+    }
+
+    /// Series impedance R + jωM of the orifice.
+    pub fn impedance(&self, omega: f64, rho: f64) -> Complex64 {
+        let resistance = self.acoustic_resistance(omega, rho) + self.jet_resistance(rho);
+        Complex64::new(resistance, omega * self.acoustic_mass(rho))
+    }
+}
+
+impl AcousticElement for Orifice {
+    fn transfer_matrix(&self, omega: f64, _c: f64, rho: f64) -> TransferMatrix {
+        TransferMatrix::new(
+            Complex64::new(1.0, 0.0),
+            self.impedance(omega, rho),
+            Complex64::new(0.0, 0.0),
+            Complex64::new(1.0, 0.0),
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[("hole diameter", self.hole_diameter)])?;
+        require_non_negative(&[
+            ("thickness", self.thickness),
+            ("velocity amplitude", self.velocity_amplitude),
+        ])
+    }
+
+    fn label(&self) -> String {
+        format!("Orifice Ø{:.1}×{:.1} mm", self.hole_diameter * 1e3, self.thickness * 1e3)
+    }
+
+    /// Jet loss Δp = ½ρ·(Q / (C_d·S_o))², assuming the hole is small
+    /// compared to the pipe.
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let jet_velocity = flow_rate.abs() / (ORIFICE_DISCHARGE_COEFFICIENT * self.area());
+        0.5 * rho * jet_velocity * jet_velocity
+    }
+}
+
+/// A lumped acoustic element given directly by its resistance R,
+/// inertance L and compliance C, e.g. from a measurement, for components
+/// without a geometric model yet.
+///
+/// The three parts are in series: Z = R + jωL + 1/(jωC). A compliance of
+/// 0 leaves out the capacitive term, so a series element with only R and
+/// L passes steady flow. As a `Shunt` the element is a branch to ground
+/// with admittance 1/Z; an L–C branch then behaves like a Helmholtz
+/// resonator tuned to ω₀ = 1/√(LC).
+#[derive(Debug, Clone)]
+pub struct LumpedRlc {
+    /// Acoustic resistance in Pa·s/m³.
+    pub resistance: f64,
+    /// Acoustic inertance (mass) in kg/m⁴.
+    pub inertance: f64,
+    /// Acoustic compliance in m³/Pa; 0 for none.
+    pub compliance: f64,
+    /// Whether the element sits in line or branches to ground.
+    pub connection: Connection,
+}
+
+impl LumpedRlc {
+    pub fn new(resistance: f64, inertance: f64, compliance: f64, connection: Connection) -> Self {
+        Self {
+            resistance,
+            inertance,
+            compliance,
+            connection,
+        }
+    }
+
+    /// Impedance R + jωL + 1/(jωC) of the element.
+    pub fn impedance(&self, omega: f64) -> Complex64 {
+        let mut reactance = omega * self.inertance;
+        if self.compliance > 0.0 {
+            reactance -= 1.0 / (omega * self.compliance);
+        }
+        Complex64::new(self.resistance, reactance)
+    }
+
+    /// Series resonance ω₀ = 1/√(LC) in rad/s, if both L and C are set.
+    pub fn resonance(&self) -> Option<f64> {
+        (self.inertance > 0.0 && self.compliance > 0.0)
+            .then(|| 1.0 / (self.inertance * self.compliance).sqrt())
+    }
+}
+
+impl AcousticElement for LumpedRlc {
+    fn transfer_matrix(&self, omega: f64, _c: f64, _rho: f64) -> TransferMatrix {
+        let one = Complex64::new(1.0, 0.0);
+        let zero = Complex64::new(0.0, 0.0);
+        let z = self.impedance(omega);
+        match self.connection {
+            Connection::Series => TransferMatrix::new(one, z, zero, one),
+            Connection::Shunt => TransferMatrix::new(one, zero, z.inv(), one),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_non_negative(&[
+            ("resistance", self.resistance),
+            ("inertance", self.inertance),
+            ("compliance", self.compliance),
+        ])?;
+        if self.connection == Connection::Shunt
+            && self.resistance == 0.0
+            && self.inertance == 0.0
+            && self.compliance == 0.0
+        {
+            return Err("a shunt element needs R, L or C, otherwise it shorts the duct".into());
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        let kind = match self.connection {
+            Connection::Series => "series",
+            Connection::Shunt => "shunt",
+        };
+        format!(
+            "RLC {kind} R={:.3e} L={:.3e} C={:.3e}",
+            self.resistance, self.inertance, self.compliance
+        )
+    }
+
+    fn connection(&self) -> Connection {
+        self.connection
+    }
+
+    /// Δp = R·Q for a series R–L element. A series compliance is a sealed
+    /// diaphragm and a shunt branch carries no steady flow, so neither
+    /// adds a drop.
+    fn pressure_drop(&self, flow_rate: f64, _rho: f64) -> f64 {
+        match self.connection {
+            Connection::Series if self.compliance == 0.0 => self.resistance * flow_rate,
+            _ => 0.0,
+        }
+    }
+}
+
+/// Normalised radiation impedance Z/(ρc/S) of an unflanged open pipe of
+/// `diameter` at wavenumber `k`, after Levine and Schwinger.
+///
+/// Uses the fit of Silva et al. (2009) to the exact solution: the
+/// reflection coefficient R = −|R|·e^(−2jkl) with
+/// |R| = (1 + 0.2ka − 0.084(ka)²)/(1 + 0.2ka + 0.416(ka)²) and end
+/// correction l/a = 0.6133·(1 + 0.044(ka)²)/(1 + 0.19(ka)²) − 0.02·sin²(2ka),
+/// accurate below ka = 3.8, well past the first cut-on at ka = 1.84.
+pub fn unflanged_radiation_impedance(k: f64, diameter: f64) -> Complex64 {
+    let a = diameter / 2.0;
+    let ka = k * a;
+    let magnitude = ((1.0 + 0.2 * ka - 0.084 * ka * ka) / (1.0 + 0.2 * ka + 0.416 * ka * ka)).max(0.0);
+    let end_correction = a * (0.6133 * (1.0 + 0.044 * ka * ka) / (1.0 + 0.19 * ka * ka) - 0.02 * (2.0 * ka).sin().powi(2));
+    let reflection = -magnitude * Complex64::new(0.0, -2.0 * k * end_correction).exp();
+    (1.0 + reflection) / (1.0 - reflection)
+}
+
+/// Normalised radiation impedance Z/(ρc/S) of a pipe of `diameter`
+/// ending in an infinite flange, radiating as a baffled piston:
+///
+/// Z/(ρc/S) = 1 − J₁(2ka)/(ka) + j·H₁(2ka)/(ka)
+///
+/// with H₁ the Struve function.
+pub fn flanged_radiation_impedance(k: f64, diameter: f64) -> Complex64 {
+    let ka = k * diameter / 2.0;
+    if ka <= 0.0 {
+        return Complex64::new(0.0, 0.0);
+    }
+    use std::f64::consts::PI;
+    let x = 2.0 * ka;
+    // Integral forms: J₁(x) = (1/π)∫₀^π cos(τ − x·sin τ) dτ and
+    // H₁(x) = (2x/π)∫₀^(π/2) sin²θ·sin(x·cos θ) dθ
+    let bessel = simpson(|tau| (tau - x * tau.sin()).cos(), 0.0, PI) / PI;
+    let struve = 2.0 * x / PI * simpson(|theta| theta.sin().powi(2) * (x * theta.cos()).sin(), 0.0, PI / 2.0);
+    Complex64::new(1.0 - bessel / ka, struve / ka)
+}
+
+/// Simpson's rule over `[a, b]`, fine enough for the smooth, mildly
+/// oscillating integrands of the radiation impedances.
+fn simpson(f: impl Fn(f64) -> f64, a: f64, b: f64) -> f64 {
+    const INTERVALS: usize = 256;
+    let h = (b - a) / INTERVALS as f64;
+    let inner: f64 = (1..INTERVALS)
+        .map(|i| f(a + i as f64 * h) * if i % 2 == 1 { 4.0 } else { 2.0 })
+        .sum();
+    (f(a) + inner + f(b)) * h / 3.0
+}
+
+/// Normalised specific impedance ζ of a perforated plate or pipe wall
+/// (Sullivan–Crocker, zero mean flow):
+///
+/// ζ = [6·10⁻³ + j·k·(t_w + 0.75·d_h)] / σ
+///
+/// where σ is the porosity (open-area fraction), t_w the wall thickness and
+/// d_h the hole diameter (all lengths in metres).
+pub fn perforate_impedance(k: f64, porosity: f64, hole_diameter: f64, wall_thickness: f64) -> Complex64 {
+    let effective_thickness = wall_thickness + 0.75 * hole_diameter;
+    Complex64::new(6e-3, k * effective_thickness) / porosity
+}
+
+/// A perforated plate spanning the chamber, dividing it in two like a
+/// [`Baffle`] but through many small holes instead of one tube.
+///
+/// The plate is a series impedance Z = (ρc/S)·ζ with ζ from
+/// [`perforate_impedance`], S the chamber area.
+#[derive(Debug, Clone)]
+pub struct PerforatedPlate {
+    /// Chamber inner diameter in metres.
+    pub chamber_diameter: f64,
+    /// Open-area fraction of the plate (0–1).
+    pub porosity: f64,
+    /// Hole diameter in metres.
+    pub hole_diameter: f64,
+    /// Plate thickness in metres.
+    pub thickness: f64,
+}
+
+impl PerforatedPlate {
+    pub fn new(chamber_diameter: f64, porosity: f64, hole_diameter: f64, thickness: f64) -> Self {
+        Self {
+            chamber_diameter,
+            porosity,
+            hole_diameter,
+            thickness,
+        }
+    }
+
+    /// Plate area in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.chamber_diameter)
+    }
+
+    /// Series acoustic impedance of the plate in Pa·s/m³.
+    pub fn impedance(&self, omega: f64, c: f64, rho: f64) -> Complex64 {
+        let zeta = perforate_impedance(omega / c, self.porosity, self.hole_diameter, self.thickness);
+        zeta * rho * c / self.area()
+    }
+}
+
+impl AcousticElement for PerforatedPlate {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        TransferMatrix::new(
+            Complex64::new(1.0, 0.0),
+            self.impedance(omega, c, rho),
+            Complex64::new(0.0, 0.0),
+            Complex64::new(1.0, 0.0),
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("chamber diameter", self.chamber_diameter),
+            ("hole diameter", self.hole_diameter),
+            ("thickness", self.thickness),
+        ])?;
+        if !(self.porosity > 0.0 && self.porosity <= 1.0) {
+            return Err(format!("porosity must be in (0, 1], got {}", self.porosity));
+        }
+        if self.hole_diameter >= self.chamber_diameter {
+            return Err(format!(
+                "hole diameter {} must be smaller than the chamber diameter {}",
+                self.hole_diameter, self.chamber_diameter
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Perforated plate Ø{:.1} mm, σ={:.0}%, holes Ø{:.1} mm",
+            self.chamber_diameter * 1e3,
+            self.porosity * 100.0,
+            self.hole_diameter * 1e3
+        )
+    }
+
+    /// Jet loss through the holes, ½ρ·(Q / (C_d·σ·S))².
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let jet_velocity = flow_rate.abs() / (ORIFICE_DISCHARGE_COEFFICIENT * self.porosity * self.area());
+        0.5 * rho * jet_velocity * jet_velocity
+    }
+}
+
+/// A straight pipe whose wall is perforated along its whole length, venting
+/// to the ambient (pressure-release) surroundings.
+///
+/// The perforate leaks volume velocity continuously along the pipe, which
+/// gives a uniform duct with complex wavenumber
+/// γ² = k² − j·k·(4/d)/ζ. Placed inside an outer shell it becomes a
+/// [`ConcentricTubeResonator`].
+#[derive(Debug, Clone)]
+pub struct PerforatedTube {
+    /// Length in metres.
+    pub length: f64,
+    /// Inner diameter in metres.
+    pub diameter: f64,
+    /// Open-area fraction of the wall (0–1).
+    pub porosity: f64,
+    /// Perforation hole diameter in metres.
+    pub hole_diameter: f64,
+    /// Pipe wall thickness in metres.
+    pub wall_thickness: f64,
+}
+
+impl PerforatedTube {
+    pub fn new(
+        length: f64,
+        diameter: f64,
+        porosity: f64,
+        hole_diameter: f64,
+        wall_thickness: f64,
+    ) -> Self {
+        Self {
+            length,
+            diameter,
+            porosity,
+            hole_diameter,
+            wall_thickness,
+        }
+    }
+
+    /// Cross-sectional area in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.diameter)
+    }
+
+    /// Normalised wall impedance ζ at wavenumber `k`.
+    pub fn wall_impedance(&self, k: f64) -> Complex64 {
+        perforate_impedance(k, self.porosity, self.hole_diameter, self.wall_thickness)
+    }
+}
+
+impl AcousticElement for PerforatedTube {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let k = omega / c;
+        let j = Complex64::new(0.0, 1.0);
+        let zeta = self.wall_impedance(k);
+        let gamma = (k * k - j * k * (4.0 / self.diameter) / zeta).sqrt();
+        let z = omega * rho / (self.area() * gamma);
+        uniform_duct_matrix(gamma, z, self.length)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("porosity", self.porosity),
+            ("hole diameter", self.hole_diameter),
+        ])?;
+        require_non_negative(&[("wall thickness", self.wall_thickness)])?;
+        if self.porosity > 1.0 {
+            return Err(format!("porosity must be <= 1, got {}", self.porosity));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Perforated pipe {:.0}×Ø{:.1} mm, σ={:.0}%",
+            self.length * 1e3,
+            self.diameter * 1e3,
+            self.porosity * 100.0
+        )
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// Empirical model for the acoustic properties of a porous absorber.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PorousModel {
+    /// Delany–Bazley (1970), fitted for 0.01 < ρf/σ < 1.
+    DelanyBazley,
+    /// Miki (1990), a positive-real refit of Delany–Bazley that stays
+    /// physical at low frequencies.
+    Miki,
+}
+
+/// Characteristic impedance Z_c (Pa·s/m) and complex wavenumber k_c (1/m)
+/// of a porous material with the given flow resistivity (Pa·s/m²).
+pub fn porous_characteristics(
+    model: PorousModel,
+    omega: f64,
+    c: f64,
+    rho: f64,
+    flow_resistivity: f64,
+) -> (Complex64, Complex64) {
+    let f = omega / (2.0 * std::f64::consts::PI);
+    let k = omega / c;
+    let (zc, kc) = match model {
+        PorousModel::DelanyBazley => {
+            let x = rho * f / flow_resistivity;
+            (
+                Complex64::new(1.0 + 0.0571 * x.powf(-0.754), -0.087 * x.powf(-0.732)),
+                Complex64::new(1.0 + 0.0978 * x.powf(-0.700), -0.189 * x.powf(-0.595)),
+            )
+        }
+        PorousModel::Miki => {
+            let x = f / flow_resistivity;
+            (
+                Complex64::new(1.0 + 0.070 * x.powf(-0.632), -0.107 * x.powf(-0.632)),
+                Complex64::new(1.0 + 0.109 * x.powf(-0.618), -0.160 * x.powf(-0.618)),
+            )
+        }
+    };
+    (zc * rho * c, kc * k)
+}
+
+/// A duct whose wall is lined with a rigidly backed porous layer (foam,
+/// felt, fibre).
+///
+/// The lining is treated as locally reacting with surface impedance
+/// Z_w = −j·Z_c·cot(k_c·h), which gives the airway the same wave equation
+/// as a perforated pipe: γ² = k² − j·k·(4/d)/ζ with ζ = Z_w/ρc.
+#[derive(Debug, Clone)]
+pub struct LinedDuct {
+    /// Length in metres.
+    pub length: f64,
+    /// Diameter of the open airway inside the lining in metres.
+    pub diameter: f64,
+    /// Radial thickness of the lining in metres.
+    pub lining_thickness: f64,
+    /// Static flow resistivity of the lining in Pa·s/m².
+    pub flow_resistivity: f64,
+    /// Empirical porous material model.
+    pub model: PorousModel,
+}
+
+impl LinedDuct {
+    pub fn new(length: f64, diameter: f64, lining_thickness: f64, flow_resistivity: f64) -> Self {
+        Self {
+            length,
+            diameter,
+            lining_thickness,
+            flow_resistivity,
+            model: PorousModel::DelanyBazley,
+        }
+    }
+
+    /// Cross-sectional area of the airway in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.diameter)
+    }
+
+    /// Normalised surface impedance ζ of the lining.
+    pub fn wall_impedance(&self, omega: f64, c: f64, rho: f64) -> Complex64 {
+        let (zc, kc) = porous_characteristics(self.model, omega, c, rho, self.flow_resistivity);
+        let kh = kc * self.lining_thickness;
+        let j = Complex64::new(0.0, 1.0);
+        -j * zc * kh.cos() / kh.sin() / (rho * c)
+    }
+}
+
+impl AcousticElement for LinedDuct {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let k = omega / c;
+        let j = Complex64::new(0.0, 1.0);
+        let zeta = self.wall_impedance(omega, c, rho);
+        let gamma = (k * k - j * k * (4.0 / self.diameter) / zeta).sqrt();
+        let z = omega * rho / (self.area() * gamma);
+        uniform_duct_matrix(gamma, z, self.length)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("lining thickness", self.lining_thickness),
+            ("flow resistivity", self.flow_resistivity),
+        ])
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Lined duct {:.0}×Ø{:.1} mm, {:.0} mm lining",
+            self.length * 1e3,
+            self.diameter * 1e3,
+            self.lining_thickness * 1e3
+        )
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// A closed side-branch tube whose end is filled with a porous plug (foam,
+/// felt, fibre), giving a damped quarter-wave resonator.
+///
+/// The plug is a rigidly backed porous layer with admittance
+/// Y_p = j·(S_b/Z_c)·tan(k_c·h) at its face; the air column in front of it
+/// carries that to the junction as the shunt admittance
+/// Y = (Y_p·cos kL + j·sin kL/Z₀) / (cos kL + j·Z₀·Y_p·sin kL),
+/// Z₀ = ρc/S_b. Without a plug this is the [`QuarterWaveResonator`]; the
+/// plug trades its deep, narrow notch for a shallower, broader one.
+#[derive(Debug, Clone)]
+pub struct AbsorptiveBranch {
+    /// Branch tube length in metres, including the plug.
+    pub length: f64,
+    /// Branch tube inner diameter in metres.
+    pub diameter: f64,
+    /// Thickness of the plug at the closed end in metres.
+    pub plug_thickness: f64,
+    /// Static flow resistivity of the plug in Pa·s/m².
+    pub flow_resistivity: f64,
+    /// Empirical porous material model.
+    pub model: PorousModel,
+}
+
+impl AbsorptiveBranch {
+    pub fn new(length: f64, diameter: f64, plug_thickness: f64, flow_resistivity: f64) -> Self {
+        Self {
+            length,
+            diameter,
+            plug_thickness,
+            flow_resistivity,
+            model: PorousModel::DelanyBazley,
+        }
+    }
+
+    /// Cross-sectional area of the branch tube in m².
+    pub fn area(&self) -> f64 {
+        area_from_diameter(self.diameter)
+    }
+
+    /// Length of the air column in front of the plug in metres.
+    pub fn air_length(&self) -> f64 {
+        self.length - self.plug_thickness
+    }
+
+    /// Branch input admittance at the junction.
+    pub fn branch_admittance(&self, omega: f64, c: f64, rho: f64) -> Complex64 {
+        let j = Complex64::new(0.0, 1.0);
+        let z0 = rho * c / self.area();
+        let plug = if self.plug_thickness > 0.0 && omega > 0.0 {
+            let (zc, kc) = porous_characteristics(self.model, omega, c, rho, self.flow_resistivity);
+            j * self.area() / zc * (kc * self.plug_thickness).tan()
+        } else {
+            Complex64::new(0.0, 0.0)
+        };
+        let kl = omega / c * self.air_length();
+        (plug * kl.cos() + j * kl.sin() / z0) / (kl.cos() + j * z0 * plug * kl.sin())
+    }
+}
+
+impl AcousticElement for AbsorptiveBranch {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        TransferMatrix::new(
+            Complex64::new(1.0, 0.0),
+            Complex64::new(0.0, 0.0),
+            self.branch_admittance(omega, c, rho),
+            Complex64::new(1.0, 0.0),
+        )
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("flow resistivity", self.flow_resistivity),
+        ])?;
+        require_non_negative(&[("plug thickness", self.plug_thickness)])?;
+        if self.plug_thickness >= self.length {
+            return Err(format!(
+                "plug thickness {} must be shorter than the branch length {}",
+                self.plug_thickness, self.length
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Damped branch {:.0}×Ø{:.1} mm, {:.0} mm plug",
+            self.length * 1e3,
+            self.diameter * 1e3,
+            self.plug_thickness * 1e3
+        )
+    }
+
+    fn connection(&self) -> Connection {
+        Connection::Shunt
+    }
+}
+
+/// A porous monolith filling the duct: a ceramic catalyst honeycomb, a
+/// printed flow straightener or a sintered block with straight pores.
+///
+/// The block is an equivalent fluid in the Johnson–Champoux–Allard model,
+/// with the parameters following from the square-cell geometry: porosity
+/// φ = (a/p)² for cell pitch p = 1/√N and open cell width a = p − t, unit
+/// tortuosity, viscous and thermal characteristic lengths Λ = Λ' = a/2,
+/// and the laminar square-channel flow resistivity σ = 28.45·μ/(φ·a²).
 #[derive(Debug, Clone)]
-pub struct StraightDuct {
-    /// Length in metres.
+pub struct Monolith {
+    /// Length of the block in metres.
     pub length: f64,
-    /// Inner diameter in metres.
+    /// Diameter of the block (and the duct it fills) in metres.
     pub diameter: f64,
+    /// Cells per m² of frontal area (400 cells per square inch is
+    /// 620 000 /m²).
+    pub cell_density: f64,
+    /// Thickness of the walls between cells in metres.
+    pub wall_thickness: f64,
 }
 
-impl StraightDuct {
-    pub fn new(length: f64, diameter: f64) -> Self {
-        Self { length, diameter }
+impl Monolith {
+    /// Darcy friction factor times Reynolds number for laminar flow in a
+    /// square channel, halved (Δp/L = 28.45·μ·U/a²).
+    const SQUARE_CHANNEL_FRICTION: f64 = 28.45;
+
+    pub fn new(length: f64, diameter: f64, cell_density: f64, wall_thickness: f64) -> Self {
+        Self {
+            length,
+            diameter,
+            cell_density,
+            wall_thickness,
+        }
     }
 
-    /// Cross-sectional area in m².
+    /// Frontal area of the block in m².
     pub fn area(&self) -> f64 {
         area_from_diameter(self.diameter)
     }
 
-    /// Characteristic impedance Z = ρc/S.
-    pub fn impedance(&self, c: f64, rho: f64) -> f64 {
-        rho * c / self.area()
+    /// Open width of one cell in metres.
+    pub fn cell_width(&self) -> f64 {
+        1.0 / self.cell_density.sqrt() - self.wall_thickness
+    }
+
+    /// Open fraction of the frontal area.
+    pub fn porosity(&self) -> f64 {
+        (self.cell_width() * self.cell_density.sqrt()).powi(2)
+    }
+
+    /// Static flow resistivity in Pa·s/m², referred to the superficial
+    /// velocity through the frontal area.
+    pub fn flow_resistivity(&self) -> f64 {
+        Self::SQUARE_CHANNEL_FRICTION * AIR_VISCOSITY / (self.porosity() * self.cell_width().powi(2))
+    }
+
+    /// Effective density (kg/m³) and bulk modulus (Pa) of the equivalent
+    /// fluid.
+    pub fn equivalent_fluid(&self, omega: f64, c: f64, rho: f64) -> (Complex64, Complex64) {
+        let j = Complex64::new(0.0, 1.0);
+        let phi = self.porosity();
+        let sigma = self.flow_resistivity();
+        let lambda = self.cell_width() / 2.0;
+        let mu = AIR_VISCOSITY;
+
+        let viscous = (1.0 + j * 4.0 * mu * rho * omega / (sigma * lambda * phi).powi(2)).sqrt();
+        let density = rho / phi * (1.0 + sigma * phi / (j * omega * rho) * viscous);
+
+        let thermal = (1.0 + j * rho * omega * AIR_PRANDTL * lambda * lambda / (16.0 * mu)).sqrt();
+        let relaxation = 1.0 + 8.0 * mu / (j * lambda * lambda * AIR_PRANDTL * omega * rho) * thermal;
+        let modulus = rho * c * c / phi / (AIR_GAMMA - (AIR_GAMMA - 1.0) / relaxation);
+        (density, modulus)
     }
 }
 
-impl AcousticElement for StraightDuct {
+impl AcousticElement for Monolith {
     fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
-        let k = omega / c;
-        let z = self.impedance(c, rho);
-        let kl = k * self.length;
+        let (density, modulus) = self.equivalent_fluid(omega, c, rho);
+        let mut gamma = omega * (density / modulus).sqrt();
+        // Forward waves decay: Im γ < 0 for e^{jωt}
+        if gamma.im > 0.0 {
+            gamma = -gamma;
+        }
+        let z = omega * density / (self.area() * gamma);
+        uniform_duct_matrix(gamma, z, self.length)
+    }
 
-        let cos_kl = Complex64::new(kl.cos(), 0.0);
-        let sin_kl = Complex64::new(kl.sin(), 0.0);
+    fn validate(&self) -> Result<(), String> {
+        require_positive(&[
+            ("length", self.length),
+            ("diameter", self.diameter),
+            ("cell density", self.cell_density),
+            ("wall thickness", self.wall_thickness),
+        ])?;
+        if self.cell_width() <= 0.0 {
+            return Err(format!(
+                "wall thickness {} must be smaller than the cell pitch {}",
+                self.wall_thickness,
+                1.0 / self.cell_density.sqrt()
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Monolith {:.0}×Ø{:.1} mm, {:.0} cpsi",
+            self.length * 1e3,
+            self.diameter * 1e3,
+            self.cell_density * 0.0254 * 0.0254
+        )
+    }
+
+    /// Laminar Darcy drop σ·L·U through the cells.
+    fn pressure_drop(&self, flow_rate: f64, _rho: f64) -> f64 {
+        self.flow_resistivity() * self.length * flow_rate.abs() / self.area()
+    }
+
+    fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+/// A concentric-tube resonator: a perforated inner pipe running through a
+/// closed outer chamber.
+///
+/// The inner pipe and the annular cavity are coupled through the perforate.
+/// With zero mean flow the coupled pressure equations
+///
+/// ```text
+/// p₁'' = −k²·p₁ + a₁·(p₁ − p₂),   a₁ = j·k·(P/S₁)/ζ
+/// p₂'' = −k²·p₂ − a₂·(p₁ − p₂),   a₂ = j·k·(P/S₂)/ζ
+/// ```
+///
+/// decouple into a plane mode (p₁ = p₂, wavenumber k) and a cross mode
+/// (wavenumber √(k² − a₁ − a₂)). The annulus is rigidly closed at both
+/// ends (u₂ = 0), which reduces the 4×4 system to a 2×2 matrix for the
+/// inner pipe.
+#[derive(Debug, Clone)]
+pub struct ConcentricTubeResonator {
+    /// Perforated inner pipe (its length is the chamber length).
+    pub inner: PerforatedTube,
+    /// Inner diameter of the outer shell in metres.
+    pub outer_diameter: f64,
+}
+
+impl ConcentricTubeResonator {
+    pub fn new(inner: PerforatedTube, outer_diameter: f64) -> Self {
+        Self {
+            inner,
+            outer_diameter,
+        }
+    }
+
+    /// Cross-sectional area of the annular cavity in m², measured from the
+    /// outside of the inner pipe wall.
+    pub fn annulus_area(&self) -> f64 {
+        let inner_outside = self.inner.diameter + 2.0 * self.inner.wall_thickness;
+        area_from_diameter(self.outer_diameter) - area_from_diameter(inner_outside)
+    }
+}
+
+impl AcousticElement for ConcentricTubeResonator {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        type M2 = [[Complex64; 2]; 2];
+        fn mul(x: &M2, y: &M2) -> M2 {
+            [
+                [
+                    x[0][0] * y[0][0] + x[0][1] * y[1][0],
+                    x[0][0] * y[0][1] + x[0][1] * y[1][1],
+                ],
+                [
+                    x[1][0] * y[0][0] + x[1][1] * y[1][0],
+                    x[1][0] * y[0][1] + x[1][1] * y[1][1],
+                ],
+            ]
+        }
+
+        let k = omega / c;
         let j = Complex64::new(0.0, 1.0);
+        let zero = Complex64::new(0.0, 0.0);
+        let one = Complex64::new(1.0, 0.0);
+        let length = self.inner.length;
+        let s1 = self.inner.area();
+        let s2 = self.annulus_area();
+        let perimeter = std::f64::consts::PI * self.inner.diameter;
 
-        TransferMatrix::new(
-            cos_kl,
-            j * Complex64::new(z, 0.0) * sin_kl,
-            j * Complex64::new(1.0 / z, 0.0) * sin_kl,
-            cos_kl,
+        let zeta = self.inner.wall_impedance(k);
+        let a1 = j * k * (perimeter / s1) / zeta;
+        let a2 = j * k * (perimeter / s2) / zeta;
+
+        // Modal basis: columns are the plane mode [1, 1] and cross mode [a₁, −a₂].
+        let v: M2 = [[one, a1], [one, -a2]];
+        let det_v = -a2 - a1;
+        let v_inv: M2 = [[-a2 / det_v, -a1 / det_v], [-one / det_v, one / det_v]];
+        let gammas = [Complex64::new(k, 0.0), (k * k - a1 - a2).sqrt()];
+
+        // Per-mode propagation p(L) = cos·p(0) + (sin/γ)·p'(0),
+        // p'(L) = −γ·sin·p(0) + cos·p'(0), mapped back to physical coordinates.
+        let modal = |f: &dyn Fn(Complex64) -> Complex64| -> M2 {
+            let d: M2 = [[f(gammas[0]), zero], [zero, f(gammas[1])]];
+            mul(&mul(&v, &d), &v_inv)
+        };
+        let ca = modal(&|g| (g * length).cos());
+        let sb = modal(&|g| (g * length).sin() / g);
+        let gs = modal(&|g| -g * (g * length).sin());
+
+        // Closed annulus: p₂'(0) = 0 and p₂'(L) = 0 eliminates p₂(0).
+        let m00 = ca[0][0] - ca[0][1] * gs[1][0] / gs[1][1];
+        let m01 = sb[0][0] - ca[0][1] * ca[1][0] / gs[1][1];
+        let m10 = gs[0][0] - gs[0][1] * gs[1][0] / gs[1][1];
+        let m11 = ca[0][0] - gs[0][1] * ca[1][0] / gs[1][1];
+
+        // Convert p' to volume velocity: p' = −jωρ·U/S₁.
+        let beta = -j * omega * rho / s1;
+        let (f00, f01, f10, f11) = (m00, m01 * beta, m10 / beta, m11);
+
+        // The sweep convention maps downstream → upstream, so invert.
+        let det_f = f00 * f11 - f01 * f10;
+        TransferMatrix::new(f11 / det_f, -f01 / det_f, -f10 / det_f, f00 / det_f)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        self.inner.validate()?;
+        if self.outer_diameter <= self.inner.diameter {
+            return Err(format!(
+                "outer diameter ({}) must exceed the inner pipe diameter ({})",
+                self.outer_diameter, self.inner.diameter
+            ));
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        format!(
+            "Concentric resonator {:.0}×Ø{:.0} mm",
+            self.inner.length * 1e3,
+            self.outer_diameter * 1e3
+        )
+    }
+}
+
+/// Two element chains fed from a common junction and recombined at a
+/// second one, e.g. a Herschel–Quincke tube or twin outlets that merge.
+///
+/// Both branches see the same pressure at each junction and their volume
+/// velocities add, so their admittance matrices sum:
+///
+/// ```text
+/// [U₁]   [ D/B  −Δ/B ] [p₁]
+/// [U₂] = [ 1/B  −A/B ] [p₂],   Δ = AD − BC
+/// ```
+///
+/// and the sum is converted back to a transfer matrix.
+pub struct ParallelBranches {
+    /// The two branches, each in chain order.
+    pub branches: [Vec<Box<dyn AcousticElement>>; 2],
+}
+
+impl ParallelBranches {
+    pub fn new(
+        first: Vec<Box<dyn AcousticElement>>,
+        second: Vec<Box<dyn AcousticElement>>,
+    ) -> Self {
+        Self {
+            branches: [first, second],
+        }
+    }
+
+    fn branch_matrix(
+        branch: &[Box<dyn AcousticElement>],
+        omega: f64,
+        c: f64,
+        rho: f64,
+    ) -> TransferMatrix {
+        branch.iter().fold(TransferMatrix::identity(), |total, elem| {
+            total.chain(&elem.transfer_matrix(omega, c, rho))
+        })
+    }
+
+    fn branch_drop(branch: &[Box<dyn AcousticElement>], flow_rate: f64, rho: f64) -> f64 {
+        branch.iter().map(|elem| elem.pressure_drop(flow_rate, rho)).sum()
+    }
+}
+
+impl AcousticElement for ParallelBranches {
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix {
+        let zero = Complex64::new(0.0, 0.0);
+        let (mut y11, mut y12, mut y21, mut y22) = (zero, zero, zero, zero);
+        for branch in &self.branches {
+            let t = Self::branch_matrix(branch, omega, c, rho);
+            let det = t.a * t.d - t.b * t.c;
+            y11 += t.d / t.b;
+            y12 -= det / t.b;
+            y21 += 1.0 / t.b;
+            y22 -= t.a / t.b;
+        }
+        // Perfect cancellation between the branches (the Herschel–Quincke
+        // notch) makes y21 vanish; keep the matrix finite.
+        let floor = 1e-12 * (y11.norm() + y22.norm());
+        if y21.norm() < floor {
+            y21 = Complex64::new(floor, 0.0);
+        }
+        TransferMatrix::new(-y22 / y21, 1.0 / y21, y12 - y11 * y22 / y21, y11 / y21)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        for (b, branch) in self.branches.iter().enumerate() {
+            if branch.is_empty() {
+                return Err(format!("branch {b} has no elements"));
+            }
+            for (i, elem) in branch.iter().enumerate() {
+                elem.validate().map_err(|reason| {
+                    format!("branch {b} element {i} ({}): {reason}", elem.label())
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        let describe = |branch: &[Box<dyn AcousticElement>]| {
+            branch.iter().map(|e| e.label()).collect::<Vec<_>>().join(" → ")
+        };
+        format!(
+            "Parallel [{}] ‖ [{}]",
+            describe(&self.branches[0]),
+            describe(&self.branches[1])
         )
     }
+
+    /// The flow splits so both branches drop the same pressure; found by
+    /// bisection on the split fraction.
+    fn pressure_drop(&self, flow_rate: f64, rho: f64) -> f64 {
+        let (mut lo, mut hi) = (0.0, 1.0);
+        for _ in 0..60 {
+            let split = 0.5 * (lo + hi);
+            let first = Self::branch_drop(&self.branches[0], split * flow_rate, rho);
+            let second = Self::branch_drop(&self.branches[1], (1.0 - split) * flow_rate, rho);
+            if first > second {
+                hi = split;
+            } else {
+                lo = split;
+            }
+        }
+        Self::branch_drop(&self.branches[0], 0.5 * (lo + hi) * flow_rate, rho)
+    }
+}
+
+/// Where a [`CustomElement`] takes its matrix from.
+#[derive(Clone)]
+pub enum MatrixSource {
+    /// Any function of frequency in Hz, e.g. a fitted model.
+    Function(Arc<dyn Fn(f64) -> TransferMatrix + Send + Sync>),
+    /// Rows of (frequency in Hz, matrix), sorted by frequency. Entries are
+    /// interpolated linearly between rows and held at the first/last row
+    /// outside the table.
+    Table(Vec<(f64, TransferMatrix)>),
+}
+
+/// An element whose transfer matrix is supplied by the user, for dropping
+/// measured two-port data or a model from elsewhere into the chain.
+///
+/// The matrix must follow the chain convention (upstream = T·downstream,
+/// volume velocity); it is used as-is regardless of `c` and `rho`.
+#[derive(Clone)]
+pub struct CustomElement {
+    /// Description shown in schematics and diagnostics.
+    pub name: String,
+    /// Matrix as a function of frequency.
+    pub source: MatrixSource,
+}
+
+impl CustomElement {
+    /// Element computed by `matrix(frequency_hz)`.
+    pub fn from_fn(name: impl Into<String>, matrix: impl Fn(f64) -> TransferMatrix + Send + Sync + 'static) -> Self {
+        Self {
+            name: name.into(),
+            source: MatrixSource::Function(Arc::new(matrix)),
+        }
+    }
+
+    /// Element interpolated from `(frequency, a, b, c, d)` rows in any order.
+    pub fn from_table(
+        name: impl Into<String>,
+        rows: impl IntoIterator<Item = (f64, Complex64, Complex64, Complex64, Complex64)>,
+    ) -> Self {
+        let mut table: Vec<(f64, TransferMatrix)> = rows
+            .into_iter()
+            .map(|(f, a, b, c, d)| (f, TransferMatrix::new(a, b, c, d)))
+            .collect();
+        table.sort_by(|x, y| x.0.total_cmp(&y.0));
+        Self {
+            name: name.into(),
+            source: MatrixSource::Table(table),
+        }
+    }
+
+    /// Matrix at `frequency` in Hz.
+    pub fn matrix_at(&self, frequency: f64) -> TransferMatrix {
+        match &self.source {
+            MatrixSource::Function(matrix) => matrix(frequency),
+            MatrixSource::Table(table) => {
+                let upper = table.partition_point(|&(f, _)| f < frequency);
+                if upper == 0 {
+                    return table[0].1;
+                }
+                if upper == table.len() {
+                    return table[upper - 1].1;
+                }
+                let ((f0, t0), (f1, t1)) = (table[upper - 1], table[upper]);
+                let w = (frequency - f0) / (f1 - f0);
+                let lerp = |x: Complex64, y: Complex64| x + (y - x) * w;
+                TransferMatrix::new(lerp(t0.a, t1.a), lerp(t0.b, t1.b), lerp(t0.c, t1.c), lerp(t0.d, t1.d))
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for CustomElement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let source = match &self.source {
+            MatrixSource::Function(_) => "function".to_string(),
+            MatrixSource::Table(table) => format!("{} rows", table.len()),
+        };
+        f.debug_struct("CustomElement")
+            .field("name", &self.name)
+            .field("source", &source)
+            .finish()
+    }
+}
+
+impl AcousticElement for CustomElement {
+    fn transfer_matrix(&self, omega: f64, _c: f64, _rho: f64) -> TransferMatrix {
+        self.matrix_at(omega / (2.0 * std::f64::consts::PI))
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let MatrixSource::Table(table) = &self.source else {
+            return Ok(());
+        };
+        if table.is_empty() {
+            return Err("table has no rows".to_string());
+        }
+        for (i, (f, t)) in table.iter().enumerate() {
+            if !(*f >= 0.0 && f.is_finite()) {
+                return Err(format!("row {i}: frequency must be >= 0, got {f}"));
+            }
+            if ![t.a, t.b, t.c, t.d].iter().all(|x| x.is_finite()) {
+                return Err(format!("row {i} at {f} Hz has a non-finite entry"));
+            }
+            if i > 0 && table[i - 1].0 == *f {
+                return Err(format!("row {i}: duplicate frequency {f} Hz"));
+            }
+        }
+        Ok(())
+    }
+
+    fn label(&self) -> String {
+        self.name.clone()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::breakout::ShellMaterial;
     use std::f64::consts::PI;
 
+    #[test]
+    fn test_duct_mean_flow_convects_waves() {
+        let (c, rho) = (343.0, 1.204);
+        let omega = 2.0 * PI * 1500.0;
+        let k = omega / c;
+        let still = StraightDuct::new(0.1, 0.01);
+        let z = still.impedance(c, rho);
+        let j = Complex64::new(0.0, 1.0);
+
+        // A vanishing flow is the stationary duct
+        let m0 = still.transfer_matrix(omega, c, rho);
+        let m1 = still.clone().with_mach(1e-12).transfer_matrix(omega, c, rho);
+        assert!((m0.a - m1.a).norm() < 1e-9 && (m0.b - m1.b).norm() < 1e-9 * m0.b.norm());
+
+        // Each travelling wave keeps its shape, with its own wavenumber
+        let mach = 0.2;
+        let duct = still.clone().with_mach(mach);
+        let t = duct.transfer_matrix(omega, c, rho);
+        for (direction, wavenumber) in [(1.0, k / (1.0 + mach)), (-1.0, -k / (1.0 - mach))] {
+            let (p2, u2) = (Complex64::new(1.0, 0.0), Complex64::new(direction / z, 0.0));
+            let (p1, u1) = (t.a * p2 + t.b * u2, t.c * p2 + t.d * u2);
+            let expected = (j * wavenumber * duct.length).exp();
+            assert!((p1 - expected).norm() < 1e-9, "{direction}: {p1} vs {expected}");
+            assert!((u1 * z - direction * expected).norm() < 1e-9);
+        }
+
+        let q = 0.2 * c * still.area();
+        assert!((still.clone().with_mean_flow(q, c).mach - 0.2).abs() < 1e-12);
+        assert!(still.with_mach(1.0).validate().is_err());
+    }
+
     #[test]
     fn test_quarter_wave_duct() {
         // At quarter wavelength, kL = π/2, cos(kL) = 0
@@ -69,4 +2563,794 @@ mod tests {
         assert!(t.a.norm() < 1e-10, "T11 should be ~0 at quarter wave");
         assert!(t.d.norm() < 1e-10, "T22 should be ~0 at quarter wave");
     }
+
+    #[test]
+    fn test_quarter_wave_resonator_matches_analytical_tl() {
+        // Side-branch TL = 10·log₁₀(1 + (S_b/(2S)·tan(kL))²)
+        use crate::constants::{speed_of_sound_and_density, STANDARD_PRESSURE};
+        use crate::gas::Gas;
+        use crate::muffler::Muffler;
+
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
+        let pipe_diameter = 10e-3;
+        let branch = QuarterWaveResonator::new(0.1, 8e-3);
+        let s_pipe = area_from_diameter(pipe_diameter);
+        let z_pipe = rho * c / s_pipe;
+        let ratio = branch.area() / (2.0 * s_pipe);
+        let length = branch.length;
+        let muffler = Muffler::new(vec![Box::new(branch)], z_pipe, z_pipe);
+
+        for freq in [100.0, 300.0, 700.0, 1200.0, 2500.0] {
+            let omega = 2.0 * PI * freq;
+            let k = omega / c;
+            let expected = 10.0 * (1.0 + (ratio * (k * length).tan()).powi(2)).log10();
+            let tl = muffler.transmission_loss(omega, c, rho);
+            assert!(
+                (tl - expected).abs() < 1e-9,
+                "TL mismatch at {freq} Hz: TMM = {tl}, analytical = {expected}"
+            );
+        }
+
+        // Near the tuning frequency the branch short-circuits the duct.
+        let f_tuned = c / (4.0 * length);
+        let tl_tuned = muffler.transmission_loss(2.0 * PI * f_tuned, c, rho);
+        assert!(tl_tuned > 60.0, "TL at tuning frequency should be very large, got {tl_tuned}");
+    }
+
+    #[test]
+    fn test_perforated_tube_tends_to_straight_duct_at_low_porosity() {
+        let (c, rho) = (343.0, 1.204);
+        let duct = StraightDuct::new(0.08, 0.01);
+        let perf = PerforatedTube::new(0.08, 0.01, 1e-9, 2e-3, 1e-3);
+        for freq in [200.0, 1500.0, 6000.0] {
+            let omega = 2.0 * PI * freq;
+            let a = duct.transfer_matrix(omega, c, rho);
+            let b = perf.transfer_matrix(omega, c, rho);
+            assert!((a.a - b.a).norm() < 1e-6, "T11 mismatch at {freq} Hz");
+            assert!((a.b - b.b).norm() / a.b.norm().max(1.0) < 1e-6, "T12 mismatch at {freq} Hz");
+            assert!((a.d - b.d).norm() < 1e-6, "T22 mismatch at {freq} Hz");
+        }
+
+        // An open perforate leaks energy: the determinant stays 1 (reciprocal)
+        // but the pipe now attenuates.
+        let t = PerforatedTube::new(0.08, 0.01, 0.1, 2e-3, 1e-3).transfer_matrix(2.0 * PI * 1000.0, c, rho);
+        let det = t.a * t.d - t.b * t.c;
+        assert!((det - Complex64::new(1.0, 0.0)).norm() < 1e-6, "det = {det}");
+        let z = rho * c / area_from_diameter(0.01);
+        let tl = t.transmission_loss(z, z);
+        assert!(tl > 1.0, "perforated pipe should attenuate, got TL = {tl}");
+    }
+
+    #[test]
+    fn test_concentric_tube_resonator_limits() {
+        let (c, rho) = (343.0, 1.204);
+        let pipe_diameter = 0.01;
+        let z = rho * c / area_from_diameter(pipe_diameter);
+
+        // Nearly closed perforate: behaves like the bare inner pipe.
+        let sealed = ConcentricTubeResonator::new(
+            PerforatedTube::new(0.1, pipe_diameter, 1e-7, 2e-3, 1e-3),
+            0.04,
+        );
+        let duct = StraightDuct::new(0.1, pipe_diameter);
+        for freq in [300.0, 1200.0] {
+            let omega = 2.0 * PI * freq;
+            let a = duct.transfer_matrix(omega, c, rho);
+            let b = sealed.transfer_matrix(omega, c, rho);
+            assert!((a.a - b.a).norm() < 1e-4, "T11 mismatch at {freq} Hz: {} vs {}", a.a, b.a);
+            assert!((a.d - b.d).norm() < 1e-4, "T22 mismatch at {freq} Hz: {} vs {}", a.d, b.d);
+        }
+
+        // Open perforate: the chamber attenuates, and a reciprocal element
+        // keeps det(T) = 1.
+        let ctr = ConcentricTubeResonator::new(
+            PerforatedTube::new(0.1, pipe_diameter, 0.2, 2e-3, 1e-3),
+            0.04,
+        );
+        let mut peak: f64 = 0.0;
+        for freq in (1..100).map(|i| i as f64 * 50.0) {
+            let t = ctr.transfer_matrix(2.0 * PI * freq, c, rho);
+            let det = t.a * t.d - t.b * t.c;
+            assert!((det - Complex64::new(1.0, 0.0)).norm() < 1e-6, "det = {det} at {freq} Hz");
+            let tl = t.transmission_loss(z, z);
+            assert!(tl.is_finite(), "TL must be finite at {freq} Hz");
+            peak = peak.max(tl);
+        }
+        assert!(peak > 10.0, "CTR should give substantial attenuation, peak TL = {peak}");
+    }
+
+    #[test]
+    fn test_conical_duct_limits() {
+        let (c, rho) = (343.0, 1.204);
+
+        // A vanishing taper must reproduce the straight duct.
+        let cone = ConicalDuct::new(0.05, 0.02, 0.02 * (1.0 + 1e-7));
+        let duct = StraightDuct::new(0.05, 0.02);
+        let omega = 2.0 * PI * 2000.0;
+        let a = cone.transfer_matrix(omega, c, rho);
+        let b = duct.transfer_matrix(omega, c, rho);
+        assert!((a.a - b.a).norm() < 1e-5, "T11: {} vs {}", a.a, b.a);
+        assert!((a.b - b.b).norm() / b.b.norm() < 1e-5, "T12: {} vs {}", a.b, b.b);
+        assert!((a.c - b.c).norm() / b.c.norm() < 1e-5, "T21: {} vs {}", a.c, b.c);
+        assert!((a.d - b.d).norm() < 1e-5, "T22: {} vs {}", a.d, b.d);
+
+        // Expanding and contracting cones are reciprocal (det = 1), and a
+        // cone is the reverse of its mirror image.
+        let expand = ConicalDuct::new(0.03, 0.006, 0.04);
+        let contract = ConicalDuct::new(0.03, 0.04, 0.006);
+        for freq in [100.0, 1000.0, 8000.0] {
+            let omega = 2.0 * PI * freq;
+            let te = expand.transfer_matrix(omega, c, rho);
+            let tc = contract.transfer_matrix(omega, c, rho);
+            let det = te.a * te.d - te.b * te.c;
+            assert!((det - Complex64::new(1.0, 0.0)).norm() < 1e-9, "det = {det} at {freq} Hz");
+            assert!((te.a - tc.d).norm() < 1e-9, "mirror symmetry broken at {freq} Hz");
+            assert!((te.b - tc.b).norm() / te.b.norm() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_profiled_duct() {
+        let (c, rho) = (343.0, 1.204);
+        let omega = 2.0 * PI * 3000.0;
+        let close = |a: TransferMatrix, b: TransferMatrix| {
+            [(a.a, b.a), (a.b, b.b), (a.c, b.c), (a.d, b.d)]
+                .iter()
+                .all(|(x, y)| (x - y).norm() <= 1e-9 * y.norm().max(1.0))
+        };
+
+        // A finely sampled taper is the same cone
+        let profile: Vec<(f64, f64)> = (0..=20).map(|i| (i as f64 * 2e-3, 3e-3 + i as f64 * 0.5e-3)).collect();
+        let taper = ProfiledDuct::new(profile);
+        assert!(taper.validate().is_ok());
+        assert!((taper.length() - 0.04).abs() < 1e-12);
+        assert_eq!(taper.segments().len(), 20);
+        let cone = ConicalDuct::new(0.04, 6e-3, 26e-3);
+        assert!(close(taper.transfer_matrix(omega, c, rho), cone.transfer_matrix(omega, c, rho)));
+
+        // A cylinder sampled at both ends is a straight duct
+        let tube = ProfiledDuct::new(vec![(0.01, 0.02), (0.09, 0.02)]);
+        let duct = StraightDuct::new(0.08, 0.04);
+        assert!(close(tube.transfer_matrix(omega, c, rho), duct.transfer_matrix(omega, c, rho)));
+
+        let parsed = ProfiledDuct::from_table("x_mm, r_mm\n0, 10\n40; 12.5\n").unwrap();
+        assert_eq!(parsed.profile, vec![(0.0, 0.01), (0.04, 0.0125)]);
+        assert!(ProfiledDuct::new(vec![(0.0, 0.01)]).validate().is_err());
+        assert!(ProfiledDuct::new(vec![(0.0, 0.01), (0.0, 0.02)]).validate().is_err());
+        assert!(ProfiledDuct::new(vec![(0.0, 0.01), (0.01, 0.0)]).validate().is_err());
+    }
+
+    #[test]
+    fn test_non_circular_sections() {
+        let c = 343.0;
+        let flat = CrossSection::Rectangular { width: 0.08, height: 0.02 };
+        assert!((flat.area() - 1.6e-3).abs() < 1e-15);
+        assert!((flat.hydraulic_diameter() - 0.032).abs() < 1e-12);
+        // The long side sets the first cross mode
+        assert!((flat.cutoff_frequency(c) - c / 0.16).abs() < 1e-9);
+
+        let round = CrossSection::Circular { diameter: 0.04 };
+        assert!((round.hydraulic_diameter() - 0.04).abs() < 1e-12);
+        assert!((round.cutoff_frequency(c) - 5025.0).abs() < 5.0);
+
+        let gap = CrossSection::Annular {
+            inner_diameter: 0.03,
+            outer_diameter: 0.04,
+        };
+        assert!((gap.hydraulic_diameter() - 0.01).abs() < 1e-12);
+        assert!(gap.cutoff_frequency(c) < round.cutoff_frequency(c));
+        assert!(CrossSection::Annular {
+            inner_diameter: 0.04,
+            outer_diameter: 0.03
+        }
+        .validate()
+        .is_err());
+
+        // Lossless plane waves only see the area
+        let square = StraightDuct::with_section(0.1, CrossSection::Rectangular { width: 0.02, height: 0.02 });
+        let same_area = StraightDuct::new(0.1, square.section.equivalent_diameter());
+        let omega = 2.0 * PI * 1000.0;
+        let (a, b) = (square.transfer_matrix(omega, c, 1.2), same_area.transfer_matrix(omega, c, 1.2));
+        assert!((a.b - b.b).norm() < 1e-9 * b.b.norm());
+        assert_eq!(square.cutoff_frequency(c), Some(c / 0.04));
+    }
+
+    #[test]
+    fn test_rough_duct_wall_losses() {
+        let (c, rho) = (343.0, 1.2);
+        let z = StraightDuct::new(0.5, 0.006).impedance(c, rho);
+        let tl = |duct: &StraightDuct, freq: f64| {
+            duct.transfer_matrix(2.0 * PI * freq, c, rho).transmission_loss(z, z)
+        };
+
+        let ideal = StraightDuct::new(0.5, 0.006);
+        let brass = ideal.clone().with_losses(WallLossModel::Viscothermal);
+        let printed = brass.clone().with_roughness(0.2e-3 / 0.006);
+
+        for &freq in &[200.0, 1000.0, 4000.0] {
+            assert!(tl(&ideal, freq).abs() < 1e-9);
+            let (smooth, rough) = (tl(&brass, freq), tl(&printed, freq));
+            assert!(smooth > 0.0, "smooth wall should attenuate at {freq} Hz");
+            assert!(rough > smooth, "rough {rough} dB <= smooth {smooth} dB at {freq} Hz");
+            // Reciprocity survives the losses
+            let t = brass.transfer_matrix(2.0 * PI * freq, c, rho);
+            let det = t.a * t.d - t.b * t.c;
+            assert!((det - Complex64::new(1.0, 0.0)).norm() < 1e-6, "det = {det}");
+        }
+        // Roughness saturates at twice the smooth loss
+        assert!(tl(&printed, 4000.0) < 2.0 * tl(&brass, 4000.0) + 1e-9);
+    }
+
+    #[test]
+    fn test_low_reduced_frequency_losses() {
+        let (c, rho) = (343.0, 1.2);
+        let model = |duct: StraightDuct, losses| duct.with_losses(losses);
+
+        // Wide duct: matches Kirchhoff's boundary-layer result
+        let wide = StraightDuct::new(0.3, 0.03);
+        let omega = 2.0 * PI * 1000.0;
+        let kirchhoff = model(wide.clone(), WallLossModel::Viscothermal).transfer_matrix(omega, c, rho);
+        let lrf = model(wide, WallLossModel::LowReducedFrequency).transfer_matrix(omega, c, rho);
+        assert!((lrf.a - kirchhoff.a).norm() < 1e-3 && (lrf.b - kirchhoff.b).norm() < 1e-3 * kirchhoff.b.norm());
+
+        // Capillary at low frequency: Poiseuille flow, R = 8μL/(πr⁴)
+        let (length, radius) = (0.1, 0.25e-3);
+        let capillary = model(StraightDuct::new(length, 2.0 * radius), WallLossModel::LowReducedFrequency);
+        let b = capillary.transfer_matrix(2.0 * PI * 5.0, c, rho).b;
+        let poiseuille = 8.0 * AIR_VISCOSITY * length / (PI * radius.powi(4));
+        assert!((b.re / poiseuille - 1.0).abs() < 0.05, "{} vs {poiseuille}", b.re);
+
+        // A narrow chamber between its pipes keeps a finite, damped notch
+        // where the lossless one passes everything (kL = π)
+        let chamber = StraightDuct::new(0.1, 0.012);
+        let z = StraightDuct::new(0.1, 0.003).impedance(c, rho);
+        let omega = PI * c / chamber.length;
+        let tl = |losses| model(chamber.clone(), losses).transfer_matrix(omega, c, rho).transmission_loss(z, z);
+        assert!(tl(WallLossModel::Lossless).abs() < 1e-9);
+        assert!(tl(WallLossModel::LowReducedFrequency) > 0.05, "{}", tl(WallLossModel::LowReducedFrequency));
+
+        // The two Bessel-ratio branches meet at the switch-over
+        let (inside, outside) = (Complex64::new(42.4, -42.4), Complex64::new(42.5, -42.5));
+        assert!((bessel_j1_over_j0(inside) - bessel_j1_over_j0(outside)).norm() < 1e-3);
+    }
+
+    #[test]
+    fn test_elastic_walls() {
+        let (c, rho) = (343.0, 1.2);
+        let pla = Shell {
+            material: ShellMaterial::Pla,
+            thickness: 1e-3,
+        };
+        let omega = 2.0 * PI * 50.0;
+
+        // Well below the ring frequency a tube follows Korteweg
+        let tube = StraightDuct::new(0.3, 0.04).with_wall(pla);
+        let n = tube.wall_compliance(omega, c, rho).unwrap();
+        let modulus = ShellMaterial::Pla.youngs_modulus() / (1.0 - 0.35f64.powi(2));
+        let korteweg = rho * c * c * 0.04 / (modulus * pla.thickness);
+        assert!((n.re - 1.0 - korteweg).abs() < 0.01 * korteweg, "{} vs {korteweg}", n.re - 1.0);
+
+        // A printed box of the same size loses several percent of its
+        // sound speed, and its half-wave resonance moves down with it
+        let section = CrossSection::Rectangular {
+            width: 0.04,
+            height: 0.04,
+        };
+        let rigid = StraightDuct::with_section(0.5, section);
+        let elastic = rigid.clone().with_wall(pla);
+        let speed_ratio = 1.0 / elastic.wall_compliance(omega, c, rho).unwrap().sqrt().re;
+        assert!(speed_ratio < 0.97 && speed_ratio > 0.5, "{speed_ratio}");
+        // First zero of A = cos(γL), the quarter-wave frequency
+        let quarter_wave = |duct: &StraightDuct| {
+            (1..2000)
+                .map(f64::from)
+                .find(|&f| duct.transfer_matrix(2.0 * PI * f, c, rho).a.re < 0.0)
+                .unwrap()
+        };
+        let shift = quarter_wave(&elastic) / quarter_wave(&rigid);
+        assert!(shift < 0.97, "{shift}");
+        assert!(rigid.wall_compliance(omega, c, rho).is_none());
+
+        assert!(StraightDuct::new(0.1, 0.04)
+            .with_wall(Shell {
+                thickness: 0.0,
+                ..pla
+            })
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_radiation_impedances() {
+        let diameter = 0.02;
+        let k_at = |ka: f64| ka / (diameter / 2.0);
+        // Low ka: R → (ka)²/2 flanged, (ka)²/4 unflanged, and the end
+        // corrections 8a/(3π) and 0.6133·a
+        let ka = 0.05;
+        let (flanged, unflanged) = (
+            flanged_radiation_impedance(k_at(ka), diameter),
+            unflanged_radiation_impedance(k_at(ka), diameter),
+        );
+        assert!((flanged.re / (ka * ka / 2.0) - 1.0).abs() < 0.01, "{flanged}");
+        assert!((unflanged.re / (ka * ka / 4.0) - 1.0).abs() < 0.01, "{unflanged}");
+        assert!((flanged.im / ka - 8.0 / (3.0 * PI)).abs() < 0.01);
+        assert!((unflanged.im / ka - 0.6133).abs() < 0.01);
+
+        // High ka: the piston radiates like a plane wave
+        let wide = flanged_radiation_impedance(k_at(20.0), diameter);
+        assert!((wide - Complex64::new(1.0, 0.0)).norm() < 0.05, "{wide}");
+        // Tabulated piston value at ka = 1: 0.4232 + 0.6468j
+        let one = flanged_radiation_impedance(k_at(1.0), diameter);
+        assert!((one - Complex64::new(0.4232, 0.6468)).norm() < 1e-3, "{one}");
+    }
+
+    #[test]
+    fn test_duct_pressure_drop() {
+        let rho = 1.2;
+        let duct = StraightDuct::new(1.0, 0.004);
+
+        // Laminar: Hagen–Poiseuille Δp = 128·μ·L·Q / (π·D⁴)
+        let q = 2.0 / 60_000.0;
+        let expected = 128.0 * AIR_VISCOSITY * 1.0 * q / (PI * 0.004f64.powi(4));
+        let dp = duct.pressure_drop(q, rho);
+        assert!((dp - expected).abs() / expected < 1e-9, "dp = {dp}, expected {expected}");
+        assert_eq!(dp, duct.clone().with_roughness(0.05).pressure_drop(q, rho));
+
+        // Turbulent: roughness raises the friction factor
+        let q = 20.0 / 60_000.0;
+        let smooth = duct.pressure_drop(q, rho);
+        let rough = duct.with_roughness(0.05).pressure_drop(q, rho);
+        assert!(rough > 1.5 * smooth, "rough {rough} Pa vs smooth {smooth} Pa");
+    }
+
+    #[test]
+    fn test_orifice_trades_attenuation_for_pressure_drop() {
+        let (c, rho) = (343.0, 1.2);
+        let z = StraightDuct::new(0.03, 0.006).impedance(c, rho);
+        let flow_rate = 2.0 / 60_000.0;
+        let tl = |orifice: &Orifice, freq: f64| {
+            orifice.transfer_matrix(2.0 * PI * freq, c, rho).transmission_loss(z, z)
+        };
+
+        let large = Orifice::new(3e-3, 1e-3);
+        let small = Orifice::new(1e-3, 1e-3);
+
+        // Mass-controlled: attenuation grows with frequency
+        assert!(tl(&small, 2000.0) > tl(&small, 200.0));
+        // Smaller hole: more attenuation, more restriction
+        for &freq in &[200.0, 1000.0, 4000.0] {
+            assert!(tl(&small, freq) > tl(&large, freq), "at {freq} Hz");
+        }
+        let dp_large = large.pressure_drop(flow_rate, rho);
+        let dp_small = small.pressure_drop(flow_rate, rho);
+        assert!((dp_small / dp_large - 81.0).abs() < 1e-9, "Δp ∝ 1/d⁴");
+    }
+
+    #[test]
+    fn test_lumped_rlc() {
+        let (c, rho) = (343.0, 1.2);
+        let duct = StraightDuct::new(0.03, 0.006);
+        let z = duct.impedance(c, rho);
+        let tl = |elem: &LumpedRlc, omega: f64| elem.transfer_matrix(omega, c, rho).transmission_loss(z, z);
+
+        // A series inertance equal to the orifice mass reproduces its reactance
+        let orifice = Orifice::new(2e-3, 1e-3);
+        let mass = LumpedRlc::new(0.0, orifice.acoustic_mass(rho), 0.0, Connection::Series);
+        let omega = 2.0 * PI * 1000.0;
+        assert!((mass.impedance(omega).im - orifice.impedance(omega, rho).im).abs() < 1e-9);
+
+        // A shunt L–C branch notches at 1/√(LC) and is transparent far away
+        let branch = LumpedRlc::new(0.0, 5e3, 2e-11, Connection::Shunt);
+        let omega0 = branch.resonance().unwrap();
+        assert!(tl(&branch, omega0 * (1.0 + 1e-9)) > 60.0);
+        assert!(tl(&branch, omega0 / 20.0) < 1.0);
+        // Damping flattens the notch
+        let damped = LumpedRlc::new(0.1 * z, 5e3, 2e-11, Connection::Shunt);
+        assert!(tl(&damped, omega0) < tl(&branch, omega0 * (1.0 + 1e-9)));
+
+        // Steady flow drop only through a series R–L
+        let flow_rate = 2.0 / 60_000.0;
+        let series = LumpedRlc::new(1e6, 0.0, 0.0, Connection::Series);
+        assert!((series.pressure_drop(flow_rate, rho) - 1e6 * flow_rate).abs() < 1e-12);
+        assert_eq!(damped.pressure_drop(flow_rate, rho), 0.0);
+
+        assert!(LumpedRlc::new(0.0, 0.0, 0.0, Connection::Shunt).validate().is_err());
+        assert!(LumpedRlc::new(-1.0, 0.0, 0.0, Connection::Series).validate().is_err());
+    }
+
+    #[test]
+    fn test_area_changes_add_junction_inertance() {
+        let (c, rho) = (343.0, 1.2);
+        let (pipe, chamber, length) = (0.006, 0.04, 0.1);
+        let z = StraightDuct::new(0.03, pipe).impedance(c, rho);
+
+        // The junction is a slug of pipe air δ long
+        let expansion = AreaChange::expansion(pipe, chamber);
+        let delta = area_change_end_correction(pipe, chamber);
+        assert!(delta > 0.0 && expansion.end_correction == delta);
+        let omega = 2.0 * PI * 500.0;
+        let t = expansion.transfer_matrix(omega, c, rho);
+        let slug = StraightDuct::new(delta, pipe).transfer_matrix(omega, c, rho);
+        assert!((t.b - slug.b).norm() < 1e-3 * slug.b.norm());
+
+        // Where the bare chamber is transparent (kL = π) the corrected
+        // one still attenuates
+        let tl = |end_correction: f64| {
+            let omega = PI * c / length;
+            AreaChange::expansion(pipe, chamber)
+                .with_end_correction(end_correction)
+                .transfer_matrix(omega, c, rho)
+                .chain(&StraightDuct::new(length, chamber).transfer_matrix(omega, c, rho))
+                .chain(
+                    &AreaChange::contraction(pipe, chamber)
+                        .with_end_correction(end_correction)
+                        .transfer_matrix(omega, c, rho),
+                )
+                .transmission_loss(z, z)
+        };
+        assert!(tl(0.0).abs() < 1e-9);
+        assert!(tl(delta) > 1e-3, "{}", tl(delta));
+
+        // Textbook loss coefficients and the resulting static drop
+        let ratio: f64 = (pipe / chamber).powi(2);
+        let expansion = AreaChange::expansion(pipe, chamber);
+        assert!((expansion.loss_coefficient() - (1.0 - ratio).powi(2)).abs() < 1e-12);
+        let contraction = AreaChange::contraction(pipe, chamber);
+        assert!((contraction.loss_coefficient() - 0.5 * (1.0 - ratio)).abs() < 1e-12);
+        let measured = expansion.clone().with_loss_coefficient(0.5);
+        let flow_rate = 2.0 / 60_000.0;
+        let dp = measured.pressure_drop(flow_rate, rho);
+        let velocity = flow_rate / area_from_diameter(pipe);
+        assert!((dp - 0.25 * rho * velocity * velocity).abs() < 1e-12);
+        assert!(AreaChange::contraction(chamber, pipe).validate().is_err());
+    }
+
+    #[test]
+    fn test_baffle_tube_resonance() {
+        let (c, rho) = (343.0, 1.2);
+        let (pipe, chamber, section) = (0.006, 0.04, 0.04);
+        let z = StraightDuct::new(0.03, pipe).impedance(c, rho);
+        let baffle = Baffle::new(chamber, 0.002, 0.006, 0.002);
+        let body = StraightDuct::new(section, chamber);
+        let tl = |f: f64| {
+            let omega = 2.0 * PI * f;
+            body.transfer_matrix(omega, c, rho)
+                .chain(&baffle.transfer_matrix(omega, c, rho))
+                .chain(&body.transfer_matrix(omega, c, rho))
+                .transmission_loss(z, z)
+        };
+
+        // The tube mass between the two volumes is a pass band at
+        // f = c/2π·√(S_t/l_eff·(1/V₁ + 1/V₂)), l_eff including both mouths
+        let volume = area_from_diameter(chamber) * section;
+        let l_eff = 0.002 + 2.0 * area_change_end_correction(pipe, chamber);
+        let predicted = c / (2.0 * PI) * (area_from_diameter(pipe) / l_eff * 2.0 / volume).sqrt();
+        let dip = (400..1000)
+            .map(|f| f as f64)
+            .min_by(|&a, &b| tl(a).total_cmp(&tl(b)))
+            .unwrap();
+        assert!((dip / predicted - 1.0).abs() < 0.1, "dip {dip} Hz vs {predicted:.0} Hz");
+        assert!(tl(dip) < 1.0);
+
+        // Above it the baffle beats a plain chamber of the same length
+        let m = (chamber / pipe).powi(2);
+        let plain_max = 20.0 * ((m + 1.0 / m) / 2.0).log10();
+        assert!(tl(2500.0) > plain_max + 10.0);
+
+        // Protruding tubes add the annulus branches; a tube shorter than
+        // the plate is rejected
+        let protruding = Baffle::new(chamber, 0.002, 0.006, 0.012);
+        assert!((protruding.protrusion() - 0.005).abs() < 1e-12);
+        assert!(protruding.annulus().is_some() && baffle.annulus().is_none());
+        assert!(Baffle::new(chamber, 0.004, 0.006, 0.002).validate().is_err());
+        assert!(protruding.pressure_drop(2.0 / 60_000.0, rho) > 0.0);
+    }
+
+    #[test]
+    fn test_offset_chamber_modes() {
+        assert!(bessel_j(0, 2.404_825_557_7).abs() < 1e-10);
+        assert!((bessel_j(1, 1.0) - 0.440_050_585_7).abs() < 1e-10);
+        let modes = circular_duct_modes();
+        assert_eq!(modes[0], (0, 0.0));
+        for (m, alpha) in [(1, 1.841_183_781), (2, 3.054_236_928), (0, 3.831_705_970)] {
+            assert!(modes.iter().any(|&(n, a)| n == m && (a - alpha).abs() < 1e-8), "α_{m} = {alpha}");
+        }
+
+        let (c, rho) = (343.0, 1.2);
+        let (pipe, chamber, length) = (0.006, 0.04, 0.1);
+        let z = StraightDuct::new(0.03, pipe).impedance(c, rho);
+        let centred = OffsetChamber::new(length, chamber, pipe, pipe);
+        let plane = |f: f64| {
+            let omega = 2.0 * PI * f;
+            let delta = area_change_end_correction(pipe, chamber);
+            AreaChange::expansion(pipe, chamber)
+                .with_end_correction(delta)
+                .transfer_matrix(omega, c, rho)
+                .chain(&StraightDuct::new(length, chamber).transfer_matrix(omega, c, rho))
+                .chain(
+                    &AreaChange::contraction(pipe, chamber)
+                        .with_end_correction(delta)
+                        .transfer_matrix(omega, c, rho),
+                )
+                .transmission_loss(z, z)
+        };
+
+        // Centred ports: the evanescent modes are the junction end
+        // corrections of the plane-wave model, and the matrix is reciprocal
+        for f in [300.0, 800.0, 1300.0, 2500.0] {
+            let t = centred.transfer_matrix(2.0 * PI * f, c, rho);
+            assert!((t.a * t.d - t.b * t.c - 1.0).norm() < 1e-6);
+            let tl = t.transmission_loss(z, z);
+            assert!((tl - plane(f)).abs() < 0.5, "{f} Hz: {tl} vs {}", plane(f));
+        }
+
+        // Ports on opposite walls couple through the (1,0) mode above its
+        // cut-on at 1.84·c/(πD) ≈ 5 kHz
+        let offset = chamber / 2.0 - pipe / 2.0 - 1e-3;
+        let opposite = centred.clone().with_offsets(offset, offset, PI);
+        let tl = |chamber: &OffsetChamber, f: f64| chamber.transfer_matrix(2.0 * PI * f, c, rho).transmission_loss(z, z);
+        assert!((tl(&opposite, 500.0) - tl(&centred, 500.0)).abs() < 1.0);
+        let high_diff = (5500..9000)
+            .step_by(50)
+            .map(|f| (tl(&opposite, f as f64) - tl(&centred, f as f64)).abs())
+            .fold(0.0, f64::max);
+        assert!(high_diff > 10.0, "{high_diff}");
+
+        assert!(centred.clone().with_offsets(0.018, 0.0, 0.0).validate().is_err());
+        assert!(opposite.validate().is_ok());
+    }
+
+    #[test]
+    fn test_monolith_equivalent_fluid() {
+        let (c, rho) = (343.0, 1.2);
+        // 400 cpsi, 0.15 mm walls: ~1.12 mm cells, ~77 % open
+        let cpsi = 400.0 / (0.0254 * 0.0254);
+        let block = Monolith::new(0.02, 0.04, cpsi, 0.15e-3);
+        assert!(block.validate().is_ok());
+        assert!((block.porosity() - 0.776).abs() < 0.01, "{}", block.porosity());
+        assert!((block.cell_width() - 1.12e-3).abs() < 0.01e-3);
+
+        // Reciprocal, and at low frequency a flow resistance σL/S matching
+        // the static drop
+        let omega = 2.0 * PI * 5.0;
+        let t = block.transfer_matrix(omega, c, rho);
+        assert!((t.a * t.d - t.b * t.c - 1.0).norm() < 1e-9);
+        let flow_rate = 1e-4;
+        let resistance = block.pressure_drop(flow_rate, rho) / flow_rate;
+        assert!((t.b.re / resistance - 1.0).abs() < 0.02, "{} vs {resistance}", t.b.re);
+
+        // High-frequency limit: air in the open cells with ρ/φ, K/φ
+        let (density, modulus) = block.equivalent_fluid(2.0 * PI * 1e6, c, rho);
+        assert!((density.re * block.porosity() / rho - 1.0).abs() < 0.05);
+        assert!((modulus.re * block.porosity() / (rho * c * c) - 1.0).abs() < 0.05);
+
+        // Finer cells attenuate more
+        let z = rho * c / block.area();
+        let tl = |cells: f64| {
+            Monolith::new(0.05, 0.04, cells * cpsi, 0.1e-3)
+                .transfer_matrix(2.0 * PI * 2000.0, c, rho)
+                .transmission_loss(z, z)
+        };
+        assert!(tl(1.0) > tl(0.25) && tl(0.25) > 0.0);
+        assert!(Monolith::new(0.02, 0.04, cpsi, 2e-3).validate().is_err());
+    }
+
+    #[test]
+    fn test_custom_element() {
+        let (c, rho) = (343.0, 1.2);
+        let duct = StraightDuct::new(0.08, 0.04);
+        let z = StraightDuct::new(0.03, 0.006).impedance(c, rho);
+        let model = duct.clone();
+        let function = CustomElement::from_fn("duct model", move |f| model.transfer_matrix(2.0 * PI * f, c, rho));
+
+        // A finely sampled table, shuffled, stands in for measured data
+        let mut rows: Vec<_> = (0..=2000)
+            .map(|i| {
+                let f = i as f64 * 5.0;
+                let t = duct.transfer_matrix(2.0 * PI * f, c, rho);
+                (f, t.a, t.b, t.c, t.d)
+            })
+            .collect();
+        rows.reverse();
+        let table = CustomElement::from_table("measured", rows);
+        assert!(table.validate().is_ok());
+        assert_eq!(table.label(), "measured");
+
+        for f in [250.0, 1234.5, 6001.0] {
+            let omega = 2.0 * PI * f;
+            let expected = duct.transfer_matrix(omega, c, rho).transmission_loss(z, z);
+            let from_fn = function.transfer_matrix(omega, c, rho).transmission_loss(z, z);
+            let from_table = table.transfer_matrix(omega, c, rho).transmission_loss(z, z);
+            assert!((from_fn - expected).abs() < 1e-12);
+            assert!((from_table - expected).abs() < 0.05, "{f} Hz: {from_table} vs {expected}");
+        }
+        // Held flat past the last row
+        let last = table.matrix_at(10_000.0);
+        assert_eq!(table.matrix_at(20_000.0).b, last.b);
+
+        let one = Complex64::new(1.0, 0.0);
+        let zero = Complex64::new(0.0, 0.0);
+        let duplicate = CustomElement::from_table("dup", [(100.0, one, zero, zero, one), (100.0, one, zero, zero, one)]);
+        assert!(duplicate.validate().is_err());
+        assert!(CustomElement::from_table("empty", []).validate().is_err());
+    }
+
+    #[test]
+    fn test_perforated_plate() {
+        let (c, rho) = (343.0, 1.2);
+        let (pipe, chamber, section) = (0.006, 0.04, 0.04);
+        let z = StraightDuct::new(0.03, pipe).impedance(c, rho);
+        let body = StraightDuct::new(section, chamber);
+        let tl = |plate: Option<&PerforatedPlate>, f: f64| {
+            let omega = 2.0 * PI * f;
+            let divider = plate.map_or(TransferMatrix::identity(), |p| p.transfer_matrix(omega, c, rho));
+            AreaChange::expansion(pipe, chamber)
+                .with_end_correction(0.0)
+                .transfer_matrix(omega, c, rho)
+                .chain(&body.transfer_matrix(omega, c, rho))
+                .chain(&divider)
+                .chain(&body.transfer_matrix(omega, c, rho))
+                .chain(&AreaChange::contraction(pipe, chamber).with_end_correction(0.0).transfer_matrix(omega, c, rho))
+                .transmission_loss(z, z)
+        };
+
+        // The hole mass equals that of σ·S worth of short tubes
+        let plate = PerforatedPlate::new(chamber, 0.05, 1e-3, 1e-3);
+        let omega = 2.0 * PI * 200.0;
+        let mass = plate.impedance(omega, c, rho).im / omega;
+        let expected = rho * (1e-3 + 0.75e-3) / (0.05 * plate.area());
+        assert!((mass / expected - 1.0).abs() < 1e-9);
+
+        // An open plate is nearly transparent
+        let open = PerforatedPlate::new(chamber, 1.0, 1e-3, 0.5e-3);
+        for f in [300.0, 1500.0, 3000.0] {
+            assert!((tl(Some(&open), f) - tl(None, f)).abs() < 0.5, "{f} Hz");
+        }
+        // A tight one is a mass between two volumes: attenuation rises well
+        // above that resonance
+        let mean_tl = |plate: Option<&PerforatedPlate>| {
+            (30..80).map(|i| tl(plate, i as f64 * 100.0)).sum::<f64>() / 50.0
+        };
+        assert!(mean_tl(Some(&plate)) > mean_tl(None) + 5.0);
+
+        assert!(plate.pressure_drop(2.0 / 60_000.0, rho) > open.pressure_drop(2.0 / 60_000.0, rho));
+        assert!(PerforatedPlate::new(chamber, 1.5, 1e-3, 1e-3).validate().is_err());
+    }
+
+    #[test]
+    fn test_bend_limits() {
+        let (c, rho) = (343.0, 1.2);
+        let omega = 2.0 * PI * 1500.0;
+        let diameter = 0.01;
+
+        // A gentle bend is just its centreline length of straight duct
+        let gentle = Bend::elbow(0.05, diameter);
+        assert_eq!(gentle.added_length(), 0.0);
+        let straight = StraightDuct::new(0.05 * std::f64::consts::FRAC_PI_2, diameter)
+            .transfer_matrix(omega, c, rho);
+        let bent = gentle.transfer_matrix(omega, c, rho);
+        assert!((bent.a - straight.a).norm() < 1e-12 && (bent.b - straight.b).norm() < 1e-6);
+
+        // A mitred corner still has acoustic length and restricts the flow
+        // far more than a smooth bend
+        let mitre = Bend::elbow(0.0, diameter);
+        assert!((mitre.added_length() - 0.25 * diameter).abs() < 1e-12);
+        assert!((mitre.loss_coefficient() - 0.946 * 0.5 - 2.05 * 0.25).abs() < 1e-9);
+        assert!(mitre.loss_coefficient() > 5.0 * gentle.loss_coefficient());
+        assert!(mitre.pressure_drop(2.0 / 60_000.0, rho) > 0.0);
+    }
+
+    #[test]
+    fn test_compliant_hose_limits() {
+        let (c, rho) = (343.0, 1.2);
+        let (length, diameter) = (1.0, 0.004);
+        let z = StraightDuct::new(length, diameter).impedance(c, rho);
+
+        // A very stiff wall is a rigid duct
+        let rigid = CompliantHose {
+            loss_factor: 0.0,
+            ..CompliantHose::new(length, diameter, 1e-3, 1e15)
+        };
+        let omega = 2.0 * PI * 700.0;
+        let t_rigid = rigid.transfer_matrix(omega, c, rho);
+        let t_duct = StraightDuct::new(length, diameter).transfer_matrix(omega, c, rho);
+        assert!((t_rigid.a - t_duct.a).norm() < 1e-6);
+        assert!((t_rigid.b - t_duct.b).norm() / z < 1e-6);
+
+        // Soft silicone (E ≈ 2 MPa): slower waves and lossy walls
+        let soft = CompliantHose::new(length, diameter, 1e-3, 2e6);
+        let n = soft.wall_loading(c, rho).sqrt();
+        assert!(n.re > 1.05, "wave slowdown = {n}");
+        let tl = soft.transfer_matrix(omega, c, rho).transmission_loss(z, z);
+        assert!(tl > 0.1, "lossy wall should attenuate, TL = {tl} dB");
+    }
+
+    #[test]
+    fn test_parallel_branches() {
+        let (c, rho) = (343.0, 1.2);
+        let d = 0.01;
+        let z = StraightDuct::new(0.1, d).impedance(c, rho);
+
+        // Two identical branches act as one duct of twice the area
+        let twin = ParallelBranches::new(
+            vec![Box::new(StraightDuct::new(0.1, d))],
+            vec![Box::new(StraightDuct::new(0.1, d))],
+        );
+        let wide = StraightDuct::new(0.1, d * 2f64.sqrt());
+        let omega = 2.0 * PI * 900.0;
+        let (t, w) = (twin.transfer_matrix(omega, c, rho), wide.transfer_matrix(omega, c, rho));
+        for (x, y) in [(t.a, w.a), (t.c * z, w.c * z), (t.b / z, w.b / z), (t.d, w.d)] {
+            assert!((x - y).norm() < 1e-9, "{x} vs {y}");
+        }
+
+        // Herschel–Quincke: equal-area paths differing by ΔL cancel where
+        // ΔL is half a wavelength
+        let (short, long) = (0.1, 0.3);
+        let hq = ParallelBranches::new(
+            vec![Box::new(StraightDuct::new(short, d))],
+            vec![Box::new(StraightDuct::new(long, d))],
+        );
+        let notch = c / (2.0 * (long - short));
+        let tl = |freq: f64| hq.transfer_matrix(2.0 * PI * freq, c, rho).transmission_loss(z, z);
+        assert!(tl(notch) > 40.0, "HQ notch TL = {} dB", tl(notch));
+        assert!(tl(0.5 * notch) < tl(notch));
+
+        // Flow splits to equalise the drop: the shorter branch carries more
+        let q = 2.0 / 60_000.0;
+        let drop = hq.pressure_drop(q, rho);
+        let short_only = StraightDuct::new(short, d).pressure_drop(q, rho);
+        assert!(drop < short_only && drop > 0.0);
+    }
+
+    #[test]
+    fn test_absorptive_branch_damps_notch() {
+        let (c, rho) = (343.0, 1.204);
+        let s_pipe = area_from_diameter(10e-3);
+        let z_pipe = rho * c / s_pipe;
+        let tl = |branch: &AbsorptiveBranch, freq: f64| {
+            branch.transfer_matrix(2.0 * PI * freq, c, rho).transmission_loss(z_pipe, z_pipe)
+        };
+
+        // Without a plug it is the plain quarter-wave branch
+        let bare = AbsorptiveBranch::new(0.1, 8e-3, 0.0, 20_000.0);
+        let plain = QuarterWaveResonator::new(0.1, 8e-3);
+        for freq in [100.0, 700.0, 2500.0] {
+            let omega = 2.0 * PI * freq;
+            let (a, b) = (bare.branch_admittance(omega, c, rho), plain.branch_admittance(omega, c, rho));
+            assert!((a - b).norm() <= 1e-12 * b.norm());
+        }
+
+        // A plug makes the notch shallower but wider
+        let plugged = AbsorptiveBranch::new(0.1, 8e-3, 0.02, 20_000.0);
+        assert!(plugged.validate().is_ok());
+        let band: Vec<f64> = (200..=1600).step_by(5).map(f64::from).collect();
+        let peak = |branch: &AbsorptiveBranch| band.iter().map(|&f| tl(branch, f)).fold(0.0, f64::max);
+        let width = |branch: &AbsorptiveBranch| band.iter().filter(|&&f| tl(branch, f) > 3.0).count();
+        assert!(peak(&plugged) < peak(&bare) - 10.0, "{} vs {}", peak(&plugged), peak(&bare));
+        assert!(peak(&plugged) > 3.0);
+        assert!(width(&plugged) > width(&bare), "{} vs {}", width(&plugged), width(&bare));
+        assert!(plugged.branch_admittance(2.0 * PI * 1000.0, c, rho).re > 0.0);
+
+        assert!(AbsorptiveBranch::new(0.1, 8e-3, 0.1, 20_000.0).validate().is_err());
+    }
+
+    #[test]
+    fn test_lined_duct_attenuates_broadband() {
+        let (c, rho) = (343.0, 1.204);
+        let z = rho * c / area_from_diameter(0.02);
+        for model in [PorousModel::DelanyBazley, PorousModel::Miki] {
+            let lined = LinedDuct {
+                model,
+                ..LinedDuct::new(0.15, 0.02, 0.01, 10_000.0)
+            };
+            let tl = |freq: f64| lined.transfer_matrix(2.0 * PI * freq, c, rho).transmission_loss(z, z);
+            let (low, high) = (tl(200.0), tl(4000.0));
+            assert!(low.is_finite() && high.is_finite());
+            assert!(low > 0.0, "{model:?}: lining should attenuate at 200 Hz, got {low}");
+            assert!(
+                high > low + 3.0,
+                "{model:?}: lining should attenuate more at high frequency: 200 Hz = {low:.2} dB, 4 kHz = {high:.2} dB"
+            );
+        }
+    }
 }
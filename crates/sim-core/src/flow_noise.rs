@@ -0,0 +1,251 @@
+use crate::elements::ORIFICE_DISCHARGE_COEFFICIENT;
+use crate::muffler::Muffler;
+use crate::SimParams;
+use std::f64::consts::PI;
+
+/// Strouhal number f·d/U of the peak of a jet's regenerated noise.
+const PEAK_STROUHAL: f64 = 0.2;
+
+/// Mean-square fluctuating drag coefficient C_f² of a constriction. Sets
+/// a duct fitting at 10 m/s near the regenerated levels VDI 2081 lists;
+/// treat the result as an order-of-magnitude estimate.
+const FORCE_COEFFICIENT: f64 = 1e-4;
+
+/// A constriction or area change where the mean flow separates into a
+/// jet and regenerates broadband noise.
+///
+/// The jet's unsteady drag acts as a dipole on the duct it issues into.
+/// Below that duct's cut-on frequency it radiates plane waves of power
+/// W = C_f²·(½ρU²·S_j)²/(ρc·S_d), where U is the jet velocity, S_j the
+/// jet area and S_d the duct area, so the power rises with U⁴. The
+/// spectrum peaks at the Strouhal frequency 0.2·U/d of the jet diameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNoiseSource {
+    /// Where the jet forms, e.g. "Orifice".
+    pub label: String,
+    /// Mean jet velocity in m/s.
+    pub velocity: f64,
+    /// Jet area S_j in m² (the vena contracta of a sharp edge).
+    pub jet_area: f64,
+    /// Diameter in metres of the opening that forms the jet.
+    pub diameter: f64,
+    /// Area S_d in m² of the duct the jet issues into.
+    pub duct_area: f64,
+}
+
+impl FlowNoiseSource {
+    /// A jet carrying `flow_rate` m³/s through `jet_area` into a duct of
+    /// `duct_area`.
+    fn new(label: &str, flow_rate: f64, jet_area: f64, diameter: f64, duct_area: f64) -> Self {
+        Self {
+            label: label.to_string(),
+            velocity: flow_rate.abs() / jet_area,
+            jet_area,
+            diameter,
+            duct_area,
+        }
+    }
+
+    /// Total regenerated sound power in W.
+    pub fn sound_power(&self, c: f64, rho: f64) -> f64 {
+        let force = 0.5 * rho * self.velocity.powi(2) * self.jet_area;
+        FORCE_COEFFICIENT * force.powi(2) / (rho * c * self.duct_area)
+    }
+
+    /// Frequency in Hz at which the spectrum peaks.
+    pub fn peak_frequency(&self) -> f64 {
+        PEAK_STROUHAL * self.velocity / self.diameter
+    }
+
+    /// Sound power spectral density in W/Hz at `frequency`: the total
+    /// power spread over (4/π)·x²/(1 + x²)²/f_p with x = f/f_p, which
+    /// integrates to one.
+    pub fn power_density(&self, frequency: f64, c: f64, rho: f64) -> f64 {
+        let peak = self.peak_frequency();
+        if peak <= 0.0 || frequency <= 0.0 {
+            return 0.0;
+        }
+        let x = frequency / peak;
+        self.sound_power(c, rho) * 4.0 / PI * x * x / (1.0 + x * x).powi(2) / peak
+    }
+}
+
+/// Regenerated noise of the whole muffler, set against the pump noise it
+/// lets through.
+///
+/// The regenerated noise is added downstream of the transmission loss:
+/// it leaves the outlet without being attenuated by the chambers, which
+/// is the worst case for sources near the outlet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FlowNoise {
+    /// Every jet in the muffler, source side first. Empty without flow.
+    pub sources: Vec<FlowNoiseSource>,
+    /// Regenerated sound power spectral density in W/Hz at each frequency
+    /// bin of the result.
+    pub spectrum: Vec<f64>,
+    /// Sound power in W of the pump's fundamental after the muffler, when
+    /// [`SimParams::pulse_pressure`] sets its level.
+    pub muffled_pump_power: Option<f64>,
+    /// Total regenerated sound power in W.
+    pub total_power: f64,
+}
+
+impl FlowNoise {
+    /// Total regenerated sound power level in dB re 1 pW.
+    pub fn sound_power_level(&self) -> f64 {
+        sound_power_level(self.total_power)
+    }
+
+    /// The source regenerating the most power.
+    pub fn loudest(&self) -> Option<&FlowNoiseSource> {
+        // Every source's power scales with ρ/c alike, so any medium ranks them
+        let power = |source: &FlowNoiseSource| source.sound_power(1.0, 1.0);
+        self.sources.iter().max_by(|a, b| power(a).total_cmp(&power(b)))
+    }
+
+    /// Whether the muffler regenerates more sound power than the pump
+    /// fundamental it lets through, i.e. it is too restrictive to help.
+    pub fn exceeds_pump_noise(&self) -> bool {
+        self.muffled_pump_power.is_some_and(|pump| self.total_power > pump)
+    }
+}
+
+/// Sound power level in dB re 1 pW of `power` W.
+pub fn sound_power_level(power: f64) -> f64 {
+    10.0 * (power.max(1e-30) / 1e-12).log10()
+}
+
+/// The jets the mean flow forms in the muffler built from `params`: the
+/// orifice or the inlet pipe discharging into the chamber, the divider's
+/// tube or holes, and the vena contracta entering the outlet pipe.
+pub fn sources(params: &SimParams) -> Vec<FlowNoiseSource> {
+    let flow_rate = params.mean_flow();
+    if flow_rate == 0.0 {
+        return Vec::new();
+    }
+    let chamber_area = params.chamber_section().area();
+    let (inlet_area, outlet_area) = (area(params.inlet_diameter), area(params.outlet_diameter));
+
+    let mut sources = vec![match &params.orifice {
+        Some(orifice) => FlowNoiseSource::new(
+            "Orifice",
+            flow_rate,
+            ORIFICE_DISCHARGE_COEFFICIENT * orifice.area(),
+            orifice.hole_diameter,
+            chamber_area,
+        ),
+        None => FlowNoiseSource::new("Inlet pipe exit", flow_rate, inlet_area, params.inlet_diameter, chamber_area),
+    }];
+    if let Some(baffle) = &params.baffle {
+        let tube = area(baffle.tube_diameter);
+        sources.push(FlowNoiseSource::new("Baffle tube", flow_rate, tube, baffle.tube_diameter, chamber_area));
+    } else if let Some(plate) = &params.perforated_baffle {
+        let holes = ORIFICE_DISCHARGE_COEFFICIENT * plate.porosity * chamber_area;
+        sources.push(FlowNoiseSource::new("Perforated plate", flow_rate, holes, plate.hole_diameter, chamber_area));
+    }
+    sources.push(FlowNoiseSource::new(
+        "Outlet pipe entry",
+        flow_rate,
+        ORIFICE_DISCHARGE_COEFFICIENT * outlet_area,
+        params.outlet_diameter,
+        outlet_area,
+    ));
+    sources
+}
+
+/// Regenerated noise of the muffler built from `params` at `frequencies`.
+/// `muffler` supplies the transmission loss at the pump's pulse frequency
+/// when the pump's pulse pressure is known.
+pub fn estimate(params: &SimParams, muffler: &Muffler, frequencies: &[f64], c: f64, rho: f64) -> FlowNoise {
+    let sources = sources(params);
+    let spectrum = frequencies
+        .iter()
+        .map(|&f| sources.iter().map(|source| source.power_density(f, c, rho)).sum())
+        .collect();
+    let total_power = sources.iter().map(|source| source.sound_power(c, rho)).sum();
+    let muffled_pump_power = params.pulse_pressure().map(|pressure| {
+        let omega = 2.0 * PI * params.num_valves as f64 * params.pump_rpm() / 60.0;
+        let incident = pressure * pressure / (2.0 * rho * c) * area(params.inlet_diameter);
+        incident * 10f64.powf(-muffler.transmission_loss(omega, c, rho) / 10.0)
+    });
+    FlowNoise {
+        sources,
+        spectrum,
+        muffled_pump_power,
+        total_power,
+    }
+}
+
+fn area(diameter: f64) -> f64 {
+    PI / 4.0 * diameter * diameter
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::Orifice;
+
+    #[test]
+    fn test_flow_noise_scaling() {
+        let (c, rho) = (343.0, 1.2);
+        let params = SimParams {
+            pump_flow: 2e-5,
+            ..SimParams::default()
+        };
+        let open = sources(&params);
+        assert_eq!(open.len(), 2);
+
+        // Halving the hole quadruples the velocity, and the power goes as
+        // U⁴·S_j² ∝ 1/S_j²: 16× louder
+        let restricted = SimParams {
+            orifice: Some(Orifice::new(1e-3, 1e-3)),
+            ..params.clone()
+        };
+        let narrow = SimParams {
+            orifice: Some(Orifice::new(0.5e-3, 1e-3)),
+            ..params.clone()
+        };
+        let (wide_jet, narrow_jet) = (&sources(&restricted)[0], &sources(&narrow)[0]);
+        assert!((narrow_jet.velocity / wide_jet.velocity - 4.0).abs() < 1e-9);
+        let ratio = narrow_jet.sound_power(c, rho) / wide_jet.sound_power(c, rho);
+        assert!((ratio - 16.0).abs() < 1e-9, "{ratio}");
+        assert!(narrow_jet.sound_power(c, rho) > open[0].sound_power(c, rho));
+
+        // The spectrum integrates to the total power
+        let df = 1.0;
+        let integral: f64 = (1..2_000_000).map(|f| wide_jet.power_density(f as f64 * df, c, rho) * df).sum();
+        assert!((integral / wide_jet.sound_power(c, rho) - 1.0).abs() < 0.01, "{integral}");
+
+        // No flow, no noise
+        let still = SimParams {
+            pump_flow: 0.0,
+            ..params
+        };
+        assert!(sources(&still).is_empty());
+    }
+
+    #[test]
+    fn test_flow_noise_warning() {
+        let (c, rho) = (343.0, 1.2);
+        let frequencies = [100.0, 1000.0];
+        let noise = |params: &SimParams| {
+            let muffler = Muffler::from_params(params).unwrap();
+            estimate(params, &muffler, &frequencies, c, rho)
+        };
+        let quiet = SimParams {
+            pump_flow: 5e-5,
+            source_pressure: Some(0.5),
+            ..SimParams::default()
+        };
+        let choked = SimParams {
+            orifice: Some(Orifice::new(1e-3, 1e-3)),
+            ..quiet.clone()
+        };
+        let (quiet, choked) = (noise(&quiet), noise(&choked));
+        assert!(!quiet.exceeds_pump_noise());
+        assert!(choked.exceeds_pump_noise(), "{} dB", choked.sound_power_level());
+        assert_eq!(choked.loudest().unwrap().label, "Orifice");
+        assert_eq!(choked.spectrum.len(), 2);
+        assert!(choked.sound_power_level() > quiet.sound_power_level());
+    }
+}
@@ -41,10 +41,113 @@ pub fn sweep(
     (frequencies, tl, hf)
 }
 
+/// How the points of a [`sweep_range`] are spread.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SweepSpacing {
+    /// Equal steps in Hz.
+    Linear,
+    /// Equal frequency ratios, for plots with a log axis.
+    Log,
+}
+
+/// `points` frequencies from `f_min` to `f_max` Hz, both included.
+pub fn spaced(f_min: f64, f_max: f64, points: usize, spacing: SweepSpacing) -> Vec<f64> {
+    let steps = points.saturating_sub(1).max(1) as f64;
+    (0..points)
+        .map(|i| {
+            let t = i as f64 / steps;
+            match spacing {
+                SweepSpacing::Linear => f_min + (f_max - f_min) * t,
+                SweepSpacing::Log => f_min * (f_max / f_min).powf(t),
+            }
+        })
+        .collect()
+}
+
+/// Sweep the transmission loss at `points` frequencies from `f_min` to
+/// `f_max` (both > 0 Hz, inclusive), independent of any FFT grid.
+///
+/// Used to resolve narrow resonances in a zoomed-in range.
+/// Returns `(frequencies, transmission_loss_db)`.
+pub fn sweep_range(
+    muffler: &Muffler,
+    f_min: f64,
+    f_max: f64,
+    points: usize,
+    spacing: SweepSpacing,
+    c: f64,
+    rho: f64,
+) -> (Vec<f64>, Vec<f64>) {
+    let frequencies = spaced(f_min, f_max, points, spacing);
+    let tl = frequencies
+        .iter()
+        .map(|&freq| muffler.transmission_loss(2.0 * PI * freq, c, rho))
+        .collect();
+    (frequencies, tl)
+}
+
+/// Sweep the transmission loss and pressure transfer function at the
+/// given frequencies (all > 0 Hz), independent of any FFT grid.
+///
+/// Returns `(transmission_loss_db, transfer_function)`.
+pub fn sweep_at(muffler: &Muffler, frequencies: &[f64], c: f64, rho: f64) -> (Vec<f64>, Vec<Complex64>) {
+    frequencies
+        .iter()
+        .map(|&freq| {
+            let omega = 2.0 * PI * freq;
+            (muffler.transmission_loss(omega, c, rho), muffler.pressure_transfer(omega, c, rho))
+        })
+        .unzip()
+}
+
+/// Insert points into the ascending sweep `(frequencies, tl, hf)` wherever
+/// the TL of neighbours differs by more than `max_step_db`, halving those
+/// intervals until they pass or are no wider than twice `min_spacing`.
+/// `evaluate` solves TL and H(f) at a batch of new frequencies.
+///
+/// Returns the refined `(frequencies, transmission_loss_db,
+/// transfer_function)`, a superset of the input.
+pub fn refine(
+    frequencies: &[f64],
+    tl: &[f64],
+    hf: &[Complex64],
+    max_step_db: f64,
+    min_spacing: f64,
+    evaluate: impl Fn(&[f64]) -> (Vec<f64>, Vec<Complex64>),
+) -> (Vec<f64>, Vec<f64>, Vec<Complex64>) {
+    let mut points: Vec<(f64, f64, Complex64)> = frequencies
+        .iter()
+        .zip(tl)
+        .zip(hf)
+        .map(|((&f, &tl), &h)| (f, tl, h))
+        .collect();
+    loop {
+        let midpoints: Vec<f64> = points
+            .windows(2)
+            .filter(|pair| (pair[1].1 - pair[0].1).abs() > max_step_db && pair[1].0 - pair[0].0 >= 2.0 * min_spacing)
+            .map(|pair| 0.5 * (pair[0].0 + pair[1].0))
+            .collect();
+        if midpoints.is_empty() {
+            break;
+        }
+        let (new_tl, new_hf) = evaluate(&midpoints);
+        points.extend(midpoints.into_iter().zip(new_tl).zip(new_hf).map(|((f, tl), h)| (f, tl, h)));
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+    let mut refined = (Vec::new(), Vec::new(), Vec::new());
+    for (f, tl, h) in points {
+        refined.0.push(f);
+        refined.1.push(tl);
+        refined.2.push(h);
+    }
+    refined
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::constants::{area_from_diameter, speed_of_sound_and_density};
+    use crate::constants::{area_from_diameter, speed_of_sound_and_density, STANDARD_PRESSURE};
+    use crate::gas::Gas;
     use crate::elements::StraightDuct;
     use crate::muffler::Muffler;
 
@@ -63,7 +166,7 @@ mod tests {
     #[test]
     fn test_expansion_chamber_analytical_validation() {
         let temperature = 20.0;
-        let (c, rho) = speed_of_sound_and_density(temperature);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, temperature, 0.0, STANDARD_PRESSURE);
 
         // Geometry
         let pipe_diameter = 6e-3; // 6 mm
@@ -132,7 +235,7 @@ mod tests {
     /// the TL is exactly zero (sin(kL) = 0 → TL = 0).
     #[test]
     fn test_expansion_chamber_zero_tl_at_resonances() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let chamber_length = 80e-3;
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
@@ -158,7 +261,7 @@ mod tests {
     /// TL_peak = 10·log₁₀(1 + 0.25·(m − 1/m)²)
     #[test]
     fn test_expansion_chamber_peak_tl() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let chamber_length = 80e-3;
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
@@ -193,7 +296,7 @@ mod tests {
 
     #[test]
     fn test_sweep_correct_bin_count() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
@@ -235,7 +338,7 @@ mod tests {
         // DC bin should be exactly unity (1.0 + 0.0i) because at zero
         // frequency the acoustic wavelength is infinite and the muffler
         // has no effect.
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
@@ -263,7 +366,7 @@ mod tests {
 
     #[test]
     fn test_sweep_frequency_bins_evenly_spaced() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
@@ -308,7 +411,7 @@ mod tests {
 
     #[test]
     fn test_sweep_all_tl_values_finite() {
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
@@ -338,4 +441,27 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_sweep_range_resolves_narrow_notch() {
+        use crate::elements::QuarterWaveResonator;
+
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
+        let z_pipe = rho * c / area_from_diameter(6e-3);
+        // Tuned halfway between two 10.77 Hz grid bins
+        let tuning = 1000.0 + 0.5 * 44100.0 / 4096.0;
+        let branch = QuarterWaveResonator::tuned(tuning, 4e-3, c);
+        let muffler = Muffler::new(vec![Box::new(branch)], z_pipe, z_pipe);
+
+        let (_, coarse, _) = sweep(&muffler, 4096, 44100.0, c, rho);
+        let coarse_peak = coarse.iter().cloned().fold(0.0, f64::max);
+        let (freqs, fine) = sweep_range(&muffler, 990.0, 1020.0, 3001, SweepSpacing::Linear, c, rho);
+        let fine_peak = fine.iter().cloned().fold(0.0, f64::max);
+        assert_eq!(freqs.len(), 3001);
+        assert!((freqs[0] - 990.0).abs() < 1e-9 && (freqs[3000] - 1020.0).abs() < 1e-9);
+        assert!(fine_peak > coarse_peak + 20.0, "fine {fine_peak:.1} dB vs coarse {coarse_peak:.1} dB");
+
+        let (log_freqs, _) = sweep_range(&muffler, 100.0, 10_000.0, 3, SweepSpacing::Log, c, rho);
+        assert!((log_freqs[1] - 1000.0).abs() < 1e-9);
+    }
 }
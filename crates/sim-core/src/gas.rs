@@ -0,0 +1,109 @@
+use crate::constants::{AIR_GAMMA, AIR_MOLAR_MASS};
+
+/// The working gas the pump moves through the muffler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Gas {
+    /// Dry air.
+    Air,
+    /// Helium, e.g. in breathing mixtures.
+    Helium,
+    /// Carbon dioxide, e.g. from a dosing pump.
+    CarbonDioxide,
+    /// Any other ideal gas or mixture, e.g. exhaust.
+    Custom {
+        /// Molar mass in kg/mol.
+        molar_mass: f64,
+        /// Ratio of specific heats.
+        gamma: f64,
+    },
+}
+
+impl Gas {
+    /// The named gases, for selectors.
+    pub const PRESETS: [Gas; 3] = [Gas::Air, Gas::Helium, Gas::CarbonDioxide];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Gas::Air => "Air",
+            Gas::Helium => "Helium",
+            Gas::CarbonDioxide => "CO₂",
+            Gas::Custom { .. } => "Custom",
+        }
+    }
+
+    /// Molar mass in kg/mol.
+    pub fn molar_mass(self) -> f64 {
+        match self {
+            Gas::Air => AIR_MOLAR_MASS,
+            Gas::Helium => 4.0026e-3,
+            Gas::CarbonDioxide => 44.01e-3,
+            Gas::Custom { molar_mass, .. } => molar_mass,
+        }
+    }
+
+    /// Ratio of specific heats γ.
+    pub fn gamma(self) -> f64 {
+        match self {
+            Gas::Air => AIR_GAMMA,
+            Gas::Helium => 5.0 / 3.0,
+            Gas::CarbonDioxide => 1.289,
+            Gas::Custom { gamma, .. } => gamma,
+        }
+    }
+
+    /// Name with the molar mass and γ, for provenance stamps.
+    pub fn describe(self) -> String {
+        format!(
+            "{} (M = {:.2} g/mol, γ = {:.3})",
+            self.name(),
+            self.molar_mass() * 1e3,
+            self.gamma()
+        )
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.molar_mass() > 0.0 && self.molar_mass().is_finite()) {
+            return Err(format!("gas molar mass must be > 0, got {}", self.molar_mass()));
+        }
+        if !(self.gamma() > 1.0 && self.gamma() <= 5.0 / 3.0) {
+            return Err(format!("gas gamma must be in (1, 5/3], got {}", self.gamma()));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{speed_of_sound_and_density, STANDARD_PRESSURE};
+
+    #[test]
+    fn test_gas_sound_speeds() {
+        let c = |gas| speed_of_sound_and_density(gas, 20.0, 0.0, STANDARD_PRESSURE).0;
+        let rho = |gas| speed_of_sound_and_density(gas, 20.0, 0.0, STANDARD_PRESSURE).1;
+        // Handbook values at 20 °C
+        assert!((c(Gas::Helium) - 1007.0).abs() < 5.0, "{}", c(Gas::Helium));
+        assert!((c(Gas::CarbonDioxide) - 267.0).abs() < 2.0, "{}", c(Gas::CarbonDioxide));
+        assert!((rho(Gas::CarbonDioxide) - 1.83).abs() < 0.01, "{}", rho(Gas::CarbonDioxide));
+
+        // A custom gas with air's properties is air
+        let air_like = Gas::Custom {
+            molar_mass: AIR_MOLAR_MASS,
+            gamma: AIR_GAMMA,
+        };
+        assert!((c(air_like) - c(Gas::Air)).abs() < 1e-9);
+        assert!(Gas::Custom { molar_mass: 0.03, gamma: 1.0 }.validate().is_err());
+        assert!(Gas::Custom { molar_mass: 0.0, gamma: 1.3 }.validate().is_err());
+
+        // Everything acoustic scales with the sound speed
+        let cutoff = |gas| {
+            let params = crate::SimParams {
+                gas,
+                ..crate::SimParams::default()
+            };
+            crate::compute(&params).unwrap().cutoff_frequency.unwrap()
+        };
+        let ratio = cutoff(Gas::CarbonDioxide) / cutoff(Gas::Air);
+        assert!((ratio - c(Gas::CarbonDioxide) / c(Gas::Air)).abs() < 1e-9);
+    }
+}
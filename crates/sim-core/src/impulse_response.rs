@@ -1,12 +1,13 @@
+use crate::numerics::IrWindow;
 use num_complex::Complex64;
 use realfft::RealFftPlanner;
-use std::f64::consts::PI;
 
 /// Convert a frequency-domain transfer function H(f) (N/2+1 complex bins)
-/// into a time-domain impulse response h(t) of length `fft_size`.
+/// into a time-domain impulse response h(t).
 ///
-/// Applies a Hann window and truncates to `fft_size / 2` samples.
-pub fn compute(transfer_function: &[Complex64], fft_size: usize) -> Vec<f64> {
+/// Truncates the `fft_size`-sample inverse FFT to `length` samples (at
+/// most `fft_size`) and applies `window`.
+pub fn compute(transfer_function: &[Complex64], fft_size: usize, window: IrWindow, length: usize) -> Vec<f64> {
     let expected_bins = fft_size / 2 + 1;
     assert_eq!(
         transfer_function.len(),
@@ -40,24 +41,93 @@ pub fn compute(transfer_function: &[Complex64], fft_size: usize) -> Vec<f64> {
         *s *= norm;
     }
 
-    // Truncate to fft_size/2 and apply a right-side cosine taper.
-    // A full Hann window zeros out sample 0, destroying the direct-path
-    // energy. Instead, keep early samples intact and taper the last 25%
-    // to avoid truncation artefacts.
-    let ir_len = fft_size / 2;
-    let taper_len = ir_len / 4;
-    let mut ir = Vec::with_capacity(ir_len);
-    for i in 0..ir_len {
-        let window = if i >= ir_len - taper_len {
-            let t = (i - (ir_len - taper_len)) as f64 / taper_len as f64;
-            0.5 * (1.0 + (PI * t).cos())
-        } else {
-            1.0
-        };
-        ir.push(output[i] * window);
+    // Truncate and window. A window that falls from the first sample
+    // would destroy the direct-path energy, so every window starts at 1.
+    let length = length.min(fft_size);
+    output
+        .iter()
+        .take(length)
+        .enumerate()
+        .map(|(i, &sample)| sample * window.weight(i, length))
+        .collect()
+}
+
+/// Fraction of the impulse response energy lost to truncation and
+/// windowing above which the auralization no longer matches H(f).
+pub const TRUNCATION_WARNING: f64 = 0.01;
+
+/// Energy Σh² of the full `fft_size`-sample impulse response of the
+/// transfer function H(f), by Parseval's theorem.
+pub fn energy(transfer_function: &[Complex64], fft_size: usize) -> f64 {
+    let last = transfer_function.len() - 1;
+    let sum: f64 = transfer_function
+        .iter()
+        .enumerate()
+        .map(|(k, h)| match k {
+            // DC and Nyquist are forced real and appear once in the spectrum
+            0 => h.re * h.re,
+            k if k == last => h.re * h.re,
+            _ => 2.0 * h.norm_sqr(),
+        })
+        .sum();
+    sum / fft_size as f64
+}
+
+/// Fraction of the energy of the transfer function's impulse response
+/// missing from `ir`, its truncated and windowed form from [`compute`].
+pub fn truncation_loss(transfer_function: &[Complex64], fft_size: usize, ir: &[f64]) -> f64 {
+    let total = energy(transfer_function, fft_size);
+    if total == 0.0 {
+        return 0.0;
     }
+    let kept: f64 = ir.iter().map(|h| h * h).sum();
+    (1.0 - kept / total).max(0.0)
+}
+
+/// Half-width of the resampling kernel, in samples of the lower rate.
+const RESAMPLE_HALF_WIDTH: f64 = 16.0;
 
-    ir
+/// Resample the impulse response `samples` from `from` Hz to `to` Hz,
+/// keeping its time scale and its gain.
+///
+/// Interpolates with a Hann-windowed sinc cut off at the Nyquist frequency
+/// of the lower rate, so downsampling does not alias. Each output sample
+/// is scaled by `from / to` so that the sum of the response, its DC gain,
+/// stays the same.
+pub fn resample(samples: &[f64], from: f64, to: f64) -> Vec<f64> {
+    if from == to || samples.is_empty() {
+        return samples.to_vec();
+    }
+    let step = from / to;
+    // Cutoff as a fraction of the source Nyquist frequency
+    let cutoff = step.recip().min(1.0);
+    let half_width = RESAMPLE_HALF_WIDTH / cutoff;
+    let length = (samples.len() as f64 / step).ceil() as usize;
+    (0..length)
+        .map(|m| {
+            let x = m as f64 * step;
+            let first = (x - half_width).ceil().max(0.0) as usize;
+            let last = ((x + half_width).floor() as usize).min(samples.len() - 1);
+            let sum: f64 = (first..=last)
+                .map(|n| {
+                    let u = x - n as f64;
+                    let window = 0.5 + 0.5 * (std::f64::consts::PI * u / half_width).cos();
+                    samples[n] * cutoff * sinc(cutoff * u) * window
+                })
+                .sum();
+            sum * step
+        })
+        .collect()
+}
+
+/// Normalised sinc, sin(πx)/(πx).
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 {
+        1.0
+    } else {
+        let px = std::f64::consts::PI * x;
+        px.sin() / px
+    }
 }
 
 #[cfg(test)]
@@ -70,10 +140,87 @@ mod tests {
         let fft_size = 256;
         let bins = fft_size / 2 + 1;
         let hf = vec![Complex64::new(1.0, 0.0); bins];
-        let ir = compute(&hf, fft_size);
+        let ir = compute(&hf, fft_size, IrWindow::Tukey { taper: 0.25 }, fft_size / 2);
         assert_eq!(ir.len(), fft_size / 2);
         // First sample should be the largest (near delta)
         let max_val = ir.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
         assert_eq!(ir[0], max_val);
     }
+
+    #[test]
+    fn test_windows_and_length() {
+        // A slowly decaying resonance: h[n] = 0.999^n·cos(0.1·n), whose tail
+        // outlasts half the FFT
+        let fft_size = 4096;
+        let decay = 0.999f64;
+        let hf: Vec<Complex64> = (0..=fft_size / 2)
+            .map(|k| {
+                let omega = 2.0 * std::f64::consts::PI * k as f64 / fft_size as f64;
+                let z = Complex64::from_polar(1.0, -omega);
+                let pole = |theta: f64| 0.5 / (1.0 - Complex64::from_polar(decay, theta) * z);
+                pole(0.1) + pole(-0.1)
+            })
+            .collect();
+        let exact = |n: usize| decay.powi(n as i32) * (0.1 * n as f64).cos();
+
+        // Full length and no window: the tail is intact up to the aliasing
+        // of the circular FFT (0.999^4096 ≈ 2 %)
+        let full = compute(&hf, fft_size, IrWindow::Rectangular, fft_size);
+        assert_eq!(full.len(), fft_size);
+        let aliasing = decay.powi(fft_size as i32) / (1.0 - decay.powi(fft_size as i32));
+        for n in [0, 100, 2500, 3000] {
+            assert!((full[n] - exact(n)).abs() < 1.1 * aliasing, "{n}: {} vs {}", full[n], exact(n));
+        }
+        assert_eq!(compute(&hf, fft_size, IrWindow::Rectangular, 2 * fft_size).len(), fft_size);
+
+        // Every window keeps the first sample; Hann ends at zero, the
+        // exponential at its set attenuation
+        let hann = compute(&hf, fft_size, IrWindow::Hann, 1000);
+        assert_eq!(hann[0], full[0]);
+        assert!((hann[500] - full[500] * 0.5).abs() < 1e-12);
+        let exponential = compute(&hf, fft_size, IrWindow::Exponential { end_db: 40.0 }, 1000);
+        assert!((exponential[500] / full[500] - 0.1).abs() < 1e-9);
+        let tukey = compute(&hf, fft_size, IrWindow::Tukey { taper: 0.5 }, 1000);
+        assert_eq!(tukey[499], full[499]);
+        assert!((tukey[750] - full[750] * 0.5).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_truncation_loss() {
+        // h[n] = 0.999^n: the first L samples hold 1 − 0.999^(2L) of the energy
+        let fft_size = 4096;
+        let decay = 0.999f64;
+        let hf: Vec<Complex64> = (0..=fft_size / 2)
+            .map(|k| {
+                let omega = 2.0 * std::f64::consts::PI * k as f64 / fft_size as f64;
+                1.0 / (1.0 - decay * Complex64::from_polar(1.0, -omega))
+            })
+            .collect();
+        let full = compute(&hf, fft_size, IrWindow::Rectangular, fft_size);
+        assert!(truncation_loss(&hf, fft_size, &full) < 1e-9);
+        let short = compute(&hf, fft_size, IrWindow::Rectangular, 500);
+        let expected = decay.powi(1000) - decay.powi(2 * fft_size as i32);
+        let loss = truncation_loss(&hf, fft_size, &short);
+        assert!((loss - expected).abs() < 1e-3, "{loss} vs {expected}");
+        assert!(loss > TRUNCATION_WARNING);
+    }
+
+    #[test]
+    fn test_resample_keeps_time_scale_and_gain() {
+        // A Gaussian pulse 2 ms in, sampled at 44.1 kHz, lands at the same
+        // time and height when resampled to 48 kHz and to 22.05 kHz
+        let pulse = |t: f64, rate: f64| (-((t - 2e-3) / 2e-4).powi(2)).exp() / rate;
+        let source: Vec<f64> = (0..400).map(|n| pulse(n as f64 / 44100.0, 44100.0)).collect();
+        for to in [48000.0, 22050.0] {
+            let resampled = resample(&source, 44100.0, to);
+            assert_eq!(resampled.len(), (400.0 * to / 44100.0f64).ceil() as usize);
+            for (m, &sample) in resampled.iter().enumerate() {
+                let expected = pulse(m as f64 / to, to);
+                assert!((sample - expected).abs() < 1e-3 * pulse(2e-3, to), "{to} Hz, {m}: {sample} vs {expected}");
+            }
+            let gain: f64 = resampled.iter().sum();
+            assert!((gain / source.iter().sum::<f64>() - 1.0).abs() < 1e-4);
+        }
+        assert_eq!(resample(&source, 44100.0, 44100.0), source);
+    }
 }
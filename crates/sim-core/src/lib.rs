@@ -1,11 +1,41 @@
+pub mod air_line;
 pub mod audio;
-pub mod constants;
+pub mod breakout;
+pub mod calibration;
+pub mod compare;
+pub(crate) mod constants;
+pub mod decay;
 pub mod elements;
-pub mod frequency_response;
-pub mod impulse_response;
+pub mod flow_noise;
+pub(crate) mod frequency_response;
+pub mod gas;
+pub(crate) mod impulse_response;
+pub mod loudness;
+pub mod measurement;
+pub mod metrics;
 pub mod muffler;
+pub mod numerics;
+pub mod prelude;
+pub(crate) mod provenance;
 pub mod pump;
-pub mod transfer_matrix;
+pub mod radiation;
+pub mod recording;
+pub mod resonances;
+pub(crate) mod rng;
+pub mod rpm_detection;
+pub mod rpm_profile;
+pub mod sensitivity;
+pub mod test_signal;
+pub(crate) mod time_domain;
+pub(crate) mod transfer_function;
+pub(crate) mod transfer_matrix;
+pub mod wav;
+
+// Types of the crate-private modules that appear in the public API
+pub use frequency_response::SweepSpacing;
+pub use provenance::Provenance;
+pub use transfer_function::TransferFunction;
+pub use transfer_matrix::TransferMatrix;
 
 use num_complex::Complex64;
 
@@ -28,14 +58,122 @@ pub struct SimParams {
     pub outlet_diameter: f64,
     /// Outlet pipe length in metres.
     pub outlet_length: f64,
+    /// Distance the inlet pipe protrudes into the chamber in metres
+    /// (0 for a flush inlet).
+    pub inlet_extension: f64,
+    /// Distance the outlet pipe protrudes into the chamber in metres
+    /// (0 for a flush outlet).
+    pub outlet_extension: f64,
+    /// How the pump is driven; linear pumps ignore `rpm`.
+    pub pump_drive: pump::PumpDrive,
     /// Pump motor speed in RPM.
     pub rpm: f64,
     /// Number of pump valves (diaphragms).
     pub num_valves: u32,
     /// Duty cycle of each valve pulse (0–1).
     pub duty_cycle: f64,
+    /// Suction stroke and valve overlap model of the pump.
+    pub valve_timing: pump::StrokeTiming,
+    /// Imbalance between the pump's valves and jitter of its motor.
+    pub valve_variation: pump::ValveVariation,
+    /// Crank geometry of a piston compressor, whose cylinders (counted by
+    /// `num_valves`) replace the diaphragm pulses; rotary drive only.
+    pub piston: Option<pump::PistonCrank>,
+    /// Dynamics of the outlet reed valves, or `None` for ideal valves.
+    pub reed_valve: Option<pump::ReedValve>,
+    /// Valve open area against crank angle shaping the pressure strokes in
+    /// place of `duty_cycle` and the suction stroke, if given.
+    pub valve_lift: Option<pump::ValveLift>,
+    /// Pump waveform given by its harmonics, replacing the modelled
+    /// strokes; `None` keeps the stroke model.
+    pub harmonic_series: Option<pump::HarmonicSeries>,
+    /// Hum, whine and bearing noise of the pump's motor, if modelled. A
+    /// linear pump hums at twice its drive's mains frequency.
+    pub motor_noise: Option<pump::MotorNoise>,
+    /// Slow random hunting of the pump speed, or `None` for a steady
+    /// speed.
+    pub speed_wander: Option<pump::SpeedWander>,
+    /// A second pump played beside this one, if any.
+    pub second_pump: Option<pump::SecondPump>,
+    /// Mean volume flow the pump pushes through the muffler in m³/s.
+    /// An attached air line or a pump displacement sets the flow instead.
+    pub pump_flow: f64,
+    /// Volume the pump displaces per revolution in m³, all valves
+    /// together. When set the pump is calibrated in physical units: its
+    /// mean flow, pulsation and radiated level follow from the waveform
+    /// (see [`SimParams::pump_volume_velocity`]).
+    pub pump_displacement: Option<f64>,
+    /// Peak pulsation pressure in Pa the pump sends into the inlet at its
+    /// pulse frequency. When set, amplitude-dependent losses (the orifice
+    /// jet) are iterated to this level; `None` keeps the chain linear.
+    pub source_pressure: Option<f64>,
+    /// Working gas the pump moves.
+    pub gas: gas::Gas,
     /// Ambient temperature in °C.
     pub temperature: f64,
+    /// Ambient relative humidity (0–1).
+    pub relative_humidity: f64,
+    /// Ambient static pressure in Pa; see [`SimParams::set_altitude`].
+    pub static_pressure: f64,
+    /// Non-circular chamber cross-section. When set it replaces
+    /// `chamber_diameter`, which the junctions, annuli and baffle then
+    /// take as the diameter of equal area.
+    pub chamber_shape: Option<elements::CrossSection>,
+    /// Optional measured bore of the chamber body. When set it replaces
+    /// `chamber_length` and `chamber_diameter`; the ports must then be
+    /// flush, without a shape, divider, offsets or shell model.
+    pub chamber_profile: Option<elements::ProfiledDuct>,
+    /// Optional orifice plate restricting the inlet where it meets the
+    /// chamber.
+    pub orifice: Option<elements::Orifice>,
+    /// Optional baffle with a connecting tube halfway along the chamber,
+    /// making it a two-chamber muffler. Its `chamber_diameter` is taken
+    /// from the chamber.
+    pub baffle: Option<elements::Baffle>,
+    /// Optional perforated plate dividing the chamber halfway instead of
+    /// a baffle. Its `chamber_diameter` is taken from the chamber.
+    pub perforated_baffle: Option<elements::PerforatedPlate>,
+    /// Optional damped side branch halfway along the outlet pipe.
+    pub side_branch: Option<elements::AbsorptiveBranch>,
+    /// Optional off-axis inlet/outlet ports. When set the chamber is
+    /// modelled with its higher-order modes (see
+    /// [`elements::OffsetChamber`]) instead of as a plane-wave duct.
+    pub port_offsets: Option<PortOffsets>,
+    /// Optional chamber shell model; sound breaking out through it adds a
+    /// parallel path to the duct-borne one.
+    pub shell: Option<breakout::Shell>,
+    /// Absolute wall roughness height of the muffler ducts in metres
+    /// (e.g. ~1.5 µm for brass, ~0.1 mm for FDM prints). Always used for
+    /// friction; acoustically only with viscothermal wall losses.
+    pub wall_roughness: f64,
+    /// Optional elastic wall of the straight pipe and chamber ducts; thin
+    /// plastic walls yield and lower the sound speed inside. `None` keeps
+    /// the walls rigid.
+    pub duct_wall: Option<breakout::Shell>,
+    /// Optional supply hose and air stone downstream of the muffler.
+    pub air_line: Option<air_line::AirLine>,
+    /// How the outlet pipe ends when no air line is attached.
+    pub outlet_termination: numerics::Termination,
+    /// Optional listening position; when set the result predicts the
+    /// sound pressure level there.
+    pub listener: Option<radiation::Listener>,
+    /// Solver and modelling settings.
+    pub numerics: numerics::Numerics,
+    /// Corrections fitted to measurements of built mufflers.
+    pub calibration: calibration::Calibration,
+}
+
+/// Radial positions of the chamber ports, measured from the chamber axis
+/// to the port centres.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortOffsets {
+    /// Inlet port offset in metres.
+    pub inlet: f64,
+    /// Outlet port offset in metres.
+    pub outlet: f64,
+    /// Angle between the ports around the axis in radians (π for ports on
+    /// opposite sides).
+    pub angle: f64,
 }
 
 impl Default for SimParams {
@@ -47,12 +185,210 @@ impl Default for SimParams {
             chamber_length: 80e-3,   // 80 mm
             outlet_diameter: 6e-3,   // 6 mm
             outlet_length: 30e-3,    // 30 mm
+            inlet_extension: 0.0,
+            outlet_extension: 0.0,
+            pump_drive: pump::PumpDrive::Rotary,
             rpm: 3000.0,
             num_valves: 3,
             duty_cycle: 0.5,
+            valve_timing: pump::StrokeTiming::default(),
+            valve_variation: pump::ValveVariation::default(),
+            piston: None,
+            reed_valve: None,
+            valve_lift: None,
+            harmonic_series: None,
+            motor_noise: None,
+            speed_wander: None,
+            second_pump: None,
+            pump_flow: 0.0,
+            pump_displacement: None,
+            source_pressure: None,
+            gas: gas::Gas::Air,
             temperature: 20.0,
+            relative_humidity: 0.0,
+            static_pressure: constants::STANDARD_PRESSURE,
+            chamber_shape: None,
+            chamber_profile: None,
+            orifice: None,
+            baffle: None,
+            perforated_baffle: None,
+            side_branch: None,
+            port_offsets: None,
+            shell: None,
+            wall_roughness: 0.0,
+            duct_wall: None,
+            air_line: None,
+            outlet_termination: numerics::Termination::Anechoic,
+            listener: None,
+            numerics: numerics::Numerics::default(),
+            calibration: calibration::Calibration::default(),
+        }
+    }
+}
+
+impl SimParams {
+    /// Effective revolutions per minute of the pump drive: the motor RPM,
+    /// or one "revolution" per mains cycle for a linear pump.
+    pub fn pump_rpm(&self) -> f64 {
+        match self.pump_drive {
+            pump::PumpDrive::Rotary => self.rpm,
+            pump::PumpDrive::Linear {
+                mains_frequency, ..
+            } => 60.0 * mains_frequency,
+        }
+    }
+
+    /// Relative stroke amplitude of the pump (1 for rotary pumps).
+    pub fn pump_stroke(&self) -> f64 {
+        match self.pump_drive {
+            pump::PumpDrive::Rotary => 1.0,
+            pump::PumpDrive::Linear { stroke, .. } => stroke,
+        }
+    }
+
+    /// A pump source configured from these parameters.
+    pub fn pump_source(&self, sample_rate: f64) -> pump::PumpSource {
+        let mut source =
+            pump::PumpSource::new(self.pump_rpm(), self.num_valves, self.duty_cycle, sample_rate);
+        source.set_timing(self.valve_timing);
+        source.set_stroke(self.pump_stroke());
+        source.set_variation(self.valve_variation.clone());
+        source.set_piston(self.piston);
+        source.set_reed(self.reed_valve);
+        source.set_lift(self.valve_lift.clone());
+        source.set_series(self.harmonic_series.clone());
+        source.set_motor(self.motor());
+        source.set_wander(self.speed_wander);
+        source
+    }
+
+    /// A pump source for `second_pump`: this pump's stroke model and motor
+    /// at the second pump's speed, valves and duty cycle, with its own
+    /// jitter and wander.
+    pub fn second_pump_source(&self, sample_rate: f64) -> Option<pump::PumpSource> {
+        let second = self.second_pump?;
+        let mut source = self.pump_source(sample_rate);
+        source.set_params(second.rpm, second.num_valves, second.duty_cycle);
+        source.reseed(pump::SECOND_PUMP_SEED);
+        Some(source)
+    }
+
+    /// Motor noise of the pump, hum following a linear pump's mains.
+    pub fn motor(&self) -> Option<pump::MotorNoise> {
+        self.motor_noise.map(|motor| match self.pump_drive {
+            pump::PumpDrive::Linear { mains_frequency, .. } => pump::MotorNoise {
+                mains_frequency,
+                ..motor
+            },
+            pump::PumpDrive::Rotary => motor,
+        })
+    }
+
+    /// The pump harmonics up to `highest` Hz, each a frequency in Hz and
+    /// an amplitude relative to the fundamental, from the Fourier series
+    /// of the pump strokes where it has a closed form and the sampled
+    /// waveform otherwise.
+    pub fn pump_harmonics(&self, highest: f64) -> Vec<(f64, f64)> {
+        harmonics_of(&self.pump_source(44100.0), highest)
+    }
+
+    /// The harmonics of `second_pump` up to `highest` Hz like
+    /// [`pump_harmonics`](Self::pump_harmonics), but relative to the first
+    /// pump's fundamental and scaled by the second pump's level; empty
+    /// without a second pump.
+    pub fn second_pump_harmonics(&self, highest: f64) -> Vec<(f64, f64)> {
+        let (Some(second), Some(pump)) = (self.second_pump, self.second_pump_source(44100.0)) else {
+            return Vec::new();
+        };
+        let reference = self.pump_source(44100.0).harmonic_levels(1)[0];
+        let own = pump.harmonic_levels(1)[0];
+        let scale = if reference > 0.0 { second.level * own / reference } else { second.level };
+        harmonics_of(&pump, highest)
+            .into_iter()
+            .map(|(frequency, amplitude)| (frequency, amplitude * scale))
+            .collect()
+    }
+
+    /// Cross-section of the chamber: `chamber_shape` if set, otherwise
+    /// circular with `chamber_diameter`.
+    pub fn chamber_section(&self) -> elements::CrossSection {
+        self.chamber_shape.unwrap_or(elements::CrossSection::Circular {
+            diameter: self.chamber_diameter,
+        })
+    }
+
+    /// Mean volume flow through the muffler in m³/s: the air stone's flow
+    /// with an air line attached, otherwise the displaced flow of a
+    /// calibrated pump, otherwise `pump_flow`.
+    pub fn mean_flow(&self) -> f64 {
+        match &self.air_line {
+            Some(line) => line.stone.flow_rate,
+            None => self.displaced_flow().unwrap_or(self.pump_flow),
+        }
+    }
+
+    /// Mean volume flow in m³/s the pump displaces, if calibrated.
+    pub fn displaced_flow(&self) -> Option<f64> {
+        self.pump_displacement.map(|displacement| displacement * self.pump_rpm() / 60.0)
+    }
+
+    /// Peak volume velocity in m³/s of the pump's pulse-frequency
+    /// harmonic, if calibrated: the waveform scaled so that its mean is
+    /// the displaced flow. `None` also when the waveform delivers nothing
+    /// on average.
+    pub fn pump_volume_velocity(&self) -> Option<f64> {
+        let flow = self.displaced_flow()?;
+        let pump = self.pump_source(44100.0);
+        let mean = pump.mean_level();
+        (mean > 0.0).then(|| flow * pump.harmonic_levels(1)[0] / mean)
+    }
+
+    /// Peak pulsation pressure in Pa at the pump's pulse frequency, as
+    /// set by `source_pressure` or else, for a calibrated pump, as the
+    /// inlet pipe carries its volume velocity without reflections.
+    pub fn pulse_pressure(&self) -> Option<f64> {
+        self.source_pressure.or_else(|| {
+            let (c, rho) = self.medium();
+            let area = constants::area_from_diameter(self.inlet_diameter);
+            self.pump_volume_velocity().map(|q| rho * c / area * q)
+        })
+    }
+
+    /// Peak volume velocity in m³/s of the pump fundamental driving the
+    /// listener's prediction: the calibrated pump's, otherwise the
+    /// listener's `source_strength`. `None` without a listener.
+    pub fn source_strength(&self) -> Option<f64> {
+        self.listener
+            .map(|listener| self.pump_volume_velocity().unwrap_or(listener.source_strength))
+    }
+
+    /// Speed of sound (m/s) and density (kg/m³) of the gas inside the line.
+    ///
+    /// An attached air line pressurises everything upstream of the stone,
+    /// which raises the density but leaves the sound speed unchanged.
+    pub fn medium(&self) -> (f64, f64) {
+        let (c, rho) = constants::speed_of_sound_and_density(
+            self.gas,
+            self.temperature,
+            self.relative_humidity,
+            self.static_pressure,
+        );
+        match &self.air_line {
+            Some(line) => (c, rho * line.density_ratio(self.static_pressure)),
+            None => (c, rho),
         }
     }
+
+    /// Altitude in metres of `static_pressure` in the standard atmosphere.
+    pub fn altitude(&self) -> f64 {
+        constants::altitude_at_pressure(self.static_pressure)
+    }
+
+    /// Set `static_pressure` to that of the standard atmosphere at
+    /// `altitude` metres above sea level.
+    pub fn set_altitude(&mut self, altitude: f64) {
+        self.static_pressure = constants::pressure_at_altitude(altitude);
+    }
 }
 
 /// Results of a simulation run — consumed by the UI for plotting and by
@@ -61,14 +397,126 @@ impl Default for SimParams {
 pub struct SimResult {
     /// Frequency bins in Hz (length N).
     pub frequencies: Vec<f64>,
-    /// Transmission loss in dB at each frequency bin.
+    /// Transmission loss in dB at each frequency bin, including the
+    /// shell breakout path if a shell is modelled.
     pub transmission_loss: Vec<f64>,
     /// Complex pressure transfer function H(f) at each frequency bin.
     pub transfer_function: Vec<Complex64>,
+    /// Unwrapped phase of H(f) in radians at each frequency bin.
+    pub phase: Vec<f64>,
+    /// Group delay −dφ/dω of H(f) in seconds at each frequency bin.
+    pub group_delay: Vec<f64>,
     /// Time-domain impulse response h(t), windowed and truncated.
     pub impulse_response: Vec<f64>,
+    /// Fraction of the energy of h(t) lost to truncating and windowing
+    /// it; the auralization departs from H(f) when this is large.
+    pub ir_truncation_loss: f64,
+    /// Decay times and early-to-late energy ratio of the impulse response.
+    pub decay: decay::Decay,
     /// Sample rate used for the impulse response (Hz).
     pub sample_rate: f64,
+    /// Static back-pressure in Pa the pump works against at the air
+    /// stone's mean flow: duct friction plus the stone itself. Only known
+    /// when an air line is attached.
+    pub back_pressure: Option<f64>,
+    /// Lowest cut-on frequency of a higher-order duct mode in Hz; the
+    /// plane-wave results above it are not reliable.
+    pub cutoff_frequency: Option<f64>,
+    /// Every element whose higher-order modes cut on below the Nyquist
+    /// frequency, lowest cutoff first.
+    pub mode_warnings: Vec<ModeWarning>,
+    /// Transmission loss in dB of the shell breakout path alone, when a
+    /// shell is modelled.
+    pub breakout_loss: Option<Vec<f64>>,
+    /// Broadband noise the mean flow regenerates at the muffler's
+    /// constrictions, on the same frequency bins.
+    pub flow_noise: flow_noise::FlowNoise,
+    /// Sound pressure level at `SimParams::listener`, on the same
+    /// frequency bins and at the pump harmonics.
+    pub radiated: Option<radiation::RadiatedSound>,
+    /// Engine version, settings and time this result was computed with.
+    pub provenance: Provenance,
+    /// The log-spaced sweep `numerics.log_sweep` asked for, off the FFT
+    /// grid.
+    pub log_sweep: Option<Sweep>,
+    /// The FFT-grid sweep with extra points where the TL changes fast,
+    /// when `numerics.refinement` is set; for plotting, as the impulse
+    /// response needs the regular grid.
+    pub refined: Option<Sweep>,
+}
+
+impl SimResult {
+    /// Whether each frequency bin lies below the plane-wave cutoff, i.e.
+    /// where the transmission loss and H(f) can be trusted.
+    pub fn plane_wave_valid(&self) -> Vec<bool> {
+        let cutoff = self.cutoff_frequency.unwrap_or(f64::INFINITY);
+        self.frequencies.iter().map(|&f| f < cutoff).collect()
+    }
+
+    /// Whether the impulse response loses more than
+    /// [`impulse_response::TRUNCATION_WARNING`] of its energy.
+    pub fn ir_truncated(&self) -> bool {
+        self.ir_truncation_loss > impulse_response::TRUNCATION_WARNING
+    }
+
+    /// The swept H(f) as an interpolatable [`TransferFunction`].
+    pub fn response(&self) -> TransferFunction {
+        TransferFunction::new(
+            self.frequencies.clone(),
+            self.transfer_function.clone(),
+        )
+    }
+}
+
+/// Transmission loss and H(f) at arbitrary frequencies, e.g. the
+/// log-spaced points of a [`numerics::LogSweep`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sweep {
+    /// Frequencies in Hz, ascending.
+    pub frequencies: Vec<f64>,
+    /// Transmission loss in dB at each frequency, including the shell
+    /// breakout path if a shell is modelled.
+    pub transmission_loss: Vec<f64>,
+    /// Complex pressure transfer function H(f) at each frequency.
+    pub transfer_function: Vec<Complex64>,
+}
+
+impl Sweep {
+    /// The swept H(f) as an interpolatable [`TransferFunction`].
+    pub fn response(&self) -> TransferFunction {
+        TransferFunction::new(self.frequencies.clone(), self.transfer_function.clone())
+    }
+}
+
+/// An element of the chain that carries higher-order modes inside the
+/// swept range, so the plane-wave model is unreliable above its cutoff.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModeWarning {
+    /// Index of the element in the chain (0 = source side).
+    pub element: usize,
+    /// The element's label.
+    pub label: String,
+    /// Cut-on frequency of its first higher-order mode in Hz.
+    pub cutoff: f64,
+}
+
+impl std::fmt::Display for ModeWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "element {} ({}): plane waves only below {:.0} Hz",
+            self.element, self.label, self.cutoff
+        )
+    }
+}
+
+/// How an element sits in the equivalent acoustic circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connection {
+    /// In line with the main flow path (ducts, chambers).
+    Series,
+    /// Branching off the main path to ground (side-branch resonators).
+    Shunt,
 }
 
 /// Trait for acoustic elements that can produce a 2×2 transfer matrix
@@ -76,7 +524,95 @@ pub struct SimResult {
 pub trait AcousticElement: Send + Sync {
     /// Compute the 2×2 transfer matrix at angular frequency `omega` (rad/s)
     /// with the given speed of sound `c` (m/s) and air density `rho` (kg/m³).
-    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> transfer_matrix::TransferMatrix;
+    fn transfer_matrix(&self, omega: f64, c: f64, rho: f64) -> TransferMatrix;
+
+    /// Short human-readable description used by schematics and diagnostics.
+    fn label(&self) -> String {
+        "Element".to_string()
+    }
+
+    /// Whether the element is in series with the main path or shunts it.
+    fn connection(&self) -> Connection {
+        Connection::Series
+    }
+
+    /// Check the element's own dimensions, returning a description of the
+    /// first problem found.
+    fn validate(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Static pressure drop in Pa for a steady volume flow `flow_rate`
+    /// (m³/s). Elements without a friction model contribute nothing.
+    fn pressure_drop(&self, _flow_rate: f64, _rho: f64) -> f64 {
+        0.0
+    }
+
+    /// Frequency in Hz above which higher-order modes propagate and the
+    /// plane-wave matrix stops being valid, if the element knows it.
+    fn cutoff_frequency(&self, _c: f64) -> Option<f64> {
+        None
+    }
+
+    /// Length in metres along the main path; 0 for lumped elements and
+    /// shunts.
+    fn length(&self) -> f64 {
+        0.0
+    }
+
+    /// Transfer matrix of the last `remaining` metres of the element, for
+    /// sampling the sound field inside it; `None` if the element cannot
+    /// be cut.
+    fn tail_matrix(&self, _remaining: f64, _omega: f64, _c: f64, _rho: f64) -> Option<TransferMatrix> {
+        None
+    }
+}
+
+/// Harmonics of `pump` up to `highest` Hz, each a frequency in Hz and an
+/// amplitude relative to its fundamental.
+fn harmonics_of(pump: &pump::PumpSource, highest: f64) -> Vec<(f64, f64)> {
+    let fundamental = pump.fundamental_frequency();
+    let count = if fundamental > 0.0 { (highest / fundamental) as usize } else { 0 };
+    let amplitudes = match pump.analytic_harmonics(count) {
+        Some(harmonics) => harmonics.iter().map(|harmonic| harmonic.amplitude).collect(),
+        None => pump.harmonic_levels(count),
+    };
+    let reference = amplitudes.first().copied().filter(|&first| first > 0.0).unwrap_or(1.0);
+    amplitudes
+        .iter()
+        .enumerate()
+        .map(|(i, &amplitude)| ((i + 1) as f64 * fundamental, amplitude / reference))
+        .collect()
+}
+
+/// Parse two-column numeric text, one pair per line, separated by commas,
+/// semicolons or whitespace. Blank lines, `#` comments and a non-numeric
+/// header line are skipped; `columns` names the pair in error messages.
+pub(crate) fn parse_pairs(text: &str, columns: &str) -> Result<Vec<(f64, f64)>, String> {
+    Ok(parse_rows(text, columns, 2)?.into_iter().map(|row| (row[0], row[1])).collect())
+}
+
+/// Parse numeric text as [`parse_pairs`] does, keeping every row of at
+/// least `min_columns` values whole.
+pub(crate) fn parse_rows(text: &str, columns: &str, min_columns: usize) -> Result<Vec<Vec<f64>>, String> {
+    let mut points = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        let fields: Vec<&str> = line
+            .split(|ch: char| ch == ',' || ch == ';' || ch.is_whitespace())
+            .filter(|field| !field.is_empty())
+            .collect();
+        let parsed: Result<Vec<f64>, _> = fields.iter().map(|field| field.parse::<f64>()).collect();
+        match parsed {
+            Ok(values) if values.len() >= min_columns => points.push(values),
+            Err(_) if points.is_empty() => continue,
+            _ => return Err(format!("line {}: expected `{columns}`, got {line:?}", number + 1)),
+        }
+    }
+    Ok(points)
 }
 
 /// Validate simulation parameters, returning an error message if any are invalid.
@@ -99,24 +635,294 @@ fn validate_params(params: &SimParams) -> Result<(), String> {
     if params.outlet_length <= 0.0 {
         return Err(format!("outlet_length must be > 0, got {}", params.outlet_length));
     }
+    if params.inlet_extension < 0.0 {
+        return Err(format!("inlet_extension must be >= 0, got {}", params.inlet_extension));
+    }
+    if params.outlet_extension < 0.0 {
+        return Err(format!("outlet_extension must be >= 0, got {}", params.outlet_extension));
+    }
+    if params.inlet_extension + params.outlet_extension >= params.chamber_length {
+        return Err(format!(
+            "inlet_extension + outlet_extension must be < chamber_length ({}), got {}",
+            params.chamber_length,
+            params.inlet_extension + params.outlet_extension
+        ));
+    }
+    if let Some(shape) = &params.chamber_shape {
+        shape.validate().map_err(|e| format!("chamber_shape: {e}"))?;
+    }
+    let chamber_diameter = params.chamber_section().equivalent_diameter();
+    if params.inlet_extension > 0.0 && params.inlet_diameter >= chamber_diameter {
+        return Err("an extended inlet needs inlet_diameter < chamber_diameter".to_string());
+    }
+    if params.outlet_extension > 0.0 && params.outlet_diameter >= chamber_diameter {
+        return Err("an extended outlet needs outlet_diameter < chamber_diameter".to_string());
+    }
     if params.duty_cycle <= 0.0 || params.duty_cycle >= 1.0 {
         return Err(format!(
             "duty_cycle must be in (0.0, 1.0) exclusive, got {}",
             params.duty_cycle
         ));
     }
+    let timing = &params.valve_timing;
+    if timing.suction_fraction < 0.0 || params.duty_cycle + timing.suction_fraction > 1.0 {
+        return Err(format!(
+            "valve_timing.suction_fraction must be in [0, 1 - duty_cycle], got {}",
+            timing.suction_fraction
+        ));
+    }
+    if timing.suction_level < 0.0 {
+        return Err(format!(
+            "valve_timing.suction_level must be >= 0, got {}",
+            timing.suction_level
+        ));
+    }
+    if !(0.0..=1.0).contains(&timing.overlap_sharing) {
+        return Err(format!(
+            "valve_timing.overlap_sharing must be in [0, 1], got {}",
+            timing.overlap_sharing
+        ));
+    }
+    params.valve_variation.validate()?;
+    if let Some(piston) = &params.piston {
+        piston.validate()?;
+        if params.pump_drive != pump::PumpDrive::Rotary {
+            return Err("a piston compressor needs the rotary pump drive".to_string());
+        }
+    }
+    if let Some(reed) = &params.reed_valve {
+        reed.validate()?;
+    }
+    if let Some(lift) = &params.valve_lift {
+        lift.validate()?;
+        if params.piston.is_some() {
+            return Err("a valve lift profile replaces the diaphragm strokes, not a piston's".to_string());
+        }
+    }
+    if let Some(series) = &params.harmonic_series {
+        series.validate()?;
+    }
+    if let Some(motor) = &params.motor_noise {
+        motor.validate()?;
+    }
+    if let Some(wander) = &params.speed_wander {
+        wander.validate()?;
+    }
+    if let Some(second) = &params.second_pump {
+        second.validate()?;
+        if second.duty_cycle + params.valve_timing.suction_fraction > 1.0 {
+            return Err(format!(
+                "second_pump.duty_cycle leaves no room for the suction stroke, got {}",
+                second.duty_cycle
+            ));
+        }
+    }
     if params.rpm <= 0.0 {
         return Err(format!("rpm must be > 0, got {}", params.rpm));
     }
+    if let pump::PumpDrive::Linear {
+        mains_frequency,
+        stroke,
+    } = params.pump_drive
+    {
+        if mains_frequency <= 0.0 {
+            return Err(format!(
+                "pump_drive.mains_frequency must be > 0, got {mains_frequency}"
+            ));
+        }
+        if stroke <= 0.0 || stroke > 2.0 {
+            return Err(format!("pump_drive.stroke must be in (0, 2], got {stroke}"));
+        }
+    }
+    if !(params.pump_flow >= 0.0 && params.pump_flow.is_finite()) {
+        return Err(format!("pump_flow must be >= 0, got {}", params.pump_flow));
+    }
+    if let Some(pressure) = params.source_pressure {
+        if !(pressure > 0.0 && pressure.is_finite()) {
+            return Err(format!("source_pressure must be > 0, got {pressure}"));
+        }
+    }
+    if let Some(listener) = &params.listener {
+        listener.validate().map_err(|e| format!("listener: {e}"))?;
+    }
     if params.num_valves == 0 {
         return Err("num_valves must be > 0".to_string());
     }
+    if let Some(displacement) = params.pump_displacement {
+        if !(displacement > 0.0 && displacement.is_finite()) {
+            return Err(format!("pump_displacement must be > 0, got {displacement}"));
+        }
+        if params.pump_volume_velocity().is_none() {
+            return Err("a calibrated pump must deliver a net flow".to_string());
+        }
+    }
     if params.temperature < -50.0 || params.temperature > 200.0 {
         return Err(format!(
             "temperature must be in [-50, 200] °C, got {}",
             params.temperature
         ));
     }
+    params.gas.validate()?;
+    if !(0.0..=1.0).contains(&params.relative_humidity) {
+        return Err(format!(
+            "relative_humidity must be in [0, 1], got {}",
+            params.relative_humidity
+        ));
+    }
+    if !(params.static_pressure > 0.0 && params.static_pressure.is_finite()) {
+        return Err(format!("static_pressure must be > 0, got {}", params.static_pressure));
+    }
+    if let Some(orifice) = &params.orifice {
+        if orifice.hole_diameter <= 0.0 || orifice.hole_diameter >= params.inlet_diameter {
+            return Err(format!(
+                "orifice.hole_diameter must be in (0, inlet_diameter), got {}",
+                orifice.hole_diameter
+            ));
+        }
+        if orifice.thickness < 0.0 {
+            return Err(format!("orifice.thickness must be >= 0, got {}", orifice.thickness));
+        }
+    }
+    if let Some(baffle) = &params.baffle {
+        if baffle.tube_diameter <= 0.0 || baffle.tube_diameter >= chamber_diameter {
+            return Err(format!(
+                "baffle.tube_diameter must be in (0, chamber_diameter), got {}",
+                baffle.tube_diameter
+            ));
+        }
+        if baffle.thickness <= 0.0 || baffle.tube_length < baffle.thickness {
+            return Err(format!(
+                "baffle.tube_length must be >= baffle.thickness > 0, got {} and {}",
+                baffle.tube_length, baffle.thickness
+            ));
+        }
+        let free_length = params.chamber_length - params.inlet_extension - params.outlet_extension;
+        if baffle.tube_length >= free_length {
+            return Err(format!(
+                "baffle.tube_length must be shorter than the free chamber length {free_length}, got {}",
+                baffle.tube_length
+            ));
+        }
+    }
+    if let Some(plate) = &params.perforated_baffle {
+        if params.baffle.is_some() {
+            return Err("use either baffle or perforated_baffle, not both".to_string());
+        }
+        if !(plate.porosity > 0.0 && plate.porosity <= 1.0) {
+            return Err(format!("perforated_baffle.porosity must be in (0, 1], got {}", plate.porosity));
+        }
+        if plate.hole_diameter <= 0.0 || plate.hole_diameter >= chamber_diameter {
+            return Err(format!(
+                "perforated_baffle.hole_diameter must be in (0, chamber_diameter), got {}",
+                plate.hole_diameter
+            ));
+        }
+        let free_length = params.chamber_length - params.inlet_extension - params.outlet_extension;
+        if plate.thickness <= 0.0 || plate.thickness >= free_length {
+            return Err(format!(
+                "perforated_baffle.thickness must be in (0, {free_length}), got {}",
+                plate.thickness
+            ));
+        }
+    }
+    if let Some(offsets) = &params.port_offsets {
+        if params.chamber_shape.is_some() || params.baffle.is_some() || params.perforated_baffle.is_some() {
+            return Err("port_offsets need a circular chamber without a baffle".to_string());
+        }
+        let radius = params.chamber_diameter / 2.0;
+        for (name, offset, diameter) in [
+            ("inlet", offsets.inlet, params.inlet_diameter),
+            ("outlet", offsets.outlet, params.outlet_diameter),
+        ] {
+            if offset < 0.0 || offset + diameter / 2.0 > radius {
+                return Err(format!(
+                    "port_offsets.{name} must be in [0, {}], got {offset}",
+                    radius - diameter / 2.0
+                ));
+            }
+        }
+    }
+    if let Some(profile) = &params.chamber_profile {
+        profile.validate().map_err(|e| format!("chamber_profile: {e}"))?;
+        if params.chamber_shape.is_some()
+            || params.baffle.is_some()
+            || params.perforated_baffle.is_some()
+            || params.port_offsets.is_some()
+            || params.shell.is_some()
+            || params.inlet_extension > 0.0
+            || params.outlet_extension > 0.0
+        {
+            return Err(
+                "chamber_profile needs flush ports and no chamber_shape, divider, port_offsets or shell".to_string(),
+            );
+        }
+        if profile.inlet_diameter() <= params.inlet_diameter || profile.outlet_diameter() <= params.outlet_diameter {
+            return Err(format!(
+                "chamber_profile must end wider than the pipes, got Ø{} and Ø{}",
+                profile.inlet_diameter(),
+                profile.outlet_diameter()
+            ));
+        }
+    }
+    if let Some(branch) = &params.side_branch {
+        branch.validate().map_err(|e| format!("side_branch: {e}"))?;
+    }
+    params.calibration.validate()?;
+    if let Some(shell) = &params.shell {
+        shell.validate()?;
+    }
+    if let Some(wall) = &params.duct_wall {
+        wall.validate().map_err(|e| format!("duct_wall: {e}"))?;
+    }
+    if params.wall_roughness < 0.0 {
+        return Err(format!("wall_roughness must be >= 0, got {}", params.wall_roughness));
+    }
+    let fft_size = params.numerics.fft_size;
+    let (min_fft, max_fft) = params.numerics.fft_size_range();
+    if !fft_size.is_power_of_two() || fft_size < min_fft || fft_size > max_fft {
+        return Err(format!(
+            "numerics.fft_size must be a power of two in [{min_fft}, {max_fft}], got {fft_size}"
+        ));
+    }
+    if let Some(log) = &params.numerics.log_sweep {
+        log.validate().map_err(|e| format!("numerics.log_sweep: {e}"))?;
+    }
+    params.numerics.ir_window.validate().map_err(|e| format!("numerics.ir_window: {e}"))?;
+    if let Some(length) = params.numerics.ir_length {
+        if length == 0 || length > fft_size {
+            return Err(format!("numerics.ir_length must be in [1, fft_size = {fft_size}], got {length}"));
+        }
+    }
+    if let Some(refinement) = &params.numerics.refinement {
+        refinement.validate().map_err(|e| format!("numerics.refinement: {e}"))?;
+    }
+    if let Some(line) = &params.air_line {
+        if line.hose_length <= 0.0 {
+            return Err(format!("air_line.hose_length must be > 0, got {}", line.hose_length));
+        }
+        if line.hose_diameter <= 0.0 {
+            return Err(format!("air_line.hose_diameter must be > 0, got {}", line.hose_diameter));
+        }
+        let stone = &line.stone;
+        if stone.pore_resistance < 0.0 {
+            return Err(format!(
+                "air_line.stone.pore_resistance must be >= 0, got {}",
+                stone.pore_resistance
+            ));
+        }
+        if stone.pore_diameter <= 0.0 {
+            return Err(format!(
+                "air_line.stone.pore_diameter must be > 0, got {}",
+                stone.pore_diameter
+            ));
+        }
+        if stone.depth < 0.0 {
+            return Err(format!("air_line.stone.depth must be >= 0, got {}", stone.depth));
+        }
+        if stone.flow_rate <= 0.0 {
+            return Err(format!("air_line.stone.flow_rate must be > 0, got {}", stone.flow_rate));
+        }
+    }
     Ok(())
 }
 
@@ -127,29 +933,230 @@ fn validate_params(params: &SimParams) -> Result<(), String> {
 pub fn compute(params: &SimParams) -> Result<SimResult, String> {
     validate_params(params)?;
 
-    let (c, rho) = constants::speed_of_sound_and_density(params.temperature);
+    let (c, rho) = params.medium();
 
     // Build element chain
-    let chain = muffler::Muffler::from_params(params);
+    let chain = muffler::Muffler::from_params(params).map_err(|e| e.to_string())?;
 
     // Sweep frequency response
     let sample_rate = 44100.0;
-    let fft_size = 4096;
-    let (frequencies, tl, transfer_fn) =
-        frequency_response::sweep(&chain, fft_size, sample_rate, c, rho);
+    let fft_size = params.numerics.fft_size;
+    let (frequencies, mut tl, mut transfer_fn) = match params.numerics.engine {
+        numerics::Engine::FrequencyDomain => frequency_response::sweep(&chain, fft_size, sample_rate, c, rho),
+        numerics::Engine::TimeDomain => time_domain::sweep(params, fft_size, sample_rate, c, rho)?,
+    };
+    let breakout_loss = params.shell.is_some().then(|| {
+        add_breakout(params, &chain, &frequencies, &mut tl, &mut transfer_fn, c, rho)
+    });
+    let refined = match (params.numerics.refinement, params.numerics.engine) {
+        (Some(refinement), numerics::Engine::FrequencyDomain) => {
+            let min_spacing = params.numerics.bin_width(sample_rate) / 2f64.powi(refinement.max_depth as i32);
+            let evaluate = |new: &[f64]| {
+                let sweep = sweep_at(params, &chain, new.to_vec(), (&[], &[], &[]), c, rho);
+                (sweep.transmission_loss, sweep.transfer_function)
+            };
+            let (frequencies, transmission_loss, transfer_function) = frequency_response::refine(
+                &frequencies,
+                &tl,
+                &transfer_fn,
+                refinement.max_step_db,
+                min_spacing,
+                evaluate,
+            );
+            Some(Sweep {
+                frequencies,
+                transmission_loss,
+                transfer_function,
+            })
+        }
+        _ => None,
+    };
+    let log_sweep = params.numerics.log_sweep.map(|log| {
+        let grid = (&frequencies[..], &tl[..], &transfer_fn[..]);
+        sweep_at(params, &chain, log.frequencies(), grid, c, rho)
+    });
 
     // Compute impulse response
-    let ir = impulse_response::compute(&transfer_fn, fft_size);
+    let ir = impulse_response::compute(
+        &transfer_fn,
+        fft_size,
+        params.numerics.ir_window,
+        params.numerics.impulse_response_length(),
+    );
+    let ir_truncation_loss = impulse_response::truncation_loss(&transfer_fn, fft_size, &ir);
+    let decay = decay::analyse(&ir, sample_rate, decay::EARLY_TIME);
+    let response = TransferFunction::new(frequencies.clone(), transfer_fn.clone());
+    let (phase, group_delay) = (response.unwrapped_phase(), response.group_delay());
+
+    let flow_noise = flow_noise::estimate(params, &chain, &frequencies, c, rho);
+    let radiated = radiation::radiate(params, &chain, &frequencies, c, rho);
+    let back_pressure = params.air_line.as_ref().map(|line| {
+        let flow_rate = line.stone.flow_rate;
+        chain.back_pressure(flow_rate, rho) + line.stone.impedance() * flow_rate
+    });
 
     Ok(SimResult {
         frequencies,
         transmission_loss: tl,
         transfer_function: transfer_fn,
+        phase,
+        group_delay,
         impulse_response: ir,
+        ir_truncation_loss,
+        decay,
         sample_rate,
+        back_pressure,
+        cutoff_frequency: chain.cutoff_frequency(c),
+        mode_warnings: mode_warnings(&chain, c, sample_rate / 2.0),
+        breakout_loss,
+        flow_noise,
+        radiated,
+        provenance: Provenance::new(&params.numerics, params.gas, c, rho),
+        log_sweep,
+        refined,
     })
 }
 
+/// Transmission loss and H(f) at `frequencies` Hz (all > 0, ascending),
+/// off the FFT grid of [`compute`].
+pub fn compute_at(params: &SimParams, frequencies: &[f64]) -> Result<Sweep, String> {
+    validate_params(params)?;
+    if let Some(&f) = frequencies.iter().find(|&&f| !(f > 0.0 && f.is_finite())) {
+        return Err(format!("frequencies must be > 0, got {f}"));
+    }
+    if frequencies.windows(2).any(|pair| pair[1] <= pair[0]) {
+        return Err("frequencies must be ascending".to_string());
+    }
+    let (c, rho) = params.medium();
+    let chain = muffler::Muffler::from_params(params).map_err(|e| e.to_string())?;
+    if params.numerics.engine == numerics::Engine::TimeDomain {
+        let (grid, grid_tl, grid_h) = time_domain::sweep(params, params.numerics.fft_size, 44100.0, c, rho)?;
+        return Ok(sweep_at(params, &chain, frequencies.to_vec(), (&grid, &grid_tl, &grid_h), c, rho));
+    }
+    Ok(sweep_at(params, &chain, frequencies.to_vec(), (&[], &[], &[]), c, rho))
+}
+
+/// Evaluate the chain at `frequencies`. The frequency-domain engine
+/// solves each point directly; the time-domain engine can only read them
+/// off the FFT-grid `grid` its march produced, which then already holds
+/// the breakout path.
+fn sweep_at(
+    params: &SimParams,
+    chain: &muffler::Muffler,
+    frequencies: Vec<f64>,
+    grid: (&[f64], &[f64], &[Complex64]),
+    c: f64,
+    rho: f64,
+) -> Sweep {
+    let (transmission_loss, transfer_function) = match params.numerics.engine {
+        numerics::Engine::FrequencyDomain => {
+            let (mut tl, mut hf) = frequency_response::sweep_at(chain, &frequencies, c, rho);
+            if params.shell.is_some() {
+                add_breakout(params, chain, &frequencies, &mut tl, &mut hf, c, rho);
+            }
+            (tl, hf)
+        }
+        numerics::Engine::TimeDomain => {
+            let (grid_f, grid_tl, grid_h) = grid;
+            let response = TransferFunction::new(grid_f.to_vec(), grid_h.to_vec());
+            frequencies
+                .iter()
+                .map(|&f| (interpolate(grid_f, grid_tl, f), response.at(f)))
+                .unzip()
+        }
+    };
+    Sweep {
+        frequencies,
+        transmission_loss,
+        transfer_function,
+    }
+}
+
+/// Transmission loss at `points` frequencies between `f_min` and `f_max`
+/// Hz, off the FFT grid of [`compute`], spaced as `spacing` says.
+///
+/// Returns `(frequencies, transmission_loss_db)`.
+pub fn compute_tl_range(
+    params: &SimParams,
+    f_min: f64,
+    f_max: f64,
+    points: usize,
+    spacing: SweepSpacing,
+) -> Result<(Vec<f64>, Vec<f64>), String> {
+    validate_params(params)?;
+    if !(f_min > 0.0 && f_min < f_max && f_max.is_finite()) {
+        return Err(format!("frequency range must satisfy 0 < f_min < f_max, got {f_min}..{f_max}"));
+    }
+    let (c, rho) = params.medium();
+    let chain = muffler::Muffler::from_params(params).map_err(|e| e.to_string())?;
+    if params.numerics.engine == numerics::Engine::TimeDomain {
+        // The march yields the FFT grid; read the requested points off it
+        let sweep = compute_at(params, &frequency_response::spaced(f_min, f_max, points, spacing))?;
+        return Ok((sweep.frequencies, sweep.transmission_loss));
+    }
+    let (frequencies, mut tl) = frequency_response::sweep_range(&chain, f_min, f_max, points, spacing, c, rho);
+    if params.shell.is_some() {
+        let mut unused = vec![Complex64::new(1.0, 0.0); frequencies.len()];
+        add_breakout(params, &chain, &frequencies, &mut tl, &mut unused, c, rho);
+    }
+    Ok((frequencies, tl))
+}
+
+/// Linear interpolation of `values` sampled at the ascending `grid`,
+/// clamped to the ends; a single point is held flat and an empty grid
+/// gives 0.
+fn interpolate(grid: &[f64], values: &[f64], x: f64) -> f64 {
+    if grid.len() < 2 {
+        return values.first().copied().unwrap_or(0.0);
+    }
+    let i = grid.partition_point(|&g| g < x).clamp(1, grid.len() - 1);
+    let t = ((x - grid[i - 1]) / (grid[i] - grid[i - 1])).clamp(0.0, 1.0);
+    values[i - 1] + (values[i] - values[i - 1]) * t
+}
+
+/// Elements of `chain` whose first higher-order mode cuts on at or below
+/// `f_max`, lowest cutoff first.
+fn mode_warnings(chain: &muffler::Muffler, c: f64, f_max: f64) -> Vec<ModeWarning> {
+    let mut warnings: Vec<ModeWarning> = chain
+        .element_cutoffs(c)
+        .into_iter()
+        .filter(|&(_, cutoff)| cutoff <= f_max)
+        .map(|(element, cutoff)| ModeWarning {
+            element,
+            label: chain.elements()[element].label(),
+            cutoff,
+        })
+        .collect();
+    warnings.sort_by(|a, b| a.cutoff.total_cmp(&b.cutoff));
+    warnings
+}
+
+/// Combine the shell breakout path with the duct-borne TL and H(f) by
+/// adding their powers (H keeps its phase), returning the breakout TL.
+fn add_breakout(
+    params: &SimParams,
+    chain: &muffler::Muffler,
+    frequencies: &[f64],
+    tl: &mut [f64],
+    transfer_fn: &mut [Complex64],
+    c: f64,
+    rho: f64,
+) -> Vec<f64> {
+    frequencies
+        .iter()
+        .zip(tl.iter_mut().zip(transfer_fn.iter_mut()))
+        .map(|(&f, (tl, h))| {
+            let ratio = breakout::breakout_ratio(chain, params, 2.0 * std::f64::consts::PI * f, c, rho).max(1e-20);
+            if f > 0.0 {
+                let duct = 10f64.powf(-*tl / 10.0);
+                *h *= ((duct + ratio) / duct).sqrt();
+                *tl = -10.0 * (duct + ratio).log10();
+            }
+            -10.0 * ratio.log10()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -291,10 +1298,13 @@ mod tests {
             chamber_length: 10e-3,   // 10 mm
             outlet_diameter: 1e-3,   // 1 mm
             outlet_length: 5e-3,     // 5 mm
+            pump_drive: pump::PumpDrive::Rotary,
             rpm: 3000.0,
             num_valves: 3,
             duty_cycle: 0.5,
+            valve_timing: pump::StrokeTiming::default(),
             temperature: 20.0,
+            ..SimParams::default()
         };
         let result = compute(&params).expect("tiny params valid");
 
@@ -316,10 +1326,13 @@ mod tests {
             chamber_length: 2.0,     // 2 m
             outlet_diameter: 0.1,    // 100 mm
             outlet_length: 1.0,      // 1 m
+            pump_drive: pump::PumpDrive::Rotary,
             rpm: 3000.0,
             num_valves: 3,
             duty_cycle: 0.5,
+            valve_timing: pump::StrokeTiming::default(),
             temperature: 20.0,
+            ..SimParams::default()
         };
         let result = compute(&params).expect("large params valid");
 
@@ -331,4 +1344,624 @@ mod tests {
             assert!(s.is_finite(), "IR should be finite for large muffler");
         }
     }
+
+    #[test]
+    fn test_extended_inlet_adds_quarter_wave_peak() {
+        let flush = SimParams::default();
+        let extended = SimParams {
+            inlet_extension: 20e-3, // 20 mm stub → annulus tuned near c/(4·0.02) ≈ 4.3 kHz
+            ..SimParams::default()
+        };
+
+        let result_flush = compute(&flush).expect("flush params valid");
+        let result_ext = compute(&extended).expect("extended params valid");
+
+        let (c, _) = extended.medium();
+        let f_tuned = c / (4.0 * extended.inlet_extension);
+        let bin = result_ext
+            .frequencies
+            .iter()
+            .position(|&f| f >= f_tuned)
+            .expect("tuning frequency within sweep");
+        let peak_ext = result_ext.transmission_loss[bin - 2..=bin + 2]
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        let peak_flush = result_flush.transmission_loss[bin - 2..=bin + 2]
+            .iter()
+            .cloned()
+            .fold(f64::NEG_INFINITY, f64::max);
+        assert!(
+            peak_ext > peak_flush + 10.0,
+            "Extended inlet should add a TL peak near {f_tuned:.0} Hz: extended={peak_ext:.1} dB, flush={peak_flush:.1} dB"
+        );
+
+        // Extensions that meet inside the chamber are rejected.
+        let overlapping = SimParams {
+            inlet_extension: 50e-3,
+            outlet_extension: 40e-3,
+            ..SimParams::default()
+        };
+        assert!(compute(&overlapping).is_err());
+    }
+
+    #[test]
+    fn test_rectangular_chamber() {
+        let round = compute(&SimParams::default()).unwrap();
+        // A square box of the same area behaves the same below cutoff
+        let side = elements::CrossSection::Circular { diameter: 40e-3 }.area().sqrt();
+        let square = compute(&SimParams {
+            chamber_shape: Some(elements::CrossSection::Rectangular {
+                width: side,
+                height: side,
+            }),
+            ..SimParams::default()
+        })
+        .unwrap();
+        let cutoff = square.cutoff_frequency.unwrap();
+        for (i, &f) in round.frequencies.iter().enumerate().filter(|&(_, &f)| f < cutoff) {
+            let diff = (round.transmission_loss[i] - square.transmission_loss[i]).abs();
+            assert!(diff < 1e-6, "at {f} Hz: {diff} dB");
+        }
+        // but its cross modes cut on lower than the circle's
+        assert!(cutoff < round.cutoff_frequency.unwrap());
+
+        let bad = SimParams {
+            chamber_shape: Some(elements::CrossSection::Rectangular {
+                width: 0.0,
+                height: 0.02,
+            }),
+            ..SimParams::default()
+        };
+        assert!(compute(&bad).is_err());
+    }
+
+    #[test]
+    fn test_perforated_baffle() {
+        let plain = compute(&SimParams::default()).unwrap();
+        let divided = SimParams {
+            perforated_baffle: Some(elements::PerforatedPlate::new(40e-3, 0.05, 1e-3, 1e-3)),
+            ..SimParams::default()
+        };
+        let result = compute(&divided).unwrap();
+        let mean = |r: &SimResult| {
+            let band: Vec<f64> = (0..r.frequencies.len())
+                .filter(|&i| (3000.0..8000.0).contains(&r.frequencies[i]))
+                .map(|i| r.transmission_loss[i])
+                .collect();
+            band.iter().sum::<f64>() / band.len() as f64
+        };
+        assert!(mean(&result) > mean(&plain) + 5.0);
+
+        let both = SimParams {
+            baffle: Some(elements::Baffle::new(40e-3, 2e-3, 6e-3, 2e-3)),
+            ..divided.clone()
+        };
+        assert!(compute(&both).is_err());
+        let mut solid = divided;
+        solid.perforated_baffle.as_mut().unwrap().porosity = 0.0;
+        assert!(compute(&solid).is_err());
+    }
+
+    #[test]
+    fn test_chamber_profile() {
+        let plain = compute(&SimParams::default()).unwrap();
+        // The default chamber sampled as a profile, with a step-free bore
+        let samples: Vec<(f64, f64)> = (0..=8).map(|i| (i as f64 * 10e-3, 20e-3)).collect();
+        let profiled = SimParams {
+            chamber_profile: Some(elements::ProfiledDuct::new(samples)),
+            ..SimParams::default()
+        };
+        let result = compute(&profiled).unwrap();
+        for (a, b) in plain.transmission_loss.iter().zip(&result.transmission_loss) {
+            assert!((a - b).abs() < 1e-6, "{a} vs {b}");
+        }
+
+        // A body that necks down in the middle is a different muffler
+        let necked = SimParams {
+            chamber_profile: Some(elements::ProfiledDuct::new(vec![
+                (0.0, 20e-3),
+                (35e-3, 20e-3),
+                (40e-3, 8e-3),
+                (45e-3, 20e-3),
+                (80e-3, 20e-3),
+            ])),
+            ..SimParams::default()
+        };
+        let necked = compute(&necked).unwrap();
+        let differs = plain
+            .transmission_loss
+            .iter()
+            .zip(&necked.transmission_loss)
+            .any(|(a, b)| (a - b).abs() > 3.0);
+        assert!(differs);
+
+        let narrow_end = SimParams {
+            chamber_profile: Some(elements::ProfiledDuct::new(vec![(0.0, 2e-3), (80e-3, 20e-3)])),
+            ..SimParams::default()
+        };
+        assert!(compute(&narrow_end).is_err());
+        let with_baffle = SimParams {
+            baffle: Some(elements::Baffle::new(40e-3, 2e-3, 6e-3, 2e-3)),
+            ..profiled
+        };
+        assert!(compute(&with_baffle).is_err());
+    }
+
+    #[test]
+    fn test_pump_flow_convects_through_chain() {
+        // 40 L/min puts the 6 mm pipes near Mach 0.07
+        let flowing = SimParams {
+            pump_flow: 40.0 / 60_000.0,
+            ..SimParams::default()
+        };
+        let (c, rho) = flowing.medium();
+        let still = muffler::Muffler::from_params(&SimParams::default()).unwrap();
+        let moving = muffler::Muffler::from_params(&flowing).unwrap();
+        let pipe = elements::StraightDuct::new(1.0, flowing.inlet_diameter).with_mean_flow(flowing.mean_flow(), c);
+        assert!((0.05..0.1).contains(&pipe.mach), "{}", pipe.mach);
+
+        // Jet losses at the junctions fill in the chamber's pass band
+        // (kL = π)
+        let pass = c / (2.0 * flowing.chamber_length);
+        let tl = |m: &muffler::Muffler, f: f64| m.transmission_loss(2.0 * std::f64::consts::PI * f, c, rho);
+        assert!(tl(&moving, pass) > tl(&still, pass) + 0.1);
+        assert!(compute(&flowing).is_ok());
+
+        let backwards = SimParams {
+            pump_flow: -1e-3,
+            ..SimParams::default()
+        };
+        assert!(compute(&backwards).is_err());
+    }
+
+    #[test]
+    fn test_side_branch() {
+        let branched = |plug_thickness| SimParams {
+            side_branch: Some(elements::AbsorptiveBranch::new(0.1, 8e-3, plug_thickness, 20_000.0)),
+            ..SimParams::default()
+        };
+        // Best TL gain over the plain muffler around the branch resonance
+        let sweep = |params: &SimParams| compute_tl_range(params, 400.0, 1500.0, 1101, frequency_response::SweepSpacing::Linear).unwrap().1;
+        let plain = sweep(&SimParams::default());
+        let gain = |params: &SimParams| {
+            let tl = sweep(params);
+            tl.iter().zip(&plain).map(|(a, b)| a - b).fold(f64::MIN, f64::max)
+        };
+        let (bare, damped) = (gain(&branched(0.0)), gain(&branched(0.03)));
+        assert!(damped > 3.0);
+        assert!(bare > damped + 10.0);
+
+        assert!(compute(&branched(0.1)).is_err());
+    }
+
+    #[test]
+    fn test_port_offsets() {
+        let plane = compute(&SimParams::default()).unwrap();
+        let offsets = PortOffsets {
+            inlet: 15e-3,
+            outlet: 15e-3,
+            angle: std::f64::consts::PI,
+        };
+        let offset = compute(&SimParams {
+            port_offsets: Some(offsets),
+            ..SimParams::default()
+        })
+        .unwrap();
+        // Same chamber at low frequency, different once cross modes carry
+        for (i, &f) in plane.frequencies.iter().enumerate().filter(|&(_, &f)| f > 0.0 && f < 1000.0) {
+            let diff = (plane.transmission_loss[i] - offset.transmission_loss[i]).abs();
+            assert!(diff < 1.0, "at {f} Hz: {diff} dB");
+        }
+        let high_diff = plane
+            .frequencies
+            .iter()
+            .enumerate()
+            .filter(|&(_, &f)| f > 5500.0 && f < 9000.0)
+            .map(|(i, _)| (plane.transmission_loss[i] - offset.transmission_loss[i]).abs())
+            .fold(0.0, f64::max);
+        assert!(high_diff > 10.0, "{high_diff}");
+
+        let outside = PortOffsets { inlet: 19e-3, ..offsets };
+        for bad in [
+            SimParams {
+                port_offsets: Some(outside),
+                ..SimParams::default()
+            },
+            SimParams {
+                port_offsets: Some(offsets),
+                baffle: Some(elements::Baffle::new(40e-3, 2e-3, 6e-3, 2e-3)),
+                ..SimParams::default()
+            },
+        ] {
+            assert!(compute(&bad).is_err());
+        }
+    }
+
+    #[test]
+    fn test_phase_and_group_delay() {
+        // A chamber barely wider than the pipes is nearly a plain 140 mm
+        // pipe: a pure delay of L/c
+        let pipe = SimParams {
+            chamber_diameter: 6.01e-3,
+            ..SimParams::default()
+        };
+        let result = compute(&pipe).unwrap();
+        let (c, _) = pipe.medium();
+        let delay = (pipe.inlet_length + pipe.chamber_length + pipe.outlet_length) / c;
+        assert_eq!(result.phase.len(), result.frequencies.len());
+        assert_eq!(result.phase[0], 0.0);
+        for (&f, &tau) in result.frequencies.iter().zip(&result.group_delay).take(500) {
+            assert!((tau / delay - 1.0).abs() < 0.01, "{f} Hz: {tau} s vs {delay} s");
+        }
+
+        // The chamber's reflections disperse the delay
+        let result = compute(&SimParams::default()).unwrap();
+        let spread = result.group_delay.iter().take(500).fold(0.0f64, |m, &t| m.max((t - delay).abs()));
+        assert!(spread > 0.1 * delay, "{spread}");
+    }
+
+    #[test]
+    fn test_piston_pump_params() {
+        let piston = SimParams {
+            piston: Some(pump::PistonCrank::default()),
+            ..SimParams::default()
+        };
+        assert!(compute(&piston).is_ok());
+        assert_ne!(piston.pump_harmonics(1000.0), SimParams::default().pump_harmonics(1000.0));
+        let linear = SimParams {
+            pump_drive: pump::PumpDrive::Linear {
+                mains_frequency: 50.0,
+                stroke: 1.0,
+            },
+            ..piston
+        };
+        assert!(compute(&linear).is_err());
+    }
+
+    #[test]
+    fn test_pump_calibration() {
+        // One valve delivering a half-sine for half of each revolution:
+        // mean 1/π and a fundamental of 1/2 in waveform units
+        let single = SimParams {
+            num_valves: 1,
+            pump_displacement: Some(1e-6),
+            ..SimParams::default()
+        };
+        let flow = 1e-6 * 3000.0 / 60.0;
+        assert_eq!(single.displaced_flow(), Some(flow));
+        assert_eq!(single.mean_flow(), flow);
+        let q = single.pump_volume_velocity().unwrap();
+        assert!((q / (flow * std::f64::consts::PI / 2.0) - 1.0).abs() < 1e-3, "{q}");
+        let (c, rho) = single.medium();
+        let area = constants::area_from_diameter(single.inlet_diameter);
+        assert!((single.pulse_pressure().unwrap() - rho * c / area * q).abs() < 1e-9);
+
+        // The listener hears the calibrated pump whatever its source
+        // strength, as an uncalibrated pump of the same flow and strength
+        let calibrated = SimParams {
+            listener: Some(radiation::Listener::default()),
+            ..single.clone()
+        };
+        let matched = SimParams {
+            pump_displacement: None,
+            pump_flow: flow,
+            listener: Some(radiation::Listener {
+                source_strength: q,
+                ..radiation::Listener::default()
+            }),
+            ..single
+        };
+        let tones = |params: &SimParams| compute(params).unwrap().radiated.unwrap().tones;
+        assert_eq!(tones(&calibrated), tones(&matched));
+        assert_eq!(SimParams::default().pulse_pressure(), None);
+        assert!(compute(&SimParams {
+            pump_displacement: Some(0.0),
+            ..SimParams::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_reed_valve_params() {
+        let reed = SimParams {
+            reed_valve: Some(pump::ReedValve::default()),
+            ..SimParams::default()
+        };
+        assert!(compute(&reed).is_ok());
+        assert_ne!(reed.pump_harmonics(1000.0), SimParams::default().pump_harmonics(1000.0));
+        let floppy = SimParams {
+            reed_valve: Some(pump::ReedValve {
+                mass: 0.0,
+                ..pump::ReedValve::default()
+            }),
+            ..reed
+        };
+        assert!(compute(&floppy).is_err());
+    }
+
+    #[test]
+    fn test_ir_truncation_loss() {
+        let result = compute(&SimParams::default()).unwrap();
+        assert!(!result.ir_truncated(), "{}", result.ir_truncation_loss);
+
+        // A 16-sample response cuts off most of the chamber's ringing
+        let short = SimParams {
+            numerics: numerics::Numerics {
+                ir_length: Some(16),
+                ..numerics::Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        let result = compute(&short).unwrap();
+        assert!(result.ir_truncated(), "{}", result.ir_truncation_loss);
+        assert!(result.ir_truncation_loss < 1.0);
+    }
+
+    #[test]
+    fn test_impulse_response_decay() {
+        // A plain pipe passes the pulse straight through; a chamber rings
+        let pipe = compute(&SimParams {
+            chamber_diameter: 6.01e-3,
+            ..SimParams::default()
+        })
+        .unwrap();
+        let chamber = compute(&SimParams {
+            chamber_diameter: 60e-3,
+            ..SimParams::default()
+        })
+        .unwrap();
+        assert!(chamber.decay.early_to_late < pipe.decay.early_to_late);
+        let t20 = chamber.decay.t20.unwrap();
+        assert!(t20 > 0.0 && t20.is_finite());
+    }
+
+    #[test]
+    fn test_numerics_settings() {
+        let fine = SimParams {
+            numerics: numerics::Numerics {
+                fft_size: 16384,
+                ..numerics::Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        let result = compute(&fine).expect("fft_size 16384 valid");
+        assert_eq!(result.frequencies.len(), 16384 / 2 + 1);
+        assert_eq!(result.impulse_response.len(), 16384 / 2);
+        assert!((result.frequencies[1] - fine.numerics.bin_width(44100.0)).abs() < 1e-12);
+
+        let mut bad = SimParams::default();
+        bad.numerics.fft_size = 3000;
+        assert!(compute(&bad).is_err(), "non power-of-two fft_size rejected");
+
+        // The whole inverse FFT as the impulse response, but no longer
+        let mut long = SimParams::default();
+        long.numerics.ir_length = Some(long.numerics.fft_size);
+        long.numerics.ir_window = numerics::IrWindow::Exponential { end_db: 60.0 };
+        assert_eq!(compute(&long).unwrap().impulse_response.len(), 4096);
+        long.numerics.ir_length = Some(8192);
+        assert!(compute(&long).unwrap_err().contains("ir_length"));
+
+        // End corrections lengthen the chamber acoustically and so shift
+        // the response.
+        let mut corrected = SimParams::default();
+        corrected.numerics.end_corrections = true;
+        let plain = compute(&SimParams::default()).expect("default params valid");
+        let corrected = compute(&corrected).expect("end corrections valid");
+        assert!(plain
+            .transmission_loss
+            .iter()
+            .zip(&corrected.transmission_loss)
+            .any(|(a, b)| (a - b).abs() > 0.1));
+    }
+
+    #[test]
+    fn test_outlet_radiation() {
+        use numerics::Termination;
+        let tl = |outlet_termination| {
+            let params = SimParams {
+                outlet_termination,
+                ..SimParams::default()
+            };
+            compute_tl_range(&params, 100.0, 3000.0, 30, frequency_response::SweepSpacing::Linear).unwrap().1
+        };
+        let anechoic = tl(Termination::Anechoic);
+        let unflanged = tl(Termination::Unflanged { diameter: None });
+        let flanged = tl(Termination::Flanged { diameter: None });
+        // A narrow open end reflects most low-frequency sound back; the
+        // flange doubles the radiated power
+        assert!(unflanged[0] > anechoic[0] + 20.0, "{} vs {}", unflanged[0], anechoic[0]);
+        assert!((unflanged[0] - flanged[0] - 3.0).abs() < 0.2, "{} vs {}", unflanged[0], flanged[0]);
+
+        // The radiated pressure follows the open end's impedance
+        let params = SimParams {
+            outlet_termination: Termination::Flanged { diameter: None },
+            ..SimParams::default()
+        };
+        let result = compute(&params).unwrap();
+        assert!(result.transfer_function.iter().all(|h| h.norm().is_finite()));
+    }
+
+    #[test]
+    fn test_ambient_air() {
+        let sea_level = SimParams::default();
+        let mut mountain = SimParams::default();
+        mountain.set_altitude(3000.0);
+        assert!((mountain.altitude() - 3000.0).abs() < 1e-6);
+        let humid = SimParams {
+            relative_humidity: 1.0,
+            ..SimParams::default()
+        };
+
+        // Thin air changes the density alone, humid air mostly the sound
+        // speed, which moves the chamber's pass bands up with it
+        let ((c0, rho0), (c1, rho1), (c2, _)) = (sea_level.medium(), mountain.medium(), humid.medium());
+        assert_eq!(c1, c0);
+        assert!((rho1 / rho0 - 0.692).abs() < 0.005, "{}", rho1 / rho0);
+        assert!(c2 > c0 + 1.0);
+        let first_pass_band = |params: &SimParams| {
+            let spacing = frequency_response::SweepSpacing::Linear;
+            let (f, tl) = compute_tl_range(params, 1500.0, 2800.0, 1301, spacing).unwrap();
+            let lowest = tl.iter().cloned().fold(f64::MAX, f64::min);
+            f[tl.iter().position(|&v| v == lowest).unwrap()]
+        };
+        let shift = first_pass_band(&humid) / first_pass_band(&sea_level);
+        assert!((shift - c2 / c0).abs() < 0.001, "{shift} vs {}", c2 / c0);
+
+        assert!(compute(&SimParams {
+            relative_humidity: 1.5,
+            ..SimParams::default()
+        })
+        .is_err());
+        assert!(compute(&SimParams {
+            static_pressure: 0.0,
+            ..SimParams::default()
+        })
+        .is_err());
+    }
+
+    #[test]
+    fn test_time_domain_engine() {
+        let numerics = numerics::Numerics {
+            fft_size: 1024,
+            engine: numerics::Engine::TimeDomain,
+            ..numerics::Numerics::default()
+        };
+        let params = SimParams {
+            numerics,
+            ..SimParams::default()
+        };
+        // Same result layout as the transfer-matrix engine
+        let result = compute(&params).unwrap();
+        assert_eq!(result.transmission_loss.len(), 513);
+        assert_eq!(result.impulse_response.len(), 512);
+        assert!(result.provenance.numerics_hash != compute(&SimParams::default()).unwrap().provenance.numerics_hash);
+        let (frequencies, tl) = compute_tl_range(&params, 200.0, 400.0, 5, SweepSpacing::Linear).unwrap();
+        assert_eq!(frequencies.len(), 5);
+        assert!(tl.windows(2).all(|w| w[1] > w[0]), "{tl:?}");
+
+        let divided = SimParams {
+            baffle: Some(elements::Baffle::new(0.04, 2e-3, 6e-3, 10e-3)),
+            ..params.clone()
+        };
+        assert!(compute(&divided).unwrap_err().contains("baffle"));
+
+        // The march is too slow for the largest FFT sizes
+        let mut long = params;
+        long.numerics.fft_size = numerics::Numerics::MAX_TIME_DOMAIN_FFT_SIZE * 2;
+        assert!(compute(&long).unwrap_err().contains("fft_size"));
+        assert_eq!(interpolate(&[100.0], &[3.0], 50.0), 3.0);
+        assert_eq!(interpolate(&[], &[], 50.0), 0.0);
+    }
+
+    #[test]
+    fn test_log_sweep() {
+        let log = numerics::LogSweep {
+            f_min: 20.0,
+            f_max: 20_000.0,
+            points_per_octave: 12,
+        };
+        // ~9.97 octaves at 12 points each, both ends on the grid when
+        // f_max sits on it
+        let frequencies = log.frequencies();
+        assert_eq!(frequencies.len(), 120);
+        assert!(frequencies.windows(2).all(|w| (w[1] / w[0] - 2f64.powf(1.0 / 12.0)).abs() < 1e-12));
+        let octave = numerics::LogSweep { f_max: 40.0, ..log };
+        assert_eq!(octave.frequencies().len(), 13);
+        assert!((octave.frequencies()[12] - 40.0).abs() < 1e-9);
+
+        let params = SimParams {
+            numerics: numerics::Numerics {
+                log_sweep: Some(log),
+                ..numerics::Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        let result = compute(&params).unwrap();
+        let sweep = result.log_sweep.as_ref().unwrap();
+        assert_eq!(sweep.frequencies, frequencies);
+        // The FFT grid is unchanged and the log points solve exactly
+        assert_eq!(result.frequencies.len(), params.numerics.fft_size / 2 + 1);
+        let (_, tl) = compute_tl_range(&params, 20.0, 40.0, 13, SweepSpacing::Log).unwrap();
+        for (a, b) in tl.iter().zip(&sweep.transmission_loss) {
+            assert!((a - b).abs() < 1e-9, "{a} vs {b}");
+        }
+        assert!(compute(&SimParams::default()).unwrap().log_sweep.is_none());
+
+        let empty = SimParams {
+            numerics: numerics::Numerics {
+                log_sweep: Some(numerics::LogSweep { points_per_octave: 0, ..log }),
+                ..numerics::Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        assert!(compute(&empty).unwrap_err().contains("points_per_octave"));
+        assert!(compute_at(&params, &[100.0, 50.0]).is_err());
+    }
+
+    #[test]
+    fn test_adaptive_refinement() {
+        // At 256 points the bins are 172 Hz wide, far coarser than the
+        // chamber's pass bands
+        let params = SimParams {
+            numerics: numerics::Numerics {
+                fft_size: 256,
+                refinement: Some(numerics::Refinement::default()),
+                ..numerics::Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        let result = compute(&params).unwrap();
+        let refined = result.refined.as_ref().unwrap();
+        assert!(refined.frequencies.len() > result.frequencies.len());
+        assert!(result.frequencies.iter().all(|f| refined.frequencies.contains(f)));
+        assert_eq!(result.impulse_response.len(), 128);
+
+        let (c, rho) = params.medium();
+        let chain = muffler::Muffler::from_params(&params).unwrap();
+        let min_spacing = params.numerics.bin_width(44100.0) / 64.0;
+        for (i, pair) in refined.frequencies.windows(2).enumerate() {
+            let step = (refined.transmission_loss[i + 1] - refined.transmission_loss[i]).abs();
+            assert!(step <= 1.0 || pair[1] - pair[0] < 2.0 * min_spacing, "{pair:?}: {step} dB");
+        }
+        let (i, &f) = refined
+            .frequencies
+            .iter()
+            .enumerate()
+            .find(|(_, f)| !result.frequencies.contains(f))
+            .unwrap();
+        let tl = chain.transmission_loss(2.0 * std::f64::consts::PI * f, c, rho);
+        assert!((refined.transmission_loss[i] - tl).abs() < 1e-9);
+
+        assert!(compute(&SimParams::default()).unwrap().refined.is_none());
+    }
+
+    #[test]
+    fn test_plane_wave_validity() {
+        // The default 40 mm chamber cuts on at ~5 kHz, its 6 mm pipes far
+        // above Nyquist
+        let result = compute(&SimParams::default()).unwrap();
+        let cutoff = result.cutoff_frequency.unwrap();
+        assert_eq!(result.mode_warnings.len(), 1);
+        let warning = &result.mode_warnings[0];
+        assert_eq!(warning.cutoff, cutoff);
+        assert!(warning.label.starts_with("Duct") && warning.to_string().contains("plane waves only below"));
+
+        let valid = result.plane_wave_valid();
+        assert_eq!(valid.len(), result.frequencies.len());
+        for (&f, &ok) in result.frequencies.iter().zip(&valid) {
+            assert_eq!(ok, f < cutoff);
+        }
+
+        // A baffle splits the chamber into two warned sections, in order
+        let divided = compute(&SimParams {
+            baffle: Some(elements::Baffle::new(60e-3, 2e-3, 8e-3, 10e-3)),
+            chamber_diameter: 60e-3,
+            ..SimParams::default()
+        })
+        .unwrap();
+        let cutoffs: Vec<f64> = divided.mode_warnings.iter().map(|w| w.cutoff).collect();
+        assert!(cutoffs.len() >= 2 && cutoffs.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert!(cutoffs[0] < cutoff);
+    }
 }
@@ -0,0 +1,249 @@
+use realfft::RealFftPlanner;
+use std::f64::consts::PI;
+
+use crate::measurement::fft_convolve;
+use crate::metrics::Weighting;
+use crate::pump::SampleSource;
+
+/// Length of program signal in seconds measured by [`design_level`].
+const PROGRAM_DURATION: f64 = 2.0;
+
+/// How perceived level is measured when matching designs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LoudnessMetric {
+    /// Integrated loudness per ITU-R BS.1770 (K-weighting, gated), in
+    /// LUFS relative to full scale.
+    Lufs,
+    /// A-weighted RMS level in dB relative to a full-scale square wave.
+    AWeighted,
+    /// Unweighted RMS level in dB relative to a full-scale square wave.
+    Rms,
+    /// Energy of the muffler's impulse response in dB, whatever the pump
+    /// plays through it: the level of white noise through the muffler.
+    IrEnergy,
+}
+
+impl LoudnessMetric {
+    /// Every metric, for selectors.
+    pub const ALL: [LoudnessMetric; 4] = [
+        LoudnessMetric::Lufs,
+        LoudnessMetric::AWeighted,
+        LoudnessMetric::Rms,
+        LoudnessMetric::IrEnergy,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LoudnessMetric::Lufs => "LUFS",
+            LoudnessMetric::AWeighted => "A-weighted RMS",
+            LoudnessMetric::Rms => "RMS",
+            LoudnessMetric::IrEnergy => "IR energy",
+        }
+    }
+}
+
+/// Level of `samples` in dB under `metric`; `-inf` for silence. Under
+/// [`LoudnessMetric::IrEnergy`] the samples are taken as an impulse
+/// response and their total energy measured.
+pub fn measure(samples: &[f64], sample_rate: f64, metric: LoudnessMetric) -> f64 {
+    let energy = || samples.iter().map(|x| x * x).sum::<f64>();
+    match metric {
+        LoudnessMetric::Lufs => integrated_loudness(samples, sample_rate),
+        LoudnessMetric::AWeighted => a_weighted_level(samples, sample_rate),
+        LoudnessMetric::Rms if samples.is_empty() => f64::NEG_INFINITY,
+        LoudnessMetric::Rms => 10.0 * (energy() / samples.len() as f64).log10(),
+        LoudnessMetric::IrEnergy => 10.0 * energy().log10(),
+    }
+}
+
+/// Level of `program` (the pump, a recording or a test signal: whatever
+/// playback feeds the muffler) heard through a muffler's
+/// `impulse_response` at `sample_rate` Hz. The difference between two
+/// designs' levels is the gain that makes them equally loud.
+///
+/// This synthesizes and convolves seconds of audio; keep it off the UI
+/// thread.
+pub fn design_level(
+    program: &mut dyn SampleSource,
+    impulse_response: &[f64],
+    sample_rate: f64,
+    metric: LoudnessMetric,
+) -> f64 {
+    if metric == LoudnessMetric::IrEnergy {
+        return measure(impulse_response, sample_rate, metric);
+    }
+    program.set_sample_rate(sample_rate);
+
+    // Skip the first IR length while the muffler rings up
+    let settle = impulse_response.len();
+    let length = (PROGRAM_DURATION * sample_rate) as usize;
+    let output = fft_convolve(&program.generate(settle + length), impulse_response);
+    measure(&output[settle..settle + length], sample_rate, metric)
+}
+
+/// Gated integrated loudness of a single channel (ITU-R BS.1770-4):
+/// 400 ms blocks overlapping by 75 %, an absolute gate at −70 LUFS and a
+/// relative gate 10 LU below the absolutely gated loudness.
+fn integrated_loudness(samples: &[f64], sample_rate: f64) -> f64 {
+    let weighted = k_weight(samples, sample_rate);
+    let block = ((0.4 * sample_rate) as usize).clamp(1, weighted.len().max(1));
+    let step = (block / 4).max(1);
+    let mean_square = |block: &[f64]| block.iter().map(|s| s * s).sum::<f64>() / block.len().max(1) as f64;
+    let loudness = |z: f64| -0.691 + 10.0 * z.log10();
+
+    let blocks: Vec<f64> = (0..=weighted.len().saturating_sub(block))
+        .step_by(step)
+        .map(|start| mean_square(&weighted[start..start + block]))
+        .filter(|&z| loudness(z) > -70.0)
+        .collect();
+    if blocks.is_empty() {
+        return f64::NEG_INFINITY;
+    }
+    let average = |blocks: &[f64]| blocks.iter().sum::<f64>() / blocks.len() as f64;
+    let threshold = loudness(average(&blocks)) - 10.0;
+    let gated: Vec<f64> = blocks.into_iter().filter(|&z| loudness(z) > threshold).collect();
+    loudness(average(&gated))
+}
+
+/// Apply the BS.1770 K-weighting: the high-shelf head model followed by
+/// the RLB high-pass, both designed for `sample_rate`.
+fn k_weight(samples: &[f64], sample_rate: f64) -> Vec<f64> {
+    // Pre-filter (high shelf, +4 dB above ~1.7 kHz)
+    let k = (PI * 1681.974450955533 / sample_rate).tan();
+    let q = 0.7071752369554196;
+    let vh = 10f64.powf(3.999843853973347 / 20.0);
+    let vb = vh.powf(0.4996667741545416);
+    let a0 = 1.0 + k / q + k * k;
+    let shelf = [
+        (vh + vb * k / q + k * k) / a0,
+        2.0 * (k * k - vh) / a0,
+        (vh - vb * k / q + k * k) / a0,
+        2.0 * (k * k - 1.0) / a0,
+        (1.0 - k / q + k * k) / a0,
+    ];
+
+    // RLB high-pass at ~38 Hz
+    let k = (PI * 38.13547087602444 / sample_rate).tan();
+    let q = 0.5003270373238773;
+    let a0 = 1.0 + k / q + k * k;
+    let high_pass = [1.0, -2.0, 1.0, 2.0 * (k * k - 1.0) / a0, (1.0 - k / q + k * k) / a0];
+
+    biquad(&biquad(samples, shelf), high_pass)
+}
+
+/// Run a biquad with normalised coefficients `[b0, b1, b2, a1, a2]`.
+fn biquad(samples: &[f64], [b0, b1, b2, a1, a2]: [f64; 5]) -> Vec<f64> {
+    let (mut x, mut y) = ([0.0; 2], [0.0; 2]);
+    samples
+        .iter()
+        .map(|&input| {
+            let output = b0 * input + b1 * x[0] + b2 * x[1] - a1 * y[0] - a2 * y[1];
+            x = [input, x[0]];
+            y = [output, y[0]];
+            output
+        })
+        .collect()
+}
+
+/// A-weighted RMS level in dB, weighting the spectrum of the whole signal
+/// (Parseval's theorem turns the weighted bins back into a mean square).
+fn a_weighted_level(samples: &[f64], sample_rate: f64) -> f64 {
+    let n = samples.len();
+    if n == 0 {
+        return f64::NEG_INFINITY;
+    }
+    let mut planner = RealFftPlanner::<f64>::new();
+    let fft = planner.plan_fft_forward(n);
+    let mut input = samples.to_vec();
+    let mut spectrum = fft.make_output_vec();
+    fft.process(&mut input, &mut spectrum).expect("FFT failed");
+
+    let weighted: f64 = spectrum
+        .iter()
+        .enumerate()
+        .map(|(k, bin)| {
+            // Bins other than DC and Nyquist stand for their mirror image too
+            let fold = if k == 0 || 2 * k == n { 1.0 } else { 2.0 };
+            let weight = Weighting::A.gain(k as f64 * sample_rate / n as f64);
+            fold * bin.norm_sqr() * weight * weight
+        })
+        .sum();
+    10.0 * (weighted / (n * n) as f64).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimParams;
+
+    fn sine(frequency: f64, amplitude: f64, sample_rate: f64, seconds: f64) -> Vec<f64> {
+        (0..(seconds * sample_rate) as usize)
+            .map(|i| amplitude * (2.0 * PI * frequency * i as f64 / sample_rate).sin())
+            .collect()
+    }
+
+    #[test]
+    fn test_metrics_on_reference_tones() {
+        let fs = 48_000.0;
+        // BS.1770: a full-scale 997 Hz sine reads −3.01 LUFS
+        let tone = sine(997.0, 1.0, fs, 3.0);
+        assert!((measure(&tone, fs, LoudnessMetric::Lufs) + 3.01).abs() < 0.05);
+        assert!((measure(&tone, fs, LoudnessMetric::AWeighted) + 3.01).abs() < 0.05);
+        assert!((measure(&tone, fs, LoudnessMetric::Rms) + 3.01).abs() < 0.01);
+        assert!((measure(&[0.5, 0.5], fs, LoudnessMetric::IrEnergy) + 3.01).abs() < 0.01);
+
+        // A-weighting takes 19.1 dB off 100 Hz; K-weighting far less
+        let low = sine(100.0, 1.0, fs, 3.0);
+        assert!((measure(&low, fs, LoudnessMetric::AWeighted) + 3.01 + 19.1).abs() < 0.1);
+        let lufs = measure(&low, fs, LoudnessMetric::Lufs);
+        assert!((-5.5..-4.0).contains(&lufs), "{lufs}");
+
+        // Both scale with gain, and silence is gated out
+        let quiet = sine(997.0, 0.1, fs, 3.0);
+        for metric in LoudnessMetric::ALL {
+            let drop = measure(&tone, fs, metric) - measure(&quiet, fs, metric);
+            assert!((drop - 20.0).abs() < 0.05, "{metric:?}: {drop}");
+            assert_eq!(measure(&vec![0.0; 48_000], fs, metric), f64::NEG_INFINITY);
+        }
+    }
+
+    #[test]
+    fn test_design_level_tracks_muffler() {
+        let small = SimParams {
+            chamber_diameter: 15e-3,
+            ..SimParams::default()
+        };
+        let large = SimParams {
+            chamber_diameter: 60e-3,
+            ..SimParams::default()
+        };
+        for metric in LoudnessMetric::ALL {
+            let level = |params: &SimParams| {
+                let result = crate::compute(params).unwrap();
+                let mut pump = params.pump_source(result.sample_rate);
+                design_level(&mut pump, &result.impulse_response, result.sample_rate, metric)
+            };
+            let (small_level, large_level) = (level(&small), level(&large));
+            assert!(small_level.is_finite() && large_level.is_finite());
+            // The bigger expansion ratio attenuates more
+            assert!(large_level < small_level - 3.0, "{metric:?}: {large_level} vs {small_level}");
+        }
+    }
+
+    #[test]
+    fn test_matched_gain_follows_ir_gain() {
+        let params = SimParams::default();
+        let result = crate::compute(&params).unwrap();
+        let fs = result.sample_rate;
+        let louder: Vec<f64> = result
+            .impulse_response
+            .iter()
+            .map(|x| x * 10f64.powf(6.0 / 20.0))
+            .collect();
+        for metric in LoudnessMetric::ALL {
+            let level = |ir: &[f64]| design_level(&mut params.pump_source(fs), ir, fs, metric);
+            let gain = level(&louder) - level(&result.impulse_response);
+            assert!((gain - 6.0).abs() < 1e-6, "{metric:?}: {gain}");
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use realfft::num_complex::Complex;
+use realfft::RealFftPlanner;
+
+use crate::test_signal::{inverse_log_sweep, log_sweep};
+use crate::SimResult;
+
+/// Outcome of a virtual swept-sine measurement.
+#[derive(Debug, Clone)]
+pub struct SweepMeasurement {
+    /// Impulse response recovered by deconvolution, the same length as
+    /// the analytical one.
+    pub impulse_response: Vec<f64>,
+    /// Energy of the difference to the analytical impulse response
+    /// relative to its own energy, in dB.
+    pub error_db: f64,
+}
+
+/// Measure an impulse response the way a lab would: play an exponential
+/// sweep from `start` to `end` Hz lasting `duration` seconds through it,
+/// "record" the output, and deconvolve with the inverse sweep.
+///
+/// Everything runs offline, so the only differences to `ir` come from the
+/// finite sweep band and length; a large error points at the DSP chain.
+pub fn measure_impulse_response(
+    ir: &[f64],
+    start: f64,
+    end: f64,
+    duration: f64,
+    sample_rate: f64,
+) -> SweepMeasurement {
+    let sweep = log_sweep(start, end, duration, sample_rate);
+    let inverse = inverse_log_sweep(start, end, duration, sample_rate);
+    let recorded = fft_convolve(&sweep, ir);
+    let deconvolved = fft_convolve(&recorded, &inverse);
+
+    // Linear response starts at the zero-lag peak of sweep ⊛ inverse
+    let offset = sweep.len() - 1;
+    let measured = deconvolved[offset..offset + ir.len()].to_vec();
+
+    let reference: f64 = ir.iter().map(|h| h * h).sum();
+    let residual: f64 = measured.iter().zip(ir).map(|(m, h)| (m - h).powi(2)).sum();
+    SweepMeasurement {
+        impulse_response: measured,
+        error_db: 10.0 * (residual / reference.max(1e-300)).max(1e-30).log10(),
+    }
+}
+
+/// Run [`measure_impulse_response`] on a simulation result with a sweep
+/// covering 5 Hz up to Nyquist. The muffler passes DC, so the sweep has to
+/// start well below the lowest frequency the impulse response resolves.
+pub fn validate(result: &SimResult, duration: f64) -> SweepMeasurement {
+    measure_impulse_response(
+        &result.impulse_response,
+        5.0,
+        result.sample_rate / 2.0,
+        duration,
+        result.sample_rate,
+    )
+}
+
+/// Linear convolution of `a` and `b` via a zero-padded real FFT.
+pub fn fft_convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let len = a.len() + b.len() - 1;
+    let fft_size = len.next_power_of_two();
+    let mut planner = RealFftPlanner::<f64>::new();
+    let forward = planner.plan_fft_forward(fft_size);
+    let inverse = planner.plan_fft_inverse(fft_size);
+
+    let spectrum = |x: &[f64]| -> Vec<Complex<f64>> {
+        let mut padded = vec![0.0; fft_size];
+        padded[..x.len()].copy_from_slice(x);
+        let mut out = forward.make_output_vec();
+        forward.process(&mut padded, &mut out).expect("FFT failed");
+        out
+    };
+    let mut product: Vec<Complex<f64>> = spectrum(a)
+        .iter()
+        .zip(spectrum(b).iter())
+        .map(|(x, y)| x * y)
+        .collect();
+
+    let mut output = vec![0.0; fft_size];
+    inverse.process(&mut product, &mut output).expect("IFFT failed");
+    output.truncate(len);
+    for s in &mut output {
+        *s /= fft_size as f64;
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{compute, SimParams};
+
+    #[test]
+    fn test_fft_convolve_matches_direct() {
+        let a = [1.0, 2.0, 3.0];
+        let b = [0.5, -1.0];
+        let conv = fft_convolve(&a, &b);
+        let expected = [0.5, 0.0, -0.5, -3.0];
+        assert_eq!(conv.len(), expected.len());
+        for (x, y) in conv.iter().zip(expected) {
+            assert!((x - y).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_sweep_recovers_simulated_ir() {
+        let result = compute(&SimParams::default()).unwrap();
+        let measurement = validate(&result, 2.0);
+        assert_eq!(measurement.impulse_response.len(), result.impulse_response.len());
+        assert!(measurement.error_db < -25.0, "error {:.1} dB", measurement.error_db);
+
+        // A sweep that skips the low octaves cannot see the muffler's
+        // low-frequency pass band
+        let narrow = measure_impulse_response(&result.impulse_response, 2000.0, 20_000.0, 2.0, 44100.0);
+        assert!(narrow.error_db > measurement.error_db + 10.0);
+    }
+}
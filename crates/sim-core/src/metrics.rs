@@ -0,0 +1,540 @@
+use crate::muffler::Muffler;
+use crate::radiation::{pressure_per_volume_velocity, spl};
+use crate::{compute_at, compute_tl_range, SimParams, SimResult, SweepSpacing, TransferFunction};
+use std::f64::consts::PI;
+
+/// Frequency weighting of a sound level (IEC 61672).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Weighting {
+    /// Follows the ear at moderate levels; what regulations and product
+    /// specs quote.
+    A,
+    /// Nearly flat, rolling off only below ~30 Hz and above ~8 kHz; used
+    /// for loud sounds and to expose low-frequency content.
+    C,
+    /// Unweighted.
+    Z,
+}
+
+impl Weighting {
+    /// Every weighting, for selectors.
+    pub const ALL: [Weighting; 3] = [Weighting::A, Weighting::C, Weighting::Z];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Weighting::A => "dB(A)",
+            Weighting::C => "dB(C)",
+            Weighting::Z => "dB(Z)",
+        }
+    }
+
+    /// Weighting as a magnitude ratio at `frequency` Hz, 1 at 1 kHz.
+    pub fn gain(self, frequency: f64) -> f64 {
+        let f2 = frequency * frequency;
+        let (pole_low, pole_high) = (20.6f64.powi(2), 12194f64.powi(2));
+        match self {
+            Weighting::A => {
+                let ra = pole_high * f2 * f2
+                    / ((f2 + pole_low)
+                        * ((f2 + 107.7f64.powi(2)) * (f2 + 737.9f64.powi(2))).sqrt()
+                        * (f2 + pole_high));
+                ra * 10f64.powf(2.0 / 20.0)
+            }
+            Weighting::C => {
+                let rc = pole_high * f2 / ((f2 + pole_low) * (f2 + pole_high));
+                rc * 10f64.powf(0.062 / 20.0)
+            }
+            Weighting::Z => 1.0,
+        }
+    }
+
+    /// Weighting in dB at `frequency` Hz, 0 at 1 kHz.
+    pub fn gain_db(self, frequency: f64) -> f64 {
+        20.0 * self.gain(frequency).log10()
+    }
+}
+
+/// Overall A-, C- and unweighted levels of one spectrum.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OverallLevels {
+    /// dB(A).
+    pub a: f64,
+    /// dB(C).
+    pub c: f64,
+    /// Unweighted dB.
+    pub z: f64,
+}
+
+impl OverallLevels {
+    /// The overall levels of `tones`, see [`overall_level`].
+    pub fn of(tones: &[(f64, f64)]) -> Self {
+        Self {
+            a: overall_level(tones, Weighting::A),
+            c: overall_level(tones, Weighting::C),
+            z: overall_level(tones, Weighting::Z),
+        }
+    }
+}
+
+/// Overall level in dB of incoherent `tones`, each a frequency in Hz and
+/// a level in dB, after `weighting`. `-inf` without tones.
+pub fn overall_level(tones: &[(f64, f64)], weighting: Weighting) -> f64 {
+    let power: f64 = tones
+        .iter()
+        .map(|&(frequency, level)| 10f64.powf((level + weighting.gain_db(frequency)) / 10.0))
+        .sum();
+    10.0 * power.log10()
+}
+
+/// The pump harmonics below the Nyquist frequency of `result`, each a
+/// frequency in Hz and a level in dB relative to the unmuffled pump
+/// fundamental: the source spectrum weighted by |H(f)|. Overall levels of
+/// these compare designs without knowing the pump's absolute strength.
+pub fn pump_tones(params: &SimParams, result: &SimResult) -> Vec<(f64, f64)> {
+    let response = result.response();
+    params
+        .pump_harmonics(result.sample_rate / 2.0)
+        .into_iter()
+        .map(|(frequency, amplitude)| (frequency, 20.0 * (amplitude * response.at(frequency).norm()).log10()))
+        .collect()
+}
+
+/// A harmonic of each of two pumps close enough in frequency to be heard
+/// beating.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Beat {
+    /// Harmonic number of the first pump's tone, 1 for its fundamental.
+    pub first_order: usize,
+    /// Harmonic number of the second pump's tone.
+    pub second_order: usize,
+    /// Mean frequency of the two tones in Hz.
+    pub frequency: f64,
+    /// Beat rate in Hz, the difference between the two tones.
+    pub rate: f64,
+    /// Level of the weaker tone through its muffler in dB relative to the
+    /// unmuffled first pump's fundamental; it bounds how deep the beat is.
+    pub level: f64,
+    /// Modulation depth of the summed envelope, 2·min/(a + b) of the two
+    /// tone amplitudes: 1 when they are equally loud.
+    pub depth: f64,
+}
+
+/// The pairs of first- and second-pump harmonics of `params` below the
+/// Nyquist frequency that lie within `max_rate` Hz of each other, loudest
+/// beat first. The first pump plays through the muffler of `first`, the
+/// second through that of `second`: pass the same result twice when they
+/// share a muffler. Empty without a second pump.
+pub fn beats(params: &SimParams, first: &SimResult, second: &SimResult, max_rate: f64) -> Vec<Beat> {
+    let highest = first.sample_rate.min(second.sample_rate) / 2.0;
+    let (first_response, second_response) = (first.response(), second.response());
+    let muffled = |tones: Vec<(f64, f64)>, response: &TransferFunction| -> Vec<(f64, f64)> {
+        tones
+            .into_iter()
+            .map(|(frequency, amplitude)| (frequency, amplitude * response.at(frequency).norm()))
+            .collect()
+    };
+    let first_tones = muffled(params.pump_harmonics(highest), &first_response);
+    let second_tones = muffled(params.second_pump_harmonics(highest), &second_response);
+
+    let mut beats: Vec<Beat> = first_tones
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &(f1, a1))| {
+            second_tones.iter().enumerate().filter_map(move |(j, &(f2, a2))| {
+                let rate = (f1 - f2).abs();
+                (rate <= max_rate && a1 + a2 > 0.0).then(|| Beat {
+                    first_order: i + 1,
+                    second_order: j + 1,
+                    frequency: 0.5 * (f1 + f2),
+                    rate,
+                    level: 20.0 * a1.min(a2).log10(),
+                    depth: 2.0 * a1.min(a2) / (a1 + a2),
+                })
+            })
+        })
+        .collect();
+    beats.sort_by(|a, b| b.level.total_cmp(&a.level));
+    beats
+}
+
+/// How the muffler treats one pump harmonic.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HarmonicLevel {
+    /// Harmonic number, 1 for the fundamental.
+    pub order: usize,
+    /// Frequency in Hz.
+    pub frequency: f64,
+    /// Transmission loss in dB at exactly this frequency.
+    pub transmission_loss: f64,
+    /// Level the pump drives the harmonic at, in dB re its fundamental.
+    pub source_level: f64,
+    /// Level through the muffler, source level plus 20·log₁₀|H(f)|, in
+    /// dB re the unmuffled fundamental.
+    pub output_level: f64,
+    /// Predicted SPL in dB re 20 µPa at `SimParams::listener`, if set.
+    pub spl: Option<f64>,
+}
+
+/// TL and output level at the pump fundamental and its multiples.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HarmonicReport {
+    /// One row per harmonic, fundamental first.
+    pub harmonics: Vec<HarmonicLevel>,
+}
+
+impl HarmonicReport {
+    /// The harmonic passing the loudest through the muffler.
+    pub fn loudest(&self) -> Option<&HarmonicLevel> {
+        self.harmonics.iter().max_by(|a, b| a.output_level.total_cmp(&b.output_level))
+    }
+}
+
+impl std::fmt::Display for HarmonicReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:>3} {:>9} {:>8} {:>8} {:>8} {:>8}", "n", "f (Hz)", "TL (dB)", "src dB", "out dB", "SPL")?;
+        for row in &self.harmonics {
+            let spl = row.spl.map_or("-".to_string(), |level| format!("{level:.1}"));
+            writeln!(
+                f,
+                "{:>3} {:>9.1} {:>8.1} {:>8.1} {:>8.1} {:>8}",
+                row.order, row.frequency, row.transmission_loss, row.source_level, row.output_level, spl
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Evaluate the design of `params` at the pump fundamental
+/// (`PumpSource::fundamental_frequency`) and its first `count` multiples
+/// in all, solved at exactly those frequencies rather than read off the
+/// FFT grid.
+pub fn harmonic_report(params: &SimParams, count: usize) -> Result<HarmonicReport, String> {
+    let fundamental = params.pump_source(44100.0).fundamental_frequency();
+    // Half a harmonic of headroom keeps the last one from rounding away
+    let harmonics = params.pump_harmonics((count as f64 + 0.5) * fundamental);
+    let frequencies: Vec<f64> = harmonics.iter().map(|&(frequency, _)| frequency).collect();
+    let sweep = compute_at(params, &frequencies)?;
+
+    let (c, rho) = params.medium();
+    let strength = params.source_strength().unwrap_or(0.0);
+    let muffler = params
+        .listener
+        .map(|_| Muffler::from_params(params).map_err(|e| e.to_string()))
+        .transpose()?;
+    let harmonics = harmonics
+        .iter()
+        .enumerate()
+        .map(|(i, &(frequency, amplitude))| {
+            let spl = params.listener.zip(muffler.as_ref()).map(|(listener, muffler)| {
+                let per_unit = pressure_per_volume_velocity(muffler, 2.0 * PI * frequency, listener.distance, c, rho);
+                spl(strength * amplitude * per_unit)
+            });
+            let source_level = 20.0 * amplitude.log10();
+            HarmonicLevel {
+                order: i + 1,
+                frequency,
+                transmission_loss: sweep.transmission_loss[i],
+                source_level,
+                output_level: source_level + 20.0 * sweep.transfer_function[i].norm().log10(),
+                spl,
+            }
+        })
+        .collect();
+    Ok(HarmonicReport { harmonics })
+}
+
+/// Points [`rating`] solves across its band.
+const RATING_POINTS: usize = 512;
+
+/// Transmission loss in dB averaged over `f_min..f_max` Hz by energy: the
+/// transmitted power fraction 10^(−TL/10) is integrated over frequency
+/// (trapezoidal, interpolating at the band edges) and turned back into
+/// decibels. Deep notches therefore count for less than their dB values
+/// suggest and leaks for more, as they do for the sound that gets out.
+/// Works on any ascending grid; `None` unless it spans the band.
+pub fn average_transmission_loss(
+    frequencies: &[f64],
+    transmission_loss: &[f64],
+    f_min: f64,
+    f_max: f64,
+) -> Option<f64> {
+    let (first, last) = (*frequencies.first()?, *frequencies.last()?);
+    if !(f_min < f_max && first <= f_min && f_max <= last) {
+        return None;
+    }
+    let fraction = |tl: f64| 10f64.powf(-tl / 10.0);
+    let at = |f: f64| {
+        let i = frequencies.partition_point(|&g| g < f).clamp(1, frequencies.len() - 1);
+        let t = (f - frequencies[i - 1]) / (frequencies[i] - frequencies[i - 1]);
+        fraction(transmission_loss[i - 1]) + (fraction(transmission_loss[i]) - fraction(transmission_loss[i - 1])) * t
+    };
+    let mut points = vec![(f_min, at(f_min))];
+    points.extend(
+        frequencies
+            .iter()
+            .zip(transmission_loss)
+            .filter(|(&f, _)| f > f_min && f < f_max)
+            .map(|(&f, &tl)| (f, fraction(tl))),
+    );
+    points.push((f_max, at(f_max)));
+    let integral: f64 = points.windows(2).map(|pair| 0.5 * (pair[0].1 + pair[1].1) * (pair[1].0 - pair[0].0)).sum();
+    Some(-10.0 * (integral / (f_max - f_min)).log10())
+}
+
+/// Single-number rating of the design of `params`: its transmission loss
+/// averaged by energy over `f_min..f_max` Hz (see
+/// [`average_transmission_loss`]), solved at 512 points off the FFT grid.
+/// Higher is better; a scalar objective for optimisation and batch
+/// comparisons.
+pub fn rating(params: &SimParams, f_min: f64, f_max: f64) -> Result<f64, String> {
+    let (frequencies, tl) = compute_tl_range(params, f_min, f_max, RATING_POINTS, SweepSpacing::Linear)?;
+    average_transmission_loss(&frequencies, &tl, f_min, f_max)
+        .ok_or_else(|| format!("cannot average over {f_min}..{f_max} Hz"))
+}
+
+/// Nominal IEC 61260 third-octave centre frequencies of one decade,
+/// divided by its lowest.
+const NOMINAL_MANTISSAS: [f64; 10] = [1.0, 1.25, 1.6, 2.0, 2.5, 3.15, 4.0, 5.0, 6.3, 8.0];
+
+/// A third-octave band on the base-10 grid of IEC 61260.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Band {
+    /// Nominal centre frequency in Hz, as labelled in lab reports.
+    pub nominal: f64,
+    /// Exact centre frequency 1000·10^(n/10) Hz.
+    pub centre: f64,
+    /// Lower band edge in Hz, centre·10^(−1/20).
+    pub lower: f64,
+    /// Upper band edge in Hz, centre·10^(1/20).
+    pub upper: f64,
+}
+
+impl Band {
+    /// Band `n`, counted from the 1 kHz band.
+    pub fn new(n: i32) -> Self {
+        let centre = 1000.0 * 10f64.powf(n as f64 / 10.0);
+        let nominal = NOMINAL_MANTISSAS[n.rem_euclid(10) as usize] * 10f64.powi(3 + n.div_euclid(10));
+        let half = 10f64.powf(1.0 / 20.0);
+        Self {
+            nominal,
+            centre,
+            lower: centre / half,
+            upper: centre * half,
+        }
+    }
+
+    /// Whether `frequency` Hz falls in the band, lower edge included.
+    pub fn contains(&self, frequency: f64) -> bool {
+        frequency >= self.lower && frequency < self.upper
+    }
+}
+
+/// The third-octave bands whose centres lie between `f_min` and `f_max`
+/// Hz, ascending; 25 Hz to 20 kHz covers the usual 30 bands.
+pub fn third_octave_bands(f_min: f64, f_max: f64) -> Vec<Band> {
+    if !(f_min > 0.0 && f_max >= f_min) {
+        return Vec::new();
+    }
+    // Band numbers rounded outwards, then trimmed to the exact centres
+    let first = (10.0 * (f_min / 1000.0).log10()).floor() as i32;
+    let last = (10.0 * (f_max / 1000.0).log10()).ceil() as i32;
+    (first..=last)
+        .map(Band::new)
+        .filter(|band| band.centre >= f_min * (1.0 - 1e-9) && band.centre <= f_max * (1.0 + 1e-9))
+        .collect()
+}
+
+/// Transmission loss in dB of each band from the finely sampled
+/// `transmission_loss` at `frequencies`: the mean transmitted power
+/// fraction of the samples in the band, −10·log₁₀(⟨10^(−TL/10)⟩). `None`
+/// for bands no sample falls in, as the low bands do on a coarse FFT
+/// grid; a `numerics.log_sweep` of a few points per band resolves them.
+pub fn band_transmission_loss(frequencies: &[f64], transmission_loss: &[f64], bands: &[Band]) -> Vec<Option<f64>> {
+    bands
+        .iter()
+        .map(|band| {
+            let fractions: Vec<f64> = frequencies
+                .iter()
+                .zip(transmission_loss)
+                .filter(|(&f, _)| band.contains(f))
+                .map(|(_, &tl)| 10f64.powf(-tl / 10.0))
+                .collect();
+            (!fractions.is_empty()).then(|| -10.0 * (fractions.iter().sum::<f64>() / fractions.len() as f64).log10())
+        })
+        .collect()
+}
+
+/// Level in dB of each band: the power sum of the `tones` (frequency in
+/// Hz, level in dB) falling in it, e.g. the pump harmonics of
+/// [`crate::radiation::RadiatedSound`]. `-inf` for bands without tones.
+pub fn band_levels(tones: &[(f64, f64)], bands: &[Band]) -> Vec<f64> {
+    bands
+        .iter()
+        .map(|band| {
+            let inside: Vec<(f64, f64)> = tones.iter().copied().filter(|&(f, _)| band.contains(f)).collect();
+            overall_level(&inside, Weighting::Z)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compute;
+    use crate::pump::SecondPump;
+
+    #[test]
+    fn test_weighting_curves() {
+        // IEC 61672-1 table values at the exact band frequencies, ±0.1 dB
+        let table = [
+            (10f64.powf(1.5), -39.4, -3.0),
+            (100.0, -19.1, -0.3),
+            (1000.0, 0.0, 0.0),
+            (4000.0, 1.0, -0.8),
+            (10000.0, -2.5, -4.4),
+        ];
+        for (f, a, c) in table {
+            assert!((Weighting::A.gain_db(f) - a).abs() < 0.1, "A at {f} Hz: {}", Weighting::A.gain_db(f));
+            assert!((Weighting::C.gain_db(f) - c).abs() < 0.1, "C at {f} Hz: {}", Weighting::C.gain_db(f));
+            assert_eq!(Weighting::Z.gain_db(f), 0.0);
+        }
+
+        // Two equal tones add 3 dB; a 100 Hz hum barely registers in dB(A)
+        let levels = OverallLevels::of(&[(1000.0, 60.0), (1000.0, 60.0)]);
+        assert!((levels.z - 63.01).abs() < 0.01);
+        let hum = OverallLevels::of(&[(100.0, 60.0)]);
+        assert!((hum.a - 40.9).abs() < 0.1 && (hum.c - 59.7).abs() < 0.1, "{hum:?}");
+        assert_eq!(overall_level(&[], Weighting::A), f64::NEG_INFINITY);
+    }
+
+    #[test]
+    fn test_third_octave_bands() {
+        let bands = third_octave_bands(25.0, 20_000.0);
+        assert_eq!(bands.len(), 30);
+        let nominal: Vec<f64> = bands.iter().map(|band| band.nominal).collect();
+        assert_eq!(&nominal[..8], &[25.0, 31.5, 40.0, 50.0, 63.0, 80.0, 100.0, 125.0]);
+        assert_eq!(nominal[16], 1000.0);
+        assert_eq!(nominal[29], 20_000.0);
+        // Contiguous, a third of an octave wide
+        for pair in bands.windows(2) {
+            assert!((pair[0].upper - pair[1].lower).abs() < 1e-9);
+        }
+        assert!(((bands[16].upper / bands[16].lower).log2() - 1.0 / 3.0).abs() < 0.01);
+
+        // Averages power fractions, not decibels: 0 and 20 dB average to
+        // 2.96 dB, not 10 dB
+        let frequencies = [800.0, 1000.0, 1100.0, 1600.0];
+        let tl = [5.0, 0.0, 20.0, 12.0];
+        let band_tl = band_transmission_loss(&frequencies, &tl, &bands[15..=18]);
+        let expected = -10.0 * ((1.0 + 0.01) / 2.0f64).log10();
+        assert!((band_tl[1].unwrap() - expected).abs() < 1e-9, "{band_tl:?}");
+        assert_eq!(band_tl[2], None);
+        assert_eq!((band_tl[0], band_tl[3]), (Some(5.0), Some(12.0)));
+
+        // Tones in one band add up in power
+        let levels = band_levels(&[(1000.0, 60.0), (1050.0, 60.0), (2000.0, 50.0)], &bands[16..=19]);
+        assert!((levels[0] - 63.01).abs() < 0.01);
+        assert_eq!(levels[1], f64::NEG_INFINITY);
+        assert_eq!(levels[3], 50.0);
+    }
+
+    #[test]
+    fn test_harmonic_report() {
+        let params = SimParams {
+            listener: Some(crate::radiation::Listener::default()),
+            ..SimParams::default()
+        };
+        let report = harmonic_report(&params, 8).unwrap();
+        assert_eq!(report.harmonics.len(), 8);
+        let (c, rho) = params.medium();
+        let muffler = Muffler::from_params(&params).unwrap();
+        for (i, row) in report.harmonics.iter().enumerate() {
+            assert_eq!(row.order, i + 1);
+            assert!((row.frequency - 150.0 * (i + 1) as f64).abs() < 1e-9);
+            let omega = 2.0 * PI * row.frequency;
+            assert!((row.transmission_loss - muffler.transmission_loss(omega, c, rho)).abs() < 1e-9);
+            assert!(row.spl.is_some());
+        }
+        assert_eq!(report.harmonics[0].source_level, 0.0);
+        assert!(report.loudest().is_some());
+        // Header plus one line per harmonic
+        assert_eq!(report.to_string().lines().count(), 9);
+
+        let quiet = harmonic_report(&SimParams::default(), 2).unwrap();
+        assert!(quiet.harmonics.iter().all(|row| row.spl.is_none()));
+        assert!(quiet.to_string().lines().nth(1).unwrap().ends_with('-'));
+    }
+
+    #[test]
+    fn test_average_transmission_loss() {
+        // Half the band at 0 dB and half blocked: half the power gets
+        // through, 3 dB, where the mean of the decibels would say 50
+        let frequencies = [100.0, 499.0, 501.0, 900.0];
+        let tl = [0.0, 0.0, 100.0, 100.0];
+        let average = average_transmission_loss(&frequencies, &tl, 100.0, 900.0).unwrap();
+        assert!((average - 3.01).abs() < 0.01, "{average}");
+        // A flat curve averages to itself over any sub-band
+        let flat = average_transmission_loss(&frequencies, &[12.0; 4], 150.0, 700.0).unwrap();
+        assert!((flat - 12.0).abs() < 1e-9);
+        assert_eq!(average_transmission_loss(&frequencies, &tl, 50.0, 900.0), None);
+
+        let params = SimParams::default();
+        let score = rating(&params, 100.0, 1000.0).unwrap();
+        let (frequencies, tl) = compute_tl_range(&params, 100.0, 1000.0, 4000, SweepSpacing::Linear).unwrap();
+        let fine = average_transmission_loss(&frequencies, &tl, 100.0, 1000.0).unwrap();
+        assert!((score - fine).abs() < 0.01, "{score} vs {fine}");
+        // A longer chamber attenuates more in this band
+        let longer = SimParams {
+            chamber_length: 0.16,
+            ..params.clone()
+        };
+        assert!(rating(&longer, 100.0, 1000.0).unwrap() > score);
+        assert!(rating(&params, 1000.0, 100.0).is_err());
+    }
+
+    #[test]
+    fn test_pump_tones_follow_the_muffler() {
+        let params = SimParams::default();
+        let result = compute(&params).unwrap();
+        let tones = pump_tones(&params, &result);
+        // 3 valves at 3000 rpm: 150 Hz harmonics below 22.05 kHz
+        assert_eq!(tones.len(), 147);
+        let (f, level) = tones[0];
+        assert!((f - 150.0).abs() < 1e-9);
+        assert!((level - result.response().magnitude_db_at(150.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_two_pumps_beat() {
+        // 3 valves at 3000 and 2960 rpm: 150 and 148 Hz fundamentals beat
+        // at 2 Hz, their second harmonics at 4 Hz
+        let params = SimParams {
+            second_pump: Some(SecondPump {
+                rpm: 2960.0,
+                level: 0.5,
+                ..SecondPump::default()
+            }),
+            ..SimParams::default()
+        };
+        let result = compute(&params).unwrap();
+        let found = beats(&params, &result, &result, 5.0);
+        let fundamental = found.iter().find(|beat| beat.first_order == 1).unwrap();
+        assert_eq!(fundamental.second_order, 1);
+        assert!((fundamental.rate - 2.0).abs() < 1e-9);
+        assert!((fundamental.depth - 2.0 * 0.5 / 1.5).abs() < 0.05, "{}", fundamental.depth);
+        assert!(found.iter().any(|beat| beat.first_order == 2 && (beat.rate - 4.0).abs() < 1e-9));
+        assert!(found.windows(2).all(|pair| pair[0].level >= pair[1].level));
+        assert!(found.iter().all(|beat| beat.rate <= 5.0));
+
+        // A muffler of its own changes how loud the second pump's tones are
+        let quieter = compute(&SimParams {
+            chamber_length: 0.2,
+            ..params.clone()
+        })
+        .unwrap();
+        let separate = beats(&params, &result, &quieter, 5.0);
+        let level = |beats: &[Beat]| beats.iter().find(|beat| beat.first_order == 1).unwrap().level;
+        assert!(level(&separate) != level(&found));
+        assert!(beats(&SimParams::default(), &result, &result, 5.0).is_empty());
+    }
+}
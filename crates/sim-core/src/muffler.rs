@@ -1,18 +1,82 @@
-use crate::elements::StraightDuct;
+use crate::air_line::AirLine;
+use crate::constants::{area_from_diameter, diameter_from_area};
+use crate::elements::{
+    flanged_radiation_impedance, unflanged_radiation_impedance, AreaChange, Baffle, CrossSection, OffsetChamber,
+    Orifice, ParallelBranches, PerforatedPlate, QuarterWaveResonator, StraightDuct,
+};
+use crate::numerics::{Numerics, Termination};
 use crate::transfer_matrix::TransferMatrix;
 use crate::{AcousticElement, SimParams};
+use num_complex::Complex64;
+
+/// Most fixed-point steps of the high-amplitude correction.
+const AMPLITUDE_ITERATIONS: usize = 100;
+
+/// Sound field at one point along the muffler's axis.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AxialPoint {
+    /// Distance in metres from the inlet along the main path.
+    pub position: f64,
+    /// Index of the element the point lies in (0 = source side); the
+    /// chain's element count for the outlet end.
+    pub element: usize,
+    /// Complex acoustic pressure in Pa.
+    pub pressure: Complex64,
+    /// Complex volume velocity in m³/s, positive downstream.
+    pub volume_velocity: Complex64,
+}
+
+/// Why a muffler chain could not be built.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BuildError {
+    /// The chain has no elements.
+    Empty,
+    /// The element at `index` (0 = source side) is inconsistent.
+    Element {
+        index: usize,
+        label: String,
+        reason: String,
+    },
+    /// The source or load impedance is not a positive finite value, so
+    /// the chain is not terminated.
+    Termination(String),
+}
+
+impl std::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BuildError::Empty => write!(f, "muffler chain has no elements"),
+            BuildError::Element {
+                index,
+                label,
+                reason,
+            } => write!(f, "element {index} ({label}): {reason}"),
+            BuildError::Termination(reason) => write!(f, "termination: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
 
 /// An ordered chain of acoustic elements forming a muffler.
 pub struct Muffler {
     elements: Vec<Box<dyn AcousticElement>>,
     /// Characteristic impedance of the inlet (source side).
     pub z_source: f64,
-    /// Characteristic impedance of the outlet (load side).
+    /// Characteristic impedance of the outlet (load side). A radiating
+    /// [`Termination`] loads the chain with its own impedance instead, see
+    /// [`Muffler::load_impedance`].
     pub z_load: f64,
+    /// Load at the downstream end.
+    termination: Termination,
+    /// Element index and length of each section of the chamber body, for
+    /// shell breakout. Empty for custom chains.
+    chamber: Vec<(usize, f64)>,
 }
 
 impl Muffler {
-    /// Create a muffler from a custom list of elements and impedances.
+    /// Create a muffler from a custom list of elements and impedances
+    /// without checking them; see [`Muffler::try_new`].
     pub fn new(
         elements: Vec<Box<dyn AcousticElement>>,
         z_source: f64,
@@ -22,24 +86,430 @@ impl Muffler {
             elements,
             z_source,
             z_load,
+            termination: Termination::Anechoic,
+            chamber: Vec::new(),
+        }
+    }
+
+    /// Create a muffler from a custom list of elements and impedances,
+    /// rejecting inconsistent chains.
+    pub fn try_new(
+        elements: Vec<Box<dyn AcousticElement>>,
+        z_source: f64,
+        z_load: f64,
+    ) -> Result<Self, BuildError> {
+        let muffler = Self::new(elements, z_source, z_load);
+        muffler.validate()?;
+        Ok(muffler)
+    }
+
+    /// Check every element and both terminations.
+    pub fn validate(&self) -> Result<(), BuildError> {
+        if self.elements.is_empty() {
+            return Err(BuildError::Empty);
         }
+        for (index, elem) in self.elements.iter().enumerate() {
+            elem.validate().map_err(|reason| BuildError::Element {
+                index,
+                label: elem.label(),
+                reason,
+            })?;
+        }
+        for (side, z) in [("source", self.z_source), ("load", self.z_load)] {
+            if !(z > 0.0 && z.is_finite()) {
+                return Err(BuildError::Termination(format!(
+                    "{side} impedance must be > 0, got {z}"
+                )));
+            }
+        }
+        match self.termination {
+            Termination::Unflanged { diameter } | Termination::Flanged { diameter } => match diameter {
+                Some(diameter) if diameter > 0.0 && diameter.is_finite() => {}
+                Some(diameter) => {
+                    return Err(BuildError::Termination(format!(
+                        "outlet diameter must be > 0, got {diameter}"
+                    )))
+                }
+                None => return Err(BuildError::Termination("open end has no diameter".to_string())),
+            },
+            Termination::AirStone(None) => {
+                return Err(BuildError::Termination("air stone has no stone".to_string()))
+            }
+            Termination::Anechoic | Termination::AirStone(Some(_)) => {}
+        }
+        Ok(())
     }
 
     /// Build a single expansion chamber muffler from simulation parameters.
-    pub fn from_params(params: &SimParams) -> Self {
-        let inlet = StraightDuct::new(params.inlet_length, params.inlet_diameter);
-        let chamber = StraightDuct::new(params.chamber_length, params.chamber_diameter);
-        let outlet = StraightDuct::new(params.outlet_length, params.outlet_diameter);
+    ///
+    /// Inlet/outlet pipes that protrude into the chamber continue as pipe
+    /// for the extension length, and the closed annulus between the pipe
+    /// and the chamber wall becomes a quarter-wave side branch at the
+    /// junction. An optional orifice plate sits at the end of the inlet
+    /// pipe, and an optional baffle or perforated plate splits the chamber
+    /// into two halves.
+    /// Off-axis ports switch the chamber to the modal [`OffsetChamber`],
+    /// which has no wall losses. `params.calibration` adds its end
+    /// correction at the junctions, its loss factor to every duct and
+    /// scales the free chamber length.
+    /// The area changes into and out of the chamber are explicit junction
+    /// elements carrying the flow losses and, if enabled, the end
+    /// corrections. Wall losses, end corrections and the load follow
+    /// `params.numerics`. With the pump's pulse pressure known (see
+    /// [`SimParams::pulse_pressure`]), the orifice
+    /// carries the jet resistance of the velocity it sees at the pump's
+    /// pulse frequency.
+    ///
+    /// Fails with the offending element's index if the chain is
+    /// inconsistent, e.g. a zero-length chamber or an extended pipe (the
+    /// annulus neck) as wide as the chamber.
+    pub fn from_params(params: &SimParams) -> Result<Self, BuildError> {
+        let numerics = &params.numerics;
+        let (c, rho) = params.medium();
+        let mean_flow = params.mean_flow();
+        let section_duct = |length: f64, section: CrossSection| {
+            let duct = StraightDuct::with_section(length, section)
+                .with_roughness(params.wall_roughness / section.hydraulic_diameter())
+                .with_losses(numerics.wall_losses)
+                .with_loss_factor(params.calibration.loss_factor)
+                .with_mean_flow(mean_flow, c);
+            match params.duct_wall {
+                Some(wall) => duct.with_wall(wall),
+                None => duct,
+            }
+        };
+        let duct = |length: f64, diameter: f64| section_duct(length, CrossSection::Circular { diameter });
+        // Junctions, annuli and the baffle only see the chamber's area
+        let chamber_section = params.chamber_section();
+        let chamber_diameter = chamber_section.equivalent_diameter();
+
+        // A measured bore meets the pipes with its own end diameters
+        let (inlet_chamber, outlet_chamber) = params.chamber_profile.as_ref().map_or(
+            (chamber_diameter, chamber_diameter),
+            |profile| (profile.inlet_diameter(), profile.outlet_diameter()),
+        );
+        let expansion = AreaChange::expansion(params.inlet_diameter, inlet_chamber)
+            .with_mean_flow(mean_flow);
+        let contraction = AreaChange::contraction(params.outlet_diameter, outlet_chamber)
+            .with_mean_flow(mean_flow);
+        // The modal chamber model supplies its own end corrections
+        let (expansion, contraction) = if numerics.end_corrections && params.port_offsets.is_none() {
+            (expansion, contraction)
+        } else {
+            (expansion.with_end_correction(0.0), contraction.with_end_correction(0.0))
+        };
+        let extra = params.calibration.end_correction;
+        let expansion_correction = expansion.end_correction + extra;
+        let contraction_correction = contraction.end_correction + extra;
+        let expansion = expansion.with_end_correction(expansion_correction);
+        let contraction = contraction.with_end_correction(contraction_correction);
+
+        let inlet = duct(params.inlet_length + params.inlet_extension, params.inlet_diameter);
+        let free_length = (params.chamber_length - params.inlet_extension - params.outlet_extension)
+            * params.calibration.length_scale;
+        let baffle = params.baffle.as_ref().map(|baffle| Baffle {
+            chamber_diameter,
+            ..baffle.clone()
+        }
+        .with_end_corrections(numerics.end_corrections));
+        let plate = params.perforated_baffle.as_ref().map(|plate| PerforatedPlate {
+            chamber_diameter,
+            ..plate.clone()
+        });
+        // The divider and the length of chamber it takes up
+        let divider: Option<(Box<dyn AcousticElement>, f64)> = match (baffle, plate) {
+            (Some(baffle), _) => {
+                let length = baffle.tube_length;
+                Some((Box::new(baffle), length))
+            }
+            (None, Some(plate)) => {
+                let length = plate.thickness;
+                Some((Box::new(plate), length))
+            }
+            (None, None) => None,
+        };
+        let chamber_sections = match &divider {
+            Some((_, length)) => vec![(free_length - length) / 2.0; 2],
+            None => vec![free_length],
+        };
+        let outlet = duct(params.outlet_length + params.outlet_extension, params.outlet_diameter);
 
-        let (c, rho) = crate::constants::speed_of_sound_and_density(params.temperature);
         let z_source = inlet.impedance(c, rho);
         let z_load = outlet.impedance(c, rho);
 
-        Self {
-            elements: vec![Box::new(inlet), Box::new(chamber), Box::new(outlet)],
-            z_source,
-            z_load,
+        let mut elements: Vec<Box<dyn AcousticElement>> = vec![Box::new(inlet)];
+        let annulus = |index: usize,
+                       extension: f64,
+                       pipe_diameter: f64|
+         -> Result<Box<dyn AcousticElement>, BuildError> {
+            let area = chamber_section.area() - area_from_diameter(pipe_diameter);
+            let branch = QuarterWaveResonator::new(extension, diameter_from_area(area.max(0.0)));
+            if area <= 0.0 {
+                return Err(BuildError::Element {
+                    index,
+                    label: branch.label(),
+                    reason: format!(
+                        "pipe diameter {pipe_diameter} must be smaller than the chamber diameter {chamber_diameter}"
+                    ),
+                });
+            }
+            Ok(Box::new(branch))
+        };
+
+        if let Some(orifice) = &params.orifice {
+            if orifice.hole_diameter >= params.inlet_diameter {
+                return Err(BuildError::Element {
+                    index: elements.len(),
+                    label: orifice.label(),
+                    reason: format!(
+                        "hole diameter {} must be smaller than the inlet diameter {}",
+                        orifice.hole_diameter, params.inlet_diameter
+                    ),
+                });
+            }
+            elements.push(Box::new(orifice.clone()));
+        }
+        let orifice_index = params.orifice.as_ref().map(|_| elements.len() - 1);
+        if params.inlet_extension > 0.0 {
+            elements.push(annulus(elements.len(), params.inlet_extension, params.inlet_diameter)?);
+        }
+        elements.push(Box::new(expansion));
+        let mut chamber = vec![(elements.len(), chamber_sections[0])];
+        match (&params.chamber_profile, &params.port_offsets) {
+            (Some(profile), _) => {
+                chamber[0].1 = profile.length();
+                elements.push(Box::new(profile.clone()));
+            }
+            (None, Some(offsets)) => elements.push(Box::new(
+                OffsetChamber::new(
+                    chamber_sections[0],
+                    params.chamber_diameter,
+                    params.inlet_diameter,
+                    params.outlet_diameter,
+                )
+                .with_offsets(offsets.inlet, offsets.outlet, offsets.angle),
+            )),
+            (None, None) => elements.push(Box::new(section_duct(chamber_sections[0], chamber_section))),
         }
+        if let Some((divider, _)) = divider {
+            elements.push(divider);
+            chamber.push((elements.len(), chamber_sections[1]));
+            elements.push(Box::new(section_duct(chamber_sections[1], chamber_section)));
+        }
+        elements.push(Box::new(contraction));
+        if params.outlet_extension > 0.0 {
+            elements.push(annulus(elements.len(), params.outlet_extension, params.outlet_diameter)?);
+        }
+        match &params.side_branch {
+            // Halfway along the outlet pipe, clear of the chamber volume
+            Some(branch) => {
+                let half = duct(outlet.length / 2.0, params.outlet_diameter);
+                elements.push(Box::new(half.clone()));
+                elements.push(Box::new(branch.clone()));
+                elements.push(Box::new(half));
+            }
+            None => elements.push(Box::new(outlet)),
+        }
+
+        let muffler = Self {
+            chamber,
+            ..Self::new(elements, z_source, z_load)
+        };
+        let muffler = match &params.air_line {
+            Some(line) => muffler.with_air_line(line, numerics, c, rho),
+            None => muffler.with_termination(&params.outlet_termination.resolved(None, params.outlet_diameter)),
+        };
+        let muffler = match (params.pulse_pressure(), &params.orifice, orifice_index) {
+            (Some(pressure), Some(orifice), Some(index)) => {
+                let omega = 2.0 * std::f64::consts::PI * params.num_valves as f64 * params.pump_rpm() / 60.0;
+                muffler.with_jet_amplitude(index, orifice, pressure, omega, c, rho)
+            }
+            _ => muffler,
+        };
+        muffler.validate()?;
+        Ok(muffler)
+    }
+
+    /// Extend the chain with the supply hose and terminate it as selected
+    /// by `numerics.termination`: on the air stone, anechoically on the
+    /// hose's own characteristic impedance, or open at the hose's end.
+    pub fn with_air_line(mut self, line: &AirLine, numerics: &Numerics, c: f64, rho: f64) -> Self {
+        let hose = line.hose(numerics.wall_losses);
+        self.z_load = hose.impedance(c, rho);
+        self.elements.push(Box::new(hose));
+        self.with_termination(&numerics.termination.resolved(Some(&line.stone), line.hose_diameter))
+    }
+
+    /// Set the orifice at `index` to the velocity amplitude it carries when
+    /// an incident wave of peak `pressure` Pa at `omega` drives the chain.
+    ///
+    /// The jet resistance throttles the flow that sets it, so the amplitude
+    /// is found by under-relaxed fixed-point iteration.
+    fn with_jet_amplitude(
+        mut self,
+        index: usize,
+        orifice: &Orifice,
+        pressure: f64,
+        omega: f64,
+        c: f64,
+        rho: f64,
+    ) -> Self {
+        let mut velocity = 0.0;
+        for _ in 0..AMPLITUDE_ITERATIONS {
+            self.elements[index] = Box::new(orifice.clone().with_velocity_amplitude(velocity));
+            let states = self.node_states(omega, c, rho);
+            let incident = (states[0].0 + self.z_source * states[0].1) / 2.0;
+            let carried = pressure * states[index].1.norm() / incident.norm() / orifice.area();
+            let next = 0.5 * (velocity + carried);
+            let converged = (next - velocity).abs() <= 1e-6 * next;
+            velocity = next;
+            if converged {
+                break;
+            }
+        }
+        self.elements[index] = Box::new(orifice.clone().with_velocity_amplitude(velocity));
+        self
+    }
+
+    /// Append a split into two parallel branches that recombine, e.g. a
+    /// T-junction feeding a Herschel–Quincke tube.
+    pub fn with_parallel(
+        mut self,
+        first: Vec<Box<dyn AcousticElement>>,
+        second: Vec<Box<dyn AcousticElement>>,
+    ) -> Self {
+        self.elements.push(Box::new(ParallelBranches::new(first, second)));
+        self
+    }
+
+    /// Replace the load impedance. `Anechoic` keeps the current load, which
+    /// chains built by `from_params` set to the outlet pipe impedance. The
+    /// open ends keep it too, as the scale of their normalised radiation
+    /// impedance, so it must be that of a pipe of their diameter.
+    pub fn with_termination(mut self, termination: &Termination) -> Self {
+        if let Termination::AirStone(Some(stone)) = termination {
+            self.z_load = stone.impedance();
+        }
+        self.termination = termination.clone();
+        self
+    }
+
+    /// The load at the downstream end.
+    pub fn termination(&self) -> &Termination {
+        &self.termination
+    }
+
+    /// Impedance loading the downstream end at angular frequency `omega`:
+    /// `z_load`, or the radiation impedance of an open end.
+    pub fn load_impedance(&self, omega: f64, c: f64) -> Complex64 {
+        let k = omega / c;
+        match self.termination {
+            Termination::Unflanged { diameter: Some(diameter) } => {
+                self.z_load * unflanged_radiation_impedance(k, diameter)
+            }
+            Termination::Flanged { diameter: Some(diameter) } => self.z_load * flanged_radiation_impedance(k, diameter),
+            _ => Complex64::new(self.z_load, 0.0),
+        }
+    }
+
+    /// The elements in chain order (source side first).
+    pub fn elements(&self) -> &[Box<dyn AcousticElement>] {
+        &self.elements
+    }
+
+    /// Element index and length of each chamber body section, in chain
+    /// order; empty unless built by [`Muffler::from_params`].
+    pub fn chamber_sections(&self) -> &[(usize, f64)] {
+        &self.chamber
+    }
+
+    /// Pressure and volume velocity at every node of the chain (node `i`
+    /// is the upstream end of element `i`, the last node the load), for a
+    /// unit pressure on the load.
+    pub fn node_states(&self, omega: f64, c: f64, rho: f64) -> Vec<(Complex64, Complex64)> {
+        let mut state = (Complex64::new(1.0, 0.0), 1.0 / self.load_impedance(omega, c));
+        let mut states = vec![state];
+        for elem in self.elements.iter().rev() {
+            let t = elem.transfer_matrix(omega, c, rho);
+            state = (t.a * state.0 + t.b * state.1, t.c * state.0 + t.d * state.1);
+            states.push(state);
+        }
+        states.reverse();
+        states
+    }
+
+    /// Pressure and volume velocity along the chain at angular frequency
+    /// `omega`, driven by a unit incident pressure wave at the inlet (the
+    /// normalisation of [`Muffler::pressure_transfer`]). Every element
+    /// interface is included; elements that can be cut (straight ducts)
+    /// are also sampled at `samples` evenly spaced points inside. Lumped
+    /// elements appear as two points at one position, either side of the
+    /// jump they cause.
+    ///
+    /// Standing-wave maxima of |p| mark where a branch resonator or
+    /// absorbent acts best.
+    pub fn axial_distribution(&self, omega: f64, c: f64, rho: f64, samples: usize) -> Vec<AxialPoint> {
+        let states = self.node_states(omega, c, rho);
+        let incident = (states[0].0 + self.z_source * states[0].1) / 2.0;
+        let mut points = Vec::new();
+        let mut position = 0.0;
+        for (index, elem) in self.elements.iter().enumerate() {
+            let length = elem.length();
+            let (pressure, volume_velocity) = states[index];
+            points.push(AxialPoint {
+                position,
+                element: index,
+                pressure: pressure / incident,
+                volume_velocity: volume_velocity / incident,
+            });
+            let (p_out, u_out) = states[index + 1];
+            for i in 1..=samples {
+                let x = length * i as f64 / (samples + 1) as f64;
+                let Some(t) = elem.tail_matrix(length - x, omega, c, rho) else {
+                    break;
+                };
+                points.push(AxialPoint {
+                    position: position + x,
+                    element: index,
+                    pressure: (t.a * p_out + t.b * u_out) / incident,
+                    volume_velocity: (t.c * p_out + t.d * u_out) / incident,
+                });
+            }
+            position += length;
+        }
+        let (pressure, volume_velocity) = states[self.elements.len()];
+        points.push(AxialPoint {
+            position,
+            element: self.elements.len(),
+            pressure: pressure / incident,
+            volume_velocity: volume_velocity / incident,
+        });
+        points
+    }
+
+    /// Static pressure drop in Pa across the whole chain for a steady
+    /// volume flow `flow_rate` (m³/s). Excludes the load.
+    pub fn back_pressure(&self, flow_rate: f64, rho: f64) -> f64 {
+        self.elements
+            .iter()
+            .map(|elem| elem.pressure_drop(flow_rate, rho))
+            .sum()
+    }
+
+    /// Lowest cut-on frequency of a higher-order mode in any element, i.e.
+    /// the upper limit of the plane-wave model.
+    pub fn cutoff_frequency(&self, c: f64) -> Option<f64> {
+        self.element_cutoffs(c).into_iter().map(|(_, cutoff)| cutoff).reduce(f64::min)
+    }
+
+    /// Index and first cut-on frequency of every element that knows its
+    /// own, in chain order.
+    pub fn element_cutoffs(&self, c: f64) -> Vec<(usize, f64)> {
+        self.elements
+            .iter()
+            .enumerate()
+            .filter_map(|(index, elem)| elem.cutoff_frequency(c).map(|cutoff| (index, cutoff)))
+            .collect()
     }
 
     /// Compute the total transfer matrix at angular frequency `omega`.
@@ -52,10 +522,16 @@ impl Muffler {
         total
     }
 
-    /// Transmission loss in dB at angular frequency `omega`.
+    /// Transmission loss in dB at angular frequency `omega`; into an open
+    /// end, of the power it radiates.
     pub fn transmission_loss(&self, omega: f64, c: f64, rho: f64) -> f64 {
         let t = self.total_transfer_matrix(omega, c, rho);
-        t.transmission_loss(self.z_source, self.z_load)
+        match self.termination {
+            Termination::Unflanged { .. } | Termination::Flanged { .. } => {
+                t.transmission_loss_into(self.z_source, self.load_impedance(omega, c), self.z_load)
+            }
+            Termination::Anechoic | Termination::AirStone(_) => t.transmission_loss(self.z_source, self.z_load),
+        }
     }
 
     /// Complex pressure transfer function at angular frequency `omega`.
@@ -66,6 +542,134 @@ impl Muffler {
         rho: f64,
     ) -> num_complex::Complex64 {
         let t = self.total_transfer_matrix(omega, c, rho);
-        t.pressure_transfer(self.z_source, self.z_load)
+        t.pressure_transfer_into(self.z_source, self.load_impedance(omega, c))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_errors_name_the_element() {
+        assert!(Muffler::from_params(&SimParams::default()).is_ok());
+
+        // Extended inlet as wide as the chamber: the annulus (element 1) has no area
+        let params = SimParams {
+            inlet_diameter: 40e-3,
+            inlet_extension: 10e-3,
+            ..SimParams::default()
+        };
+        match Muffler::from_params(&params) {
+            Err(BuildError::Element { index, .. }) => assert_eq!(index, 1),
+            other => panic!("expected an element error, got {:?}", other.err()),
+        }
+
+        // Zero-length element in a custom chain
+        let chain: Vec<Box<dyn AcousticElement>> = vec![
+            Box::new(StraightDuct::new(0.03, 0.006)),
+            Box::new(StraightDuct::new(0.0, 0.04)),
+        ];
+        let err = Muffler::try_new(chain, 1.0, 1.0).err().expect("zero length rejected");
+        assert!(err.to_string().starts_with("element 1 "), "{err}");
+
+        // Unterminated chain
+        let chain: Vec<Box<dyn AcousticElement>> = vec![Box::new(StraightDuct::new(0.03, 0.006))];
+        assert!(matches!(
+            Muffler::try_new(chain, 1.0, 0.0),
+            Err(BuildError::Termination(_))
+        ));
+    }
+
+    #[test]
+    fn test_termination_takes_data_from_the_chain() {
+        let line = AirLine::default();
+        let mut params = SimParams {
+            air_line: Some(line.clone()),
+            ..SimParams::default()
+        };
+        let muffler = Muffler::from_params(&params).unwrap();
+        assert_eq!(*muffler.termination(), Termination::AirStone(Some(line.stone.clone())));
+        params.numerics.termination = Termination::Unflanged { diameter: None };
+        let muffler = Muffler::from_params(&params).unwrap();
+        assert_eq!(*muffler.termination(), Termination::Unflanged { diameter: Some(line.hose_diameter) });
+
+        // Without an air line there is no stone to take
+        let params = SimParams {
+            outlet_termination: Termination::AirStone(None),
+            ..SimParams::default()
+        };
+        assert!(matches!(Muffler::from_params(&params), Err(BuildError::Termination(_))));
+    }
+
+    #[test]
+    fn test_orifice_jet_amplitude() {
+        let (c, rho) = SimParams::default().medium();
+        let orifice = Orifice::new(2e-3, 1e-3);
+        let driven = |source_pressure| SimParams {
+            orifice: Some(orifice.clone()),
+            source_pressure,
+            ..SimParams::default()
+        };
+        let omega = 2.0 * std::f64::consts::PI * 3.0 * 3000.0 / 60.0;
+        // Velocity amplitude the orifice (element 1) was iterated to,
+        // recovered from its resistance
+        let velocity = |muffler: &Muffler| {
+            let resistance = muffler.elements()[1].transfer_matrix(omega, c, rho).b.re;
+            let per_velocity = orifice.clone().with_velocity_amplitude(1.0).jet_resistance(rho);
+            (resistance - orifice.acoustic_resistance(omega, rho)) / per_velocity
+        };
+
+        let linear = Muffler::from_params(&driven(None)).unwrap();
+        assert!(velocity(&linear).abs() < 1e-9);
+
+        let loud = Muffler::from_params(&driven(Some(2000.0))).unwrap();
+        let settled = velocity(&loud);
+        // Self-consistent: the chain carries the velocity the jet was sized for
+        let states = loud.node_states(omega, c, rho);
+        let incident = (states[0].0 + loud.z_source * states[0].1) / 2.0;
+        let carried = 2000.0 * states[1].1.norm() / incident.norm() / orifice.area();
+        assert!((carried / settled - 1.0).abs() < 1e-4, "{carried} vs {settled}");
+
+        // The jet throttles its own flow: ten times the pressure gives less
+        // than ten times the velocity
+        let louder = velocity(&Muffler::from_params(&driven(Some(20_000.0))).unwrap());
+        assert!(louder > settled && louder < 10.0 * settled * 0.9, "{louder} vs {settled}");
+        assert!(crate::compute(&driven(Some(-1.0))).is_err());
+    }
+
+    #[test]
+    fn test_axial_distribution() {
+        let (c, rho) = SimParams::default().medium();
+        let omega = 2.0 * std::f64::consts::PI * 1000.0;
+        let k = omega / c;
+
+        // Anechoic pipe: a pure travelling wave e^{-jkx}
+        let pipe = StraightDuct::new(0.2, 0.01);
+        let z = pipe.impedance(c, rho);
+        let muffler = Muffler::new(vec![Box::new(pipe)], z, z);
+        let points = muffler.axial_distribution(omega, c, rho, 9);
+        assert_eq!(points.len(), 11);
+        for point in &points {
+            let expected = Complex64::new(0.0, -k * point.position).exp();
+            assert!((point.pressure - expected).norm() < 1e-9, "{point:?}");
+            assert!((point.volume_velocity * z - expected).norm() < 1e-9);
+        }
+
+        // The muffler's own points end on H(f) at the outlet and cover the
+        // whole chain; the open outlet puts a pressure minimum near the end
+        let params = SimParams {
+            outlet_termination: Termination::Unflanged { diameter: None },
+            ..SimParams::default()
+        };
+        let muffler = Muffler::from_params(&params).unwrap();
+        let points = muffler.axial_distribution(omega, c, rho, 4);
+        let last = points.last().unwrap();
+        assert!((last.pressure - muffler.pressure_transfer(omega, c, rho)).norm() < 1e-9);
+        let total = params.inlet_length + params.chamber_length + params.outlet_length;
+        assert!((last.position - total).abs() < 1e-12);
+        assert!(points.windows(2).all(|pair| pair[1].position >= pair[0].position));
+        let peak = points.iter().map(|point| point.pressure.norm()).fold(0.0, f64::max);
+        assert!(last.pressure.norm() < 0.2 * peak);
     }
 }
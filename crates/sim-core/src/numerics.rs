@@ -0,0 +1,312 @@
+use crate::air_line::AirStone;
+
+/// How duct walls dissipate acoustic energy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WallLossModel {
+    /// Ideal rigid, lossless walls (classic plane-wave TMM).
+    Lossless,
+    /// Kirchhoff viscothermal boundary-layer losses, scaled by wall roughness.
+    Viscothermal,
+    /// Low-reduced-frequency (Zwikker–Kosten) viscothermal losses: the
+    /// full Bessel-function solution across the bore, which stays valid in
+    /// narrow pipes where the boundary layers fill the section.
+    LowReducedFrequency,
+}
+
+/// Load presented at the downstream end of the chain.
+///
+/// Data left `None` is taken from the chain when the muffler is built:
+/// the stone of the air line, or the diameter of the pipe that ends open.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Termination {
+    /// Reflection-free termination matching the last pipe, as on a test
+    /// bench with an anechoic outlet.
+    Anechoic,
+    /// A submerged air stone; its impedance depends on the water depth.
+    AirStone(Option<AirStone>),
+    /// An open pipe end of `diameter` radiating into free space
+    /// (Levine–Schwinger).
+    Unflanged { diameter: Option<f64> },
+    /// An open pipe end of `diameter` set in an infinite flange, radiating
+    /// as a baffled piston.
+    Flanged { diameter: Option<f64> },
+}
+
+impl Termination {
+    /// The ways an outlet pipe can end, for selectors.
+    pub const OUTLET: [Termination; 3] = [
+        Termination::Anechoic,
+        Termination::Unflanged { diameter: None },
+        Termination::Flanged { diameter: None },
+    ];
+
+    /// The loads an air line can end on, for selectors.
+    pub const AIR_LINE: [Termination; 2] = [Termination::AirStone(None), Termination::Anechoic];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Termination::Anechoic => "Anechoic",
+            Termination::AirStone(_) => "Air stone",
+            Termination::Unflanged { .. } => "Open pipe (unflanged)",
+            Termination::Flanged { .. } => "Open pipe (flanged)",
+        }
+    }
+
+    /// This termination with the data left `None` filled in from `stone`
+    /// and `diameter`.
+    pub fn resolved(&self, stone: Option<&AirStone>, diameter: f64) -> Termination {
+        match self {
+            Termination::Anechoic => Termination::Anechoic,
+            Termination::AirStone(own) => Termination::AirStone(own.clone().or_else(|| stone.cloned())),
+            Termination::Unflanged { diameter: own } => Termination::Unflanged {
+                diameter: own.or(Some(diameter)),
+            },
+            Termination::Flanged { diameter: own } => Termination::Flanged {
+                diameter: own.or(Some(diameter)),
+            },
+        }
+    }
+}
+
+/// How the muffler's response is computed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Plane-wave transfer matrices, frequency by frequency: linear, fast
+    /// and covering every element.
+    FrequencyDomain,
+    /// Nonlinear quasi-1D flow marched in time, for high pulsation levels
+    /// and strong mean flow. Covers straight pipes and plain or profiled
+    /// chambers only, with rigid lossless walls and an anechoic outlet.
+    TimeDomain,
+}
+
+/// Window applied to the impulse response after it is cut to length.
+/// Each keeps the first sample intact, so the direct path survives.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IrWindow {
+    /// Plain truncation: keeps the whole tail but clicks if it has not
+    /// died away by the end.
+    Rectangular,
+    /// Flat, then a cosine taper over the last `taper` fraction (0–1) of
+    /// the samples.
+    Tukey { taper: f64 },
+    /// The falling half of a Hann window over the whole length.
+    Hann,
+    /// Exponential decay reaching `end_db` dB of attenuation at the last
+    /// sample, like added damping.
+    Exponential { end_db: f64 },
+}
+
+impl IrWindow {
+    /// The window of each kind, with default settings, for selectors.
+    pub const ALL: [IrWindow; 4] = [
+        IrWindow::Tukey { taper: 0.25 },
+        IrWindow::Hann,
+        IrWindow::Rectangular,
+        IrWindow::Exponential { end_db: 60.0 },
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            IrWindow::Rectangular => "Rectangular",
+            IrWindow::Tukey { .. } => "Tukey",
+            IrWindow::Hann => "Hann",
+            IrWindow::Exponential { .. } => "Exponential",
+        }
+    }
+
+    /// Weight of sample `i` of `length`.
+    pub fn weight(self, i: usize, length: usize) -> f64 {
+        let t = i as f64 / length.max(1) as f64;
+        match self {
+            IrWindow::Rectangular => 1.0,
+            IrWindow::Tukey { taper } => {
+                let start = 1.0 - taper;
+                if t < start || taper <= 0.0 {
+                    1.0
+                } else {
+                    0.5 * (1.0 + (std::f64::consts::PI * (t - start) / taper).cos())
+                }
+            }
+            IrWindow::Hann => 0.5 * (1.0 + (std::f64::consts::PI * t).cos()),
+            IrWindow::Exponential { end_db } => 10f64.powf(-end_db * t / 20.0),
+        }
+    }
+
+    /// Check the window's setting.
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            IrWindow::Tukey { taper } if !(0.0..=1.0).contains(&taper) => {
+                Err(format!("Tukey taper must be in [0, 1], got {taper}"))
+            }
+            IrWindow::Exponential { end_db } if !(end_db > 0.0 && end_db.is_finite()) => {
+                Err(format!("exponential end_db must be > 0, got {end_db}"))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Logarithmically spaced sweep computed next to the FFT grid, with a
+/// fixed number of points per octave so the low end is resolved as
+/// finely as the high end.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LogSweep {
+    /// Lowest frequency in Hz.
+    pub f_min: f64,
+    /// Highest frequency in Hz; the last point falls on or just below it.
+    pub f_max: f64,
+    /// Points in each octave.
+    pub points_per_octave: u32,
+}
+
+impl Default for LogSweep {
+    fn default() -> Self {
+        Self {
+            f_min: 20.0,
+            f_max: 20_000.0,
+            points_per_octave: 24,
+        }
+    }
+}
+
+impl LogSweep {
+    /// Check that the range is positive and ascending and has points.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.f_min > 0.0 && self.f_min < self.f_max && self.f_max.is_finite()) {
+            return Err(format!(
+                "frequency range must satisfy 0 < f_min < f_max, got {}..{}",
+                self.f_min, self.f_max
+            ));
+        }
+        if self.points_per_octave == 0 {
+            return Err("points_per_octave must be >= 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// The sweep frequencies f_min·2^(i/N) up to `f_max`, ascending.
+    pub fn frequencies(&self) -> Vec<f64> {
+        let per_octave = self.points_per_octave as f64;
+        // Guard the last point against rounding when f_max is on the grid
+        let steps = ((self.f_max / self.f_min).log2() * per_octave + 1e-9).floor() as usize;
+        (0..=steps)
+            .map(|i| self.f_min * (i as f64 / per_octave).exp2())
+            .collect()
+    }
+}
+
+/// Adaptive refinement of the FFT-grid sweep: extra points are solved
+/// where the transmission loss changes quickly, so narrow notches show up
+/// at coarse FFT sizes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Refinement {
+    /// Largest TL change in dB allowed between neighbouring points.
+    pub max_step_db: f64,
+    /// How many times a bin may be halved, so points end up no closer
+    /// than the bin width / 2^max_depth.
+    pub max_depth: u32,
+}
+
+impl Default for Refinement {
+    fn default() -> Self {
+        Self {
+            max_step_db: 1.0,
+            max_depth: 6,
+        }
+    }
+}
+
+impl Refinement {
+    /// Deepest supported subdivision.
+    pub const MAX_DEPTH: u32 = 12;
+
+    /// Check the step is positive and the depth supported.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.max_step_db > 0.0 && self.max_step_db.is_finite()) {
+            return Err(format!("max_step_db must be > 0, got {}", self.max_step_db));
+        }
+        if self.max_depth > Self::MAX_DEPTH {
+            return Err(format!("max_depth must be <= {}, got {}", Self::MAX_DEPTH, self.max_depth));
+        }
+        Ok(())
+    }
+}
+
+/// Numerical and modelling choices for a simulation run.
+///
+/// Kept separate from the geometry so the same muffler can be re-run with
+/// different accuracy/speed trade-offs, and stored alongside it so a
+/// result can be reproduced exactly.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Numerics {
+    /// FFT size of the sweep; sets both the frequency resolution
+    /// (`sample_rate / fft_size`) and the impulse response length
+    /// (`fft_size / 2`). Must be a power of two.
+    pub fft_size: usize,
+    /// Duct wall loss model.
+    pub wall_losses: WallLossModel,
+    /// Add the evanescent-mode end correction at each pipe/chamber area
+    /// change.
+    pub end_corrections: bool,
+    /// Load at the end of an attached air line.
+    pub termination: Termination,
+    /// Solver producing the transmission loss and H(f).
+    pub engine: Engine,
+    /// Also sweep these log-spaced frequencies, returned in
+    /// `SimResult::log_sweep`.
+    pub log_sweep: Option<LogSweep>,
+    /// Refine the FFT-grid sweep around rapid TL changes, returned in
+    /// `SimResult::refined`. Frequency-domain engine only.
+    pub refinement: Option<Refinement>,
+    /// Window applied to the impulse response.
+    pub ir_window: IrWindow,
+    /// Impulse response length in samples, at most `fft_size`; `None`
+    /// keeps `fft_size / 2`. Longer responses keep the tails of long,
+    /// lightly damped chambers.
+    pub ir_length: Option<usize>,
+}
+
+impl Default for Numerics {
+    fn default() -> Self {
+        Self {
+            fft_size: 4096,
+            wall_losses: WallLossModel::Lossless,
+            end_corrections: false,
+            termination: Termination::AirStone(None),
+            engine: Engine::FrequencyDomain,
+            log_sweep: None,
+            refinement: None,
+            ir_window: IrWindow::Tukey { taper: 0.25 },
+            ir_length: None,
+        }
+    }
+}
+
+impl Numerics {
+    /// Smallest and largest supported FFT sizes.
+    pub const FFT_SIZE_RANGE: (usize, usize) = (256, 65536);
+
+    /// Largest FFT size of the time-domain engine, whose march runs one
+    /// step per sample and would stall the caller for seconds beyond it.
+    pub const MAX_TIME_DOMAIN_FFT_SIZE: usize = 8192;
+
+    /// Smallest and largest FFT sizes the chosen engine supports.
+    pub fn fft_size_range(&self) -> (usize, usize) {
+        let (min, max) = Self::FFT_SIZE_RANGE;
+        match self.engine {
+            Engine::FrequencyDomain => (min, max),
+            Engine::TimeDomain => (min, Self::MAX_TIME_DOMAIN_FFT_SIZE),
+        }
+    }
+
+    /// Length in samples of the impulse response.
+    pub fn impulse_response_length(&self) -> usize {
+        self.ir_length.unwrap_or(self.fft_size / 2)
+    }
+
+    /// Frequency resolution in Hz at the given sample rate.
+    pub fn bin_width(&self, sample_rate: f64) -> f64 {
+        sample_rate / self.fft_size as f64
+    }
+}
@@ -0,0 +1,71 @@
+//! The types and entry points meant for code outside this crate: the UI,
+//! bindings and other tools. `use sim_core::prelude::*;` brings in
+//! everything needed to describe a design, run it and listen to it.
+//! Anything not reachable from here or the public modules may change
+//! without notice.
+
+pub use crate::{
+    compute, compute_at, compute_tl_range, AcousticElement, Connection, ModeWarning, PortOffsets, SimParams, SimResult,
+};
+pub use crate::{Provenance, Sweep, SweepSpacing, TransferFunction, TransferMatrix};
+
+pub use crate::air_line::{AirLine, AirStone};
+pub use crate::audio::{
+    AudioBackend, AudioPipeline, CpalBackend, Notch, NullBackend, OutputLevels, PipelineStats, SourcePosition,
+};
+pub use crate::breakout::{Shell, ShellMaterial};
+pub use crate::calibration::{Calibration, CalibrationFit};
+pub use crate::compare::Comparison;
+pub use crate::decay::Decay;
+pub use crate::elements::{
+    AbsorptiveBranch, AreaChange, AreaChangeDirection, Baffle, Bend, CompliantHose, ConcentricTubeResonator,
+    ConicalDuct, CrossSection, CustomElement, LinedDuct, LumpedRlc, MatrixSource, Monolith, OffsetChamber,
+    Orifice, ParallelBranches, PerforatedPlate, PerforatedTube, PorousModel, ProfiledDuct, QuarterWaveResonator,
+    StraightDuct,
+};
+pub use crate::flow_noise::{FlowNoise, FlowNoiseSource};
+pub use crate::gas::Gas;
+pub use crate::loudness::LoudnessMetric;
+pub use crate::measurement::SweepMeasurement;
+pub use crate::metrics::{Band, Beat, HarmonicLevel, HarmonicReport, OverallLevels, Weighting};
+pub use crate::muffler::{AxialPoint, BuildError, Muffler};
+pub use crate::numerics::{Engine, IrWindow, LogSweep, Numerics, Refinement, Termination, WallLossModel};
+pub use crate::pump::{
+    Harmonic, HarmonicSeries, MotorNoise, PistonCrank, PumpDrive, ReedValve, SampleSource, SecondPump, SpeedWander,
+    StrokeTiming, ValveLift, ValveVariation,
+};
+pub use crate::radiation::{Listener, RadiatedSound};
+pub use crate::recording::Recording;
+pub use crate::resonances::{FeatureKind, Resonance};
+pub use crate::rpm_detection::RpmEstimate;
+pub use crate::rpm_profile::RpmProfile;
+pub use crate::sensitivity::{sensitivities, Parameter, Sensitivity};
+pub use crate::test_signal::TestSignal;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prelude_runs_a_design() {
+        let params = SimParams {
+            numerics: Numerics {
+                wall_losses: WallLossModel::Lossless,
+                ..Numerics::default()
+            },
+            ..SimParams::default()
+        };
+        let result: SimResult = compute(&params).unwrap();
+        let muffler = Muffler::from_params(&params).unwrap();
+        let (c, rho) = params.medium();
+        let omega = 2.0 * std::f64::consts::PI * 500.0;
+        let matrix: TransferMatrix = muffler.total_transfer_matrix(omega, c, rho);
+        let tl = matrix.transmission_loss(muffler.z_source, muffler.z_load);
+        assert!((tl - muffler.transmission_loss(omega, c, rho)).abs() < 1e-12);
+
+        let (_, tl) = compute_tl_range(&params, 100.0, 1000.0, 10, SweepSpacing::Linear).unwrap();
+        assert_eq!(tl.len(), 10);
+        let response: TransferFunction = result.response();
+        assert!(response.at(500.0).norm().is_finite());
+    }
+}
@@ -0,0 +1,77 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::gas::Gas;
+use crate::numerics::Numerics;
+
+/// Where a result came from: enough to tell whether an archived result can
+/// be reproduced by the current physics engine.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Provenance {
+    /// `sim-core` crate version.
+    pub crate_version: &'static str,
+    /// Git commit the engine was built from, or "unknown" outside a checkout.
+    pub git_hash: &'static str,
+    /// Stable hash of the numerics settings (see [`numerics_hash`]).
+    pub numerics_hash: u64,
+    /// Description of the gas model and the medium properties used.
+    pub gas_model: String,
+    /// Seconds since the Unix epoch when the result was computed.
+    pub timestamp: u64,
+}
+
+impl Provenance {
+    /// Stamp a result computed now with the given settings and medium.
+    pub fn new(numerics: &Numerics, gas: Gas, c: f64, rho: f64) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION"),
+            git_hash: env!("SIM_CORE_GIT_HASH"),
+            numerics_hash: numerics_hash(numerics),
+            gas_model: format!("ideal-gas {} (c = {c:.2} m/s, ρ = {rho:.4} kg/m³)", gas.describe()),
+            timestamp,
+        }
+    }
+
+    /// One-line summary for export headers.
+    pub fn summary(&self) -> String {
+        format!(
+            "sim-core {} ({}) numerics={:016x} gas={} t={}",
+            self.crate_version, self.git_hash, self.numerics_hash, self.gas_model, self.timestamp
+        )
+    }
+}
+
+/// FNV-1a hash of the numerics settings. Unlike `DefaultHasher` this is
+/// stable across Rust releases, so archived hashes stay comparable.
+pub fn numerics_hash(numerics: &Numerics) -> u64 {
+    format!("{numerics:?}")
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_numerics_hash_tracks_settings() {
+        let default = Numerics::default();
+        assert_eq!(numerics_hash(&default), numerics_hash(&Numerics::default()));
+
+        let finer = Numerics {
+            fft_size: 8192,
+            ..Numerics::default()
+        };
+        assert_ne!(numerics_hash(&default), numerics_hash(&finer));
+
+        let stamp = Provenance::new(&default, Gas::Air, 343.2, 1.204);
+        assert_eq!(stamp.crate_version, env!("CARGO_PKG_VERSION"));
+        assert!(stamp.timestamp > 0);
+        assert!(stamp.summary().contains(&format!("{:016x}", stamp.numerics_hash)));
+    }
+}
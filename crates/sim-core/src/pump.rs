@@ -1,9 +1,690 @@
+use num_complex::Complex64;
+use realfft::RealFftPlanner;
 use std::f64::consts::PI;
 
+use crate::rng;
+
+/// Points per motor revolution of the band-limited wavetable.
+const TABLE_SIZE: usize = 8192;
+
+/// Steps per octave the speed is rounded to for the reed valve response,
+/// the only part of the waveform that depends on speed, so a gliding
+/// speed does not recompute it every block.
+const REED_SPEED_STEPS: f64 = 48.0;
+
+/// Settings the wavetable's spectrum was computed for; it is recomputed
+/// when they change.
+#[derive(Debug, Clone, PartialEq)]
+struct TableKey {
+    /// Rounded speed of the reed valve response, if modelled.
+    reed_speed: Option<i64>,
+    num_valves: u32,
+    duty_cycle: f64,
+    timing: StrokeTiming,
+    stroke: f64,
+    piston: Option<PistonCrank>,
+    reed: Option<ReedValve>,
+    lift: Option<ValveLift>,
+    series: Option<HarmonicSeries>,
+    sample_rate: f64,
+    amplitudes: Vec<f64>,
+    phase_offsets: Vec<f64>,
+}
+
+/// Largest RMS revolution-length jitter, as a fraction of a revolution.
+pub const MAX_JITTER: f64 = 0.2;
+
+/// Stroke timing of each valve beyond its pressure-stroke duty cycle.
+///
+/// Each valve runs a pressure stroke (a positive half-sine lasting
+/// `duty_cycle` of a revolution) followed by a suction stroke (a negative
+/// half-sine lasting `suction_fraction`), then dwells. Unequal stroke
+/// shapes break the half-wave symmetry of the waveform, shifting energy
+/// between even and odd harmonics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrokeTiming {
+    /// Fraction of a revolution spent on the suction stroke.
+    pub suction_fraction: f64,
+    /// Suction stroke amplitude relative to the pressure stroke, as seen at
+    /// the outlet (0 when the outlet valve seals perfectly).
+    pub suction_level: f64,
+    /// How much overlapping pressure strokes share the outlet valve, 0–1.
+    /// At 0 overlapping pulses add independently; at 1 they are averaged,
+    /// as when the valve, not the diaphragms, limits the flow.
+    pub overlap_sharing: f64,
+}
+
+impl Default for StrokeTiming {
+    fn default() -> Self {
+        Self {
+            suction_fraction: 0.5,
+            suction_level: 0.0,
+            overlap_sharing: 0.0,
+        }
+    }
+}
+
+/// Differences between nominally identical valves, and the wobble of a
+/// real motor.
+///
+/// A perfectly symmetric pump only produces multiples of the valve pulse
+/// frequency; a weak or mistimed valve adds every multiple of the motor
+/// revolution, and jitter smears the tones into sidebands.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValveVariation {
+    /// Output of each valve relative to nominal, first valve first; valves
+    /// past the end of the list are nominal.
+    pub amplitudes: Vec<f64>,
+    /// How late each valve fires as a fraction of a revolution (negative
+    /// for early); valves past the end of the list are on time.
+    pub phase_offsets: Vec<f64>,
+    /// RMS variation of the length of each revolution as a fraction of a
+    /// revolution, drawn afresh every revolution.
+    pub jitter: f64,
+}
+
+impl ValveVariation {
+    /// Output of valve `valve` relative to nominal.
+    pub fn amplitude(&self, valve: usize) -> f64 {
+        self.amplitudes.get(valve).copied().unwrap_or(1.0)
+    }
+
+    /// Timing offset of valve `valve` as a fraction of a revolution.
+    pub fn phase_offset(&self, valve: usize) -> f64 {
+        self.phase_offsets.get(valve).copied().unwrap_or(0.0)
+    }
+
+    /// Check the amplitudes are non-negative, the offsets within half a
+    /// revolution and the jitter within [0, `MAX_JITTER`].
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(a) = self.amplitudes.iter().find(|a| **a < 0.0 || !a.is_finite()) {
+            return Err(format!("valve amplitudes must be >= 0, got {a}"));
+        }
+        if let Some(o) = self.phase_offsets.iter().find(|o| o.is_nan() || o.abs() >= 0.5) {
+            return Err(format!("valve phase offsets must be in (-0.5, 0.5), got {o}"));
+        }
+        if !(0.0..=MAX_JITTER).contains(&self.jitter) {
+            return Err(format!("jitter must be in [0, {MAX_JITTER}], got {}", self.jitter));
+        }
+        Ok(())
+    }
+}
+
+/// Crank geometry of a reciprocating piston compressor.
+///
+/// Each cylinder delivers the piston's swept flow A·ẋ while the piston
+/// rises and draws in while it falls. The finite connecting rod makes the
+/// piston move faster near top dead centre than near bottom, adding even
+/// harmonics a pure sine would lack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PistonCrank {
+    /// Cylinder bore in metres.
+    pub bore: f64,
+    /// Piston stroke in metres, twice the crank radius.
+    pub stroke: f64,
+    /// Connecting rod length over crank radius, above 1 (typically 3–5).
+    pub rod_ratio: f64,
+}
+
+impl Default for PistonCrank {
+    fn default() -> Self {
+        Self {
+            bore: 20e-3,
+            stroke: 10e-3,
+            rod_ratio: 4.0,
+        }
+    }
+}
+
+impl PistonCrank {
+    /// Check the bore and stroke are positive and the rod longer than the
+    /// crank radius.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.bore > 0.0 && self.bore.is_finite()) {
+            return Err(format!("piston bore must be > 0, got {}", self.bore));
+        }
+        if !(self.stroke > 0.0 && self.stroke.is_finite()) {
+            return Err(format!("piston stroke must be > 0, got {}", self.stroke));
+        }
+        if !(self.rod_ratio > 1.0 && self.rod_ratio.is_finite()) {
+            return Err(format!("piston rod_ratio must be > 1, got {}", self.rod_ratio));
+        }
+        Ok(())
+    }
+
+    /// Volume in m³ one cylinder sweeps per revolution.
+    pub fn swept_volume(&self) -> f64 {
+        PI / 4.0 * self.bore * self.bore * self.stroke
+    }
+
+    /// Mean delivered flow in m³/s of `cylinders` cylinders at `rpm`.
+    pub fn mean_flow(&self, rpm: f64, cylinders: u32) -> f64 {
+        self.swept_volume() * cylinders as f64 * rpm / 60.0
+    }
+
+    /// Piston velocity over crank radius × angular speed at crank angle
+    /// `theta` from top dead centre: sin θ·(1 + cos θ/√(λ² − sin² θ)).
+    /// Positive while the piston delivers.
+    pub fn velocity(&self, theta: f64) -> f64 {
+        let (sin, cos) = theta.sin_cos();
+        sin * (1.0 + cos / (self.rod_ratio * self.rod_ratio - sin * sin).sqrt())
+    }
+}
+
+/// Open area of each outlet valve against crank angle, as pump makers
+/// specify their machines.
+///
+/// Each valve delivers in proportion to its open area, so the profile
+/// replaces the half-sine pressure stroke; `duty_cycle` and the suction
+/// stroke then no longer apply. Valves fire in turn, each `1/num_valves`
+/// of a revolution after the last.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ValveLift {
+    /// Crank angle in degrees within [0, 360) and open area in any unit,
+    /// in angle order. The area is interpolated linearly between points,
+    /// wrapping from the last point to the first, and scaled to its peak.
+    pub points: Vec<(f64, f64)>,
+}
+
+impl ValveLift {
+    /// Parse a `crank angle, open area` table, angles in degrees, in the
+    /// format of [`parse_tl_table`](crate::calibration::parse_tl_table).
+    pub fn from_table(text: &str) -> Result<Self, String> {
+        Ok(Self {
+            points: crate::parse_pairs(text, "crank angle, open area")?,
+        })
+    }
+
+    /// Check there are at least two points, angles rise within [0, 360)
+    /// and areas are non-negative with a peak above zero.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.points.len() < 2 {
+            return Err("a valve lift profile needs at least two points".to_string());
+        }
+        if let Some(&(angle, _)) = self.points.iter().find(|&&(angle, _)| !(0.0..360.0).contains(&angle)) {
+            return Err(format!("valve lift crank angles must be in [0, 360), got {angle}"));
+        }
+        for pair in self.points.windows(2) {
+            if pair[1].0 <= pair[0].0 {
+                return Err(format!("valve lift crank angles must rise, got {} after {}", pair[1].0, pair[0].0));
+            }
+        }
+        if let Some(&(angle, area)) = self.points.iter().find(|&&(_, area)| !(area >= 0.0 && area.is_finite())) {
+            return Err(format!("valve open area must be >= 0, got {area} at {angle}°"));
+        }
+        if self.peak() <= 0.0 {
+            return Err("a valve lift profile needs an open area above zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Largest open area in the table.
+    fn peak(&self) -> f64 {
+        self.points.iter().map(|&(_, area)| area).fold(0.0, f64::max)
+    }
+
+    /// Open area at crank angle `theta` radians relative to the peak.
+    pub fn opening(&self, theta: f64) -> f64 {
+        let angle = theta.to_degrees().rem_euclid(360.0);
+        let next = self.points.partition_point(|&(a, _)| a <= angle);
+        // Wrap around from the last point to the first
+        let (a0, area0) = match next.checked_sub(1) {
+            Some(i) => self.points[i],
+            None => self.points.last().map(|&(a, area)| (a - 360.0, area)).unwrap_or_default(),
+        };
+        let (a1, area1) = match self.points.get(next) {
+            Some(&point) => point,
+            None => self.points.first().map(|&(a, area)| (a + 360.0, area)).unwrap_or_default(),
+        };
+        let area = if a1 > a0 { area0 + (area1 - area0) * (angle - a0) / (a1 - a0) } else { area0 };
+        area / self.peak()
+    }
+}
+
+/// One harmonic of the pump fundamental in a [`HarmonicSeries`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Harmonic {
+    /// Peak amplitude in the units of the pump waveform.
+    pub amplitude: f64,
+    /// Phase in radians of the harmonic's cosine at the start of a
+    /// revolution.
+    pub phase: f64,
+}
+
+/// A pump waveform given directly by its harmonics, as from an order
+/// analysis of a real pump. It replaces the modelled strokes, so the
+/// timing, variation, piston and reed settings no longer shape it.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HarmonicSeries {
+    /// Harmonics of the fundamental, first harmonic first.
+    pub harmonics: Vec<Harmonic>,
+}
+
+impl HarmonicSeries {
+    /// The first `count` harmonics of `pump`'s modelled waveform, as a
+    /// starting point for editing.
+    pub fn of(pump: &PumpSource, count: usize) -> Self {
+        Self {
+            harmonics: pump.harmonics(count),
+        }
+    }
+
+    /// Parse an order spectrum with one `harmonic, amplitude[, phase]`
+    /// row per line, phase in degrees (0 if left out), in the format of
+    /// [`parse_tl_table`](crate::calibration::parse_tl_table). Harmonics
+    /// not listed are silent.
+    pub fn from_table(text: &str) -> Result<Self, String> {
+        let rows = crate::parse_rows(text, "harmonic, amplitude[, phase]", 2)?;
+        let mut harmonics = Vec::new();
+        for row in rows {
+            let order = row[0];
+            if !(order >= 1.0 && order.fract() == 0.0 && order <= MAX_HARMONIC as f64) {
+                return Err(format!("harmonic must be a whole number in [1, {MAX_HARMONIC}], got {order}"));
+            }
+            let index = order as usize - 1;
+            if harmonics.len() <= index {
+                harmonics.resize(index + 1, Harmonic { amplitude: 0.0, phase: 0.0 });
+            }
+            harmonics[index] = Harmonic {
+                amplitude: row[1],
+                phase: row.get(2).copied().unwrap_or(0.0).to_radians(),
+            };
+        }
+        Ok(Self { harmonics })
+    }
+
+    /// Check there is at least one audible harmonic and every amplitude
+    /// and phase is finite, amplitudes non-negative.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.harmonics.len() > MAX_HARMONIC {
+            return Err(format!(
+                "a harmonic series holds at most {MAX_HARMONIC} harmonics, got {}",
+                self.harmonics.len()
+            ));
+        }
+        for (i, harmonic) in self.harmonics.iter().enumerate() {
+            if !(harmonic.amplitude >= 0.0 && harmonic.amplitude.is_finite() && harmonic.phase.is_finite()) {
+                return Err(format!("harmonic {} must have a finite amplitude >= 0 and phase", i + 1));
+            }
+        }
+        if !self.harmonics.iter().any(|harmonic| harmonic.amplitude > 0.0) {
+            return Err("a harmonic series needs a harmonic above zero amplitude".to_string());
+        }
+        Ok(())
+    }
+
+    /// Waveform at `phase` of the series' fundamental.
+    fn waveform(&self, phase: f64) -> f64 {
+        self.harmonics
+            .iter()
+            .enumerate()
+            .map(|(i, harmonic)| harmonic.amplitude * ((i + 1) as f64 * phase + harmonic.phase).cos())
+            .sum()
+    }
+}
+
+/// Noise of the motor driving the pump, on top of its pulsation.
+///
+/// AC motors hum at twice the mains frequency, brushless drives whine at
+/// their PWM frequency, and a worn bearing modulates the pulsation at its
+/// defect frequency, adding sidebands around every harmonic. Levels are
+/// peak amplitudes relative to the pump waveform; 0 leaves a component
+/// out. Part of the hum and whine reaches the listener through the
+/// motor's housing rather than through the muffler.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MotorNoise {
+    /// Mains frequency in Hz; the hum is at twice it.
+    pub mains_frequency: f64,
+    /// Level of the mains hum.
+    pub hum: f64,
+    /// PWM switching frequency of the drive in Hz.
+    pub pwm_frequency: f64,
+    /// Level of the PWM whine.
+    pub pwm_whine: f64,
+    /// Bearing defect frequency as a multiple of the shaft speed.
+    pub bearing_order: f64,
+    /// Depth of the bearing's modulation of the pulsation, 0–1.
+    pub bearing: f64,
+    /// Fraction of the hum and whine bypassing the muffler, 0–1.
+    pub bypass: f64,
+}
+
+impl Default for MotorNoise {
+    fn default() -> Self {
+        Self {
+            mains_frequency: 50.0,
+            hum: 0.02,
+            pwm_frequency: 16_000.0,
+            pwm_whine: 0.005,
+            bearing_order: 3.2,
+            bearing: 0.05,
+            bypass: 0.3,
+        }
+    }
+}
+
+impl MotorNoise {
+    /// Check the frequencies are positive and the levels within range.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("mains_frequency", self.mains_frequency),
+            ("pwm_frequency", self.pwm_frequency),
+            ("bearing_order", self.bearing_order),
+        ] {
+            if !(value > 0.0 && value.is_finite()) {
+                return Err(format!("motor_noise.{name} must be > 0, got {value}"));
+            }
+        }
+        for (name, value) in [("hum", self.hum), ("pwm_whine", self.pwm_whine)] {
+            if !(value >= 0.0 && value.is_finite()) {
+                return Err(format!("motor_noise.{name} must be >= 0, got {value}"));
+            }
+        }
+        for (name, value) in [("bearing", self.bearing), ("bypass", self.bypass)] {
+            if !(0.0..=1.0).contains(&value) {
+                return Err(format!("motor_noise.{name} must be in [0, 1], got {value}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Largest RMS speed wander, as a fraction of the nominal speed.
+pub const MAX_WANDER: f64 = 0.2;
+
+/// Slow random hunting of the pump speed (wow and flutter).
+///
+/// A loaded motor never holds its speed exactly: it drifts by a few
+/// percent, and the slowly beating harmonics are much of what makes a
+/// real pump sound alive. The speed follows low-pass filtered noise whose
+/// spectrum rolls off above `bandwidth`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpeedWander {
+    /// RMS deviation of the speed as a fraction of nominal.
+    pub depth: f64,
+    /// Corner frequency in Hz of the deviation's spectrum.
+    pub bandwidth: f64,
+}
+
+impl Default for SpeedWander {
+    fn default() -> Self {
+        Self {
+            depth: 0.02,
+            bandwidth: 0.5,
+        }
+    }
+}
+
+impl SpeedWander {
+    /// Check the depth is within range and the bandwidth positive.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=MAX_WANDER).contains(&self.depth) {
+            return Err(format!("speed_wander.depth must be in [0, {MAX_WANDER}], got {}", self.depth));
+        }
+        if !(self.bandwidth > 0.0 && self.bandwidth.is_finite()) {
+            return Err(format!("speed_wander.bandwidth must be > 0, got {}", self.bandwidth));
+        }
+        Ok(())
+    }
+}
+
+/// Seed of the second pump's jitter and wander, so that it does not wobble
+/// in step with the first.
+pub(crate) const SECOND_PUMP_SEED: u64 = 0x2545_F491_4F6C_DD1D;
+
+/// A second pump running beside the first, as in dual-pump aerator
+/// installations.
+///
+/// It shares the first pump's stroke model and motor but runs at its own
+/// speed with its own valve count, so close harmonics of the two beat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SecondPump {
+    /// Motor speed in RPM, whatever the first pump's drive.
+    pub rpm: f64,
+    /// Number of valves.
+    pub num_valves: u32,
+    /// Duty cycle of each valve pulse (0–1).
+    pub duty_cycle: f64,
+    /// Pulsation amplitude relative to the first pump.
+    pub level: f64,
+}
+
+impl Default for SecondPump {
+    fn default() -> Self {
+        Self {
+            rpm: 2950.0,
+            num_valves: 3,
+            duty_cycle: 0.5,
+            level: 1.0,
+        }
+    }
+}
+
+impl SecondPump {
+    /// Check the speed and level are positive, and that there is at least
+    /// one valve with a duty cycle in (0, 1).
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.rpm > 0.0 && self.rpm.is_finite()) {
+            return Err(format!("second_pump.rpm must be > 0, got {}", self.rpm));
+        }
+        if self.num_valves == 0 {
+            return Err("second_pump.num_valves must be >= 1".to_string());
+        }
+        if !(self.duty_cycle > 0.0 && self.duty_cycle < 1.0) {
+            return Err(format!("second_pump.duty_cycle must be in (0, 1), got {}", self.duty_cycle));
+        }
+        if !(self.level >= 0.0 && self.level.is_finite()) {
+            return Err(format!("second_pump.level must be >= 0, got {}", self.level));
+        }
+        Ok(())
+    }
+}
+
+/// Fourier coefficient at motor order `order` of a unit half-sine pulse
+/// lasting `width` of a revolution from the start of it.
+fn half_sine_coefficient(width: f64, order: f64) -> Complex64 {
+    let (a, w) = (PI / width, 2.0 * PI * order);
+    if (w - a).abs() < 1e-9 * a {
+        // The pulse's own frequency: the limit of the general form
+        return Complex64::new(0.0, -width / 2.0);
+    }
+    a * (Complex64::new(1.0, 0.0) + Complex64::from_polar(1.0, -w * width)) / (a * a - w * w)
+}
+
+/// Highest harmonic a [`HarmonicSeries`] may hold.
+pub const MAX_HARMONIC: usize = 100;
+
+/// Revolutions the reed valves are simulated for to settle into their
+/// periodic motion; only the last is kept.
+const REED_REVOLUTIONS: usize = 4;
+
+/// Amplitude below which a slap ring is dropped, relative to its start.
+const RING_CUTOFF: f64 = 1e-4;
+
+/// Dynamics of a reed (flapper) outlet valve.
+///
+/// The reed is a damped mass on a spring pushed open by the stroke
+/// pressure once it exceeds the opening pressure. It lags the stroke,
+/// flutters, and slaps back onto its seat, the impact ringing at the
+/// reed's natural frequency: the "ticking" of a diaphragm pump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReedValve {
+    /// Reed stiffness in N/m.
+    pub stiffness: f64,
+    /// Effective moving mass of the reed in kg.
+    pub mass: f64,
+    /// Damping ratio of the reed's motion, below 1.
+    pub damping: f64,
+    /// Pressure that cracks the valve open, as a fraction of the peak
+    /// stroke pressure.
+    pub opening_pressure: f64,
+    /// Coefficient of restitution of the reed on its seat, 0–1.
+    pub restitution: f64,
+    /// Level of the slap relative to the stroke pressure, per unit
+    /// impact speed (full lift per radian of the natural frequency).
+    pub slap: f64,
+}
+
+impl Default for ReedValve {
+    fn default() -> Self {
+        // A 5 mg reed on 200 N/m: about 1 kHz
+        Self {
+            stiffness: 200.0,
+            mass: 5e-6,
+            damping: 0.02,
+            opening_pressure: 0.1,
+            restitution: 0.3,
+            slap: 1.0,
+        }
+    }
+}
+
+impl ReedValve {
+    /// Check every property is within its range.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.stiffness > 0.0 && self.stiffness.is_finite()) {
+            return Err(format!("reed stiffness must be > 0, got {}", self.stiffness));
+        }
+        if !(self.mass > 0.0 && self.mass.is_finite()) {
+            return Err(format!("reed mass must be > 0, got {}", self.mass));
+        }
+        if !(0.0..1.0).contains(&self.damping) {
+            return Err(format!("reed damping must be in [0, 1), got {}", self.damping));
+        }
+        if !(0.0..1.0).contains(&self.opening_pressure) {
+            return Err(format!(
+                "reed opening_pressure must be in [0, 1), got {}",
+                self.opening_pressure
+            ));
+        }
+        if !(0.0..=1.0).contains(&self.restitution) {
+            return Err(format!("reed restitution must be in [0, 1], got {}", self.restitution));
+        }
+        if !(self.slap >= 0.0 && self.slap.is_finite()) {
+            return Err(format!("reed slap must be >= 0, got {}", self.slap));
+        }
+        Ok(())
+    }
+
+    /// Natural frequency of the reed in Hz.
+    pub fn natural_frequency(&self) -> f64 {
+        (self.stiffness / self.mass).sqrt() / (2.0 * PI)
+    }
+
+    /// Pressure the valve delivers and the slap it makes, sampled like
+    /// `drive`, the pressure across the valve over one revolution of
+    /// `period` seconds, once the reed has settled into periodic motion.
+    ///
+    /// Lift is counted in units of the static lift at peak pressure. A
+    /// seated reed sticks until the pressure reaches the opening pressure,
+    /// then springs open. The valve passes the whole stroke while lifted
+    /// at least as far as the pressure would statically hold it, and in
+    /// proportion to its lift while it lags behind.
+    fn respond(&self, drive: &[f64], period: f64) -> (Vec<f64>, Vec<f64>) {
+        let points = drive.len();
+        let omega = 2.0 * PI * self.natural_frequency();
+        let dt = period / points as f64;
+        // Keep the explicit integration well inside its stability limit
+        let substeps = ((omega * dt / 0.1).ceil() as usize).max(1);
+        let h = dt / substeps as f64;
+
+        let (mut lift, mut speed) = (0.0f64, 0.0f64);
+        let mut delivered = vec![0.0; points];
+        let mut impacts: Vec<(f64, f64)> = Vec::new();
+        for step in 0..REED_REVOLUTIONS * points {
+            let i = step % points;
+            let force = drive[i];
+            for sub in 0..substeps {
+                if lift <= 0.0 && force <= self.opening_pressure {
+                    // Held on its seat
+                    lift = 0.0;
+                    speed = 0.0;
+                    break;
+                }
+                speed += (omega * omega * (force - lift) - 2.0 * self.damping * omega * speed) * h;
+                lift += speed * h;
+                if lift < 0.0 {
+                    impacts.push(((step * substeps + sub) as f64 * h, -speed / omega));
+                    lift = 0.0;
+                    speed *= -self.restitution;
+                }
+            }
+            if step / points == REED_REVOLUTIONS - 1 {
+                let opening = if lift >= force { 1.0 } else { lift / force };
+                delivered[i] = drive[i].max(0.0) * opening.max(0.0);
+            }
+        }
+
+        // Each impact rings the reed at its damped natural frequency
+        let damped = omega * (1.0 - self.damping * self.damping).sqrt();
+        let decay = self.damping * omega;
+        let last = (REED_REVOLUTIONS - 1) as f64 * period;
+        let slap = (0..points)
+            .map(|i| {
+                let t = last + i as f64 * dt;
+                impacts
+                    .iter()
+                    .filter(|&&(start, _)| start <= t)
+                    .map(|&(start, impact)| {
+                        let envelope = (-decay * (t - start)).exp();
+                        if envelope < RING_CUTOFF {
+                            0.0
+                        } else {
+                            self.slap * impact * envelope * (damped * (t - start)).sin()
+                        }
+                    })
+                    .sum()
+            })
+            .collect();
+        (delivered, slap)
+    }
+}
+
+/// How the pump diaphragms are driven.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PumpDrive {
+    /// Motor-driven eccentric: valve pulses follow the motor RPM.
+    Rotary,
+    /// Mains-synchronous linear (electromagnetic) pump: the armature
+    /// vibrates at the mains frequency, and the stroke amplitude rather
+    /// than the speed sets the output.
+    Linear {
+        /// Mains frequency in Hz (50 or 60).
+        mains_frequency: f64,
+        /// Stroke relative to the full travel; above 1 the diaphragm hits
+        /// its end stop and the pulses flatten.
+        stroke: f64,
+    },
+}
+
+/// Anything the audio pipeline can play through the muffler in place of
+/// the built-in pump: [`PumpSource`], a test signal, a recording, or a
+/// custom model such as a rotary vane pump or a fan.
+///
+/// The pipeline calls `generate` once per block from its feeder thread,
+/// after passing on the current pump RPM and the output sample rate.
+pub trait SampleSource {
+    /// Generate the next `count` samples.
+    fn generate(&mut self, count: usize) -> Vec<f64>;
+
+    /// Follow the pump speed set in the UI. Sources that do not depend on
+    /// it keep the default, which ignores it.
+    fn set_rpm(&mut self, _rpm: f64) {}
+
+    /// Render at `sample_rate` Hz, the output device's rate. Called before
+    /// the first block.
+    fn set_sample_rate(&mut self, _sample_rate: f64) {}
+}
+
 /// A multi-valve diaphragm pump pressure source.
 ///
 /// Each valve produces a half-rectified sinusoidal pulse once per motor
-/// revolution, phase-shifted by `2π / num_valves` from the previous valve.
+/// revolution, phase-shifted by `2π / num_valves` from the previous valve,
+/// optionally followed by a suction stroke (see [`StrokeTiming`]).
+///
+/// The pulses' corners would alias when sampled directly, audibly so at
+/// high RPM, so [`generate`](Self::generate) plays one revolution from a
+/// wavetable holding only the harmonics below the Nyquist frequency.
 pub struct PumpSource {
     /// Motor speed in RPM.
     pub rpm: f64,
@@ -11,10 +692,58 @@ pub struct PumpSource {
     pub num_valves: u32,
     /// Duty cycle (fraction of revolution each valve is active), 0–1.
     pub duty_cycle: f64,
+    /// Suction stroke and valve overlap model.
+    pub timing: StrokeTiming,
+    /// Relative stroke amplitude; pulses clip at the end stop (1.0).
+    pub stroke: f64,
+    /// Per-valve imbalance and motor jitter.
+    pub variation: ValveVariation,
+    /// Piston kinematics replacing the diaphragm pulses, each valve being
+    /// a cylinder; `duty_cycle` and `suction_fraction` then no longer
+    /// apply, as each piston delivers for half a revolution.
+    pub piston: Option<PistonCrank>,
+    /// Dynamics of the outlet reed valves; `None` for ideal valves that
+    /// pass each stroke unchanged.
+    pub reed: Option<ReedValve>,
+    /// Measured valve open area replacing the half-sine pressure stroke,
+    /// if any.
+    pub lift: Option<ValveLift>,
+    /// Harmonics played instead of the modelled strokes, if any.
+    pub series: Option<HarmonicSeries>,
+    /// Noise of the driving motor, if modelled.
+    pub motor: Option<MotorNoise>,
+    /// Phases in radians of the hum, the PWM whine and the bearing
+    /// modulation.
+    motor_phases: [f64; 3],
+    /// Motor noise bypassing the muffler, from the last block generated.
+    bypass: Vec<f64>,
+    /// Slow random hunting of the speed, if modelled.
+    pub wander: Option<SpeedWander>,
+    /// Current speed deviation from the wander, as a fraction of nominal.
+    deviation: f64,
     /// Current phase angle in radians (wraps at 2π).
     phase: f64,
+    /// RPM the last block ended at, once one has played. The next block
+    /// ramps from it to `rpm` so a change between blocks does not step
+    /// the pitch.
+    previous_rpm: Option<f64>,
+    /// Speed of the current revolution relative to nominal, set by the
+    /// jitter.
+    speed: f64,
+    /// xorshift64 state for the jitter.
+    rng: u64,
     /// Sample rate in Hz.
     sample_rate: f64,
+    /// Spectrum of one revolution of the waveform, before band-limiting.
+    spectrum: Vec<Complex64>,
+    /// Settings `spectrum` was computed for; recomputed when they change.
+    table_key: Option<TableKey>,
+    /// One band-limited revolution of the waveform, without DC, plus a
+    /// wrap-around sample for interpolation.
+    table: Vec<f64>,
+    /// Highest harmonic `table` holds; rebuilt from `spectrum` when the
+    /// speed moves it.
+    table_highest: Option<usize>,
 }
 
 impl PumpSource {
@@ -23,8 +752,27 @@ impl PumpSource {
             rpm,
             num_valves,
             duty_cycle,
+            timing: StrokeTiming::default(),
+            stroke: 1.0,
+            variation: ValveVariation::default(),
+            piston: None,
+            reed: None,
+            lift: None,
+            series: None,
+            motor: None,
+            motor_phases: [0.0; 3],
+            bypass: Vec::new(),
+            wander: None,
+            deviation: 0.0,
             phase: 0.0,
+            previous_rpm: None,
+            speed: 1.0,
+            rng: 0x9E37_79B9_7F4A_7C15,
             sample_rate,
+            spectrum: Vec::new(),
+            table_key: None,
+            table: Vec::new(),
+            table_highest: None,
         }
     }
 
@@ -40,38 +788,414 @@ impl PumpSource {
         self.duty_cycle = duty_cycle;
     }
 
-    /// Generate `count` samples of the pump pressure waveform.
+    /// Update the stroke timing without resetting phase.
+    pub fn set_timing(&mut self, timing: StrokeTiming) {
+        self.timing = timing;
+    }
+
+    /// Update the relative stroke amplitude without resetting phase.
+    pub fn set_stroke(&mut self, stroke: f64) {
+        self.stroke = stroke;
+    }
+
+    /// Update the per-valve imbalance and jitter without resetting phase.
+    pub fn set_variation(&mut self, variation: ValveVariation) {
+        self.variation = variation;
+    }
+
+    /// Use piston kinematics (`Some`) or diaphragm pulses (`None`) without
+    /// resetting phase.
+    pub fn set_piston(&mut self, piston: Option<PistonCrank>) {
+        self.piston = piston;
+    }
+
+    /// Model the reed valve dynamics (`Some`) or use ideal valves (`None`)
+    /// without resetting phase.
+    pub fn set_reed(&mut self, reed: Option<ReedValve>) {
+        self.reed = reed;
+    }
+
+    /// Shape the pressure strokes by the valve open area of `lift`
+    /// (`Some`) or by half-sines (`None`) without resetting phase.
+    pub fn set_lift(&mut self, lift: Option<ValveLift>) {
+        self.lift = lift;
+    }
+
+    /// Play the waveform of `series` (`Some`) instead of the modelled
+    /// strokes (`None`) without resetting phase.
+    pub fn set_series(&mut self, series: Option<HarmonicSeries>) {
+        self.series = series;
+    }
+
+    /// Add the noise of the motor (`Some`) or leave it out (`None`)
+    /// without resetting phase.
+    pub fn set_motor(&mut self, motor: Option<MotorNoise>) {
+        self.motor = motor;
+    }
+
+    /// Restart the random jitter and wander from `seed`.
+    pub fn reseed(&mut self, seed: u64) {
+        // xorshift never leaves a zero state
+        self.rng = seed.max(1);
+    }
+
+    /// Let the speed wander (`Some`) or hold it (`None`) without resetting
+    /// phase.
+    pub fn set_wander(&mut self, wander: Option<SpeedWander>) {
+        self.wander = wander;
+        if wander.is_none() {
+            self.deviation = 0.0;
+        }
+    }
+
+    /// The share of the motor noise that bypasses the muffler, for the
+    /// samples of the last [`generate`](Self::generate); empty without
+    /// motor noise.
+    pub fn take_bypass(&mut self) -> Vec<f64> {
+        std::mem::take(&mut self.bypass)
+    }
+
+    /// Fraction of a revolution during which at least two pressure strokes
+    /// overlap.
+    pub fn valve_overlap(&self) -> f64 {
+        let n = self.num_valves as f64;
+        ((self.duty_cycle - 1.0 / n).max(0.0) * n).min(1.0)
+    }
+
+    /// Pressure stroke (≥ 0) and suction stroke (≤ 0) of valve `valve`
+    /// at motor phase `phase`, before any valve dynamics.
+    fn strokes(&self, valve: u32, phase: f64) -> (f64, f64) {
+        let pressure_angle = self.duty_cycle * 2.0 * PI;
+        let suction_angle = self.timing.suction_fraction * 2.0 * PI;
+        let offset = self.variation.phase_offset(valve as usize);
+        let valve_phase = phase + 2.0 * PI * (valve as f64 / self.num_valves as f64 - offset);
+        let theta = valve_phase.rem_euclid(2.0 * PI);
+        let amplitude = self.variation.amplitude(valve as usize);
+        if let Some(piston) = &self.piston {
+            // Delivery on the way up, suction on the way down
+            let velocity = amplitude * piston.velocity(theta);
+            (velocity.max(0.0), velocity.min(0.0))
+        } else if let Some(lift) = &self.lift {
+            (amplitude * (self.stroke * lift.opening(theta)).min(1.0), 0.0)
+        } else if theta < pressure_angle {
+            // Half-rectified sinusoid within the active window,
+            // limited by the diaphragm end stop
+            (amplitude * (self.stroke * (PI * theta / pressure_angle).sin()).min(1.0), 0.0)
+        } else if theta < pressure_angle + suction_angle {
+            (0.0, -amplitude * (self.stroke * (PI * (theta - pressure_angle) / suction_angle).sin()).min(1.0))
+        } else {
+            (0.0, 0.0)
+        }
+    }
+
+    /// Outlet pressure from the delivered pressure strokes of the valves
+    /// and their summed suction.
+    fn combine(&self, pressures: impl Iterator<Item = f64>, suction: f64) -> f64 {
+        let (pressure, active) = pressures
+            .filter(|&p| p > 0.0)
+            .fold((0.0, 0u32), |(sum, active), p| (sum + p, active + 1));
+        // Overlapping pressure strokes push through the same outlet valve.
+        let sharing = 1.0 + self.timing.overlap_sharing * active.saturating_sub(1) as f64;
+        pressure / sharing + self.timing.suction_level * suction
+    }
+
+    /// Instantaneous (DC-biased) outlet pressure at motor phase `phase`,
+    /// with ideal valves.
+    fn waveform(&self, phase: f64) -> f64 {
+        let strokes: Vec<(f64, f64)> = (0..self.num_valves).map(|v| self.strokes(v, phase)).collect();
+        let suction = strokes.iter().map(|&(_, suction)| suction).sum();
+        self.combine(strokes.iter().map(|&(pressure, _)| pressure), suction)
+    }
+
+    /// `points` samples of one revolution of the outlet pressure, starting
+    /// at phase 0, including the reed valve dynamics if modelled.
+    fn revolution(&self, points: usize) -> Vec<f64> {
+        let phase = |i: usize| 2.0 * PI * i as f64 / points as f64;
+        if let Some(series) = &self.series {
+            let valves = self.num_valves as f64;
+            return (0..points).map(|i| series.waveform(valves * phase(i))).collect();
+        }
+        let Some(reed) = &self.reed else {
+            return (0..points).map(|i| self.waveform(phase(i))).collect();
+        };
+        let period = 60.0 / self.reed_rpm();
+        let mut output = vec![0.0; points];
+        let mut suction = vec![0.0; points];
+        let mut delivered = Vec::with_capacity(self.num_valves as usize);
+        for v in 0..self.num_valves {
+            let strokes: Vec<(f64, f64)> = (0..points).map(|i| self.strokes(v, phase(i))).collect();
+            for (total, &(_, s)) in suction.iter_mut().zip(&strokes) {
+                *total += s;
+            }
+            // The suction stroke pulls the reed back onto its seat
+            let across: Vec<f64> = strokes.iter().map(|&(p, s)| p + s).collect();
+            let (pressure, slap) = reed.respond(&across, period);
+            for (out, s) in output.iter_mut().zip(slap) {
+                *out += s;
+            }
+            delivered.push(pressure);
+        }
+        for (i, out) in output.iter_mut().enumerate() {
+            *out += self.combine(delivered.iter().map(|pressure| pressure[i]), suction[i]);
+        }
+        output
+    }
+
+    /// Amplitudes of the first `count` harmonics of the fundamental, from
+    /// one revolution of the waveform. Useful for showing the even/odd
+    /// balance set by the stroke timing.
+    pub fn harmonic_levels(&self, count: usize) -> Vec<f64> {
+        self.harmonics(count).iter().map(|harmonic| harmonic.amplitude).collect()
+    }
+
+    /// Amplitudes and phases of the first `count` harmonics of the
+    /// fundamental, from one revolution of the waveform.
+    pub fn harmonics(&self, count: usize) -> Vec<Harmonic> {
+        const POINTS: usize = 4096;
+        let samples = self.revolution(POINTS);
+        (1..=count)
+            .map(|h| {
+                let order = (h * self.num_valves as usize) as f64;
+                let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+                    let angle = 2.0 * PI * order * i as f64 / POINTS as f64;
+                    (re + x * angle.cos(), im - x * angle.sin())
+                });
+                Harmonic {
+                    amplitude: 2.0 * (re * re + im * im).sqrt() / POINTS as f64,
+                    phase: im.atan2(re),
+                }
+            })
+            .collect()
+    }
+
+    /// Amplitudes and phases of the first `count` harmonics of the
+    /// fundamental from the Fourier series of the stroke pulses: exact,
+    /// where [`harmonics`](Self::harmonics) samples the waveform. `None`
+    /// where the waveform has no closed form: pistons, valve lift
+    /// profiles, reed valves, strokes limited by the end stop, and
+    /// overlapping pulses sharing the outlet.
+    pub fn analytic_harmonics(&self, count: usize) -> Option<Vec<Harmonic>> {
+        if let Some(series) = &self.series {
+            let silent = Harmonic { amplitude: 0.0, phase: 0.0 };
+            return Some((0..count).map(|i| series.harmonics.get(i).copied().unwrap_or(silent)).collect());
+        }
+        let overlapping = self.valve_overlap() > 0.0 || self.variation.phase_offsets.iter().any(|&o| o != 0.0);
+        if self.piston.is_some()
+            || self.lift.is_some()
+            || self.reed.is_some()
+            || self.stroke > 1.0
+            || (self.timing.overlap_sharing > 0.0 && overlapping)
+        {
+            return None;
+        }
+        let valves = self.num_valves as usize;
+        let suction = self.timing.suction_level > 0.0 && self.timing.suction_fraction > 0.0;
+        let harmonics = (1..=count)
+            .map(|h| {
+                let order = (h * valves) as f64;
+                let shift = |delay: f64| Complex64::from_polar(1.0, -2.0 * PI * order * delay);
+                // One valve's pressure stroke, then its suction stroke
+                let mut pulse = half_sine_coefficient(self.duty_cycle, order);
+                if suction {
+                    pulse -= self.timing.suction_level
+                        * half_sine_coefficient(self.timing.suction_fraction, order)
+                        * shift(self.duty_cycle);
+                }
+                let total: Complex64 = (0..valves)
+                    .map(|v| {
+                        let start = self.variation.phase_offset(v) - v as f64 / valves as f64;
+                        self.variation.amplitude(v) * self.stroke * pulse * shift(start)
+                    })
+                    .sum();
+                Harmonic {
+                    amplitude: 2.0 * total.norm(),
+                    phase: total.arg(),
+                }
+            })
+            .collect();
+        Some(harmonics)
+    }
+
+    /// Mean of one revolution of the waveform: the net delivery the
+    /// harmonics ride on.
+    pub fn mean_level(&self) -> f64 {
+        const POINTS: usize = 4096;
+        self.revolution(POINTS).iter().sum::<f64>() / POINTS as f64
+    }
+
+    /// Generate `count` samples of the pump pressure waveform, band-limited
+    /// to the Nyquist frequency.
+    ///
+    /// The waveform carries no DC bias, so the full signal energy is in the
+    /// RPM-dependent AC component. Without this, overlapping valve pulses
+    /// create a near-constant waveform where RPM changes are inaudible.
     pub fn generate(&mut self, count: usize) -> Vec<f64> {
-        let d_phase = 2.0 * PI * (self.rpm / 60.0) / self.sample_rate;
-        let mut output = Vec::with_capacity(count);
+        let key = TableKey {
+            reed_speed: self.reed.map(|_| self.reed_speed()),
+            num_valves: self.num_valves,
+            duty_cycle: self.duty_cycle,
+            timing: self.timing,
+            stroke: self.stroke,
+            piston: self.piston,
+            reed: self.reed,
+            lift: self.lift.clone(),
+            series: self.series.clone(),
+            sample_rate: self.sample_rate,
+            amplitudes: self.variation.amplitudes.clone(),
+            phase_offsets: self.variation.phase_offsets.clone(),
+        };
+        if self.table_key.as_ref() != Some(&key) {
+            self.spectrum = self.revolution_spectrum();
+            self.table_key = Some(key);
+            self.table_highest = None;
+        }
+        let highest = self.highest_harmonic();
+        if self.table_highest != Some(highest) {
+            self.table = self.band_limited_table(highest);
+            self.table_highest = Some(highest);
+        }
 
-        for _ in 0..count {
-            let mut sample = 0.0;
-            for v in 0..self.num_valves {
-                let valve_phase = self.phase + 2.0 * PI * v as f64 / self.num_valves as f64;
-                let theta = valve_phase % (2.0 * PI);
-                let active_angle = self.duty_cycle * 2.0 * PI;
-                if theta < active_angle {
-                    // Half-rectified sinusoid within the active window
-                    sample += (PI * theta / active_angle).sin();
+        let d_phase = |rpm: f64| 2.0 * PI * (rpm / 60.0) / self.sample_rate;
+        let (from, to) = (d_phase(self.previous_rpm.unwrap_or(self.rpm)), d_phase(self.rpm));
+        self.previous_rpm = Some(self.rpm);
+        let mut output = Vec::with_capacity(count);
+        self.bypass.clear();
+        // Tones at or above the Nyquist frequency are left out
+        let audible = |level: f64, frequency: f64| if frequency < self.sample_rate / 2.0 { level } else { 0.0 };
+        let motor = self.motor.map(|motor| MotorNoise {
+            hum: audible(motor.hum, 2.0 * motor.mains_frequency),
+            pwm_whine: audible(motor.pwm_whine, motor.pwm_frequency),
+            ..motor
+        });
+        for n in 0..count {
+            let position = self.phase / (2.0 * PI) * TABLE_SIZE as f64;
+            let i = (position as usize).min(TABLE_SIZE - 1);
+            let t = position - i as f64;
+            let pulse = self.table[i] + (self.table[i + 1] - self.table[i]) * t;
+            if let Some(wander) = self.wander {
+                self.deviation = self.next_deviation(&wander);
+            }
+            let d_phase = from + (to - from) * (n + 1) as f64 / count as f64;
+            let step = d_phase * self.speed * (1.0 + self.deviation).max(0.1);
+            match motor {
+                Some(motor) => {
+                    let [hum, whine, bearing] = self.motor_phases;
+                    let tones = motor.hum * hum.cos() + motor.pwm_whine * whine.cos();
+                    output.push(pulse * (1.0 + motor.bearing * bearing.cos()) + (1.0 - motor.bypass) * tones);
+                    self.bypass.push(motor.bypass * tones);
+                    self.advance_motor(&motor, step);
                 }
+                None => output.push(pulse),
             }
-            output.push(sample);
-            self.phase += d_phase;
+            self.phase += step;
             if self.phase >= 2.0 * PI {
                 self.phase -= 2.0 * PI;
+                self.speed = self.next_speed();
             }
         }
+        output
+    }
+
+    /// Step the motor noise phases on by one sample, the shaft turning
+    /// `shaft_step` radians.
+    fn advance_motor(&mut self, motor: &MotorNoise, shaft_step: f64) {
+        let step = |frequency: f64| 2.0 * PI * frequency / self.sample_rate;
+        let [hum, whine, bearing] = &mut self.motor_phases;
+        *hum = (*hum + step(2.0 * motor.mains_frequency)) % (2.0 * PI);
+        *whine = (*whine + step(motor.pwm_frequency)) % (2.0 * PI);
+        *bearing = (*bearing + motor.bearing_order * shaft_step) % (2.0 * PI);
+    }
+
+    /// Speed of the next revolution: nominal without jitter, otherwise
+    /// the inverse of a revolution length drawn around 1 with an RMS
+    /// spread of `jitter`.
+    fn next_speed(&mut self) -> f64 {
+        if self.variation.jitter == 0.0 {
+            return 1.0;
+        }
+        // Sum of four uniform samples, scaled to unit variance: close
+        // enough to Gaussian and bounded
+        let spread: f64 = (0..4).map(|_| rng::uniform(&mut self.rng)).sum::<f64>() * 0.75f64.sqrt();
+        1.0 / (1.0 + self.variation.jitter * spread).max(0.1)
+    }
+
+    /// Speed deviation one sample on: an Ornstein–Uhlenbeck process, white
+    /// noise through a one-pole low-pass at the wander bandwidth, scaled so
+    /// its RMS is the wander depth.
+    fn next_deviation(&mut self, wander: &SpeedWander) -> f64 {
+        let decay = (-2.0 * PI * wander.bandwidth / self.sample_rate).exp();
+        // Uniform noise scaled to unit variance
+        let noise = rng::uniform(&mut self.rng) * 3f64.sqrt();
+        decay * self.deviation + wander.depth * (1.0 - decay * decay).sqrt() * noise
+    }
+
+    /// Speed the reed valve response is computed for, as a count of
+    /// `1 / REED_SPEED_STEPS` octaves.
+    fn reed_speed(&self) -> i64 {
+        (self.rpm.log2() * REED_SPEED_STEPS).round() as i64
+    }
+
+    /// RPM the reed valve response is computed for: the speed rounded to
+    /// a step of [`REED_SPEED_STEPS`].
+    fn reed_rpm(&self) -> f64 {
+        (self.reed_speed() as f64 / REED_SPEED_STEPS).exp2()
+    }
+
+    /// Highest motor harmonic below the Nyquist frequency, and below a
+    /// quarter of the table so linear interpolation stays accurate.
+    fn highest_harmonic(&self) -> usize {
+        let motor_frequency = self.rpm / 60.0;
+        let highest = if motor_frequency > 0.0 {
+            ((self.sample_rate / 2.0 / motor_frequency).ceil() as usize).saturating_sub(1)
+        } else {
+            0
+        };
+        highest.min(TABLE_SIZE / 4)
+    }
 
-        // Remove DC bias so the full signal energy is in the RPM-dependent
-        // AC component. Without this, overlapping valve pulses create a
-        // near-constant waveform where RPM changes are inaudible.
-        let mean = output.iter().sum::<f64>() / output.len() as f64;
-        for s in &mut output {
-            *s -= mean;
+    /// Spectrum of one revolution of the waveform, sampled at
+    /// `TABLE_SIZE` points.
+    fn revolution_spectrum(&self) -> Vec<Complex64> {
+        let forward = RealFftPlanner::<f64>::new().plan_fft_forward(TABLE_SIZE);
+        let mut table = self.revolution(TABLE_SIZE);
+        let mut spectrum = forward.make_output_vec();
+        forward.process(&mut table, &mut spectrum).expect("wavetable FFT failed");
+        spectrum
+    }
+
+    /// One revolution of the waveform keeping only the motor harmonics up
+    /// to `highest`, without DC, with a wrap-around sample.
+    fn band_limited_table(&self, highest: usize) -> Vec<f64> {
+        let inverse = RealFftPlanner::<f64>::new().plan_fft_inverse(TABLE_SIZE);
+        let mut spectrum: Vec<Complex64> = self
+            .spectrum
+            .iter()
+            .enumerate()
+            .map(|(k, &bin)| if k == 0 || k > highest { Complex64::new(0.0, 0.0) } else { bin })
+            .collect();
+        let mut table = inverse.make_output_vec();
+        inverse.process(&mut spectrum, &mut table).expect("wavetable IFFT failed");
+
+        let norm = 1.0 / TABLE_SIZE as f64;
+        for sample in &mut table {
+            *sample *= norm;
         }
+        table.push(table[0]);
+        table
+    }
+}
 
-        output
+impl SampleSource for PumpSource {
+    fn generate(&mut self, count: usize) -> Vec<f64> {
+        PumpSource::generate(self, count)
+    }
+
+    fn set_rpm(&mut self, rpm: f64) {
+        self.rpm = rpm;
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
     }
 }
 
@@ -214,4 +1338,493 @@ mod tests {
         let mean = samples.iter().sum::<f64>() / samples.len() as f64;
         assert!(mean.abs() < 1e-6, "Pump output should be zero-mean, got {mean}");
     }
+
+    #[test]
+    fn test_stroke_asymmetry_shifts_harmonic_balance() {
+        // Single valve, 50 % pressure stroke: an equal suction stroke makes
+        // a pure sine, a shorter one brings in even harmonics.
+        let mut pump = PumpSource::new(3000.0, 1, 0.5, 44100.0);
+        pump.set_timing(StrokeTiming {
+            suction_fraction: 0.5,
+            suction_level: 1.0,
+            overlap_sharing: 0.0,
+        });
+        let symmetric = pump.harmonic_levels(4);
+        assert!(symmetric[1] < 1e-6 * symmetric[0], "{symmetric:?}");
+
+        pump.timing.suction_fraction = 0.25;
+        let asymmetric = pump.harmonic_levels(4);
+        assert!(asymmetric[1] > 0.05 * asymmetric[0], "{asymmetric:?}");
+
+        // Default timing reproduces the plain pressure-pulse waveform
+        let legacy = PumpSource::new(3000.0, 3, 0.5, 44100.0);
+        assert_eq!(legacy.timing, StrokeTiming::default());
+    }
+
+    #[test]
+    fn test_overlap_sharing_limits_peak() {
+        let mut pump = PumpSource::new(3000.0, 3, 0.5, 44100.0);
+        assert!((pump.valve_overlap() - 0.5).abs() < 1e-12);
+
+        // Peak outlet pressure over one revolution, before DC removal
+        let peak = |pump: &PumpSource| {
+            (0..3600)
+                .map(|i| pump.waveform(2.0 * PI * i as f64 / 3600.0))
+                .fold(f64::MIN, f64::max)
+        };
+        let independent = peak(&pump);
+        pump.timing.overlap_sharing = 1.0;
+        let shared = peak(&pump);
+        assert!(shared <= 1.0 + 1e-12);
+        assert!(shared < independent, "shared {shared} vs independent {independent}");
+    }
+
+    #[test]
+    fn test_linear_pump_stroke() {
+        // A 50 Hz twin-diaphragm linear pump hums at 100 Hz
+        let mut params = crate::SimParams {
+            num_valves: 2,
+            pump_drive: PumpDrive::Linear {
+                mains_frequency: 50.0,
+                stroke: 0.5,
+            },
+            ..crate::SimParams::default()
+        };
+        let pump = params.pump_source(44100.0);
+        assert!((pump.fundamental_frequency() - 100.0).abs() < 1e-9);
+
+        // Half stroke halves the output but keeps the spectrum shape
+        let full = PumpSource::new(3000.0, 2, 0.5, 44100.0).harmonic_levels(3);
+        let half = pump.harmonic_levels(3);
+        assert!((half[0] / full[0] - 0.5).abs() < 1e-9);
+
+        // Overdriving into the end stop adds harmonics
+        params.pump_drive = PumpDrive::Linear {
+            mains_frequency: 50.0,
+            stroke: 1.5,
+        };
+        let clipped = params.pump_source(44100.0).harmonic_levels(3);
+        assert!(clipped[1] / clipped[0] > full[1] / full[0]);
+    }
+
+    #[test]
+    fn test_generate_is_band_limited() {
+        // 9000 rpm, one valve at 8 kHz: motor harmonic 27 (4050 Hz) would
+        // fold back to 3950 Hz, between the true harmonics
+        let sample_rate = 8000.0;
+        let mut pump = PumpSource::new(9000.0, 1, 0.3, sample_rate);
+        pump.set_stroke(1.5);
+        let level_at = |samples: &[f64], frequency: f64| {
+            let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+                let angle = 2.0 * PI * frequency * i as f64 / sample_rate;
+                (re + x * angle.cos(), im - x * angle.sin())
+            });
+            2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+        };
+        let band_limited = pump.generate(8000);
+        let naive: Vec<f64> = (0..8000)
+            .map(|i| pump.waveform(2.0 * PI * 150.0 * i as f64 / sample_rate))
+            .collect();
+        let fundamental = level_at(&band_limited, 150.0);
+        assert!(level_at(&naive, 3950.0) > 1e-4 * fundamental);
+        assert!(level_at(&band_limited, 3950.0) < 1e-6 * fundamental);
+        // The harmonics below Nyquist are kept
+        let harmonics = pump.harmonic_levels(26);
+        for order in [1, 26] {
+            let level = harmonics[order - 1];
+            let kept = level_at(&band_limited, 150.0 * order as f64);
+            assert!((kept / level - 1.0).abs() < 1e-3, "harmonic {order}: {kept} vs {level}");
+        }
+    }
+
+    #[test]
+    fn test_valve_imbalance_and_jitter() {
+        // Motor revolution at 50 Hz, valve pulses at 150 Hz
+        let sample_rate = 44100.0;
+        let level_at = |samples: &[f64], frequency: f64| {
+            let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+                let angle = 2.0 * PI * frequency * i as f64 / sample_rate;
+                (re + x * angle.cos(), im - x * angle.sin())
+            });
+            2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+        };
+        let mut pump = PumpSource::new(3000.0, 3, 0.3, sample_rate);
+        let symmetric = pump.generate(44100);
+        assert!(level_at(&symmetric, 50.0) < 1e-4 * level_at(&symmetric, 150.0));
+
+        // A weak valve or a late one brings in the 1×-rev tone
+        for variation in [
+            ValveVariation {
+                amplitudes: vec![0.8],
+                ..ValveVariation::default()
+            },
+            ValveVariation {
+                phase_offsets: vec![0.0, 0.05],
+                ..ValveVariation::default()
+            },
+        ] {
+            let mut pump = PumpSource::new(3000.0, 3, 0.3, sample_rate);
+            pump.set_variation(variation);
+            let imbalanced = pump.generate(44100);
+            assert!(level_at(&imbalanced, 50.0) > 0.05 * level_at(&imbalanced, 150.0));
+        }
+
+        // Jitter spreads the valve tone into its neighbours
+        pump.set_variation(ValveVariation {
+            jitter: 0.05,
+            ..ValveVariation::default()
+        });
+        let jittered = pump.generate(44100);
+        assert!(level_at(&jittered, 150.0) < 0.95 * level_at(&symmetric, 150.0));
+        assert!(level_at(&jittered, 140.0) > 100.0 * level_at(&symmetric, 140.0));
+
+        assert!(ValveVariation::default().validate().is_ok());
+        let bad = ValveVariation {
+            jitter: 0.5,
+            ..ValveVariation::default()
+        };
+        assert!(bad.validate().is_err());
+    }
+
+    #[test]
+    fn test_piston_kinematics() {
+        let crank = PistonCrank::default();
+        // Peak speed sits before mid-stroke, nearer top dead centre, and
+        // exceeds the crank pin speed
+        let peak = (0..3600)
+            .map(|i| PI * i as f64 / 3600.0)
+            .max_by(|a, b| crank.velocity(*a).total_cmp(&crank.velocity(*b)))
+            .unwrap();
+        assert!(peak < PI / 2.0, "{peak}");
+        assert!(crank.velocity(peak) > 1.0);
+        // An infinitely long rod would give a pure sine
+        let long = PistonCrank {
+            rod_ratio: 1e9,
+            ..crank
+        };
+        assert!((long.velocity(1.0) - 1.0f64.sin()).abs() < 1e-9);
+
+        // One cylinder delivering half the time: the half-wave rectified
+        // sine has no third harmonic, a finite rod brings one in and
+        // strengthens the second
+        let mut pump = PumpSource::new(3000.0, 1, 0.5, 44100.0);
+        pump.set_piston(Some(long));
+        let sine = pump.harmonic_levels(3);
+        pump.set_piston(Some(crank));
+        let piston = pump.harmonic_levels(3);
+        assert!(piston[1] / piston[0] > sine[1] / sine[0] * 1.02, "{piston:?} vs {sine:?}");
+        assert!(sine[2] < 1e-6 * sine[0]);
+        assert!(piston[2] > 0.03 * piston[0] && piston[2] < 0.1 * piston[0], "{piston:?}");
+
+        // 20 mm bore, 10 mm stroke: 3.14 mL per revolution
+        assert!((crank.swept_volume() - 3.1416e-6).abs() < 1e-9);
+        assert!((crank.mean_flow(3000.0, 2) - 2.0 * 50.0 * crank.swept_volume()).abs() < 1e-12);
+        assert!(PistonCrank { rod_ratio: 1.0, ..crank }.validate().is_err());
+    }
+
+    #[test]
+    fn test_valve_lift_profile() {
+        // A half-sine open area over the first 144° is the half-sine
+        // pressure stroke of a 40 % duty cycle
+        let table: String = (0..360)
+            .map(|angle| {
+                let area = if angle <= 144 { 4.0 * (PI * angle as f64 / 144.0).sin() } else { 0.0 };
+                format!("{angle}, {area}\n")
+            })
+            .collect();
+        let lift = ValveLift::from_table(&table).unwrap();
+        assert!(lift.validate().is_ok());
+        assert!((lift.opening(0.4 * PI) - 1.0).abs() < 1e-12);
+        assert!((lift.opening(-PI / 2.0)).abs() < 1e-12);
+        let default = PumpSource::new(3000.0, 2, 0.4, 44100.0);
+        let mut lifted = PumpSource::new(3000.0, 2, 0.4, 44100.0);
+        lifted.set_lift(Some(lift));
+        assert!(lifted.analytic_harmonics(5).is_none());
+        for (a, b) in default.harmonic_levels(5).iter().zip(lifted.harmonic_levels(5)) {
+            assert!((a - b).abs() < 1e-3 * default.harmonic_levels(1)[0], "{a} vs {b}");
+        }
+
+        // Interpolation wraps from the last point to the first
+        let wrapped = ValveLift {
+            points: vec![(90.0, 2.0), (270.0, 0.0)],
+        };
+        assert!((wrapped.opening(0.0) - 0.5).abs() < 1e-12);
+        assert!((wrapped.opening(PI) - 0.5).abs() < 1e-12);
+
+        assert!(ValveLift { points: vec![(0.0, 1.0)] }.validate().is_err());
+        assert!(ValveLift { points: vec![(0.0, 1.0), (360.0, 1.0)] }.validate().is_err());
+        assert!(ValveLift { points: vec![(90.0, 1.0), (45.0, 1.0)] }.validate().is_err());
+        assert!(ValveLift { points: vec![(0.0, 0.0), (90.0, 0.0)] }.validate().is_err());
+        assert!(ValveLift { points: vec![(0.0, -1.0), (90.0, 1.0)] }.validate().is_err());
+    }
+
+    #[test]
+    fn test_sample_source_trait() {
+        // A custom source only has to produce samples
+        struct Constant(f64);
+        impl SampleSource for Constant {
+            fn generate(&mut self, count: usize) -> Vec<f64> {
+                vec![self.0; count]
+            }
+        }
+        let mut sources: Vec<Box<dyn SampleSource>> = vec![
+            Box::new(Constant(0.5)),
+            Box::new(PumpSource::new(3000.0, 3, 0.5, 8000.0)),
+        ];
+        for source in &mut sources {
+            source.set_sample_rate(44100.0);
+            source.set_rpm(6000.0);
+            assert_eq!(source.generate(64).len(), 64);
+        }
+        assert_eq!(sources[0].generate(2), vec![0.5, 0.5]);
+
+        // The pump follows the RPM and rate handed to it: at 6000 rpm and
+        // 44.1 kHz one revolution is 441 samples
+        let mut pump = PumpSource::new(3000.0, 3, 0.3, 8000.0);
+        SampleSource::set_rpm(&mut pump, 6000.0);
+        SampleSource::set_sample_rate(&mut pump, 44100.0);
+        let samples = SampleSource::generate(&mut pump, 882);
+        assert!((samples[0] - samples[441]).abs() < 1e-9);
+        assert!((pump.rpm - 6000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_harmonic_series_source() {
+        // An order spectrum is reproduced exactly, phases included
+        let series = HarmonicSeries::from_table("harmonic, amplitude, phase\n1, 0.5, 0\n2 0.25 90\n4, 0.1").unwrap();
+        assert_eq!(series.harmonics.len(), 4);
+        assert_eq!(series.harmonics[2].amplitude, 0.0);
+        assert!(series.validate().is_ok());
+        let mut pump = PumpSource::new(3000.0, 3, 0.5, 44100.0);
+        pump.set_series(Some(series.clone()));
+        let harmonics = pump.harmonics(5);
+        for (i, expected) in series.harmonics.iter().enumerate() {
+            assert!((harmonics[i].amplitude - expected.amplitude).abs() < 1e-9, "{harmonics:?}");
+            if expected.amplitude > 0.0 {
+                assert!((harmonics[i].phase - expected.phase).abs() < 1e-9, "{harmonics:?}");
+            }
+        }
+        assert!(harmonics[4].amplitude < 1e-9);
+
+        // Starting from the modelled pump reproduces its spectrum
+        let modelled = PumpSource::new(3000.0, 3, 0.3, 44100.0);
+        let mut copy = PumpSource::new(3000.0, 3, 0.3, 44100.0);
+        copy.set_series(Some(HarmonicSeries::of(&modelled, 20)));
+        let (a, b) = (modelled.harmonic_levels(20), copy.harmonic_levels(20));
+        assert!(a.iter().zip(&b).all(|(a, b)| (a - b).abs() < 1e-9), "{a:?} vs {b:?}");
+
+        assert!(HarmonicSeries::from_table("1.5, 0.5").is_err());
+        assert!(HarmonicSeries::default().validate().is_err());
+    }
+
+    #[test]
+    fn test_analytic_harmonics_match_the_waveform() {
+        let check = |pump: &PumpSource| {
+            let analytic = pump.analytic_harmonics(12).unwrap();
+            let sampled = pump.harmonics(12);
+            for (a, s) in analytic.iter().zip(&sampled) {
+                assert!((a.amplitude - s.amplitude).abs() < 1e-5, "{analytic:?} vs {sampled:?}");
+                if a.amplitude > 1e-3 {
+                    let difference = (a.phase - s.phase + PI).rem_euclid(2.0 * PI) - PI;
+                    assert!(difference.abs() < 1e-3, "{analytic:?} vs {sampled:?}");
+                }
+            }
+        };
+        check(&PumpSource::new(3000.0, 3, 0.3, 44100.0));
+
+        // Suction strokes, uneven valves and a short linear stroke
+        let mut pump = PumpSource::new(2400.0, 2, 0.35, 44100.0);
+        pump.set_timing(StrokeTiming {
+            suction_fraction: 0.4,
+            suction_level: 0.6,
+            overlap_sharing: 0.0,
+        });
+        pump.set_variation(ValveVariation {
+            amplitudes: vec![0.8],
+            phase_offsets: vec![0.0, 0.03],
+            jitter: 0.0,
+        });
+        pump.set_stroke(0.7);
+        check(&pump);
+
+        // Overlapping pulses sharing the outlet have no closed form
+        pump.set_timing(StrokeTiming {
+            overlap_sharing: 0.5,
+            ..StrokeTiming::default()
+        });
+        pump.set_params(2400.0, 2, 0.7);
+        assert!(pump.analytic_harmonics(4).is_none());
+        pump.set_timing(StrokeTiming::default());
+        pump.set_piston(Some(PistonCrank::default()));
+        assert!(pump.analytic_harmonics(4).is_none());
+    }
+
+    #[test]
+    fn test_motor_noise() {
+        let sample_rate = 44100.0;
+        let level = |samples: &[f64], frequency: f64| {
+            let (re, im) = samples.iter().enumerate().fold((0.0, 0.0), |(re, im), (i, &x)| {
+                let angle = 2.0 * PI * frequency * i as f64 / sample_rate;
+                (re + x * angle.cos(), im + x * angle.sin())
+            });
+            2.0 * (re * re + im * im).sqrt() / samples.len() as f64
+        };
+
+        // Hum at twice the mains frequency, split between the muffler
+        // and the bypass; the PWM whine is above the Nyquist frequency
+        let mut pump = PumpSource::new(3000.0, 1, 0.5, sample_rate);
+        pump.set_motor(Some(MotorNoise {
+            mains_frequency: 55.0,
+            hum: 0.1,
+            pwm_frequency: 30_000.0,
+            bearing: 0.0,
+            bypass: 0.25,
+            ..MotorNoise::default()
+        }));
+        let through = pump.generate(44100);
+        let bypass = pump.take_bypass();
+        assert!((level(&through, 110.0) - 0.075).abs() < 1e-3, "{}", level(&through, 110.0));
+        assert!((level(&bypass, 110.0) - 0.025).abs() < 1e-3);
+        assert!(bypass.iter().map(|x| x.abs()).fold(0.0, f64::max) < 0.0251);
+        assert!(pump.take_bypass().is_empty());
+
+        // A bearing at 0.2 × shaft speed puts sidebands 10 Hz either side
+        // of the 50 Hz fundamental
+        let mut pump = PumpSource::new(3000.0, 1, 0.5, sample_rate);
+        let fundamental = pump.harmonic_levels(1)[0];
+        pump.set_motor(Some(MotorNoise {
+            hum: 0.0,
+            pwm_whine: 0.0,
+            bearing_order: 0.2,
+            bearing: 0.5,
+            ..MotorNoise::default()
+        }));
+        let modulated = pump.generate(44100);
+        assert!((level(&modulated, 60.0) / (0.25 * fundamental) - 1.0).abs() < 0.01);
+        assert!((level(&modulated, 40.0) / (0.25 * fundamental) - 1.0).abs() < 0.01);
+
+        assert!(MotorNoise { bypass: 1.5, ..MotorNoise::default() }.validate().is_err());
+        assert!(MotorNoise::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_speed_change_ramps_over_the_block() {
+        // 600 rpm at 6 kHz turns 2π/600 rad per sample
+        let step = 2.0 * PI / 600.0;
+        let mut pump = PumpSource::new(600.0, 3, 0.5, 6000.0);
+        pump.generate(100);
+        assert!((pump.phase - 100.0 * step).abs() < 1e-9);
+
+        // Doubling the speed ramps the step up across the next block
+        pump.set_params(1200.0, 3, 0.5);
+        let start = pump.phase;
+        pump.generate(100);
+        assert!((pump.phase - start - 150.5 * step).abs() < 1e-9);
+        let start = pump.phase;
+        pump.generate(100);
+        assert!((pump.phase - start - 200.0 * step).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_speed_wander() {
+        // At 1 kHz the deviation has the requested RMS and decorrelates
+        // over 1/(2π·bandwidth) s
+        let wander = SpeedWander {
+            depth: 0.03,
+            bandwidth: 0.5,
+        };
+        let mut pump = PumpSource::new(3000.0, 1, 0.5, 1000.0);
+        pump.set_wander(Some(wander));
+        let deviations: Vec<f64> = (0..200_000)
+            .map(|_| {
+                pump.deviation = pump.next_deviation(&wander);
+                pump.deviation
+            })
+            .collect();
+        let power = deviations.iter().map(|d| d * d).sum::<f64>() / deviations.len() as f64;
+        assert!((power.sqrt() / wander.depth - 1.0).abs() < 0.15, "{}", power.sqrt());
+        let lag = 100;
+        let correlation = deviations.iter().zip(&deviations[lag..]).map(|(a, b)| a * b).sum::<f64>()
+            / (deviations.len() - lag) as f64
+            / power;
+        let expected = (-2.0 * PI * wander.bandwidth * lag as f64 / 1000.0).exp();
+        assert!((correlation - expected).abs() < 0.1, "{correlation} vs {expected}");
+
+        // The wandering pump drifts out of step with a steady one, and
+        // holds its speed again once the wander is removed
+        let mut steady = PumpSource::new(3000.0, 1, 0.5, 44100.0);
+        let mut wandering = PumpSource::new(3000.0, 1, 0.5, 44100.0);
+        wandering.set_wander(Some(wander));
+        let (a, b) = (steady.generate(44100), wandering.generate(44100));
+        assert!(a.iter().zip(&b).any(|(a, b)| (a - b).abs() > 0.1));
+        wandering.set_wander(None);
+        assert_eq!(wandering.deviation, 0.0);
+        assert!(SpeedWander { depth: 0.5, ..wander }.validate().is_err());
+        assert!(SpeedWander { bandwidth: 0.0, ..wander }.validate().is_err());
+    }
+
+    #[test]
+    fn test_reed_valve_dynamics() {
+        let ideal = PumpSource::new(3000.0, 1, 0.3, 44100.0);
+        let ideal_levels = ideal.harmonic_levels(60);
+        let with_reed = |reed: ReedValve| {
+            let mut pump = PumpSource::new(3000.0, 1, 0.3, 44100.0);
+            pump.set_reed(Some(reed));
+            pump
+        };
+
+        // A stiff, light, well damped reed that opens at once passes the
+        // stroke almost unchanged
+        let stiff = with_reed(ReedValve {
+            stiffness: 1e6,
+            damping: 0.9,
+            opening_pressure: 0.0,
+            slap: 0.0,
+            ..ReedValve::default()
+        });
+        let levels = stiff.harmonic_levels(3);
+        assert!((levels[0] / ideal_levels[0] - 1.0).abs() < 0.02, "{levels:?} vs {ideal_levels:?}");
+
+        // The default reed ticks: its slap rings near 1 kHz (harmonic 20
+        // of the 50 Hz revolution), far above what the smooth pulse holds
+        let reed = ReedValve::default();
+        assert!((reed.natural_frequency() - 1006.6).abs() < 0.1);
+        let ticking = with_reed(reed).harmonic_levels(60);
+        let high = |levels: &[f64]| levels[15..25].iter().map(|l| l * l).sum::<f64>().sqrt();
+        assert!(high(&ticking) > 5.0 * high(&ideal_levels), "{} vs {}", high(&ticking), high(&ideal_levels));
+
+        // The opening pressure holds the valve shut at the start of the
+        // stroke
+        let lagging = with_reed(ReedValve {
+            opening_pressure: 0.5,
+            slap: 0.0,
+            ..reed
+        });
+        let revolution = lagging.revolution(1000);
+        assert!(revolution[..50].iter().all(|&p| p == 0.0));
+        assert!(revolution[100..300].iter().any(|&p| p > 0.0));
+
+        assert!(ReedValve { damping: 1.0, ..reed }.validate().is_err());
+        assert!(reed.validate().is_ok());
+    }
+
+    #[test]
+    fn test_small_speed_change_keeps_table() {
+        // A nudge in speed that keeps the harmonic cutoff and the reed's
+        // speed step reuses the wavetable instead of rebuilding it
+        let mut pump = PumpSource::new(3000.0, 3, 0.5, 44100.0);
+        pump.set_reed(Some(ReedValve::default()));
+        pump.generate(64);
+        let (spectrum, table) = (pump.spectrum.clone(), pump.table.clone());
+        pump.set_rpm(3002.0);
+        pump.generate(64);
+        assert_eq!(pump.spectrum, spectrum);
+        assert_eq!(pump.table, table);
+
+        // A larger one moves the cutoff and rebuilds only the table
+        pump.set_rpm(2991.0);
+        pump.generate(64);
+        assert_eq!(pump.spectrum, spectrum);
+        assert_eq!(pump.table_highest, Some(442));
+    }
 }
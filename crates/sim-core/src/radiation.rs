@@ -0,0 +1,177 @@
+use crate::metrics::{self, Weighting};
+use crate::muffler::Muffler;
+use crate::numerics::Termination;
+use crate::SimParams;
+use std::f64::consts::PI;
+
+/// Reference RMS pressure of sound pressure level, 20 µPa.
+const REFERENCE_PRESSURE: f64 = 20e-6;
+
+/// Highest pump harmonic frequency in Hz included in the tones.
+const HIGHEST_TONE: f64 = 20_000.0;
+
+/// Where the radiated sound is predicted, and how hard the pump drives
+/// the muffler.
+///
+/// The pump is taken as an ideal volume-velocity source: its diaphragms
+/// displace the same volume whatever the muffler's input impedance.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Listener {
+    /// Peak volume velocity in m³/s of the pump's fundamental at the
+    /// inlet. The higher harmonics follow the pump waveform. A pump
+    /// calibrated by its displacement supplies its own instead.
+    pub source_strength: f64,
+    /// Distance in metres from the outlet.
+    pub distance: f64,
+}
+
+impl Default for Listener {
+    fn default() -> Self {
+        Self {
+            source_strength: 1e-5,
+            distance: 1.0,
+        }
+    }
+}
+
+impl Listener {
+    /// Check that the source strength and distance are positive.
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.source_strength > 0.0 && self.source_strength.is_finite()) {
+            return Err(format!("source_strength must be > 0, got {}", self.source_strength));
+        }
+        if !(self.distance > 0.0 && self.distance.is_finite()) {
+            return Err(format!("distance must be > 0, got {}", self.distance));
+        }
+        Ok(())
+    }
+}
+
+/// Sound pressure level at the listener.
+///
+/// The outlet radiates the power its load absorbs, spread evenly over a
+/// sphere (a half sphere for a flanged outlet) of the listening
+/// distance: p²_rms = ρc·W/(Ω·r²). An anechoic outlet radiates like a
+/// bare pipe end that reflects nothing. Directivity, the shell breakout
+/// path and room reflections are left out, and the transfer matrices
+/// supply the response whichever engine runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RadiatedSound {
+    /// Distance in metres from the outlet.
+    pub distance: f64,
+    /// SPL in dB re 20 µPa at each frequency bin of the result, were the
+    /// pump to drive a tone of its fundamental's strength at that
+    /// frequency.
+    pub spectrum: Vec<f64>,
+    /// Frequency in Hz and SPL in dB re 20 µPa of each pump harmonic up to
+    /// 20 kHz, fundamental first.
+    pub tones: Vec<(f64, f64)>,
+}
+
+impl RadiatedSound {
+    /// Overall SPL of the pump tones in dB re 20 µPa under `weighting`.
+    pub fn overall_level(&self, weighting: Weighting) -> f64 {
+        metrics::overall_level(&self.tones, weighting)
+    }
+}
+
+/// RMS pressure in Pa at `distance` from the outlet of `muffler` per m³/s
+/// of peak volume velocity driven into its inlet at angular frequency
+/// `omega`.
+pub fn pressure_per_volume_velocity(muffler: &Muffler, omega: f64, distance: f64, c: f64, rho: f64) -> f64 {
+    let t = muffler.total_transfer_matrix(omega, c, rho);
+    let z_load = muffler.load_impedance(omega, c);
+    // Upstream volume velocity U₁ = (C·Z_L + D)·U₂
+    let outlet = 1.0 / (t.c * z_load + t.d).norm();
+    let power = 0.5 * outlet * outlet * z_load.re;
+    let solid_angle = match muffler.termination() {
+        Termination::Flanged { .. } => 2.0 * PI,
+        _ => 4.0 * PI,
+    };
+    (rho * c * power.max(0.0) / solid_angle).sqrt() / distance
+}
+
+/// Sound the muffler built from `params` radiates to `params.listener`,
+/// at `frequencies` and at the pump harmonics.
+pub fn radiate(params: &SimParams, muffler: &Muffler, frequencies: &[f64], c: f64, rho: f64) -> Option<RadiatedSound> {
+    let listener = params.listener?;
+    let strength = params.source_strength()?;
+    let level = |frequency: f64, strength: f64| {
+        let pressure = strength * pressure_per_volume_velocity(muffler, 2.0 * PI * frequency, listener.distance, c, rho);
+        spl(pressure)
+    };
+    let spectrum = frequencies
+        .iter()
+        .map(|&f| if f > 0.0 { level(f, strength) } else { f64::NEG_INFINITY })
+        .collect();
+
+    let tones = params
+        .pump_harmonics(HIGHEST_TONE)
+        .into_iter()
+        .map(|(frequency, amplitude)| (frequency, level(frequency, strength * amplitude)))
+        .collect();
+
+    Some(RadiatedSound {
+        distance: listener.distance,
+        spectrum,
+        tones,
+    })
+}
+
+/// Sound pressure level in dB re 20 µPa of `pressure` Pa RMS.
+pub fn spl(pressure: f64) -> f64 {
+    20.0 * (pressure / REFERENCE_PRESSURE).log10()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_pipe_radiation() {
+        let (c, rho) = (343.0, 1.2);
+        // A chamber barely wider than the pipes passes the whole volume
+        // velocity into the anechoic load: W = ½Q²·ρc/S
+        let params = SimParams {
+            chamber_diameter: 6.01e-3,
+            listener: Some(Listener::default()),
+            ..SimParams::default()
+        };
+        let muffler = Muffler::from_params(&params).unwrap();
+        let q = 1e-5;
+        let s = PI / 4.0 * params.inlet_diameter.powi(2);
+        let expected = (rho * c * 0.5 * q * q * rho * c / s / (4.0 * PI)).sqrt();
+        let pressure = q * pressure_per_volume_velocity(&muffler, 2.0 * PI * 200.0, 1.0, c, rho);
+        assert!((pressure / expected - 1.0).abs() < 0.01, "{pressure} vs {expected}");
+
+        // Inverse square law
+        let far = q * pressure_per_volume_velocity(&muffler, 2.0 * PI * 200.0, 2.0, c, rho);
+        assert!((spl(pressure) - spl(far) - 6.02).abs() < 0.01);
+
+        let sound = radiate(&params, &muffler, &[0.0, 200.0], c, rho).unwrap();
+        assert_eq!(sound.spectrum[0], f64::NEG_INFINITY);
+        assert!((sound.spectrum[1] - spl(pressure)).abs() < 1e-9);
+        // 3 valves at 3000 rpm: harmonics of 150 Hz up to 20 kHz
+        assert_eq!(sound.tones.len(), 133);
+        assert!((sound.tones[0].0 - 150.0).abs() < 1e-9);
+        assert!(sound.overall_level(Weighting::Z) >= sound.tones[0].1);
+        assert!(radiate(&SimParams::default(), &muffler, &[200.0], c, rho).is_none());
+    }
+
+    #[test]
+    fn test_open_outlet_radiates_less_at_low_frequency() {
+        let (c, rho) = (343.0, 1.2);
+        let at = |outlet_termination| {
+            let params = SimParams {
+                outlet_termination,
+                ..SimParams::default()
+            };
+            let muffler = Muffler::from_params(&params).unwrap();
+            spl(pressure_per_volume_velocity(&muffler, 2.0 * PI * 100.0, 1.0, c, rho))
+        };
+        // A small open end reflects most of the low-frequency sound back
+        let (anechoic, open) = (at(Termination::Anechoic), at(Termination::Unflanged { diameter: None }));
+        assert!(open < anechoic - 10.0, "{open} vs {anechoic}");
+        assert!(at(Termination::Flanged { diameter: None }).is_finite());
+    }
+}
@@ -0,0 +1,146 @@
+use std::path::Path;
+use std::sync::Arc;
+
+use crate::pump::SampleSource;
+use crate::wav;
+
+/// A recorded pump waveform, played in a loop in place of the synthesized
+/// pump to hear a real pump through candidate mufflers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Recording {
+    /// Samples with the DC offset removed.
+    samples: Vec<f64>,
+    /// Sample rate of the recording in Hz.
+    sample_rate: f64,
+}
+
+impl Recording {
+    /// A recording of `samples` at `sample_rate` Hz. The DC offset of a
+    /// pressure recording is removed, like the synthesized pump's.
+    pub fn new(samples: Vec<f64>, sample_rate: f64) -> Result<Self, String> {
+        if samples.is_empty() {
+            return Err("recording has no samples".to_string());
+        }
+        if !(sample_rate > 0.0 && sample_rate.is_finite()) {
+            return Err(format!("sample_rate must be > 0, got {sample_rate}"));
+        }
+        if !samples.iter().all(|s| s.is_finite()) {
+            return Err("recording contains non-finite samples".to_string());
+        }
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        Ok(Self {
+            samples: samples.into_iter().map(|s| s - mean).collect(),
+            sample_rate,
+        })
+    }
+
+    /// Load a WAV recording, mixed down to mono.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let (samples, sample_rate) = wav::read(path)?;
+        Self::new(samples, sample_rate)
+    }
+
+    pub fn samples(&self) -> &[f64] {
+        &self.samples
+    }
+
+    /// Sample rate of the recording in Hz.
+    pub fn sample_rate(&self) -> f64 {
+        self.sample_rate
+    }
+
+    /// Length of one loop in seconds.
+    pub fn duration(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate
+    }
+}
+
+/// Loops a [`Recording`] at an output sample rate, optionally sped up or
+/// slowed down to follow the pump's RPM.
+pub struct RecordingPlayer {
+    recording: Arc<Recording>,
+    /// Read position in recording samples.
+    position: f64,
+    /// Output sample rate in Hz.
+    sample_rate: f64,
+    /// Playback speed relative to the recording; 2 plays an octave up.
+    pitch: f64,
+}
+
+impl RecordingPlayer {
+    pub fn new(recording: Arc<Recording>, sample_rate: f64) -> Self {
+        Self {
+            recording,
+            position: 0.0,
+            sample_rate,
+            pitch: 1.0,
+        }
+    }
+
+    /// The recording being played.
+    pub fn recording(&self) -> &Arc<Recording> {
+        &self.recording
+    }
+
+    /// Set the playback speed, e.g. the current RPM over the RPM the pump
+    /// was recorded at.
+    pub fn set_pitch(&mut self, pitch: f64) {
+        self.pitch = pitch;
+    }
+
+    /// Generate `count` samples, linearly interpolated between recording
+    /// samples and wrapping from the end of the recording to its start.
+    pub fn generate(&mut self, count: usize) -> Vec<f64> {
+        let samples = &self.recording.samples;
+        let length = samples.len() as f64;
+        let step = self.pitch * self.recording.sample_rate / self.sample_rate;
+        (0..count)
+            .map(|_| {
+                let i = self.position as usize;
+                let t = self.position - i as f64;
+                let next = if i + 1 < samples.len() { i + 1 } else { 0 };
+                let sample = samples[i] + (samples[next] - samples[i]) * t;
+                self.position = (self.position + step) % length;
+                sample
+            })
+            .collect()
+    }
+}
+
+impl SampleSource for RecordingPlayer {
+    fn generate(&mut self, count: usize) -> Vec<f64> {
+        RecordingPlayer::generate(self, count)
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        self.sample_rate = sample_rate;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recording_loops_and_follows_pitch() {
+        let recording = Recording::new(vec![1.0, 2.0, 3.0, 4.0], 8000.0).unwrap();
+        assert_eq!(recording.samples(), &[-1.5, -0.5, 0.5, 1.5]);
+        assert!((recording.duration() - 0.0005).abs() < 1e-12);
+        assert!(Recording::new(Vec::new(), 8000.0).is_err());
+        assert!(Recording::new(vec![1.0], 0.0).is_err());
+
+        // Played at twice the recording's rate: every other sample is
+        // interpolated, and the loop wraps back to the start
+        let recording = Arc::new(recording);
+        let mut player = RecordingPlayer::new(Arc::clone(&recording), 16_000.0);
+        assert_eq!(
+            player.generate(10),
+            vec![-1.5, -1.0, -0.5, 0.0, 0.5, 1.0, 1.5, 0.0, -1.5, -1.0]
+        );
+
+        // Pitched up an octave at the recording's rate, every other sample
+        let mut player = RecordingPlayer::new(recording, 8000.0);
+        player.set_pitch(2.0);
+        assert_eq!(player.generate(4), vec![-1.5, 0.5, -1.5, 0.5]);
+    }
+}
@@ -0,0 +1,166 @@
+/// Whether a feature of the TL curve is a maximum or a minimum.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureKind {
+    /// TL maximum: the muffler blocks this frequency best.
+    Peak,
+    /// TL minimum: a pass band, where the muffler lets sound through.
+    Null,
+}
+
+/// A peak or null of a transmission loss curve.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Resonance {
+    pub kind: FeatureKind,
+    /// Frequency in Hz, refined between samples by a parabola through the
+    /// extreme sample and its neighbours.
+    pub frequency: f64,
+    /// TL in dB at the extreme sample.
+    pub transmission_loss: f64,
+    /// How far in dB the feature stands out from the curve on either side
+    /// before the curve passes it again, the lower of the two sides.
+    pub prominence: f64,
+    /// Quality factor frequency / 3 dB bandwidth, when the curve falls (or
+    /// rises) 3 dB on both sides within the sweep.
+    pub q: Option<f64>,
+}
+
+impl Resonance {
+    /// Whether `frequency` Hz lies inside the feature's 3 dB band, or
+    /// within `tolerance` Hz of it when the band is unknown.
+    pub fn covers(&self, frequency: f64, tolerance: f64) -> bool {
+        let half_width = self.q.map_or(tolerance, |q| self.frequency / (2.0 * q));
+        (frequency - self.frequency).abs() <= half_width.max(tolerance)
+    }
+}
+
+/// Find the peaks and nulls of the transmission loss `tl` sampled at the
+/// ascending `frequencies`, keeping those at least `min_prominence` dB
+/// prominent. The DC bin is ignored. Features come in frequency order.
+pub fn find(frequencies: &[f64], tl: &[f64], min_prominence: f64) -> Vec<Resonance> {
+    let start = frequencies.iter().position(|&f| f > 0.0).unwrap_or(frequencies.len());
+    let (frequencies, tl) = (&frequencies[start..], &tl[start..]);
+    let n = tl.len();
+    let mut features = Vec::new();
+    for i in 1..n.saturating_sub(1) {
+        let kind = if tl[i] > tl[i - 1] && tl[i] >= tl[i + 1] {
+            FeatureKind::Peak
+        } else if tl[i] < tl[i - 1] && tl[i] <= tl[i + 1] {
+            FeatureKind::Null
+        } else {
+            continue;
+        };
+        // Work on the curve flipped so that every feature is a peak
+        let sign = if kind == FeatureKind::Peak { 1.0 } else { -1.0 };
+        let height = |j: usize| sign * tl[j];
+        let prominence = side_depth(height, i, (0..i).rev()).min(side_depth(height, i, i + 1..n));
+        if prominence < min_prominence {
+            continue;
+        }
+        let width = |range: &mut dyn Iterator<Item = usize>| {
+            let mut previous = i;
+            for j in range {
+                if height(j) <= height(i) - 3.0 {
+                    let t = (height(previous) - (height(i) - 3.0)) / (height(previous) - height(j));
+                    return Some(frequencies[previous] + (frequencies[j] - frequencies[previous]) * t);
+                }
+                previous = j;
+            }
+            None
+        };
+        let lower = width(&mut (0..i).rev());
+        let upper = width(&mut (i + 1..n));
+        let frequency = vertex(&frequencies[i - 1..=i + 1], &tl[i - 1..=i + 1]);
+        features.push(Resonance {
+            kind,
+            frequency,
+            transmission_loss: tl[i],
+            prominence,
+            q: lower.zip(upper).map(|(lower, upper)| frequency / (upper - lower)),
+        });
+    }
+    features
+}
+
+/// The nulls of `resonances` that a pump harmonic at one of `harmonics`
+/// Hz falls into (see [`Resonance::covers`]), as `(harmonic number,
+/// null)`.
+pub fn harmonic_nulls<'a>(
+    resonances: &'a [Resonance],
+    harmonics: &[f64],
+    tolerance: f64,
+) -> Vec<(usize, &'a Resonance)> {
+    harmonics
+        .iter()
+        .enumerate()
+        .flat_map(|(i, &harmonic)| {
+            resonances
+                .iter()
+                .filter(move |null| null.kind == FeatureKind::Null && null.covers(harmonic, tolerance))
+                .map(move |null| (i + 1, null))
+        })
+        .collect()
+}
+
+/// Depth below the top at `i` of the lowest point of `side` before the
+/// curve rises above the top again.
+fn side_depth(height: impl Fn(usize) -> f64, i: usize, side: impl Iterator<Item = usize>) -> f64 {
+    let mut lowest = height(i);
+    for j in side {
+        if height(j) > height(i) {
+            break;
+        }
+        lowest = lowest.min(height(j));
+    }
+    height(i) - lowest
+}
+
+/// Abscissa of the vertex of the parabola through three points, kept
+/// between the outer two.
+fn vertex(x: &[f64], y: &[f64]) -> f64 {
+    let (d1, d2) = ((y[1] - y[0]) / (x[1] - x[0]), (y[2] - y[1]) / (x[2] - x[1]));
+    let curvature = (d2 - d1) / (x[2] - x[0]);
+    if curvature == 0.0 {
+        return x[1];
+    }
+    (0.5 * (x[0] + x[1]) - d1 / (2.0 * curvature)).clamp(x[0], x[2])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_resonances() {
+        // A resonance curve 20 dB high at 1000 Hz with Q = 10 on a 5 dB
+        // floor, next to a null at 3000 Hz
+        let frequencies: Vec<f64> = (0..=5000).map(|i| i as f64).collect();
+        let tl: Vec<f64> = frequencies
+            .iter()
+            .map(|&f| {
+                let x = 10.0 * (f / 1000.0 - 1000.0 / f.max(1.0));
+                let peak = 10.0 * (100.0 / (1.0 + x * x)).log10();
+                let null = -4.0 * (-((f - 3000.0) / 50.0).powi(2)).exp();
+                5.0 + peak.max(0.0) + null
+            })
+            .collect();
+        let features = find(&frequencies, &tl, 1.0);
+        assert_eq!(features.len(), 2, "{features:?}");
+        let (peak, null) = (features[0], features[1]);
+        assert_eq!(peak.kind, FeatureKind::Peak);
+        assert!((peak.frequency - 1000.0).abs() < 0.5);
+        assert!((peak.transmission_loss - 25.0).abs() < 1e-9);
+        assert!((peak.prominence - 20.0).abs() < 0.1);
+        assert!((peak.q.unwrap() - 10.0).abs() < 0.1, "{:?}", peak.q);
+        assert_eq!(null.kind, FeatureKind::Null);
+        assert!((null.frequency - 3000.0).abs() < 0.5);
+        assert!((null.prominence - 4.0).abs() < 0.01);
+
+        // Small ripple is ignored at a higher threshold
+        assert_eq!(find(&frequencies, &tl, 10.0).len(), 1);
+
+        // The third harmonic of 1000 Hz sits in the null
+        let hits = harmonic_nulls(&features, &[1000.0, 2000.0, 3010.0], 10.0);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0, 3);
+    }
+}
@@ -0,0 +1,23 @@
+/// Uniform sample in [−1, 1) from a xorshift64 generator, stepping its
+/// `state` (which must not be zero).
+pub(crate) fn uniform(state: &mut u64) -> f64 {
+    *state ^= *state << 13;
+    *state ^= *state >> 7;
+    *state ^= *state << 17;
+    (*state >> 11) as f64 / (1u64 << 52) as f64 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uniform_spans_its_range() {
+        let mut state = 0x9E37_79B9_7F4A_7C15;
+        let samples: Vec<f64> = (0..10_000).map(|_| uniform(&mut state)).collect();
+        assert!(samples.iter().all(|x| (-1.0..1.0).contains(x)));
+        let mean = samples.iter().sum::<f64>() / samples.len() as f64;
+        assert!(mean.abs() < 0.05, "{mean}");
+        assert!(samples.iter().any(|&x| x < -0.9) && samples.iter().any(|&x| x > 0.9));
+    }
+}
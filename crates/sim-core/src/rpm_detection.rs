@@ -0,0 +1,214 @@
+use realfft::RealFftPlanner;
+use std::f64::consts::PI;
+
+use crate::SimParams;
+
+/// Number of harmonics combined in the harmonic product spectrum.
+const HPS_HARMONICS: usize = 5;
+/// Floor of each harmonic's magnitude relative to the strongest bin, so
+/// that a harmonic falling in a spectral zero of the pulse shape does not
+/// rule out the true fundamental.
+const HPS_FLOOR: f64 = 1e-3;
+/// Search range for the valve-pulse fundamental in Hz.
+const MIN_FUNDAMENTAL: f64 = 10.0;
+const MAX_FUNDAMENTAL: f64 = 2000.0;
+/// Longest analysis FFT; longer recordings are truncated.
+const MAX_FFT_SIZE: usize = 1 << 19;
+
+/// One (RPM, valve count) interpretation of a detected fundamental.
+#[derive(Debug, Clone)]
+pub struct RpmCandidate {
+    /// Motor speed in RPM implied by `num_valves`.
+    pub rpm: f64,
+    /// Assumed number of valves.
+    pub num_valves: u32,
+    /// Strongest rotation-order component (multiples of RPM/60 that are
+    /// *not* multiples of the fundamental), in dB relative to the
+    /// fundamental. A perfectly symmetric pump shows only the noise floor
+    /// here; real pumps with unequal valves leak clearly above it, which is
+    /// the evidence that this valve count is right.
+    pub rotation_order_level_db: f64,
+}
+
+impl RpmCandidate {
+    /// Copy this interpretation into the simulation parameters.
+    pub fn apply_to(&self, params: &mut SimParams) {
+        params.rpm = self.rpm;
+        params.num_valves = self.num_valves;
+    }
+}
+
+/// Result of analysing a pump recording.
+#[derive(Debug, Clone)]
+pub struct RpmEstimate {
+    /// Detected valve-pulse fundamental in Hz (`num_valves × RPM / 60`).
+    pub fundamental: f64,
+    /// One candidate per valve count from 1 to `max_valves`.
+    pub candidates: Vec<RpmCandidate>,
+}
+
+impl RpmEstimate {
+    /// The candidate with the strongest rotation-order evidence. Falls back
+    /// to the single-valve interpretation when no candidate rises above
+    /// the noise floor by at least 20 dB.
+    pub fn best(&self) -> Option<&RpmCandidate> {
+        let floor = self
+            .candidates
+            .iter()
+            .map(|c| c.rotation_order_level_db)
+            .fold(f64::INFINITY, f64::min);
+        self.candidates
+            .iter()
+            .filter(|c| c.num_valves > 1 && c.rotation_order_level_db > floor + 20.0)
+            .max_by(|a, b| a.rotation_order_level_db.total_cmp(&b.rotation_order_level_db))
+            .or_else(|| self.candidates.first())
+    }
+}
+
+/// Estimate the pump fundamental and RPM/valve-count candidates from a
+/// recording of the pump.
+///
+/// Uses a harmonic product spectrum over 10 Hz–2 kHz. Returns `None` if the
+/// recording is too short (under ~0.1 s) or silent.
+pub fn detect_rpm(samples: &[f64], sample_rate: f64, max_valves: u32) -> Option<RpmEstimate> {
+    let (magnitude, bin_width) = magnitude_spectrum(samples, sample_rate)?;
+
+    // Harmonic product spectrum (as a sum of logs). Each harmonic looks at a
+    // small neighbourhood so window smearing doesn't miss the peak.
+    let peak_near = |bin: f64, radius: usize| -> f64 {
+        let centre = bin.round() as usize;
+        let lo = centre.saturating_sub(radius);
+        let hi = (centre + radius).min(magnitude.len() - 1);
+        if lo > hi {
+            return 0.0;
+        }
+        magnitude[lo..=hi].iter().cloned().fold(0.0, f64::max)
+    };
+    let eps = magnitude.iter().cloned().fold(0.0, f64::max) * HPS_FLOOR;
+    if eps <= 0.0 {
+        return None;
+    }
+
+    let min_bin = (MIN_FUNDAMENTAL / bin_width).ceil().max(1.0) as usize;
+    let max_bin = ((MAX_FUNDAMENTAL / bin_width) as usize).min((magnitude.len() - 1) / HPS_HARMONICS);
+    if min_bin >= max_bin {
+        return None;
+    }
+
+    let mut best_bin = min_bin;
+    let mut best_score = f64::NEG_INFINITY;
+    for bin in min_bin..=max_bin {
+        let score: f64 = (1..=HPS_HARMONICS)
+            .map(|h| (peak_near((bin * h) as f64, h / 2) + eps).ln())
+            .sum();
+        if score > best_score {
+            best_score = score;
+            best_bin = bin;
+        }
+    }
+
+    // Refine to the local maximum and interpolate the peak position.
+    let lo = best_bin.saturating_sub(2).max(1);
+    let hi = (best_bin + 2).min(magnitude.len() - 2);
+    let peak = (lo..=hi)
+        .max_by(|&a, &b| magnitude[a].total_cmp(&magnitude[b]))
+        .unwrap_or(best_bin);
+    let (l, m, r) = (
+        (magnitude[peak - 1] + eps).ln(),
+        (magnitude[peak] + eps).ln(),
+        (magnitude[peak + 1] + eps).ln(),
+    );
+    let denom = l - 2.0 * m + r;
+    let offset = if denom.abs() > 1e-12 { 0.5 * (l - r) / denom } else { 0.0 };
+    let fundamental = (peak as f64 + offset.clamp(-0.5, 0.5)) * bin_width;
+    let fundamental_level = magnitude[peak];
+
+    let candidates = (1..=max_valves.max(1))
+        .map(|n| {
+            let rotation = fundamental / n as f64;
+            let strongest = (1..n as usize * HPS_HARMONICS)
+                .filter(|k| k % n as usize != 0)
+                .map(|k| peak_near(k as f64 * rotation / bin_width, 2))
+                .fold(0.0, f64::max);
+            let rotation_order_level_db = if n == 1 {
+                0.0
+            } else {
+                20.0 * ((strongest + eps) / fundamental_level).log10()
+            };
+            RpmCandidate {
+                rpm: 60.0 * rotation,
+                num_valves: n,
+                rotation_order_level_db,
+            }
+        })
+        .collect();
+
+    Some(RpmEstimate {
+        fundamental,
+        candidates,
+    })
+}
+
+/// Hann-windowed, zero-padded magnitude spectrum. Returns the magnitudes and
+/// the bin width in Hz.
+fn magnitude_spectrum(samples: &[f64], sample_rate: f64) -> Option<(Vec<f64>, f64)> {
+    let len = samples.len().min(MAX_FFT_SIZE);
+    if (len as f64) < 0.1 * sample_rate {
+        return None;
+    }
+    let samples = &samples[..len];
+    let mean = samples.iter().sum::<f64>() / len as f64;
+
+    let fft_size = len.next_power_of_two();
+    let mut input = vec![0.0; fft_size];
+    for (i, (dst, &s)) in input.iter_mut().zip(samples).enumerate() {
+        let w = 0.5 * (1.0 - (2.0 * PI * i as f64 / (len - 1) as f64).cos());
+        *dst = (s - mean) * w;
+    }
+
+    let mut planner = RealFftPlanner::<f64>::new();
+    let fft = planner.plan_fft_forward(fft_size);
+    let mut spectrum = fft.make_output_vec();
+    fft.process(&mut input, &mut spectrum).ok()?;
+
+    let magnitude = spectrum.iter().map(|c| c.norm()).collect();
+    Some((magnitude, sample_rate / fft_size as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pump::PumpSource;
+
+    #[test]
+    fn test_detects_pump_fundamental_and_rpm() {
+        // At 50 % duty three overlapping half-sines cancel every odd multiple
+        // of 150 Hz, so use a shorter pulse that keeps the fundamental.
+        let sample_rate = 44100.0;
+        let mut pump = PumpSource::new(3000.0, 3, 0.3, sample_rate);
+        let samples = pump.generate(44100);
+
+        let estimate = detect_rpm(&samples, sample_rate, 6).expect("pump signal should be detected");
+        assert!(
+            (estimate.fundamental - 150.0).abs() < 1.0,
+            "fundamental = {} Hz, expected 150 Hz",
+            estimate.fundamental
+        );
+        let three = estimate
+            .candidates
+            .iter()
+            .find(|c| c.num_valves == 3)
+            .expect("3-valve candidate");
+        assert!((three.rpm - 3000.0).abs() < 20.0, "rpm = {}", three.rpm);
+
+        let mut params = SimParams::default();
+        three.apply_to(&mut params);
+        assert_eq!(params.num_valves, 3);
+    }
+
+    #[test]
+    fn test_rejects_silence_and_short_input() {
+        assert!(detect_rpm(&[0.0; 44100], 44100.0, 6).is_none());
+        assert!(detect_rpm(&[1.0; 100], 44100.0, 6).is_none());
+    }
+}
@@ -0,0 +1,96 @@
+/// Pump speed against time for playback: the pump follows it instead of
+/// a fixed RPM, so a spin-up or spin-down sweeps every harmonic across
+/// the muffler's resonances.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpmProfile {
+    /// Breakpoints as (time in s from the start, RPM), in time order and
+    /// starting at 0 s. The RPM is interpolated linearly between them.
+    pub points: Vec<(f64, f64)>,
+    /// Start over after the last breakpoint instead of holding its RPM.
+    pub looped: bool,
+}
+
+impl RpmProfile {
+    /// Spin up from `low` to `high` RPM over `spin_up` seconds, hold for
+    /// `hold` seconds, then spin back down over `spin_down` seconds.
+    pub fn ramp(low: f64, high: f64, spin_up: f64, hold: f64, spin_down: f64) -> Self {
+        Self {
+            points: vec![
+                (0.0, low),
+                (spin_up, high),
+                (spin_up + hold, high),
+                (spin_up + hold + spin_down, low),
+            ],
+            looped: false,
+        }
+    }
+
+    /// Check the breakpoints start at 0 s, never go back in time and only
+    /// hold positive RPMs.
+    pub fn validate(&self) -> Result<(), String> {
+        let Some(&(start, _)) = self.points.first() else {
+            return Err("an RPM profile needs at least one point".to_string());
+        };
+        if start != 0.0 {
+            return Err(format!("an RPM profile must start at 0 s, got {start}"));
+        }
+        for pair in self.points.windows(2) {
+            if !(pair[1].0 >= pair[0].0 && pair[1].0.is_finite()) {
+                return Err(format!("RPM profile times must not decrease, got {} after {}", pair[1].0, pair[0].0));
+            }
+        }
+        if let Some(&(time, rpm)) = self.points.iter().find(|&&(_, rpm)| !(rpm > 0.0 && rpm.is_finite())) {
+            return Err(format!("RPM profile speeds must be > 0, got {rpm} at {time} s"));
+        }
+        Ok(())
+    }
+
+    /// Length of the profile in seconds.
+    pub fn duration(&self) -> f64 {
+        self.points.last().map_or(0.0, |&(time, _)| time)
+    }
+
+    /// RPM `time` seconds into the profile.
+    pub fn rpm_at(&self, time: f64) -> f64 {
+        let duration = self.duration();
+        let time = if self.looped && duration > 0.0 { time.rem_euclid(duration) } else { time };
+        let next = self.points.partition_point(|&(t, _)| t <= time);
+        match (next.checked_sub(1).map(|i| self.points[i]), self.points.get(next)) {
+            (Some((t0, rpm0)), Some(&(t1, rpm1))) => rpm0 + (rpm1 - rpm0) * (time - t0) / (t1 - t0),
+            (Some((_, rpm)), None) | (None, Some(&(_, rpm))) => rpm,
+            (None, None) => 0.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rpm_profile() {
+        let ramp = RpmProfile::ramp(1000.0, 5000.0, 4.0, 2.0, 2.0);
+        assert!(ramp.validate().is_ok());
+        assert_eq!(ramp.duration(), 8.0);
+        assert_eq!(ramp.rpm_at(0.0), 1000.0);
+        assert_eq!(ramp.rpm_at(1.0), 2000.0);
+        assert_eq!(ramp.rpm_at(5.0), 5000.0);
+        assert_eq!(ramp.rpm_at(7.0), 3000.0);
+        assert_eq!(ramp.rpm_at(100.0), 1000.0);
+
+        // A looped profile starts over; an instant step takes the later RPM
+        let looped = RpmProfile {
+            points: vec![(0.0, 1000.0), (1.0, 2000.0), (1.0, 4000.0), (2.0, 4000.0)],
+            looped: true,
+        };
+        assert!(looped.validate().is_ok());
+        assert_eq!(looped.rpm_at(2.5), 1500.0);
+        assert_eq!(looped.rpm_at(1.0), 4000.0);
+
+        assert!(RpmProfile { points: vec![(1.0, 1000.0)], looped: false }.validate().is_err());
+        assert!(RpmProfile { points: vec![(0.0, 1000.0), (0.5, 0.0)], looped: false }.validate().is_err());
+        assert!(RpmProfile { points: vec![(0.0, 1000.0), (2.0, 900.0), (1.0, 800.0)], looped: false }
+            .validate()
+            .is_err());
+    }
+}
@@ -0,0 +1,462 @@
+use crate::elements::CrossSection;
+use crate::{compute_tl_range, SimParams, SweepSpacing};
+
+/// Finite-difference step relative to the parameter's value.
+const RELATIVE_STEP: f64 = 1e-4;
+
+/// Absolute step for a parameter that sits at zero. Every such field is a
+/// length in metres, a fraction or a volume flow, all of which this
+/// resolves without leaving their range.
+const ZERO_STEP: f64 = 1e-6;
+
+/// A continuous field of [`SimParams`] the transmission loss depends on,
+/// addressed by its path (e.g. `"orifice.hole_diameter"`).
+#[derive(Debug, Clone, Copy)]
+pub struct Parameter {
+    pub name: &'static str,
+    get: fn(&SimParams) -> Option<f64>,
+    set: fn(&mut SimParams, f64),
+}
+
+impl Parameter {
+    /// Current value, or `None` if the feature it belongs to is absent
+    /// (e.g. an orifice field without an orifice).
+    pub fn get(&self, params: &SimParams) -> Option<f64> {
+        (self.get)(params)
+    }
+
+    /// Set the value; does nothing if the feature is absent.
+    pub fn set(&self, params: &mut SimParams, value: f64) {
+        (self.set)(params, value)
+    }
+}
+
+/// Every continuous parameter of a design, in `SimParams` order. Discrete
+/// choices (models, gas, terminations) and the pump waveform, which does
+/// not enter the transmission loss, are left out.
+pub const PARAMETERS: &[Parameter] = &[
+    Parameter {
+        name: "inlet_diameter",
+        get: |p| Some(p.inlet_diameter),
+        set: |p, v| p.inlet_diameter = v,
+    },
+    Parameter {
+        name: "inlet_length",
+        get: |p| Some(p.inlet_length),
+        set: |p, v| p.inlet_length = v,
+    },
+    Parameter {
+        name: "chamber_diameter",
+        get: |p| p.chamber_shape.is_none().then_some(p.chamber_diameter),
+        set: |p, v| p.chamber_diameter = v,
+    },
+    Parameter {
+        name: "chamber_length",
+        get: |p| Some(p.chamber_length),
+        set: |p, v| p.chamber_length = v,
+    },
+    Parameter {
+        name: "outlet_diameter",
+        get: |p| Some(p.outlet_diameter),
+        set: |p, v| p.outlet_diameter = v,
+    },
+    Parameter {
+        name: "outlet_length",
+        get: |p| Some(p.outlet_length),
+        set: |p, v| p.outlet_length = v,
+    },
+    Parameter {
+        name: "inlet_extension",
+        get: |p| Some(p.inlet_extension),
+        set: |p, v| p.inlet_extension = v,
+    },
+    Parameter {
+        name: "outlet_extension",
+        get: |p| Some(p.outlet_extension),
+        set: |p, v| p.outlet_extension = v,
+    },
+    Parameter {
+        name: "pump_flow",
+        get: |p| Some(p.pump_flow),
+        set: |p, v| p.pump_flow = v,
+    },
+    Parameter {
+        name: "source_pressure",
+        get: |p| p.source_pressure,
+        set: |p, v| {
+            if p.source_pressure.is_some() {
+                p.source_pressure = Some(v);
+            }
+        },
+    },
+    Parameter {
+        name: "temperature",
+        get: |p| Some(p.temperature),
+        set: |p, v| p.temperature = v,
+    },
+    Parameter {
+        name: "relative_humidity",
+        get: |p| Some(p.relative_humidity),
+        set: |p, v| p.relative_humidity = v,
+    },
+    Parameter {
+        name: "static_pressure",
+        get: |p| Some(p.static_pressure),
+        set: |p, v| p.static_pressure = v,
+    },
+    Parameter {
+        name: "chamber_shape.width",
+        get: |p| match p.chamber_shape {
+            Some(CrossSection::Rectangular { width, .. }) => Some(width),
+            _ => None,
+        },
+        set: |p, v| {
+            if let Some(CrossSection::Rectangular { width, .. }) = &mut p.chamber_shape {
+                *width = v;
+            }
+        },
+    },
+    Parameter {
+        name: "chamber_shape.height",
+        get: |p| match p.chamber_shape {
+            Some(CrossSection::Rectangular { height, .. }) => Some(height),
+            _ => None,
+        },
+        set: |p, v| {
+            if let Some(CrossSection::Rectangular { height, .. }) = &mut p.chamber_shape {
+                *height = v;
+            }
+        },
+    },
+    Parameter {
+        name: "orifice.hole_diameter",
+        get: |p| p.orifice.as_ref().map(|o| o.hole_diameter),
+        set: |p, v| {
+            if let Some(o) = &mut p.orifice {
+                o.hole_diameter = v;
+            }
+        },
+    },
+    Parameter {
+        name: "orifice.thickness",
+        get: |p| p.orifice.as_ref().map(|o| o.thickness),
+        set: |p, v| {
+            if let Some(o) = &mut p.orifice {
+                o.thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "baffle.thickness",
+        get: |p| p.baffle.as_ref().map(|b| b.thickness),
+        set: |p, v| {
+            if let Some(b) = &mut p.baffle {
+                b.thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "baffle.tube_diameter",
+        get: |p| p.baffle.as_ref().map(|b| b.tube_diameter),
+        set: |p, v| {
+            if let Some(b) = &mut p.baffle {
+                b.tube_diameter = v;
+            }
+        },
+    },
+    Parameter {
+        name: "baffle.tube_length",
+        get: |p| p.baffle.as_ref().map(|b| b.tube_length),
+        set: |p, v| {
+            if let Some(b) = &mut p.baffle {
+                b.tube_length = v;
+            }
+        },
+    },
+    Parameter {
+        name: "perforated_baffle.porosity",
+        get: |p| p.perforated_baffle.as_ref().map(|b| b.porosity),
+        set: |p, v| {
+            if let Some(b) = &mut p.perforated_baffle {
+                b.porosity = v;
+            }
+        },
+    },
+    Parameter {
+        name: "perforated_baffle.hole_diameter",
+        get: |p| p.perforated_baffle.as_ref().map(|b| b.hole_diameter),
+        set: |p, v| {
+            if let Some(b) = &mut p.perforated_baffle {
+                b.hole_diameter = v;
+            }
+        },
+    },
+    Parameter {
+        name: "perforated_baffle.thickness",
+        get: |p| p.perforated_baffle.as_ref().map(|b| b.thickness),
+        set: |p, v| {
+            if let Some(b) = &mut p.perforated_baffle {
+                b.thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "side_branch.length",
+        get: |p| p.side_branch.as_ref().map(|b| b.length),
+        set: |p, v| {
+            if let Some(b) = &mut p.side_branch {
+                b.length = v;
+            }
+        },
+    },
+    Parameter {
+        name: "side_branch.diameter",
+        get: |p| p.side_branch.as_ref().map(|b| b.diameter),
+        set: |p, v| {
+            if let Some(b) = &mut p.side_branch {
+                b.diameter = v;
+            }
+        },
+    },
+    Parameter {
+        name: "side_branch.plug_thickness",
+        get: |p| p.side_branch.as_ref().map(|b| b.plug_thickness),
+        set: |p, v| {
+            if let Some(b) = &mut p.side_branch {
+                b.plug_thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "side_branch.flow_resistivity",
+        get: |p| p.side_branch.as_ref().map(|b| b.flow_resistivity),
+        set: |p, v| {
+            if let Some(b) = &mut p.side_branch {
+                b.flow_resistivity = v;
+            }
+        },
+    },
+    Parameter {
+        name: "port_offsets.inlet",
+        get: |p| p.port_offsets.as_ref().map(|o| o.inlet),
+        set: |p, v| {
+            if let Some(o) = &mut p.port_offsets {
+                o.inlet = v;
+            }
+        },
+    },
+    Parameter {
+        name: "port_offsets.outlet",
+        get: |p| p.port_offsets.as_ref().map(|o| o.outlet),
+        set: |p, v| {
+            if let Some(o) = &mut p.port_offsets {
+                o.outlet = v;
+            }
+        },
+    },
+    Parameter {
+        name: "port_offsets.angle",
+        get: |p| p.port_offsets.as_ref().map(|o| o.angle),
+        set: |p, v| {
+            if let Some(o) = &mut p.port_offsets {
+                o.angle = v;
+            }
+        },
+    },
+    Parameter {
+        name: "shell.thickness",
+        get: |p| p.shell.as_ref().map(|s| s.thickness),
+        set: |p, v| {
+            if let Some(s) = &mut p.shell {
+                s.thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "wall_roughness",
+        get: |p| Some(p.wall_roughness),
+        set: |p, v| p.wall_roughness = v,
+    },
+    Parameter {
+        name: "duct_wall.thickness",
+        get: |p| p.duct_wall.as_ref().map(|w| w.thickness),
+        set: |p, v| {
+            if let Some(w) = &mut p.duct_wall {
+                w.thickness = v;
+            }
+        },
+    },
+    Parameter {
+        name: "air_line.hose_length",
+        get: |p| p.air_line.as_ref().map(|l| l.hose_length),
+        set: |p, v| {
+            if let Some(l) = &mut p.air_line {
+                l.hose_length = v;
+            }
+        },
+    },
+    Parameter {
+        name: "air_line.hose_diameter",
+        get: |p| p.air_line.as_ref().map(|l| l.hose_diameter),
+        set: |p, v| {
+            if let Some(l) = &mut p.air_line {
+                l.hose_diameter = v;
+            }
+        },
+    },
+    Parameter {
+        name: "air_line.stone.depth",
+        get: |p| p.air_line.as_ref().map(|l| l.stone.depth),
+        set: |p, v| {
+            if let Some(l) = &mut p.air_line {
+                l.stone.depth = v;
+            }
+        },
+    },
+    Parameter {
+        name: "air_line.stone.flow_rate",
+        get: |p| p.air_line.as_ref().map(|l| l.stone.flow_rate),
+        set: |p, v| {
+            if let Some(l) = &mut p.air_line {
+                l.stone.flow_rate = v;
+            }
+        },
+    },
+    Parameter {
+        name: "calibration.end_correction",
+        get: |p| Some(p.calibration.end_correction),
+        set: |p, v| p.calibration.end_correction = v,
+    },
+    Parameter {
+        name: "calibration.loss_factor",
+        get: |p| Some(p.calibration.loss_factor),
+        set: |p, v| p.calibration.loss_factor = v,
+    },
+    Parameter {
+        name: "calibration.length_scale",
+        get: |p| Some(p.calibration.length_scale),
+        set: |p, v| p.calibration.length_scale = v,
+    },
+];
+
+/// Derivative of the transmission loss with respect to one parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Sensitivity {
+    /// Path of the parameter, as in [`Parameter::name`].
+    pub parameter: &'static str,
+    /// Its value in the design.
+    pub value: f64,
+    /// ∂TL/∂parameter in dB per SI unit at each swept frequency.
+    pub gradient: Vec<f64>,
+}
+
+impl Sensitivity {
+    /// Largest TL change in dB anywhere in the sweep when the parameter
+    /// moves by `fraction` of its value, to first order: the bar length of
+    /// a tornado plot. Zero for a parameter at zero.
+    pub fn swing(&self, fraction: f64) -> f64 {
+        let scale = (self.value * fraction).abs();
+        self.gradient.iter().map(|g| (g * scale).abs()).fold(0.0, f64::max)
+    }
+}
+
+/// ∂TL/∂parameter for every [`PARAMETERS`] entry present in `params`,
+/// over the same sweep as [`compute_tl_range`].
+///
+/// Uses central differences with a step of 10⁻⁴ of the value, one-sided
+/// where a step would leave the valid range (e.g. an extension at zero).
+/// Returns the frequencies and one [`Sensitivity`] per parameter, in
+/// table order.
+pub fn sensitivities(
+    params: &SimParams,
+    f_min: f64,
+    f_max: f64,
+    points: usize,
+    spacing: SweepSpacing,
+) -> Result<(Vec<f64>, Vec<Sensitivity>), String> {
+    let (frequencies, base) = compute_tl_range(params, f_min, f_max, points, spacing)?;
+    let mut result = Vec::new();
+    for parameter in PARAMETERS {
+        let Some(value) = parameter.get(params) else {
+            continue;
+        };
+        let step = if value == 0.0 { ZERO_STEP } else { RELATIVE_STEP * value.abs() };
+        let tl_at = |value: f64| {
+            let mut varied = params.clone();
+            parameter.set(&mut varied, value);
+            compute_tl_range(&varied, f_min, f_max, points, spacing).map(|(_, tl)| tl)
+        };
+        let difference = |a: &[f64], b: &[f64], width: f64| -> Vec<f64> {
+            a.iter().zip(b).map(|(a, b)| (a - b) / width).collect()
+        };
+        let gradient = match (tl_at(value + step), tl_at(value - step)) {
+            (Ok(up), Ok(down)) => difference(&up, &down, 2.0 * step),
+            (Ok(up), Err(_)) => difference(&up, &base, step),
+            (Err(_), Ok(down)) => difference(&base, &down, step),
+            (Err(e), Err(_)) => return Err(format!("{}: {e}", parameter.name)),
+        };
+        result.push(Sensitivity {
+            parameter: parameter.name,
+            value,
+            gradient,
+        });
+    }
+    Ok((frequencies, result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::elements::Orifice;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_chamber_length_sensitivity() {
+        let params = SimParams::default();
+        let (frequencies, all) = sensitivities(&params, 200.0, 1800.0, 9, SweepSpacing::Linear).unwrap();
+        let find = |name: &str| all.iter().find(|s| s.parameter == name);
+        assert!(find("orifice.hole_diameter").is_none());
+
+        // Simple expansion chamber: TL = 10·log₁₀(1 + ¼(m − 1/m)²·sin²kL)
+        let (c, _) = params.medium();
+        let m = (params.chamber_diameter / params.inlet_diameter).powi(2);
+        let q = 0.25 * (m - 1.0 / m).powi(2);
+        let length = find("chamber_length").unwrap();
+        for (f, g) in frequencies.iter().zip(&length.gradient) {
+            let k = 2.0 * PI * f / c;
+            let kl = k * params.chamber_length;
+            let expected = 10.0 / std::f64::consts::LN_10 * q * k * (2.0 * kl).sin() / (1.0 + q * kl.sin().powi(2));
+            assert!((g - expected).abs() < 1e-3 * expected.abs().max(1.0), "{f} Hz: {g} vs {expected}");
+        }
+
+        // An extension at zero can only grow: the one-sided step still works
+        assert!(find("inlet_extension").unwrap().gradient.iter().all(|g| g.is_finite()));
+
+        // The chamber dominates the tornado; the outlet pipe length does
+        // not enter an anechoic TL at all
+        let swing = |name: &str| find(name).unwrap().swing(0.1);
+        assert!(swing("chamber_diameter") > 1.0);
+        assert!(swing("outlet_length") < 1e-6);
+
+        let restricted = SimParams {
+            orifice: Some(Orifice::new(2e-3, 1e-3)),
+            ..params
+        };
+        let (_, all) = sensitivities(&restricted, 200.0, 400.0, 3, SweepSpacing::Linear).unwrap();
+        assert!(all.iter().any(|s| s.parameter == "orifice.hole_diameter"));
+    }
+
+    #[test]
+    fn test_parameters_round_trip() {
+        let mut params = SimParams {
+            orifice: Some(Orifice::new(2e-3, 1e-3)),
+            ..SimParams::default()
+        };
+        for parameter in PARAMETERS {
+            if let Some(value) = parameter.get(&params) {
+                parameter.set(&mut params, value + 1.0);
+                assert_eq!(parameter.get(&params), Some(value + 1.0), "{}", parameter.name);
+            }
+        }
+    }
+}
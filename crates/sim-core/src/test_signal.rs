@@ -0,0 +1,299 @@
+use std::f64::consts::PI;
+
+use crate::pump::SampleSource;
+use crate::rng;
+
+/// Peak level of the generated signals, matching the pump waveform.
+const LEVEL: f64 = 0.5;
+
+/// Galois LFSR feedback masks giving a maximal-length sequence for each
+/// register order (index = order).
+const MLS_TAPS: [u32; 21] = [
+    0, 0, 0x3, 0x6, 0xC, 0x14, 0x30, 0x60, 0xB8, 0x110, 0x240, 0x500, 0x829, 0x100D, 0x2015,
+    0x6000, 0xD008, 0x12000, 0x20400, 0x40023, 0x90000,
+];
+
+/// A standard test signal to play through the muffler instead of the pump.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TestSignal {
+    /// Pure tone at `frequency` Hz.
+    Sine { frequency: f64 },
+    /// Exponential (log) sine sweep from `start` to `end` Hz over
+    /// `duration` seconds, repeated.
+    LogSweep { start: f64, end: f64, duration: f64 },
+    /// Uniform white noise.
+    WhiteNoise,
+    /// Pink (−3 dB/octave) noise.
+    PinkNoise,
+    /// Maximum-length sequence of period 2^order − 1, repeated.
+    Mls { order: u32 },
+}
+
+impl TestSignal {
+    /// Supported MLS register orders.
+    pub const MLS_ORDER_RANGE: (u32, u32) = (2, 20);
+
+    /// Check the signal's settings against the sample rate.
+    pub fn validate(&self, sample_rate: f64) -> Result<(), String> {
+        let nyquist = sample_rate / 2.0;
+        match *self {
+            TestSignal::Sine { frequency } => {
+                if !(frequency > 0.0 && frequency < nyquist) {
+                    return Err(format!("sine frequency must be in (0, {nyquist}) Hz, got {frequency}"));
+                }
+            }
+            TestSignal::LogSweep {
+                start,
+                end,
+                duration,
+            } => {
+                if !(start > 0.0 && start < end && end <= nyquist) {
+                    return Err(format!(
+                        "sweep must satisfy 0 < start < end <= {nyquist} Hz, got {start}..{end}"
+                    ));
+                }
+                if !(duration > 0.0 && duration.is_finite()) {
+                    return Err(format!("sweep duration must be > 0, got {duration}"));
+                }
+            }
+            TestSignal::Mls { order } => {
+                let (min, max) = Self::MLS_ORDER_RANGE;
+                if !(min..=max).contains(&order) {
+                    return Err(format!("MLS order must be in {min}..={max}, got {order}"));
+                }
+            }
+            TestSignal::WhiteNoise | TestSignal::PinkNoise => {}
+        }
+        Ok(())
+    }
+}
+
+/// Streams a [`TestSignal`] block by block, like
+/// [`PumpSource::generate`](crate::pump::PumpSource::generate).
+pub struct SignalGenerator {
+    signal: TestSignal,
+    sample_rate: f64,
+    /// Samples generated since the start of the current period.
+    position: u64,
+    /// Sine phase in radians.
+    phase: f64,
+    /// xorshift64 state for the noise signals.
+    rng: u64,
+    /// Paul Kellet's pink noise filter states.
+    pink: [f64; 7],
+    /// LFSR register for the MLS.
+    register: u32,
+}
+
+impl SignalGenerator {
+    pub fn new(signal: TestSignal, sample_rate: f64) -> Self {
+        Self {
+            signal,
+            sample_rate,
+            position: 0,
+            phase: 0.0,
+            rng: 0x9E37_79B9_7F4A_7C15,
+            pink: [0.0; 7],
+            register: 1,
+        }
+    }
+
+    /// The signal being generated.
+    pub fn signal(&self) -> TestSignal {
+        self.signal
+    }
+
+    /// Switch to another signal, restarting it if it changed.
+    pub fn set_signal(&mut self, signal: TestSignal) {
+        if signal != self.signal {
+            *self = Self::new(signal, self.sample_rate);
+        }
+    }
+
+    /// Generate the next `count` samples.
+    pub fn generate(&mut self, count: usize) -> Vec<f64> {
+        (0..count).map(|_| self.next_sample()).collect()
+    }
+
+    fn next_sample(&mut self) -> f64 {
+        let t = self.position as f64 / self.sample_rate;
+        self.position += 1;
+        match self.signal {
+            TestSignal::Sine { frequency } => {
+                let sample = LEVEL * self.phase.sin();
+                self.phase = (self.phase + 2.0 * PI * frequency / self.sample_rate) % (2.0 * PI);
+                sample
+            }
+            TestSignal::LogSweep {
+                start,
+                end,
+                duration,
+            } => {
+                if self.position >= (duration * self.sample_rate).round() as u64 {
+                    self.position = 0;
+                }
+                LEVEL * log_sweep_at(start, end, duration, t)
+            }
+            TestSignal::WhiteNoise => LEVEL * rng::uniform(&mut self.rng),
+            TestSignal::PinkNoise => {
+                let white = rng::uniform(&mut self.rng);
+                let b = &mut self.pink;
+                b[0] = 0.99886 * b[0] + white * 0.0555179;
+                b[1] = 0.99332 * b[1] + white * 0.0750759;
+                b[2] = 0.96900 * b[2] + white * 0.1538520;
+                b[3] = 0.86650 * b[3] + white * 0.3104856;
+                b[4] = 0.55000 * b[4] + white * 0.5329522;
+                b[5] = -0.7616 * b[5] - white * 0.0168980;
+                let pink = b.iter().sum::<f64>() + white * 0.5362;
+                b[6] = white * 0.115926;
+                // The filter has a gain of about 9 at low frequencies
+                LEVEL * pink * 0.11
+            }
+            TestSignal::Mls { order } => {
+                let bit = self.register & 1;
+                self.register >>= 1;
+                if bit == 1 {
+                    self.register ^= MLS_TAPS[order as usize];
+                    LEVEL
+                } else {
+                    -LEVEL
+                }
+            }
+        }
+    }
+}
+
+/// Farina's exponential sweep x(t) = sin(2π·f₁·T/R·(e^{t·R/T} − 1)),
+/// R = ln(f₂/f₁).
+fn log_sweep_at(start: f64, end: f64, duration: f64, t: f64) -> f64 {
+    let rate = (end / start).ln();
+    (2.0 * PI * start * duration / rate * ((t * rate / duration).exp() - 1.0)).sin()
+}
+
+/// One period of the log sweep at unit amplitude.
+pub fn log_sweep(start: f64, end: f64, duration: f64, sample_rate: f64) -> Vec<f64> {
+    let len = (duration * sample_rate).round() as usize;
+    (0..len)
+        .map(|n| log_sweep_at(start, end, duration, n as f64 / sample_rate))
+        .collect()
+}
+
+/// Inverse filter for deconvolving a recorded log sweep into an impulse
+/// response: the time-reversed sweep with a −6 dB/octave envelope that
+/// undoes the sweep's pink spectrum.
+///
+/// Convolving the sweep from [`log_sweep`] with this filter gives a unit
+/// peak at index `len − 1`; convolving a system's response to the sweep
+/// gives the system's impulse response there, with harmonic distortion
+/// products landing earlier.
+pub fn inverse_log_sweep(start: f64, end: f64, duration: f64, sample_rate: f64) -> Vec<f64> {
+    let sweep = log_sweep(start, end, duration, sample_rate);
+    let len = sweep.len();
+    let rate = (end / start).ln();
+    let mut inverse: Vec<f64> = (0..len)
+        .map(|n| {
+            let t = n as f64 / sample_rate;
+            sweep[len - 1 - n] * (-t * rate / duration).exp()
+        })
+        .collect();
+    // Normalise the zero-lag term of sweep ⊛ inverse to 1
+    let peak: f64 = sweep
+        .iter()
+        .zip(inverse.iter().rev())
+        .map(|(x, h)| x * h)
+        .sum();
+    for h in &mut inverse {
+        *h /= peak;
+    }
+    inverse
+}
+
+impl SampleSource for SignalGenerator {
+    fn generate(&mut self, count: usize) -> Vec<f64> {
+        SignalGenerator::generate(self, count)
+    }
+
+    fn set_sample_rate(&mut self, sample_rate: f64) {
+        if sample_rate != self.sample_rate {
+            *self = Self::new(self.signal, sample_rate);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mls_is_maximal_with_flat_autocorrelation() {
+        let (min, max) = TestSignal::MLS_ORDER_RANGE;
+        for order in min..=max {
+            let period = (1usize << order) - 1;
+            let mut gen = SignalGenerator::new(TestSignal::Mls { order }, 44100.0);
+            gen.generate(period);
+            assert_eq!(gen.register, 1, "order {order} is not maximal");
+        }
+
+        // Periodic autocorrelation is N at lag 0 and −1 elsewhere
+        let order = 7;
+        let period = (1usize << order) - 1;
+        let seq: Vec<f64> = SignalGenerator::new(TestSignal::Mls { order }, 44100.0)
+            .generate(period)
+            .iter()
+            .map(|s| s / LEVEL)
+            .collect();
+        for lag in 0..period {
+            let r: f64 = (0..period).map(|i| seq[i] * seq[(i + lag) % period]).sum();
+            let expected = if lag == 0 { period as f64 } else { -1.0 };
+            assert!((r - expected).abs() < 1e-9, "lag {lag}: {r}");
+        }
+    }
+
+    #[test]
+    fn test_log_sweep_deconvolves_to_impulse() {
+        let (start, end, duration, sample_rate) = (50.0, 3000.0, 0.1, 8000.0);
+        let sweep = log_sweep(start, end, duration, sample_rate);
+        let inverse = inverse_log_sweep(start, end, duration, sample_rate);
+        let len = sweep.len();
+
+        let mut conv = vec![0.0; 2 * len - 1];
+        for (i, &x) in sweep.iter().enumerate() {
+            for (j, &h) in inverse.iter().enumerate() {
+                conv[i + j] += x * h;
+            }
+        }
+        assert!((conv[len - 1] - 1.0).abs() < 1e-9);
+        let sidelobe = conv
+            .iter()
+            .enumerate()
+            .filter(|&(n, _)| n.abs_diff(len - 1) > 8)
+            .map(|(_, v)| v.abs())
+            .fold(0.0, f64::max);
+        assert!(sidelobe < 0.1, "sidelobe {sidelobe}");
+
+        // The streamed sweep repeats the same period
+        let mut gen = SignalGenerator::new(TestSignal::LogSweep { start, end, duration }, sample_rate);
+        let streamed = gen.generate(2 * len);
+        assert!((streamed[len + 10] - LEVEL * sweep[10]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_pink_noise_is_darker_than_white() {
+        // Ratio of first-difference power to signal power falls with
+        // a steeper spectrum
+        let roughness = |signal: TestSignal| {
+            let x = SignalGenerator::new(signal, 44100.0).generate(1 << 16);
+            let power: f64 = x.iter().map(|v| v * v).sum();
+            let diff: f64 = x.windows(2).map(|w| (w[1] - w[0]).powi(2)).sum();
+            assert!(x.iter().all(|v| v.abs() <= 1.0));
+            diff / power
+        };
+        let white = roughness(TestSignal::WhiteNoise);
+        let pink = roughness(TestSignal::PinkNoise);
+        assert!((white - 2.0).abs() < 0.1, "white {white}");
+        assert!(pink < 0.5 * white, "pink {pink}");
+
+        assert!(TestSignal::Sine { frequency: 30_000.0 }.validate(44100.0).is_err());
+        assert!(TestSignal::Mls { order: 24 }.validate(44100.0).is_err());
+    }
+}
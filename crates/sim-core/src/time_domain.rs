@@ -0,0 +1,395 @@
+use crate::numerics::Termination;
+use crate::SimParams;
+use num_complex::Complex64;
+use realfft::RealFftPlanner;
+use std::f64::consts::PI;
+
+/// Grid cells per wavelength at the Nyquist frequency.
+const CELLS_PER_WAVELENGTH: f64 = 16.0;
+
+/// Courant number of the explicit time step.
+const COURANT: f64 = 0.8;
+
+/// Peak of the probe pulse in Pa when the pump's pulse pressure is
+/// unknown: small enough to keep the response linear.
+const LINEAR_PROBE: f64 = 1.0;
+
+/// Frequencies in Hz, transmission loss in dB and H(f) of a sweep.
+type Sweep = (Vec<f64>, Vec<f64>, Vec<Complex64>);
+
+/// Time-domain counterpart of `frequency_response::sweep`: frequencies,
+/// transmission loss in dB and H(f) on the FFT grid, from a nonlinear
+/// flow simulation of the muffler built from `params`.
+///
+/// The muffler becomes a quasi-1D duct of varying area, filled with the
+/// gas at its mean flow, and the isentropic Euler equations are marched
+/// with a second-order finite-volume scheme (MUSCL reconstruction with a
+/// van Leer limiter, Rusanov fluxes, two-stage Runge–Kutta). Both ends
+/// are non-reflecting: the inlet sends in a Gaussian pressure pulse of
+/// peak [`SimParams::pulse_pressure`] (1 Pa if unknown) as the incoming Riemann
+/// invariant, and the outlet absorbs whatever arrives. H(f) is the ratio
+/// of the outlet and incident pressure spectra; a second run without the
+/// pulse is subtracted so the mean flow settling does not leak into it.
+///
+/// The walls are rigid and inviscid, so the wall loss and end-correction
+/// settings do not apply. Elements the 1D grid cannot represent (side
+/// branches, dividers, extended pipes, offset ports) and outlet loads
+/// other than anechoic are rejected.
+pub fn sweep(
+    params: &SimParams,
+    fft_size: usize,
+    sample_rate: f64,
+    c: f64,
+    rho: f64,
+) -> Result<Sweep, String> {
+    if let Some(feature) = unsupported(params) {
+        return Err(format!(
+            "the time-domain engine cannot model {feature}; use the frequency-domain engine"
+        ));
+    }
+    let nyquist = sample_rate / 2.0;
+    let dx = c / nyquist / CELLS_PER_WAVELENGTH;
+    let duct = Duct::new(&cell_areas(params, dx), dx, params.gas.gamma(), c, rho, params.mean_flow());
+
+    // The probe spans ten cells; its spectrum falls to e⁻⁸ at the Nyquist
+    // frequency, which keeps sampling it free of aliasing
+    let amplitude = params.pulse_pressure().unwrap_or(LINEAR_PROBE);
+    let width = 2.0 / (PI * nyquist);
+    let delay = 5.0 * width;
+    let probe = |t: f64| amplitude * (-0.5 * ((t - delay) / width).powi(2)).exp();
+
+    let mut response = duct.clone().run(fft_size, sample_rate, probe);
+    if params.mean_flow() != 0.0 {
+        let settling = duct.run(fft_size, sample_rate, |_| 0.0);
+        for (p, p_mean) in response.iter_mut().zip(settling) {
+            *p -= p_mean;
+        }
+    }
+    let incident: Vec<f64> = (0..fft_size).map(|k| probe(k as f64 / sample_rate)).collect();
+
+    let (output, input) = (spectrum(response), spectrum(incident));
+    let bin_width = sample_rate / fft_size as f64;
+    let area_ratio = (params.inlet_diameter / params.outlet_diameter).powi(2);
+    let mut frequencies = Vec::with_capacity(output.len());
+    let mut tl = Vec::with_capacity(output.len());
+    let mut hf = Vec::with_capacity(output.len());
+    for (i, (out, inc)) in output.into_iter().zip(input).enumerate() {
+        frequencies.push(i as f64 * bin_width);
+        if i == 0 {
+            // As in the frequency-domain sweep: unity transfer at DC
+            tl.push(0.0);
+            hf.push(Complex64::new(1.0, 0.0));
+            continue;
+        }
+        let h = out / inc;
+        tl.push(10.0 * (area_ratio / h.norm_sqr().max(1e-32)).log10());
+        hf.push(h);
+    }
+    Ok((frequencies, tl, hf))
+}
+
+/// The first feature of `params` the time-domain engine cannot model.
+fn unsupported(params: &SimParams) -> Option<&'static str> {
+    let features = [
+        (params.orifice.is_some(), "an orifice"),
+        (params.baffle.is_some(), "a baffle"),
+        (params.perforated_baffle.is_some(), "a perforated baffle"),
+        (params.side_branch.is_some(), "a side branch"),
+        (params.port_offsets.is_some(), "offset ports"),
+        (params.shell.is_some(), "shell breakout"),
+        (params.duct_wall.is_some(), "elastic duct walls"),
+        (params.air_line.is_some(), "an air line"),
+        (params.inlet_extension > 0.0 || params.outlet_extension > 0.0, "extended pipes"),
+        (params.outlet_termination != Termination::Anechoic, "an open outlet end"),
+    ];
+    features.into_iter().find_map(|(present, feature)| present.then_some(feature))
+}
+
+/// Cross-sectional area of each grid cell, inlet first: the inlet pipe,
+/// the chamber (following a measured profile if there is one) and the
+/// outlet pipe, each rounded to whole cells of `dx`.
+fn cell_areas(params: &SimParams, dx: f64) -> Vec<f64> {
+    let circle = |diameter: f64| PI / 4.0 * diameter * diameter;
+    let mut areas = Vec::new();
+    let mut section = |length: f64, area: &dyn Fn(f64) -> f64| {
+        let cells = (length / dx).round().max(1.0) as usize;
+        areas.extend((0..cells).map(|i| area((i as f64 + 0.5) / cells as f64)));
+    };
+    section(params.inlet_length, &|_| circle(params.inlet_diameter));
+    match &params.chamber_profile {
+        Some(profile) => {
+            let start = profile.profile[0].0;
+            let radius = |fraction: f64| {
+                let x = start + fraction * profile.length();
+                let segment = profile.profile.windows(2).find(|w| x <= w[1].0).unwrap_or(&profile.profile[..2]);
+                let ((x0, r0), (x1, r1)) = (segment[0], segment[1]);
+                r0 + (r1 - r0) * (x - x0) / (x1 - x0)
+            };
+            section(profile.length(), &|fraction| PI * radius(fraction).powi(2));
+        }
+        None => {
+            let area = params.chamber_section().area();
+            section(params.chamber_length * params.calibration.length_scale, &|_| area);
+        }
+    }
+    section(params.outlet_length, &|_| circle(params.outlet_diameter));
+    areas
+}
+
+/// One-sided spectrum of a real signal.
+fn spectrum(mut signal: Vec<f64>) -> Vec<Complex64> {
+    let fft = RealFftPlanner::<f64>::new().plan_fft_forward(signal.len());
+    let mut bins = fft.make_output_vec();
+    fft.process(&mut signal, &mut bins).expect("FFT failed");
+    bins.into_iter().map(|c| Complex64::new(c.re, c.im)).collect()
+}
+
+/// Van Leer's limited slope from the left and right differences.
+fn limited_slope(left: f64, right: f64) -> f64 {
+    if left * right <= 0.0 {
+        0.0
+    } else {
+        2.0 * left * right / (left + right)
+    }
+}
+
+/// Density and velocity of the gas in one cell.
+#[derive(Debug, Clone, Copy)]
+struct State {
+    rho: f64,
+    u: f64,
+}
+
+/// The muffler as a quasi-1D duct of isentropic gas on a uniform grid.
+///
+/// Each cell holds the density and momentum per unit area. A face between
+/// cells of different area passes flux through the smaller of the two;
+/// the wall left over (e.g. the end plate of a chamber) pushes back with
+/// the cell's pressure, which keeps a gas at rest exactly at rest.
+#[derive(Debug, Clone)]
+struct Duct {
+    dx: f64,
+    /// Cell areas in m².
+    area: Vec<f64>,
+    /// Face areas in m², one more than the cells.
+    face: Vec<f64>,
+    gamma: f64,
+    c0: f64,
+    rho0: f64,
+    p0: f64,
+    /// Mean velocities the inlet and outlet boundaries hold.
+    inlet_velocity: f64,
+    outlet_velocity: f64,
+    rho: Vec<f64>,
+    momentum: Vec<f64>,
+}
+
+impl Duct {
+    fn new(area: &[f64], dx: f64, gamma: f64, c0: f64, rho0: f64, flow_rate: f64) -> Self {
+        let n = area.len();
+        let face = (0..=n)
+            .map(|j| area[j.saturating_sub(1)].min(area[j.min(n - 1)]))
+            .collect();
+        Self {
+            dx,
+            area: area.to_vec(),
+            face,
+            gamma,
+            c0,
+            rho0,
+            p0: rho0 * c0 * c0 / gamma,
+            inlet_velocity: flow_rate / area[0],
+            outlet_velocity: flow_rate / area[n - 1],
+            rho: vec![rho0; n],
+            momentum: area.iter().map(|a| rho0 * flow_rate / a).collect(),
+        }
+    }
+
+    fn pressure(&self, rho: f64) -> f64 {
+        self.p0 * (rho / self.rho0).powf(self.gamma)
+    }
+
+    fn sound_speed(&self, rho: f64) -> f64 {
+        self.c0 * (rho / self.rho0).powf((self.gamma - 1.0) / 2.0)
+    }
+
+    /// The state carrying Riemann invariants J± = u ± 2c/(γ − 1).
+    fn riemann_state(&self, j_plus: f64, j_minus: f64) -> State {
+        let c = (self.gamma - 1.0) * (j_plus - j_minus) / 4.0;
+        State {
+            rho: self.rho0 * (c / self.c0).powf(2.0 / (self.gamma - 1.0)),
+            u: (j_plus + j_minus) / 2.0,
+        }
+    }
+
+    /// Boundary states at the inlet, sending in a simple wave of excess
+    /// pressure `incident`, and at the anechoic outlet.
+    fn boundaries(&self, rho: &[f64], momentum: &[f64], incident: f64) -> (State, State) {
+        let g = 2.0 / (self.gamma - 1.0);
+        let invariant = |i: usize, sign: f64| momentum[i] / rho[i] + sign * g * self.sound_speed(rho[i]);
+        let c_incident = self.c0 * (1.0 + incident / self.p0).powf(1.0 / (self.gamma * g));
+        let j_plus = self.inlet_velocity + g * self.c0 + 2.0 * g * (c_incident - self.c0);
+        let inlet = self.riemann_state(j_plus, invariant(0, -1.0));
+        let last = rho.len() - 1;
+        let outlet = self.riemann_state(invariant(last, 1.0), self.outlet_velocity - g * self.c0);
+        (inlet, outlet)
+    }
+
+    /// Rusanov flux of mass and momentum per unit area between two states.
+    fn flux(&self, left: State, right: State) -> (f64, f64) {
+        let physical = |s: State| (s.rho * s.u, s.rho * s.u * s.u + self.pressure(s.rho));
+        let speed = (left.u.abs() + self.sound_speed(left.rho)).max(right.u.abs() + self.sound_speed(right.rho));
+        let (fl, fr) = (physical(left), physical(right));
+        (
+            0.5 * (fl.0 + fr.0) - 0.5 * speed * (right.rho - left.rho),
+            0.5 * (fl.1 + fr.1) - 0.5 * speed * (right.rho * right.u - left.rho * left.u),
+        )
+    }
+
+    /// Time derivatives of density and momentum with the inlet sending in
+    /// `incident` Pa.
+    fn rates(&self, rho: &[f64], momentum: &[f64], incident: f64) -> (Vec<f64>, Vec<f64>) {
+        let n = rho.len();
+        let (inlet, outlet) = self.boundaries(rho, momentum, incident);
+        // Reconstruct density and volume velocity u·A, which stay smooth
+        // across an area change where the velocity jumps. Two ghost cells
+        // at each end hold the boundary states.
+        let (first, last) = (self.area[0], self.area[n - 1]);
+        let mut states = vec![(inlet.rho, inlet.u * first); 2];
+        states.extend((0..n).map(|i| (rho[i], momentum[i] / rho[i] * self.area[i])));
+        states.extend([(outlet.rho, outlet.u * last); 2]);
+        let slope = |k: usize| {
+            let (a, b, c) = (states[k - 1], states[k], states[k + 1]);
+            (limited_slope(b.0 - a.0, c.0 - b.0), limited_slope(b.1 - a.1, c.1 - b.1))
+        };
+        let fluxes: Vec<(f64, f64)> = (0..=n)
+            .map(|j| {
+                let (l, r) = (j + 1, j + 2);
+                let (dl, dr) = (slope(l), slope(r));
+                let left = State {
+                    rho: states[l].0 + 0.5 * dl.0,
+                    u: (states[l].1 + 0.5 * dl.1) / self.face[j],
+                };
+                let right = State {
+                    rho: states[r].0 - 0.5 * dr.0,
+                    u: (states[r].1 - 0.5 * dr.1) / self.face[j],
+                };
+                self.flux(left, right)
+            })
+            .collect();
+
+        let mut d_rho = vec![0.0; n];
+        let mut d_momentum = vec![0.0; n];
+        for i in 0..n {
+            let (a_left, a_right) = (self.face[i], self.face[i + 1]);
+            let volume = self.area[i] * self.dx;
+            d_rho[i] = -(a_right * fluxes[i + 1].0 - a_left * fluxes[i].0) / volume;
+            d_momentum[i] = (-(a_right * fluxes[i + 1].1 - a_left * fluxes[i].1)
+                + self.pressure(rho[i]) * (a_right - a_left))
+                / volume;
+        }
+        (d_rho, d_momentum)
+    }
+
+    /// March for `samples` periods of `sample_rate` while the inlet sends
+    /// in `incident(t)` Pa, and return the excess pressure leaving the
+    /// outlet at each sample.
+    fn run(mut self, samples: usize, sample_rate: f64, incident: impl Fn(f64) -> f64) -> Vec<f64> {
+        let duration = samples as f64 / sample_rate;
+        let outlet_pressure = |duct: &Self, rho: &[f64], momentum: &[f64], t: f64| {
+            duct.pressure(duct.boundaries(rho, momentum, incident(t)).1.rho) - duct.p0
+        };
+        let mut output = Vec::with_capacity(samples);
+        let mut t = 0.0;
+        let mut previous = outlet_pressure(&self, &self.rho, &self.momentum, t);
+        output.push(previous);
+        while output.len() < samples && t < duration {
+            let speed = self
+                .rho
+                .iter()
+                .zip(&self.momentum)
+                .map(|(&rho, &m)| (m / rho).abs() + self.sound_speed(rho))
+                .fold(0.0, f64::max);
+            let dt = COURANT * self.dx / speed;
+
+            // Two-stage, strong-stability-preserving Runge–Kutta
+            let (d_rho, d_momentum) = self.rates(&self.rho, &self.momentum, incident(t));
+            let rho_1: Vec<f64> = self.rho.iter().zip(&d_rho).map(|(q, d)| q + dt * d).collect();
+            let momentum_1: Vec<f64> = self.momentum.iter().zip(&d_momentum).map(|(q, d)| q + dt * d).collect();
+            let (d_rho, d_momentum) = self.rates(&rho_1, &momentum_1, incident(t + dt));
+            for i in 0..self.rho.len() {
+                self.rho[i] = 0.5 * (self.rho[i] + rho_1[i] + dt * d_rho[i]);
+                self.momentum[i] = 0.5 * (self.momentum[i] + momentum_1[i] + dt * d_momentum[i]);
+            }
+            t += dt;
+
+            let current = outlet_pressure(&self, &self.rho, &self.momentum, t);
+            while output.len() < samples && output.len() as f64 / sample_rate <= t {
+                let fraction = 1.0 - (t - output.len() as f64 / sample_rate) / dt;
+                output.push(previous + (current - previous) * fraction);
+            }
+            previous = current;
+        }
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::{speed_of_sound_and_density, STANDARD_PRESSURE};
+    use crate::elements::Orifice;
+    use crate::gas::Gas;
+    use crate::muffler::Muffler;
+
+    #[test]
+    fn test_time_domain_matches_transfer_matrices() {
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
+        let (fft_size, sample_rate) = (1024, 11025.0);
+        let params = SimParams::default();
+        let (frequencies, tl, hf) = sweep(&params, fft_size, sample_rate, c, rho).unwrap();
+        assert_eq!(frequencies.len(), fft_size / 2 + 1);
+
+        // At a low level the nonlinear march reproduces the linear chamber
+        let muffler = Muffler::from_params(&params).unwrap();
+        for (i, &f) in frequencies.iter().enumerate().filter(|&(_, &f)| f > 50.0 && f < 500.0) {
+            let omega = 2.0 * PI * f;
+            let expected = muffler.transmission_loss(omega, c, rho);
+            assert!((tl[i] - expected).abs() < 1.0, "{f} Hz: {} vs {expected} dB", tl[i]);
+            let h = muffler.pressure_transfer(omega, c, rho);
+            assert!((hf[i] - h).norm() < 0.15 * h.norm().max(0.1), "{f} Hz: {} vs {h}", hf[i]);
+        }
+
+        // A strong pulse steepens on its way through and shifts the result
+        let loud = SimParams {
+            source_pressure: Some(20e3),
+            ..params.clone()
+        };
+        let (_, tl_loud, _) = sweep(&loud, fft_size, sample_rate, c, rho).unwrap();
+        let deviation = tl.iter().zip(&tl_loud).map(|(a, b)| (a - b).abs()).fold(0.0, f64::max);
+        assert!(deviation > 0.5, "{deviation}");
+
+        let restricted = SimParams {
+            orifice: Some(Orifice::new(2e-3, 1e-3)),
+            ..params
+        };
+        let error = sweep(&restricted, fft_size, sample_rate, c, rho).unwrap_err();
+        assert!(error.contains("orifice"), "{error}");
+    }
+
+    #[test]
+    fn test_time_domain_mean_flow() {
+        // The settling of the mean flow cancels out: a straight pipe still
+        // passes the pulse unchanged apart from its delay
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
+        let params = SimParams {
+            chamber_diameter: 6e-3,
+            pump_flow: 2e-5,
+            ..SimParams::default()
+        };
+        let (frequencies, tl, _) = sweep(&params, 512, 11025.0, c, rho).unwrap();
+        for (f, loss) in frequencies.iter().zip(&tl).filter(|&(&f, _)| f < 1000.0) {
+            assert!(loss.abs() < 0.2, "{f} Hz: {loss} dB");
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use num_complex::Complex64;
+use std::f64::consts::PI;
+use std::ops::Mul;
+
+/// A complex frequency response H(f) sampled on an ascending frequency grid.
+///
+/// Wraps the `(frequencies, transfer_function)` pair produced by a sweep so
+/// consumers can evaluate it at arbitrary frequencies instead of re-deriving
+/// FFT bin indices.
+#[derive(Debug, Clone)]
+pub struct TransferFunction {
+    /// Frequency grid in Hz, strictly ascending.
+    pub frequencies: Vec<f64>,
+    /// Complex response at each frequency.
+    pub values: Vec<Complex64>,
+}
+
+impl TransferFunction {
+    /// Build a transfer function from matching frequency and value arrays.
+    ///
+    /// Panics if the lengths differ.
+    pub fn new(frequencies: Vec<f64>, values: Vec<Complex64>) -> Self {
+        assert_eq!(
+            frequencies.len(),
+            values.len(),
+            "frequencies and values must have the same length"
+        );
+        Self {
+            frequencies,
+            values,
+        }
+    }
+
+    /// Unity response on the given frequency grid.
+    pub fn unity(frequencies: Vec<f64>) -> Self {
+        let values = vec![Complex64::new(1.0, 0.0); frequencies.len()];
+        Self {
+            frequencies,
+            values,
+        }
+    }
+
+    /// Number of frequency points.
+    pub fn len(&self) -> usize {
+        self.frequencies.len()
+    }
+
+    /// True if the response has no frequency points.
+    pub fn is_empty(&self) -> bool {
+        self.frequencies.is_empty()
+    }
+
+    /// Evaluate H at `freq` (Hz) by linear interpolation between the
+    /// neighbouring grid points. Frequencies outside the grid clamp to the
+    /// nearest endpoint; an empty response evaluates to zero.
+    pub fn at(&self, freq: f64) -> Complex64 {
+        let n = self.frequencies.len();
+        if n == 0 {
+            return Complex64::new(0.0, 0.0);
+        }
+        if freq <= self.frequencies[0] {
+            return self.values[0];
+        }
+        if freq >= self.frequencies[n - 1] {
+            return self.values[n - 1];
+        }
+        // First index whose frequency is > freq; guaranteed in 1..n here.
+        let hi = self.frequencies.partition_point(|&f| f <= freq);
+        let lo = hi - 1;
+        let span = self.frequencies[hi] - self.frequencies[lo];
+        if span <= 0.0 {
+            return self.values[lo];
+        }
+        let t = (freq - self.frequencies[lo]) / span;
+        self.values[lo] * (1.0 - t) + self.values[hi] * t
+    }
+
+    /// Magnitude of H at `freq` in dB (20·log₁₀|H|).
+    pub fn magnitude_db_at(&self, freq: f64) -> f64 {
+        20.0 * self.at(freq).norm().max(1e-16).log10()
+    }
+
+    /// Phase of H in radians at each grid point, unwrapped so consecutive
+    /// points never jump by more than π. Starts in (−π, π] at the first
+    /// point; the grid must be fine enough that the true phase changes by
+    /// less than π between points.
+    pub fn unwrapped_phase(&self) -> Vec<f64> {
+        let mut phase: Vec<f64> = Vec::with_capacity(self.values.len());
+        for h in &self.values {
+            let wrapped = h.arg();
+            let unwrapped = match phase.last() {
+                Some(&previous) => wrapped + 2.0 * PI * ((previous - wrapped) / (2.0 * PI)).round(),
+                None => wrapped,
+            };
+            phase.push(unwrapped);
+        }
+        phase
+    }
+
+    /// Group delay τ = −dφ/dω in seconds at each grid point, from central
+    /// differences of the unwrapped phase (one-sided at the ends). A pure
+    /// delay H = e^{−jωτ} gives τ everywhere.
+    pub fn group_delay(&self) -> Vec<f64> {
+        let phase = self.unwrapped_phase();
+        let n = phase.len();
+        if n < 2 {
+            return vec![0.0; n];
+        }
+        (0..n)
+            .map(|i| {
+                let (lo, hi) = (i.saturating_sub(1), (i + 1).min(n - 1));
+                let d_omega = 2.0 * PI * (self.frequencies[hi] - self.frequencies[lo]);
+                -(phase[hi] - phase[lo]) / d_omega
+            })
+            .collect()
+    }
+
+    /// Resample onto an arbitrary ascending frequency grid.
+    pub fn resample(&self, frequencies: &[f64]) -> TransferFunction {
+        let values = frequencies.iter().map(|&f| self.at(f)).collect();
+        TransferFunction {
+            frequencies: frequencies.to_vec(),
+            values,
+        }
+    }
+
+    /// Resample onto the `fft_size/2 + 1` real-FFT bins at `sample_rate`,
+    /// ready for `impulse_response::compute`.
+    pub fn resample_to_fft_grid(&self, fft_size: usize, sample_rate: f64) -> TransferFunction {
+        let bin_width = sample_rate / fft_size as f64;
+        let grid: Vec<f64> = (0..=fft_size / 2).map(|i| i as f64 * bin_width).collect();
+        self.resample(&grid)
+    }
+
+    /// Cascade two systems: H(f) = self(f) · other(f), evaluated on this
+    /// response's grid (`other` is interpolated as needed).
+    pub fn cascade(&self, other: &TransferFunction) -> TransferFunction {
+        let values = self
+            .frequencies
+            .iter()
+            .zip(self.values.iter())
+            .map(|(&f, &h)| h * other.at(f))
+            .collect();
+        TransferFunction {
+            frequencies: self.frequencies.clone(),
+            values,
+        }
+    }
+
+    /// Inverse system 1/H(f). Points where |H| is effectively zero map to
+    /// zero rather than infinity.
+    pub fn inverse(&self) -> TransferFunction {
+        let values = self
+            .values
+            .iter()
+            .map(|&h| {
+                if h.norm() < 1e-15 {
+                    Complex64::new(0.0, 0.0)
+                } else {
+                    h.inv()
+                }
+            })
+            .collect();
+        TransferFunction {
+            frequencies: self.frequencies.clone(),
+            values,
+        }
+    }
+}
+
+impl Mul for &TransferFunction {
+    type Output = TransferFunction;
+
+    fn mul(self, rhs: &TransferFunction) -> TransferFunction {
+        self.cascade(rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ramp() -> TransferFunction {
+        TransferFunction::new(
+            vec![0.0, 100.0, 200.0],
+            vec![
+                Complex64::new(1.0, 0.0),
+                Complex64::new(0.0, 2.0),
+                Complex64::new(-1.0, 0.0),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_at_grid_points_and_midpoints() {
+        let tf = ramp();
+        assert!((tf.at(100.0) - Complex64::new(0.0, 2.0)).norm() < 1e-12);
+        assert!((tf.at(50.0) - Complex64::new(0.5, 1.0)).norm() < 1e-12);
+        // Clamped outside the grid
+        assert!((tf.at(-10.0) - Complex64::new(1.0, 0.0)).norm() < 1e-12);
+        assert!((tf.at(1e6) - Complex64::new(-1.0, 0.0)).norm() < 1e-12);
+    }
+
+    #[test]
+    fn test_cascade_with_inverse_is_unity() {
+        let tf = ramp();
+        let product = &tf * &tf.inverse();
+        for &h in &product.values {
+            assert!((h - Complex64::new(1.0, 0.0)).norm() < 1e-12, "got {h}");
+        }
+    }
+
+    #[test]
+    fn test_phase_and_group_delay_of_a_delay() {
+        // 1 ms delay: the phase falls 2π every kHz, well past ±π
+        let delay = 1e-3;
+        let frequencies: Vec<f64> = (0..=200).map(|i| i as f64 * 25.0).collect();
+        let values = frequencies
+            .iter()
+            .map(|&f| Complex64::from_polar(0.5, -2.0 * PI * f * delay))
+            .collect();
+        let tf = TransferFunction::new(frequencies.clone(), values);
+        for (&f, &phase) in frequencies.iter().zip(&tf.unwrapped_phase()) {
+            assert!((phase + 2.0 * PI * f * delay).abs() < 1e-9, "{f} Hz: {phase}");
+        }
+        for &tau in &tf.group_delay() {
+            assert!((tau - delay).abs() < 1e-12, "{tau}");
+        }
+        assert!(TransferFunction::unity(vec![0.0]).group_delay() == vec![0.0]);
+    }
+
+    #[test]
+    fn test_resample_to_fft_grid_bin_count() {
+        let tf = TransferFunction::unity(vec![0.0, 22050.0]);
+        let resampled = tf.resample_to_fft_grid(256, 44100.0);
+        assert_eq!(resampled.len(), 129);
+        assert!((resampled.frequencies[128] - 22050.0).abs() < 1e-9);
+    }
+}
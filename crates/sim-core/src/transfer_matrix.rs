@@ -50,12 +50,30 @@ impl TransferMatrix {
         20.0 * magnitude.log10()
     }
 
+    /// Transmission loss (dB) into a complex load `z_load`, e.g. an open
+    /// end's radiation impedance: the incident power over the power the
+    /// load absorbs, referred to an outlet pipe of impedance `z_outlet`.
+    /// Equals [`Self::transmission_loss`] for a real load `z_outlet`.
+    ///
+    /// TL = 10·log₁₀(|T₁₁·Z_L + T₁₂ + Z₁·T₂₁·Z_L + Z₁·T₂₂|² / (4·Zₙ·Re Z_L))
+    pub fn transmission_loss_into(&self, z_source: f64, z_load: Complex64, z_outlet: f64) -> f64 {
+        let numerator = self.a * z_load + self.b + z_source * (self.c * z_load + self.d);
+        let absorbed = (4.0 * z_outlet * z_load.re).max(1e-300);
+        10.0 * (numerator.norm_sqr() / absorbed).max(1e-32).log10()
+    }
+
     /// Complex pressure transfer function H(f).
     ///
     /// H(f) = 2 / (T₁₁ + T₁₂/Zₙ + Z₁·T₂₁ + Z₁·T₂₂/Zₙ)
     pub fn pressure_transfer(&self, z_source: f64, z_load: f64) -> Complex64 {
+        self.pressure_transfer_into(z_source, Complex64::new(z_load, 0.0))
+    }
+
+    /// Pressure transfer function H(f) into a complex load `z_load`: the
+    /// pressure on the load over the incident pressure.
+    pub fn pressure_transfer_into(&self, z_source: f64, z_load: Complex64) -> Complex64 {
         let zs = Complex64::new(z_source, 0.0);
-        let zl = Complex64::new(z_load, 0.0);
+        let zl = z_load;
         let denom = self.a + self.b / zl + zs * self.c + zs * self.d / zl;
         if denom.norm() < 1e-15 {
             return Complex64::new(0.0, 0.0);
@@ -118,11 +136,12 @@ mod tests {
     #[test]
     fn test_extreme_large_chamber_produces_finite_tl() {
         // Very large chamber: 10 m diameter
-        use crate::constants::{area_from_diameter, speed_of_sound_and_density};
+        use crate::constants::{area_from_diameter, speed_of_sound_and_density, STANDARD_PRESSURE};
+        use crate::gas::Gas;
         use crate::elements::StraightDuct;
         use crate::muffler::Muffler;
 
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 10.0; // 10 metres
         let chamber_length = 1.0;
@@ -145,11 +164,12 @@ mod tests {
     #[test]
     fn test_extreme_small_chamber_produces_finite_tl() {
         // Very small chamber: 1 mm diameter
-        use crate::constants::{area_from_diameter, speed_of_sound_and_density};
+        use crate::constants::{area_from_diameter, speed_of_sound_and_density, STANDARD_PRESSURE};
+        use crate::gas::Gas;
         use crate::elements::StraightDuct;
         use crate::muffler::Muffler;
 
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 1e-3; // 1 mm
         let chamber_length = 5e-3;   // 5 mm
@@ -171,11 +191,12 @@ mod tests {
     #[test]
     fn test_very_high_frequency_near_nyquist_produces_finite_tl() {
         // Near Nyquist: 22050 Hz (half of 44100 sample rate)
-        use crate::constants::{area_from_diameter, speed_of_sound_and_density};
+        use crate::constants::{area_from_diameter, speed_of_sound_and_density, STANDARD_PRESSURE};
+        use crate::gas::Gas;
         use crate::elements::StraightDuct;
         use crate::muffler::Muffler;
 
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
@@ -202,11 +223,12 @@ mod tests {
     #[test]
     fn test_very_low_frequency_produces_finite_tl() {
         // Very low frequency: 1 Hz
-        use crate::constants::{area_from_diameter, speed_of_sound_and_density};
+        use crate::constants::{area_from_diameter, speed_of_sound_and_density, STANDARD_PRESSURE};
+        use crate::gas::Gas;
         use crate::elements::StraightDuct;
         use crate::muffler::Muffler;
 
-        let (c, rho) = speed_of_sound_and_density(20.0);
+        let (c, rho) = speed_of_sound_and_density(Gas::Air, 20.0, 0.0, STANDARD_PRESSURE);
         let pipe_diameter = 6e-3;
         let chamber_diameter = 40e-3;
         let chamber_length = 80e-3;
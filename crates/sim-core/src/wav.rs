@@ -0,0 +1,257 @@
+use std::io;
+use std::path::Path;
+
+use crate::provenance::Provenance;
+
+/// WAVE format tag of integer PCM samples.
+const FORMAT_PCM: u16 = 1;
+
+/// WAVE format tag of IEEE floating-point samples.
+const FORMAT_IEEE_FLOAT: u16 = 3;
+
+/// WAVE format tag whose real format follows in a sub-format GUID.
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// Bytes per 32-bit float sample.
+const BYTES_PER_SAMPLE: u32 = 4;
+
+/// Encode `samples` as a mono 32-bit float WAV file at `sample_rate` Hz.
+///
+/// The samples are written as they are, without normalising, so that a
+/// convolution reverb or REW sees the muffler's true gain.
+pub fn encode(samples: &[f64], sample_rate: f64) -> Vec<u8> {
+    encode_with_comment(samples, sample_rate, None)
+}
+
+/// Encode `samples` like [`encode`], with the summary of `provenance` in
+/// a LIST/INFO comment (ICMT) chunk, so the file can be traced to the
+/// engine and settings that produced it.
+pub fn encode_with_provenance(samples: &[f64], sample_rate: f64, provenance: &Provenance) -> Vec<u8> {
+    encode_with_comment(samples, sample_rate, Some(&provenance.summary()))
+}
+
+fn encode_with_comment(samples: &[f64], sample_rate: f64, comment: Option<&str>) -> Vec<u8> {
+    let rate = sample_rate.round() as u32;
+    let data_size = samples.len() as u32 * BYTES_PER_SAMPLE;
+    // INFO list holding the comment as a NUL-terminated ICMT chunk
+    let info = comment.map(|comment| {
+        let mut text = comment.as_bytes().to_vec();
+        text.push(0);
+        let mut info = b"INFOICMT".to_vec();
+        info.extend_from_slice(&(text.len() as u32).to_le_bytes());
+        if text.len() % 2 == 1 {
+            text.push(0);
+        }
+        info.extend_from_slice(&text);
+        info
+    });
+    let list_size = info.as_ref().map_or(0, |info| 8 + info.len() as u32);
+    let mut bytes = Vec::with_capacity(58 + (list_size + data_size) as usize);
+    // RIFF header, a format chunk, the fact chunk non-PCM formats need, the
+    // comment and the samples
+    bytes.extend_from_slice(b"RIFF");
+    bytes.extend_from_slice(&(4 + (8 + 18) + (8 + 4) + list_size + (8 + data_size)).to_le_bytes());
+    bytes.extend_from_slice(b"WAVE");
+    bytes.extend_from_slice(b"fmt ");
+    bytes.extend_from_slice(&18u32.to_le_bytes());
+    bytes.extend_from_slice(&FORMAT_IEEE_FLOAT.to_le_bytes());
+    bytes.extend_from_slice(&1u16.to_le_bytes());
+    bytes.extend_from_slice(&rate.to_le_bytes());
+    bytes.extend_from_slice(&(rate * BYTES_PER_SAMPLE).to_le_bytes());
+    bytes.extend_from_slice(&(BYTES_PER_SAMPLE as u16).to_le_bytes());
+    bytes.extend_from_slice(&(8 * BYTES_PER_SAMPLE as u16).to_le_bytes());
+    bytes.extend_from_slice(&0u16.to_le_bytes());
+    bytes.extend_from_slice(b"fact");
+    bytes.extend_from_slice(&4u32.to_le_bytes());
+    bytes.extend_from_slice(&(samples.len() as u32).to_le_bytes());
+    if let Some(info) = info {
+        bytes.extend_from_slice(b"LIST");
+        bytes.extend_from_slice(&(info.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&info);
+    }
+    bytes.extend_from_slice(b"data");
+    bytes.extend_from_slice(&data_size.to_le_bytes());
+    for &sample in samples {
+        bytes.extend_from_slice(&(sample as f32).to_le_bytes());
+    }
+    bytes
+}
+
+/// Write `samples` to `path` as a mono 32-bit float WAV file at
+/// `sample_rate` Hz; see [`encode`].
+pub fn write(path: impl AsRef<Path>, samples: &[f64], sample_rate: f64) -> io::Result<()> {
+    std::fs::write(path, encode(samples, sample_rate))
+}
+
+/// Write `samples` to `path` stamped with `provenance`; see
+/// [`encode_with_provenance`].
+pub fn write_with_provenance(
+    path: impl AsRef<Path>,
+    samples: &[f64],
+    sample_rate: f64,
+    provenance: &Provenance,
+) -> io::Result<()> {
+    std::fs::write(path, encode_with_provenance(samples, sample_rate, provenance))
+}
+
+/// The (id, body) chunks of a RIFF body starting at `offset`, each body
+/// cut short at the end of `bytes`.
+fn chunks(bytes: &[u8], mut offset: usize) -> impl Iterator<Item = (&[u8], &[u8])> {
+    std::iter::from_fn(move || {
+        if offset + 8 > bytes.len() {
+            return None;
+        }
+        let id = &bytes[offset..offset + 4];
+        let size = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into().unwrap()) as usize;
+        let body = offset + 8;
+        let end = body.saturating_add(size).min(bytes.len());
+        // Chunks are padded to an even length
+        offset = body.saturating_add(size + size % 2);
+        Some((id, &bytes[body..end]))
+    })
+}
+
+/// Decode a PCM (8, 16, 24 or 32-bit) or float (32 or 64-bit) WAV file
+/// into mono samples, averaging the channels, and its sample rate in Hz.
+/// PCM samples are scaled to ±1.
+pub fn decode(bytes: &[u8]) -> Result<(Vec<f64>, f64), String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return Err("not a RIFF/WAVE file".to_string());
+    }
+    // (format tag, channels, sample rate, bits per sample)
+    let mut format: Option<(u16, usize, u32, usize)> = None;
+    let mut data: Option<&[u8]> = None;
+    for (id, body) in chunks(bytes, 12) {
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let u16_at = |i: usize| u16::from_le_bytes([body[i], body[i + 1]]);
+                let u32_at = |i: usize| u32::from_le_bytes(body[i..i + 4].try_into().unwrap());
+                let mut tag = u16_at(0);
+                if tag == FORMAT_EXTENSIBLE && body.len() >= 26 {
+                    // The sub-format GUID starts with the plain format tag
+                    tag = u16_at(24);
+                }
+                format = Some((tag, u16_at(2) as usize, u32_at(4), u16_at(14) as usize));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+    }
+    let (tag, channels, rate, bits) = format.ok_or("missing fmt chunk")?;
+    let data = data.ok_or("missing data chunk")?;
+    if channels == 0 || rate == 0 {
+        return Err(format!("unsupported WAV layout: {channels} channels at {rate} Hz"));
+    }
+
+    let width = bits / 8;
+    let sample: fn(&[u8]) -> f64 = match (tag, bits) {
+        (FORMAT_PCM, 8) => |b| (b[0] as f64 - 128.0) / 128.0,
+        (FORMAT_PCM, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f64 / 32_768.0,
+        (FORMAT_PCM, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f64 / 8_388_608.0,
+        (FORMAT_PCM, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64 / 2_147_483_648.0,
+        (FORMAT_IEEE_FLOAT, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f64,
+        (FORMAT_IEEE_FLOAT, 64) => |b| f64::from_le_bytes(b.try_into().unwrap()),
+        _ => return Err(format!("unsupported WAV format {tag} with {bits} bits per sample")),
+    };
+    let samples = data
+        .chunks_exact(width * channels)
+        .map(|frame| frame.chunks_exact(width).map(sample).sum::<f64>() / channels as f64)
+        .collect();
+    Ok((samples, rate as f64))
+}
+
+/// The comment (ICMT) in the LIST/INFO chunk of a WAV file, such as the
+/// provenance written by [`encode_with_provenance`], if it has one.
+pub fn decode_comment(bytes: &[u8]) -> Option<String> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        return None;
+    }
+    chunks(bytes, 12)
+        .filter(|&(id, body)| id == b"LIST" && body.starts_with(b"INFO"))
+        .flat_map(|(_, info)| chunks(info, 4))
+        .find(|&(id, _)| id == b"ICMT")
+        .map(|(_, text)| String::from_utf8_lossy(text).trim_end_matches('\0').to_string())
+}
+
+/// Read a WAV file from `path`; see [`decode`].
+pub fn read(path: impl AsRef<Path>) -> Result<(Vec<f64>, f64), String> {
+    let path = path.as_ref();
+    let bytes = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    decode(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gas::Gas;
+    use crate::numerics::Numerics;
+
+    #[test]
+    fn test_encode_float_wav() {
+        let samples = [1.0, -0.5, 0.25, 2.0];
+        let bytes = encode(&samples, 48_000.0);
+        let u16_at = |i: usize| u16::from_le_bytes([bytes[i], bytes[i + 1]]);
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        assert_eq!(&bytes[0..4], b"RIFF");
+        assert_eq!(u32_at(4) as usize, bytes.len() - 8);
+        assert_eq!(&bytes[8..16], b"WAVEfmt ");
+        assert_eq!(u16_at(20), FORMAT_IEEE_FLOAT);
+        assert_eq!(u16_at(22), 1);
+        assert_eq!(u32_at(24), 48_000);
+        assert_eq!(u32_at(28), 192_000);
+        assert_eq!(u16_at(34), 32);
+        assert_eq!(&bytes[38..42], b"fact");
+        assert_eq!(u32_at(46), 4);
+        assert_eq!(&bytes[50..54], b"data");
+        assert_eq!(u32_at(54), 16);
+        // Samples are stored unscaled, beyond ±1 included
+        let decoded: Vec<f64> = bytes[58..]
+            .chunks(4)
+            .map(|b| f32::from_le_bytes(b.try_into().unwrap()) as f64)
+            .collect();
+        assert_eq!(decoded, samples);
+    }
+
+    #[test]
+    fn test_decode() {
+        let samples = [1.0, -0.5, 0.25, 2.0];
+        assert_eq!(decode(&encode(&samples, 48_000.0)).unwrap(), (samples.to_vec(), 48_000.0));
+
+        // 16-bit stereo PCM mixes down to mono, with a stray odd-sized chunk
+        let mut bytes = b"RIFF\0\0\0\0WAVEfmt \x10\0\0\0".to_vec();
+        for field in [1u16, 2] {
+            bytes.extend_from_slice(&field.to_le_bytes());
+        }
+        bytes.extend_from_slice(&8000u32.to_le_bytes());
+        bytes.extend_from_slice(&32_000u32.to_le_bytes());
+        bytes.extend_from_slice(&4u16.to_le_bytes());
+        bytes.extend_from_slice(&16u16.to_le_bytes());
+        bytes.extend_from_slice(b"LIST\x01\0\0\0x\0");
+        bytes.extend_from_slice(b"data\x08\0\0\0");
+        for value in [16_384i16, 0, -32_768, -32_768] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        assert_eq!(decode(&bytes).unwrap(), (vec![0.25, -1.0], 8000.0));
+
+        assert!(decode(b"RIFF\0\0\0\0WAVE").is_err());
+        assert!(decode(b"not a wav file").is_err());
+    }
+
+    #[test]
+    fn test_provenance_comment() {
+        let samples = [1.0, -0.5, 0.25];
+        let provenance = Provenance::new(&Numerics::default(), Gas::Air, 343.2, 1.204);
+        let bytes = encode_with_provenance(&samples, 48_000.0, &provenance);
+        assert_eq!(u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize, bytes.len() - 8);
+        assert_eq!(decode_comment(&bytes), Some(provenance.summary()));
+        assert_eq!(decode(&bytes).unwrap(), (samples.to_vec(), 48_000.0));
+
+        // Odd and even comment lengths both pad to whole chunks
+        for comment in ["odd", "even"] {
+            let bytes = encode_with_comment(&samples, 8000.0, Some(comment));
+            assert_eq!(bytes.len() % 2, 0);
+            assert_eq!(decode_comment(&bytes).as_deref(), Some(comment));
+        }
+        assert_eq!(decode_comment(&encode(&samples, 48_000.0)), None);
+    }
+}
@@ -1,14 +1,119 @@
+use std::sync::{mpsc, Arc};
+
 use sim_core::audio::AudioPipeline;
+use sim_core::muffler::{AxialPoint, BuildError, Muffler};
+use sim_core::pump::SampleSource;
+use sim_core::recording::{Recording, RecordingPlayer};
+use sim_core::rpm_profile::RpmProfile;
+use sim_core::test_signal::{SignalGenerator, TestSignal};
 use sim_core::{SimParams, SimResult};
 
-use crate::{geometry_view, plot_view, ui, ui::UiState};
+use sim_core::SweepSpacing;
+use sim_core::loudness::{self, LoudnessMetric};
+
+use crate::plot_view::ZoomedTl;
+use crate::{geometry_view, plot_view, schematic_view, ui, ui::UiState};
+
+/// Zoomed in to fewer FFT bins than this, the plot gets its own sweep.
+const ZOOM_MIN_BINS: f64 = 64.0;
+/// Points in the zoomed sweep.
+const ZOOM_POINTS: usize = 1024;
+/// Pump harmonics checked for landing in a TL null.
+const CHECKED_HARMONICS: usize = 10;
+/// Points sampled inside each duct for the standing-wave overlay.
+const STANDING_WAVE_SAMPLES: usize = 16;
+/// Fastest beat in Hz between the two pumps' harmonics that is listed.
+const MAX_BEAT_RATE: f64 = 10.0;
+/// Largest gain in dB applied either way to match loudness, so a near
+/// silent design cannot blast the next one.
+const MAX_GAIN_OFFSET_DB: f64 = 20.0;
+
+/// What playback levels are measured with besides the design: a change
+/// starts a new reference.
+#[derive(PartialEq)]
+struct LevelKey {
+    metric: LoudnessMetric,
+    test_signal: Option<TestSignal>,
+    /// The recording played and the RPM it follows, if any.
+    recording: Option<(*const Recording, Option<f64>)>,
+}
+
+/// Playback levels of the reference design and the current one, in dB
+/// under `key`, measured on a worker thread.
+struct LevelMatch {
+    key: LevelKey,
+    /// Level of the reference design, once measured.
+    reference: Option<f64>,
+    /// Level of the current design, kept until its successor is measured.
+    current: Option<f64>,
+    /// The running measurement.
+    pending: Option<mpsc::Receiver<f64>>,
+    /// The design changed since the running measurement started.
+    stale: bool,
+}
+
+impl LevelMatch {
+    /// Take the running measurement once done, unless the design changed
+    /// while it ran.
+    fn poll(&mut self) {
+        let Some(pending) = &self.pending else {
+            return;
+        };
+        match pending.try_recv() {
+            Ok(level) => {
+                self.pending = None;
+                if !self.stale {
+                    self.current = Some(level);
+                    self.reference.get_or_insert(level);
+                }
+            }
+            Err(mpsc::TryRecvError::Empty) => {}
+            Err(mpsc::TryRecvError::Disconnected) => self.pending = None,
+        }
+    }
+}
+
+/// The dry signal playback feeds the muffler, at `sample_rate` Hz: the
+/// test signal, else the recording, else the pump (also standing in for
+/// live input, which cannot be replayed).
+fn program(
+    params: &SimParams,
+    ui_state: &UiState,
+    recording: Option<&Arc<Recording>>,
+    sample_rate: f64,
+) -> Box<dyn SampleSource + Send> {
+    match (ui_state.test_signal, recording) {
+        (Some(signal), _) => Box::new(SignalGenerator::new(signal, sample_rate)),
+        (None, Some(recording)) => {
+            let mut player = RecordingPlayer::new(Arc::clone(recording), sample_rate);
+            if ui_state.recording_follows_rpm {
+                player.set_pitch(params.pump_rpm() / ui_state.recording_rpm);
+            }
+            Box::new(player)
+        }
+        (None, None) => Box::new(params.pump_source(sample_rate)),
+    }
+}
 
 pub struct App {
     params: SimParams,
     ui_state: UiState,
     result: SimResult,
+    /// Element chain of `params`, rebuilt when they change.
+    chain: Result<Muffler, BuildError>,
+    /// Pressure along `chain` at the standing-wave frequency, once shown.
+    standing_wave: Option<(f64, Vec<AxialPoint>)>,
     audio: AudioPipeline,
     was_playing: bool,
+    zoom: Option<ZoomedTl>,
+    level_match: Option<LevelMatch>,
+    /// Recording of a real pump played instead of the model, if loaded.
+    recording: Option<Arc<Recording>>,
+    /// Pump harmonics through the muffler of `result`, once shown.
+    pump_tones: Option<Vec<(f64, f64)>>,
+    /// Design the second pump plays through when it has a muffler of its
+    /// own.
+    second_muffler: Option<SimResult>,
 }
 
 impl App {
@@ -16,34 +121,268 @@ impl App {
         let params = SimParams::default();
         let result = sim_core::compute(&params).expect("default params must be valid");
         let audio = AudioPipeline::new();
-        audio.swap_ir(result.impulse_response.clone());
-        audio.set_pump_params(params.rpm, params.num_valves, params.duty_cycle);
+        audio.swap_ir(result.impulse_response.clone(), result.sample_rate);
+        audio.configure_pump(&params);
 
         Self {
+            chain: Muffler::from_params(&params),
+            standing_wave: None,
             params,
             ui_state: UiState::default(),
             result,
             audio,
             was_playing: false,
+            zoom: None,
+            level_match: None,
+            recording: None,
+            pump_tones: None,
+            second_muffler: None,
+        }
+    }
+}
+
+impl App {
+    /// Re-sweep the visible range when the plot is zoomed in past the FFT
+    /// grid, so narrow resonances show up; drop the sweep when zoomed out.
+    fn update_zoom(&mut self, (f_min, f_max): (f64, f64)) {
+        let nyquist = self.result.sample_rate / 2.0;
+        let (f_min, f_max) = (f_min.max(1.0), f_max.min(nyquist));
+        let bin_width = self.params.numerics.bin_width(self.result.sample_rate);
+        if f_max <= f_min || (f_max - f_min) / bin_width > ZOOM_MIN_BINS {
+            self.zoom = None;
+            return;
+        }
+        if self.zoom.as_ref().is_some_and(|zoom| zoom.range == (f_min, f_max)) {
+            return;
+        }
+        self.zoom = sim_core::compute_tl_range(
+            &self.params,
+            f_min,
+            f_max,
+            ZOOM_POINTS,
+            SweepSpacing::Linear,
+        )
+        .ok()
+        .map(|(frequencies, tl)| ZoomedTl {
+            range: (f_min, f_max),
+            points: frequencies.into_iter().zip(tl).map(|(f, tl)| [f, tl]).collect(),
+        });
+    }
+}
+
+impl App {
+    /// Keep playback as loud as the reference design while loudness
+    /// matching is on, re-measuring the current design in the background
+    /// when it was `recomputed`.
+    fn update_level_match(&mut self, ctx: &egui::Context, recomputed: bool) {
+        if !self.ui_state.match_loudness {
+            self.level_match = None;
+            self.ui_state.gain_offset_db = None;
+            self.ui_state.gain_offset_limited = false;
+            self.audio.set_gain_offset(0.0);
+            return;
+        }
+        let metric = self.ui_state.loudness_metric;
+        let key = LevelKey {
+            metric,
+            test_signal: self.ui_state.test_signal,
+            recording: self.recording.as_ref().map(|recording| {
+                let follows = self.ui_state.recording_follows_rpm.then_some(self.ui_state.recording_rpm);
+                (Arc::as_ptr(recording), follows)
+            }),
+        };
+        let matched = match &mut self.level_match {
+            Some(matched) if matched.key == key && !self.ui_state.reset_level_reference => {
+                matched.stale |= recomputed;
+                matched
+            }
+            _ => {
+                self.ui_state.reset_level_reference = false;
+                self.level_match.insert(LevelMatch {
+                    key,
+                    reference: None,
+                    current: None,
+                    pending: None,
+                    stale: true,
+                })
+            }
+        };
+        matched.poll();
+        if matched.pending.is_none() && matched.stale {
+            let sample_rate = self.result.sample_rate;
+            let mut program = program(&self.params, &self.ui_state, self.recording.as_ref(), sample_rate);
+            let impulse_response = self.result.impulse_response.clone();
+            let (sender, receiver) = mpsc::channel();
+            let ctx = ctx.clone();
+            std::thread::spawn(move || {
+                let level = loudness::design_level(program.as_mut(), &impulse_response, sample_rate, metric);
+                if sender.send(level).is_ok() {
+                    ctx.request_repaint();
+                }
+            });
+            matched.pending = Some(receiver);
+            matched.stale = false;
+        }
+        let Some(offset) = matched
+            .reference
+            .zip(matched.current)
+            .map(|(reference, current)| reference - current)
+        else {
+            // Nothing to match against until both are measured
+            self.ui_state.gain_offset_db = None;
+            self.ui_state.gain_offset_limited = false;
+            self.audio.set_gain_offset(0.0);
+            return;
+        };
+        let offset = if offset.is_finite() { offset } else { 0.0 };
+        let limited = offset.clamp(-MAX_GAIN_OFFSET_DB, MAX_GAIN_OFFSET_DB);
+        self.ui_state.gain_offset_db = Some(limited);
+        self.ui_state.gain_offset_limited = limited != offset;
+        self.audio.set_gain_offset(limited);
+    }
+
+    /// Load or drop the pump recording when asked, and keep playback
+    /// looping it.
+    fn update_recording(&mut self) {
+        if std::mem::take(&mut self.ui_state.clear_recording) {
+            self.recording = None;
+            self.ui_state.recording_status = None;
+        }
+        if std::mem::take(&mut self.ui_state.load_recording) {
+            match Recording::load(&self.ui_state.recording_path) {
+                Ok(recording) => {
+                    self.ui_state.recording_status = Some(format!(
+                        "Looping {:.2} s at {} Hz",
+                        recording.duration(),
+                        recording.sample_rate()
+                    ));
+                    self.recording = Some(Arc::new(recording));
+                }
+                Err(e) => self.ui_state.recording_status = Some(e),
+            }
+        }
+        let recorded_rpm = self.ui_state.recording_follows_rpm.then_some(self.ui_state.recording_rpm);
+        self.audio.set_recording(self.recording.clone(), recorded_rpm);
+    }
+
+    /// Load or drop the room / headphone IR as the buttons ask.
+    fn update_room_ir(&mut self) {
+        if std::mem::take(&mut self.ui_state.clear_room_ir) {
+            let _ = self.audio.swap_room_ir(None);
+            self.ui_state.room_ir_status = None;
+        }
+        if std::mem::take(&mut self.ui_state.load_room_ir) {
+            let loaded = sim_core::wav::read(&self.ui_state.room_ir_path).and_then(|(ir, sample_rate)| {
+                let duration = ir.len() as f64 / sample_rate;
+                self.audio.swap_room_ir(Some((ir, sample_rate)))?;
+                Ok(format!("Convolving {duration:.2} s at {sample_rate} Hz"))
+            });
+            self.ui_state.room_ir_status = Some(loaded.unwrap_or_else(|e| e));
+        }
+    }
+
+    /// Open or close the live input device as the checkbox asks.
+    fn update_live_input(&mut self) {
+        match (self.ui_state.live_input, self.audio.is_live_input()) {
+            (true, false) => match self.audio.start_live_input() {
+                Ok(name) => self.ui_state.live_input_status = Some(format!("Listening to {name}")),
+                Err(e) => {
+                    self.ui_state.live_input = false;
+                    self.ui_state.live_input_status = Some(e);
+                }
+            },
+            (false, true) => {
+                self.audio.stop_live_input();
+                self.ui_state.live_input_status = None;
+            }
+            _ => {}
+        }
+    }
+}
+
+impl App {
+    /// Sample the pressure along the chain when the standing-wave overlay
+    /// is on and its frequency or the chain changed.
+    fn update_standing_wave(&mut self) {
+        let frequency = self.ui_state.standing_wave;
+        if self.standing_wave.as_ref().map(|(f, _)| *f) == frequency {
+            return;
+        }
+        self.standing_wave = frequency.and_then(|frequency| {
+            let muffler = self.chain.as_ref().ok()?;
+            let (c, rho) = self.params.medium();
+            let omega = 2.0 * std::f64::consts::PI * frequency;
+            Some((frequency, muffler.axial_distribution(omega, c, rho, STANDING_WAVE_SAMPLES)))
+        });
+    }
+}
+
+impl App {
+    /// Play the RPM sweep set up in the UI, or the fixed RPM without one.
+    fn update_rpm_sweep(&mut self, ctx: &egui::Context) {
+        let state = &self.ui_state;
+        let profile = state.rpm_sweep.then(|| RpmProfile {
+            looped: state.sweep_loop,
+            ..RpmProfile::ramp(
+                state.sweep_low_rpm,
+                state.sweep_high_rpm,
+                state.sweep_ramp,
+                state.sweep_hold,
+                state.sweep_ramp,
+            )
+        });
+        self.audio.set_rpm_profile(profile);
+        self.ui_state.sweep_rpm = self.audio.profile_rpm();
+        if self.ui_state.sweep_rpm.is_some() {
+            // Keep the speed readout moving
+            ctx.request_repaint();
+        }
+    }
+}
+
+impl App {
+    /// Give the second pump its own muffler (a snapshot of the design at
+    /// the time) or share the current one, and list the beats between the
+    /// pumps when either muffler was `recomputed` or swapped.
+    fn update_second_pump(&mut self, recomputed: bool) {
+        let own = self.ui_state.second_own_muffler && self.params.second_pump.is_some();
+        let snapshot = std::mem::take(&mut self.ui_state.snapshot_second_muffler);
+        let mut swapped = false;
+        if own && (snapshot || self.second_muffler.is_none()) {
+            self.audio.swap_second_ir(Some((self.result.impulse_response.clone(), self.result.sample_rate)));
+            self.second_muffler = Some(self.result.clone());
+            swapped = true;
+        } else if !own && self.second_muffler.take().is_some() {
+            self.audio.swap_second_ir(None);
+            swapped = true;
+        }
+        if recomputed || swapped {
+            let second = self.second_muffler.as_ref().unwrap_or(&self.result);
+            self.ui_state.beats = sim_core::metrics::beats(&self.params, &self.result, second, MAX_BEAT_RATE);
         }
     }
 }
 
 impl eframe::App for App {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
-        geometry_view::draw_geometry(ctx, &self.params);
+        self.update_standing_wave();
+        let standing_wave = self.standing_wave.as_ref().map(|(_, points)| points.as_slice());
+        geometry_view::draw_geometry(ctx, &self.params, standing_wave);
         let changed = ui::draw_controls(ctx, &mut self.params, &mut self.ui_state);
 
+        let mut recomputed = false;
         if changed {
+            self.chain = Muffler::from_params(&self.params);
+            self.standing_wave = None;
             match sim_core::compute(&self.params) {
                 Ok(result) => {
+                    recomputed = true;
                     self.result = result;
-                    self.audio.swap_ir(self.result.impulse_response.clone());
-                    self.audio.set_pump_params(
-                        self.params.rpm,
-                        self.params.num_valves,
-                        self.params.duty_cycle,
-                    );
+                    self.pump_tones = None;
+                    self.ui_state.validation_error_db = None;
+                    self.zoom = None;
+                    self.audio.swap_ir(self.result.impulse_response.clone(), self.result.sample_rate);
+                    self.audio.configure_pump(&self.params);
                 }
                 Err(e) => {
                     eprintln!("Simulation error: {e}");
@@ -51,10 +390,71 @@ impl eframe::App for App {
             }
         }
 
-        plot_view::draw_tl_plot(ctx, &self.result);
+        if self.ui_state.export_ir {
+            self.ui_state.export_ir = false;
+            let path = &self.ui_state.ir_export_path;
+            let written = sim_core::wav::write_with_provenance(
+                path,
+                &self.result.impulse_response,
+                self.result.sample_rate,
+                &self.result.provenance,
+            );
+            self.ui_state.ir_export_status = Some(match written {
+                Ok(()) => format!(
+                    "Wrote {} samples at {} Hz to {path}",
+                    self.result.impulse_response.len(),
+                    self.result.sample_rate
+                ),
+                Err(e) => format!("Could not write {path}: {e}"),
+            });
+        }
+
+        if self.ui_state.run_validation {
+            self.ui_state.run_validation = false;
+            let measurement = sim_core::measurement::validate(&self.result, 2.0);
+            self.ui_state.validation_error_db = Some(measurement.error_db);
+        }
+
+        if self.ui_state.show_schematic {
+            schematic_view::draw_schematic(ctx, &self.chain);
+        }
+
+        let muted = self.ui_state.mute_band.then_some(self.ui_state.notch.frequency);
+        let fundamental = self.params.pump_source(self.result.sample_rate).fundamental_frequency();
+        let harmonics: Vec<f64> = (1..=CHECKED_HARMONICS).map(|n| n as f64 * fundamental).collect();
+        if self.ui_state.show_pump_tones && self.pump_tones.is_none() {
+            self.pump_tones = Some(sim_core::metrics::pump_tones(&self.params, &self.result));
+        }
+        let tones = self.pump_tones.as_deref().filter(|_| self.ui_state.show_pump_tones);
+        let plot = plot_view::draw_tl_plot(ctx, &self.result, self.zoom.as_ref(), muted, &harmonics, tones);
+        self.update_zoom(plot.visible);
+        if let (true, Some(frequency)) = (self.ui_state.mute_band, plot.clicked) {
+            self.ui_state.notch.frequency = frequency;
+        }
 
         // Handle audio play/stop toggle.
         self.audio.set_volume(self.ui_state.volume as f64);
+        self.audio.set_dry_wet(self.ui_state.dry_wet as f64);
+        self.audio.set_bypass(self.ui_state.bypass_muffler);
+        self.audio.set_glide_time(self.ui_state.glide_time as f64);
+        if std::mem::take(&mut self.ui_state.reset_clip) {
+            self.audio.reset_clip();
+        }
+        self.ui_state.output_levels = self.audio.output_levels();
+        self.ui_state.pipeline_stats = self.audio.stats();
+        if self.audio.is_playing() {
+            // Keep the level meter and pipeline stats moving
+            ctx.request_repaint();
+        }
+        self.audio.set_test_signal(self.ui_state.test_signal);
+        self.update_recording();
+        self.update_live_input();
+        self.update_room_ir();
+        self.update_rpm_sweep(ctx);
+        self.update_second_pump(recomputed);
+        self.update_level_match(ctx, recomputed);
+        self.audio.set_notch(self.ui_state.mute_band.then_some(self.ui_state.notch));
+        self.audio.set_position(self.ui_state.spatialize.then_some(self.ui_state.position));
         if self.ui_state.play_audio && !self.was_playing {
             self.audio.play();
             self.was_playing = true;
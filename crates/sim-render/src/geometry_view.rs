@@ -1,12 +1,15 @@
 // 2D muffler cross-section drawn with egui painter — Phase 3 implementation.
 
+use sim_core::elements::CrossSection;
+use sim_core::muffler::AxialPoint;
 use sim_core::SimParams;
 
 /// Draw a simplified 2D cross-section of the muffler in a top panel.
 ///
 /// Three rectangles represent the inlet pipe, expansion chamber, and outlet pipe.
 /// Widths are proportional to pipe/chamber lengths, heights to diameters.
-pub fn draw_geometry(ctx: &egui::Context, params: &SimParams) {
+/// A `standing_wave` is overlaid as its pressure magnitude along the axis.
+pub fn draw_geometry(ctx: &egui::Context, params: &SimParams, standing_wave: Option<&[AxialPoint]>) {
     egui::TopBottomPanel::top("geometry")
         .min_height(120.0)
         .show(ctx, |ui| {
@@ -19,10 +22,19 @@ pub fn draw_geometry(ctx: &egui::Context, params: &SimParams) {
 
             // Compute scale so the full muffler fits in the available width
             // with some padding.
-            let total_length_m =
-                params.inlet_length + params.chamber_length + params.outlet_length;
-            let max_diameter_m = params
-                .chamber_diameter
+            let chamber_length = params
+                .chamber_profile
+                .as_ref()
+                .map_or(params.chamber_length, |profile| profile.length());
+            let total_length_m = params.inlet_length + chamber_length + params.outlet_length;
+            // Side view: a rectangular chamber shows its height
+            let chamber_height = match (&params.chamber_profile, params.chamber_section()) {
+                (Some(profile), _) => profile.max_diameter(),
+                (None, CrossSection::Circular { diameter }) => diameter,
+                (None, CrossSection::Rectangular { height, .. }) => height,
+                (None, CrossSection::Annular { outer_diameter, .. }) => outer_diameter,
+            };
+            let max_diameter_m = chamber_height
                 .max(params.inlet_diameter)
                 .max(params.outlet_diameter);
 
@@ -40,13 +52,14 @@ pub fn draw_geometry(ctx: &egui::Context, params: &SimParams) {
             let center_y = rect.center().y;
             let start_x = rect.left() + padding;
 
-            // Helper to draw a pipe/chamber segment as a centered rectangle.
-            let draw_segment =
-                |painter: &egui::Painter, x: f32, length_m: f64, diameter_m: f64, color: egui::Color32| {
+            // Helper to draw a pipe/chamber segment as a rectangle centred
+            // `offset_m` above the axis.
+            let draw_offset_segment =
+                |painter: &egui::Painter, x: f32, offset_m: f64, length_m: f64, diameter_m: f64, color: egui::Color32| {
                     let w = length_m as f32 * scale_x;
                     let h = diameter_m as f32 * scale_y;
                     let segment_rect = egui::Rect::from_center_size(
-                        egui::pos2(x + w / 2.0, center_y),
+                        egui::pos2(x + w / 2.0, center_y - offset_m as f32 * scale_y),
                         egui::vec2(w, h),
                     );
                     painter.rect_filled(segment_rect, 2.0, color);
@@ -58,20 +71,126 @@ pub fn draw_geometry(ctx: &egui::Context, params: &SimParams) {
                     );
                     w
                 };
+            let draw_segment =
+                |painter: &egui::Painter, x: f32, length_m: f64, diameter_m: f64, color: egui::Color32| {
+                    draw_offset_segment(painter, x, 0.0, length_m, diameter_m, color)
+                };
+
+            // Off-axis ports, with the outlet projected onto the inlet's plane
+            let (inlet_offset, outlet_offset) = params
+                .port_offsets
+                .map_or((0.0, 0.0), |offsets| (offsets.inlet, offsets.outlet * offsets.angle.cos()));
 
             // Draw inlet pipe
             let mut x = start_x;
             let inlet_color = egui::Color32::from_rgb(80, 120, 180);
-            let w = draw_segment(&painter, x, params.inlet_length, params.inlet_diameter, inlet_color);
+            let w = draw_offset_segment(&painter, x, inlet_offset, params.inlet_length, params.inlet_diameter, inlet_color);
             x += w;
 
             // Draw expansion chamber
             let chamber_color = egui::Color32::from_rgb(180, 100, 60);
-            let w = draw_segment(&painter, x, params.chamber_length, params.chamber_diameter, chamber_color);
+            let chamber_x = x;
+            let w = match &params.chamber_profile {
+                Some(profile) => {
+                    // The measured bore, one trapezoid per segment
+                    let start = profile.profile.first().map_or(0.0, |&(position, _)| position);
+                    let point = |position: f64, radius: f64| {
+                        egui::pos2(
+                            x + (position - start) as f32 * scale_x,
+                            center_y - radius as f32 * scale_y,
+                        )
+                    };
+                    for pair in profile.profile.windows(2) {
+                        let ((x0, r0), (x1, r1)) = (pair[0], pair[1]);
+                        painter.add(egui::Shape::convex_polygon(
+                            vec![point(x0, r0), point(x1, r1), point(x1, -r1), point(x0, -r0)],
+                            chamber_color,
+                            egui::Stroke::NONE,
+                        ));
+                    }
+                    let stroke = egui::Stroke::new(1.5, egui::Color32::WHITE);
+                    for side in [1.0, -1.0] {
+                        let edge = profile.profile.iter().map(|&(position, radius)| point(position, side * radius));
+                        painter.add(egui::Shape::line(edge.collect(), stroke));
+                    }
+                    profile.length() as f32 * scale_x
+                }
+                None => draw_segment(&painter, x, params.chamber_length, chamber_height, chamber_color),
+            };
             x += w;
 
-            // Draw outlet pipe
+            // Draw pipe extensions protruding into the chamber
+            if params.inlet_extension > 0.0 {
+                draw_offset_segment(
+                    &painter,
+                    chamber_x,
+                    inlet_offset,
+                    params.inlet_extension,
+                    params.inlet_diameter,
+                    inlet_color,
+                );
+            }
             let outlet_color = egui::Color32::from_rgb(80, 160, 120);
-            draw_segment(&painter, x, params.outlet_length, params.outlet_diameter, outlet_color);
+            if params.outlet_extension > 0.0 {
+                let ext_x = x - params.outlet_extension as f32 * scale_x;
+                draw_offset_segment(
+                    &painter,
+                    ext_x,
+                    outlet_offset,
+                    params.outlet_extension,
+                    params.outlet_diameter,
+                    outlet_color,
+                );
+            }
+
+            // Draw the baffle plate and its connecting tube halfway along
+            // the free chamber length
+            if let Some(baffle) = &params.baffle {
+                let free_length = params.chamber_length - params.inlet_extension - params.outlet_extension;
+                let middle = params.inlet_extension + free_length / 2.0;
+                let baffle_color = egui::Color32::from_rgb(140, 140, 140);
+                let plate_x = chamber_x + (middle - baffle.thickness / 2.0) as f32 * scale_x;
+                draw_segment(&painter, plate_x, baffle.thickness, chamber_height, baffle_color);
+                let tube_x = chamber_x + (middle - baffle.tube_length / 2.0) as f32 * scale_x;
+                draw_segment(&painter, tube_x, baffle.tube_length, baffle.tube_diameter, chamber_color);
+            }
+
+            if let Some(plate) = &params.perforated_baffle {
+                let free_length = params.chamber_length - params.inlet_extension - params.outlet_extension;
+                let middle = params.inlet_extension + free_length / 2.0;
+                let plate_color = egui::Color32::from_rgb(170, 170, 120);
+                let plate_x = chamber_x + (middle - plate.thickness / 2.0) as f32 * scale_x;
+                draw_segment(&painter, plate_x, plate.thickness, chamber_height, plate_color);
+            }
+
+            // Draw outlet pipe
+            draw_offset_segment(
+                &painter,
+                x,
+                outlet_offset,
+                params.outlet_length,
+                params.outlet_diameter,
+                outlet_color,
+            );
+
+            // |p| along the axis, scaled to the drawing height; an air line
+            // beyond the outlet is cut off
+            if let Some(points) = standing_wave {
+                let peak = points.iter().map(|point| point.pressure.norm()).fold(0.0, f64::max);
+                if peak > 0.0 {
+                    let bottom = center_y + draw_height / 2.0;
+                    let curve = points
+                        .iter()
+                        .filter(|point| point.position <= total_length_m)
+                        .map(|point| {
+                            egui::pos2(
+                                start_x + point.position as f32 * scale_x,
+                                bottom - (point.pressure.norm() / peak) as f32 * draw_height,
+                            )
+                        })
+                        .collect();
+                    painter.add(egui::Shape::line(curve, egui::Stroke::new(2.0, egui::Color32::YELLOW)));
+                }
+            }
         });
 }
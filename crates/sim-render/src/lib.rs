@@ -1,6 +1,7 @@
 pub mod app;
 pub mod geometry_view;
 pub mod plot_view;
+pub mod schematic_view;
 pub mod ui;
 
 use app::App;
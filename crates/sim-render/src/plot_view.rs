@@ -1,23 +1,145 @@
 // TL plot via egui_plot — Phase 3 implementation.
 
-use egui_plot::{Line, Plot};
+use egui_plot::{Bar, BarChart, Line, Plot, Points, VLine};
+use sim_core::metrics::Weighting;
+use sim_core::resonances::{self, FeatureKind};
 use sim_core::SimResult;
 
-/// Draw the transmission loss plot in the central panel.
-pub fn draw_tl_plot(ctx: &egui::Context, result: &SimResult) {
+/// A finer TL sweep over the frequency range the plot is zoomed into.
+pub struct ZoomedTl {
+    /// Frequency range in Hz the sweep covers.
+    pub range: (f64, f64),
+    /// `[frequency, TL]` points.
+    pub points: Vec<[f64; 2]>,
+}
+
+/// What the user did with the TL plot this frame.
+pub struct PlotResponse {
+    /// Visible frequency range in Hz.
+    pub visible: (f64, f64),
+    /// Frequency in Hz the user clicked, if any.
+    pub clicked: Option<f64>,
+}
+
+/// Smallest prominence in dB of a TL peak or null worth marking.
+const MIN_PROMINENCE_DB: f64 = 3.0;
+
+/// Draw the transmission loss plot in the central panel, overlaying
+/// `zoom` and marking the `muted` frequency if given. Warns when one of
+/// the pump `harmonics` (Hz) falls into a TL null. `pump_tones` (see
+/// [`sim_core::metrics::pump_tones`]) are drawn as bars if given.
+pub fn draw_tl_plot(
+    ctx: &egui::Context,
+    result: &SimResult,
+    zoom: Option<&ZoomedTl>,
+    muted: Option<f64>,
+    harmonics: &[f64],
+    pump_tones: Option<&[(f64, f64)]>,
+) -> PlotResponse {
     egui::CentralPanel::default().show(ctx, |ui| {
-        ui.heading("Transmission Loss");
+        ui.heading("Transmission Loss")
+            .on_hover_text(result.provenance.summary());
+        if let Some(back_pressure) = result.back_pressure {
+            ui.label(format!("Back-pressure at stone flow: {:.2} kPa", back_pressure / 1000.0));
+        }
+        if let Some(first) = result.mode_warnings.first() {
+            let details: Vec<String> = result.mode_warnings.iter().map(|w| w.to_string()).collect();
+            ui.label(format!("Plane-wave model unreliable above {:.0} Hz", first.cutoff))
+                .on_hover_text(details.join("\n"));
+        }
+        if result.ir_truncated() {
+            ui.label(format!(
+                "Impulse response drops {:.0} % of its energy",
+                100.0 * result.ir_truncation_loss
+            ))
+            .on_hover_text("The audio no longer matches the plotted response; raise the FFT size or IR length");
+        }
+        let decay = &result.decay;
+        if let Some(t20) = decay.t20 {
+            ui.label(format!(
+                "Decay T20 {:.0} ms, early/late {:+.1} dB",
+                t20 * 1000.0,
+                decay.early_to_late
+            ))
+            .on_hover_text(format!(
+                "Time the impulse response takes to fall 60 dB, and its energy before {:.0} ms over the energy after",
+                decay.early_time * 1000.0
+            ));
+        }
+        if let Some(radiated) = &result.radiated {
+            ui.label(format!(
+                "At {:.1} m: {:.0} dB(A), {:.0} dB(C)",
+                radiated.distance,
+                radiated.overall_level(Weighting::A),
+                radiated.overall_level(Weighting::C)
+            ));
+        }
+        let flow_noise = &result.flow_noise;
+        if let Some(loudest) = flow_noise.loudest() {
+            let text = format!(
+                "Flow noise: {:.0} dB re 1 pW ({} jet at {:.1} m/s)",
+                flow_noise.sound_power_level(),
+                loudest.label.to_lowercase(),
+                loudest.velocity
+            );
+            if flow_noise.exceeds_pump_noise() {
+                ui.colored_label(egui::Color32::LIGHT_RED, format!("{text} exceeds the muffled pump tone"));
+            } else {
+                ui.label(text);
+            }
+        }
 
-        // Build plot points from simulation result
-        let points: Vec<[f64; 2]> = result
-            .frequencies
-            .iter()
-            .zip(result.transmission_loss.iter())
-            .filter(|(&f, _)| f > 0.0) // skip DC for cleaner plot
-            .map(|(&f, &tl)| [f, tl])
-            .collect();
+        // Build plot points from simulation result, preferring the log
+        // sweep or the refined grid when there is one, and grey out the
+        // bins above the plane-wave cutoff
+        let (frequencies, transmission_loss) = match result.log_sweep.as_ref().or(result.refined.as_ref()) {
+            Some(sweep) => (&sweep.frequencies, &sweep.transmission_loss),
+            None => (&result.frequencies, &result.transmission_loss),
+        };
+        let features = resonances::find(frequencies, transmission_loss, MIN_PROMINENCE_DB);
+        let bin_width = result.frequencies.get(1).copied().unwrap_or(0.0);
+        if let Some((order, null)) = resonances::harmonic_nulls(&features, harmonics, bin_width / 2.0).first() {
+            ui.colored_label(
+                egui::Color32::LIGHT_RED,
+                format!(
+                    "Pump harmonic {order} ({:.0} Hz) falls in a TL null at {:.0} Hz ({:.1} dB)",
+                    harmonics[order - 1],
+                    null.frequency,
+                    null.transmission_loss
+                ),
+            );
+        }
+        let marks = |kind| -> Vec<[f64; 2]> {
+            features
+                .iter()
+                .filter(|feature| feature.kind == kind)
+                .map(|feature| [feature.frequency, feature.transmission_loss])
+                .collect()
+        };
+        let (peaks, nulls) = (marks(FeatureKind::Peak), marks(FeatureKind::Null));
+        let cutoff = result.cutoff_frequency.unwrap_or(f64::INFINITY);
+        let (mut points, mut unreliable) = (Vec::new(), Vec::new());
+        for (&f, &tl) in frequencies.iter().zip(transmission_loss) {
+            if f <= 0.0 {
+                continue; // skip DC for cleaner plot
+            }
+            if f < cutoff {
+                points.push([f, tl]);
+            } else {
+                // Start the grey part where the valid one ends
+                if unreliable.is_empty() {
+                    unreliable.extend(points.last().copied());
+                }
+                unreliable.push([f, tl]);
+            }
+        }
 
         let line = Line::new(points).name("TL (dB)");
+        let unreliable_line = (!unreliable.is_empty()).then(|| {
+            Line::new(unreliable)
+                .color(egui::Color32::GRAY)
+                .name("TL above cutoff (dB)")
+        });
 
         Plot::new("tl_plot")
             .x_axis_label("Frequency (Hz)")
@@ -25,6 +147,57 @@ pub fn draw_tl_plot(ctx: &egui::Context, result: &SimResult) {
             .legend(egui_plot::Legend::default())
             .show(ui, |plot_ui| {
                 plot_ui.line(line);
-            });
-    });
+                if let Some(line) = unreliable_line {
+                    plot_ui.line(line);
+                }
+                plot_ui.points(Points::new(peaks).radius(3.0).color(egui::Color32::LIGHT_GREEN).name("TL peaks"));
+                plot_ui.points(Points::new(nulls).radius(3.0).color(egui::Color32::LIGHT_RED).name("TL nulls"));
+                if let Some(cutoff) = result.cutoff_frequency {
+                    plot_ui.vline(VLine::new(cutoff).name("Plane-wave cutoff"));
+                }
+                if let Some(breakout) = &result.breakout_loss {
+                    let points: Vec<[f64; 2]> = result
+                        .frequencies
+                        .iter()
+                        .zip(breakout)
+                        .filter(|(&f, _)| f > 0.0)
+                        .map(|(&f, &tl)| [f, tl])
+                        .collect();
+                    plot_ui.line(Line::new(points).name("Shell breakout (dB)"));
+                }
+                if let Some(zoom) = zoom {
+                    plot_ui.line(Line::new(zoom.points.clone()).name("TL, zoomed sweep (dB)"));
+                }
+                if let Some(frequency) = muted {
+                    plot_ui.vline(VLine::new(frequency).name("Muted band"));
+                }
+                if let Some(tones) = pump_tones {
+                    let width = tones.first().map_or(1.0, |&(fundamental, _)| fundamental / 4.0);
+                    let bars = tones
+                        .iter()
+                        .filter(|(_, level)| level.is_finite())
+                        .map(|&(frequency, level)| Bar::new(frequency, level).width(width))
+                        .collect();
+                    plot_ui.bar_chart(
+                        BarChart::new(bars)
+                            .color(egui::Color32::from_rgb(230, 160, 60))
+                            .name("Pump harmonics after muffler (dB re source)"),
+                    );
+                }
+                let clicked = plot_ui
+                    .response()
+                    .clicked()
+                    .then(|| plot_ui.pointer_coordinate())
+                    .flatten()
+                    .map(|point| point.x)
+                    .filter(|&f| f > 0.0);
+                let bounds = plot_ui.plot_bounds();
+                PlotResponse {
+                    visible: (bounds.min()[0], bounds.max()[0]),
+                    clicked,
+                }
+            })
+            .inner
+    })
+    .inner
 }
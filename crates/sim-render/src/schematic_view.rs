@@ -0,0 +1,156 @@
+// Equivalent acoustic circuit of the muffler element chain, drawn with egui painter.
+
+use sim_core::muffler::{BuildError, Muffler};
+use sim_core::numerics::Termination;
+use sim_core::Connection;
+
+/// Where each element of `muffler` sits in the equivalent circuit, with
+/// its label, from the source to the load.
+pub fn circuit(muffler: &Muffler) -> Vec<(Connection, String)> {
+    muffler
+        .elements()
+        .iter()
+        .map(|elem| (elem.connection(), elem.label()))
+        .collect()
+}
+
+/// Label of the load impedance closing the circuit.
+fn load_label(muffler: &Muffler) -> String {
+    match muffler.termination() {
+        Termination::Unflanged { .. } => "ZL = open end".to_string(),
+        Termination::Flanged { .. } => "ZL = flanged end".to_string(),
+        Termination::Anechoic | Termination::AirStone(_) => format!("ZL = {:.2e}", muffler.z_load),
+    }
+}
+
+/// Draw the equivalent acoustic circuit of `chain` in a bottom panel, or
+/// the reason it could not be built.
+///
+/// The source sits on the left, the load impedance on the right. Series
+/// elements (ducts, chambers) are boxes on the top rail; shunt elements
+/// (side-branch resonators) hang between the rail and the ground return.
+pub fn draw_schematic(ctx: &egui::Context, chain: &Result<Muffler, BuildError>) {
+    egui::TopBottomPanel::bottom("schematic")
+        .resizable(true)
+        .min_height(140.0)
+        .show(ctx, |ui| {
+            ui.heading("Equivalent Circuit");
+
+            let muffler = match chain {
+                Ok(muffler) => muffler,
+                Err(e) => {
+                    ui.colored_label(ui.visuals().error_fg_color, format!("Invalid chain: {e}"));
+                    return;
+                }
+            };
+
+            let available = ui.available_size();
+            let (response, painter) = ui.allocate_painter(available, egui::Sense::hover());
+            let rect = response.rect;
+
+            let elements = circuit(muffler);
+            let padding = 20.0;
+            let rail_y = rect.top() + padding + 10.0;
+            let ground_y = rect.bottom() - padding;
+            let slots = elements.len() + 2; // source + elements + load
+            let slot_w = (rect.width() - 2.0 * padding) / slots as f32;
+            let slot_x = |i: usize| rect.left() + padding + slot_w * (i as f32 + 0.5);
+
+            let wire = egui::Stroke::new(1.5, egui::Color32::LIGHT_GRAY);
+            let text_color = ui.visuals().text_color();
+            let font = egui::FontId::proportional(11.0);
+            let box_w = (slot_w * 0.8).min(160.0);
+            let box_h = 22.0;
+
+            // Rails
+            painter.line_segment(
+                [egui::pos2(slot_x(0), rail_y), egui::pos2(slot_x(slots - 1), rail_y)],
+                wire,
+            );
+            painter.line_segment(
+                [egui::pos2(slot_x(0), ground_y), egui::pos2(slot_x(slots - 1), ground_y)],
+                wire,
+            );
+
+            // Source: pressure source with internal impedance Z_s
+            let src_x = slot_x(0);
+            painter.line_segment([egui::pos2(src_x, rail_y), egui::pos2(src_x, ground_y)], wire);
+            let src_center = egui::pos2(src_x, (rail_y + ground_y) / 2.0);
+            painter.circle_filled(src_center, 12.0, ui.visuals().panel_fill);
+            painter.circle_stroke(src_center, 12.0, wire);
+            painter.text(src_center, egui::Align2::CENTER_CENTER, "~", font.clone(), text_color);
+            painter.text(
+                egui::pos2(src_x, ground_y + 2.0),
+                egui::Align2::CENTER_TOP,
+                format!("Pump (Zs = {:.2e})", muffler.z_source),
+                font.clone(),
+                text_color,
+            );
+
+            let draw_box = |center: egui::Pos2, color: egui::Color32, label: &str| {
+                let r = egui::Rect::from_center_size(center, egui::vec2(box_w, box_h));
+                painter.rect_filled(r, 2.0, color);
+                painter.rect_stroke(r, 2.0, wire, egui::StrokeKind::Outside);
+                painter.text(center, egui::Align2::CENTER_CENTER, label, font.clone(), egui::Color32::WHITE);
+            };
+
+            for (i, (connection, label)) in elements.iter().enumerate() {
+                let x = slot_x(i + 1);
+                match connection {
+                    Connection::Series => {
+                        draw_box(
+                            egui::pos2(x, rail_y),
+                            egui::Color32::from_rgb(80, 120, 180),
+                            label,
+                        );
+                    }
+                    Connection::Shunt => {
+                        painter.line_segment([egui::pos2(x, rail_y), egui::pos2(x, ground_y)], wire);
+                        painter.circle_filled(egui::pos2(x, rail_y), 3.0, egui::Color32::LIGHT_GRAY);
+                        draw_box(
+                            egui::pos2(x, (rail_y + ground_y) / 2.0),
+                            egui::Color32::from_rgb(180, 100, 60),
+                            label,
+                        );
+                    }
+                }
+            }
+
+            // Load impedance to ground
+            let load_x = slot_x(slots - 1);
+            painter.line_segment([egui::pos2(load_x, rail_y), egui::pos2(load_x, ground_y)], wire);
+            draw_box(
+                egui::pos2(load_x, (rail_y + ground_y) / 2.0),
+                egui::Color32::from_rgb(80, 160, 120),
+                &load_label(muffler),
+            );
+        });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sim_core::elements::AbsorptiveBranch;
+    use sim_core::SimParams;
+
+    #[test]
+    fn test_circuit_places_side_branch_as_shunt() {
+        let params = SimParams::default();
+        let plain = circuit(&Muffler::from_params(&params).unwrap());
+        assert!(plain.iter().all(|(connection, _)| *connection == Connection::Series));
+
+        let params = SimParams {
+            side_branch: Some(AbsorptiveBranch::new(0.1, 0.01, 0.02, 20_000.0)),
+            ..params
+        };
+        let branched = circuit(&Muffler::from_params(&params).unwrap());
+        // The branch splits the outlet pipe into two series halves
+        assert_eq!(branched.len(), plain.len() + 2);
+        let shunts: Vec<usize> = (0..branched.len())
+            .filter(|&i| branched[i].0 == Connection::Shunt)
+            .collect();
+        assert_eq!(shunts, [branched.len() - 2]);
+        assert!(branched[shunts[0]].1.starts_with("Damped branch"));
+        assert_eq!(branched[shunts[0] - 1], branched[shunts[0] + 1]);
+    }
+}
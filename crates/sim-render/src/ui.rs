@@ -1,11 +1,147 @@
 // egui control panel: sliders, toggles, readouts — Phase 3 implementation.
 
-use sim_core::SimParams;
+use sim_core::air_line::AirLine;
+use sim_core::audio::{Notch, OutputLevels, PipelineStats, SourcePosition};
+use sim_core::breakout::{Shell, ShellMaterial};
+use sim_core::calibration::{self, Calibration};
+use sim_core::elements::{AbsorptiveBranch, Baffle, CrossSection, Orifice, PerforatedPlate, ProfiledDuct};
+use sim_core::gas::Gas;
+use sim_core::loudness::LoudnessMetric;
+use sim_core::metrics::Beat;
+use sim_core::numerics::{Engine, IrWindow, LogSweep, Refinement, Termination, WallLossModel};
+use sim_core::pump::{
+    Harmonic, HarmonicSeries, MotorNoise, PistonCrank, PumpDrive, ReedValve, SecondPump, SpeedWander, ValveLift,
+    MAX_HARMONIC,
+};
+use sim_core::radiation::Listener;
+use sim_core::test_signal::TestSignal;
+use sim_core::{AcousticElement, PortOffsets, SimParams};
+
+/// Sample rate `sim_core::compute` sweeps at, for the resolution readout
+/// and the pump preview.
+const SAMPLE_RATE: f64 = 44100.0;
 
 /// Extra UI-only state that doesn't belong in SimParams.
 pub struct UiState {
     pub play_audio: bool,
     pub volume: f32,
+    /// Share of the muffled signal in playback, 0 (dry) to 1 (wet).
+    pub dry_wet: f32,
+    /// Play the unmuffled pump instead of the muffled one.
+    pub bypass_muffler: bool,
+    /// Time constant in seconds over which playback glides to a new pump
+    /// speed and duty cycle.
+    pub glide_time: f32,
+    /// Output levels and limiter gain reduction, for the meter.
+    pub output_levels: OutputLevels,
+    /// Set when the clip indicator is clicked, to clear it.
+    pub reset_clip: bool,
+    /// Underruns and timing of the audio pipeline, for display.
+    pub pipeline_stats: PipelineStats,
+    /// Test signal to play instead of the pump, if any.
+    pub test_signal: Option<TestSignal>,
+    /// Mute `notch` from playback.
+    pub mute_band: bool,
+    /// Band to mute; clicking the TL plot moves it.
+    pub notch: Notch,
+    /// Pump harmonic the "Select" button moves the notch to.
+    pub mute_harmonic: u32,
+    /// Place the outlet around the listener instead of in their head.
+    pub spatialize: bool,
+    /// Where the outlet is placed.
+    pub position: SourcePosition,
+    /// Offset playback so every design is as loud as the reference one.
+    pub match_loudness: bool,
+    pub loudness_metric: LoudnessMetric,
+    /// Set by the "Use as reference" button; the app clears it once it has
+    /// taken the current design's level as the reference.
+    pub reset_level_reference: bool,
+    /// Gain the app applies to match the reference, for display; `None`
+    /// until both levels are measured.
+    pub gain_offset_db: Option<f64>,
+    /// Whether matching the reference needs more gain than the app
+    /// applies, so the levels still differ.
+    pub gain_offset_limited: bool,
+    pub show_schematic: bool,
+    /// Overlay the pump harmonics' levels through the muffler on the TL
+    /// plot.
+    pub show_pump_tones: bool,
+    /// Frequency in Hz of the standing wave drawn over the cross-section,
+    /// if shown.
+    pub standing_wave: Option<f64>,
+    /// Set by the "Run sweep" button; the app clears it once the virtual
+    /// measurement has run.
+    pub run_validation: bool,
+    /// Error of the last virtual sweep measurement in dB, until the
+    /// parameters change.
+    pub validation_error_db: Option<f64>,
+    /// Pasted `frequency, TL` measurement of the built design.
+    pub measurement_text: String,
+    /// Outcome of the last calibration fit, or why it failed.
+    pub calibration_status: Option<String>,
+    /// Pasted `harmonic, amplitude[, phase]` order spectrum of the pump.
+    pub series_text: String,
+    /// Why the last order spectrum failed to load, if it did.
+    pub series_status: Option<String>,
+    /// Pasted `crank angle, open area` valve lift profile.
+    pub lift_text: String,
+    /// Why the last valve lift profile failed to load, if it did.
+    pub lift_status: Option<String>,
+    /// Pasted `position, radius` chamber bore profile in millimetres.
+    pub profile_text: String,
+    /// Why the last profile failed to load, if it did.
+    pub profile_status: Option<String>,
+    /// File the "Export IR" button writes the impulse response to.
+    pub ir_export_path: String,
+    /// Set by the "Export IR" button; the app clears it once it has
+    /// written the file.
+    pub export_ir: bool,
+    /// Outcome of the last impulse response export.
+    pub ir_export_status: Option<String>,
+    /// WAV recording of a real pump to loop instead of the synthesized one.
+    pub recording_path: String,
+    /// Set by the "Load" and "Clear" recording buttons; the app clears
+    /// them once it has loaded or dropped the recording.
+    pub load_recording: bool,
+    pub clear_recording: bool,
+    /// Loaded recording, or why it failed to load.
+    pub recording_status: Option<String>,
+    /// Play the input device through the muffler instead of the pump.
+    pub live_input: bool,
+    /// Input device playing, or why it could not be opened.
+    pub live_input_status: Option<String>,
+    /// WAV file of a room response or headphone correction to hear the
+    /// output through.
+    pub room_ir_path: String,
+    /// Set by the "Load" and "Clear" room IR buttons; the app clears them
+    /// once it has loaded or dropped the response.
+    pub load_room_ir: bool,
+    pub clear_room_ir: bool,
+    /// Loaded room IR, or why it failed to load.
+    pub room_ir_status: Option<String>,
+    /// Speed the recording up and down with the pump RPM.
+    pub recording_follows_rpm: bool,
+    /// RPM the pump was recorded at.
+    pub recording_rpm: f64,
+    /// Sweep the pump speed during playback instead of holding the RPM.
+    pub rpm_sweep: bool,
+    /// RPM the sweep starts and ends at, and its top speed.
+    pub sweep_low_rpm: f64,
+    pub sweep_high_rpm: f64,
+    /// Seconds spent spinning up (and down again), and at the top speed.
+    pub sweep_ramp: f64,
+    pub sweep_hold: f64,
+    /// Repeat the sweep.
+    pub sweep_loop: bool,
+    /// RPM playback is at within the sweep, for display.
+    pub sweep_rpm: Option<f64>,
+    /// Play the second pump through a snapshot of a design rather than
+    /// the current muffler.
+    pub second_own_muffler: bool,
+    /// Snapshot the current design as the second pump's muffler.
+    pub snapshot_second_muffler: bool,
+    /// Beats between the two pumps, loudest first, for display.
+    pub beats: Vec<Beat>,
 }
 
 impl Default for UiState {
@@ -13,6 +149,64 @@ impl Default for UiState {
         Self {
             play_audio: false,
             volume: 0.5,
+            dry_wet: 1.0,
+            bypass_muffler: false,
+            glide_time: 0.05,
+            output_levels: OutputLevels::default(),
+            reset_clip: false,
+            pipeline_stats: PipelineStats::default(),
+            test_signal: None,
+            mute_band: false,
+            spatialize: false,
+            position: SourcePosition::default(),
+            notch: Notch {
+                frequency: 1000.0,
+                bandwidth: 50.0,
+            },
+            mute_harmonic: 1,
+            match_loudness: false,
+            loudness_metric: LoudnessMetric::Lufs,
+            reset_level_reference: false,
+            gain_offset_db: None,
+            gain_offset_limited: false,
+            show_schematic: false,
+            show_pump_tones: false,
+            standing_wave: None,
+            run_validation: false,
+            validation_error_db: None,
+            measurement_text: String::new(),
+            calibration_status: None,
+            series_text: String::new(),
+            series_status: None,
+            lift_text: String::new(),
+            lift_status: None,
+            profile_text: String::new(),
+            profile_status: None,
+            ir_export_path: "muffler_ir.wav".to_string(),
+            export_ir: false,
+            ir_export_status: None,
+            recording_path: "pump.wav".to_string(),
+            load_recording: false,
+            clear_recording: false,
+            recording_status: None,
+            live_input: false,
+            live_input_status: None,
+            room_ir_path: "room.wav".to_string(),
+            load_room_ir: false,
+            clear_room_ir: false,
+            room_ir_status: None,
+            recording_follows_rpm: false,
+            recording_rpm: 3000.0,
+            rpm_sweep: false,
+            sweep_low_rpm: 1000.0,
+            sweep_high_rpm: 6000.0,
+            sweep_ramp: 5.0,
+            sweep_hold: 2.0,
+            sweep_loop: true,
+            sweep_rpm: None,
+            second_own_muffler: false,
+            snapshot_second_muffler: false,
+            beats: Vec::new(),
         }
     }
 }
@@ -33,15 +227,40 @@ pub fn draw_controls(
             ui.separator();
 
             // --- Chamber ---
-            ui.label("Chamber Diameter (mm)");
-            let mut chamber_diam_mm = (params.chamber_diameter * 1000.0) as f32;
-            if ui
-                .add(egui::Slider::new(&mut chamber_diam_mm, 10.0..=100.0))
-                .changed()
-            {
-                params.chamber_diameter = chamber_diam_mm as f64 / 1000.0;
+            let mut rectangular = matches!(params.chamber_shape, Some(CrossSection::Rectangular { .. }));
+            if ui.checkbox(&mut rectangular, "Rectangular chamber").changed() {
+                params.chamber_shape = rectangular.then_some(CrossSection::Rectangular {
+                    width: params.chamber_diameter,
+                    height: params.chamber_diameter,
+                });
                 changed = true;
             }
+            match &mut params.chamber_shape {
+                Some(CrossSection::Rectangular { width, height }) => {
+                    for (label, side) in [("Chamber Width (mm)", width), ("Chamber Height (mm)", height)] {
+                        ui.label(label);
+                        let mut side_mm = (*side * 1000.0) as f32;
+                        if ui
+                            .add(egui::Slider::new(&mut side_mm, 5.0..=150.0))
+                            .changed()
+                        {
+                            *side = side_mm as f64 / 1000.0;
+                            changed = true;
+                        }
+                    }
+                }
+                _ => {
+                    ui.label("Chamber Diameter (mm)");
+                    let mut chamber_diam_mm = (params.chamber_diameter * 1000.0) as f32;
+                    if ui
+                        .add(egui::Slider::new(&mut chamber_diam_mm, 10.0..=100.0))
+                        .changed()
+                    {
+                        params.chamber_diameter = chamber_diam_mm as f64 / 1000.0;
+                        changed = true;
+                    }
+                }
+            }
 
             ui.label("Chamber Length (mm)");
             let mut chamber_len_mm = (params.chamber_length * 1000.0) as f32;
@@ -53,6 +272,49 @@ pub fn draw_controls(
                 changed = true;
             }
 
+            egui::CollapsingHeader::new("Chamber profile").show(ui, |ui| {
+                ui.label("Bore from CAD (position, radius in mm per line)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut ui_state.profile_text)
+                        .desired_rows(4)
+                        .code_editor(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Load").clicked() {
+                        let loaded = ProfiledDuct::from_table(&ui_state.profile_text)
+                            .and_then(|profile| profile.validate().map(|()| profile));
+                        match loaded {
+                            Ok(profile) => {
+                                // The profile takes over the chamber body
+                                params.chamber_length = profile.length();
+                                params.chamber_diameter = profile.max_diameter();
+                                params.chamber_shape = None;
+                                params.baffle = None;
+                                params.perforated_baffle = None;
+                                params.port_offsets = None;
+                                params.shell = None;
+                                params.inlet_extension = 0.0;
+                                params.outlet_extension = 0.0;
+                                params.chamber_profile = Some(profile);
+                                ui_state.profile_status = None;
+                                changed = true;
+                            }
+                            Err(e) => ui_state.profile_status = Some(e),
+                        }
+                    }
+                    if ui.button("Clear").clicked() && params.chamber_profile.is_some() {
+                        params.chamber_profile = None;
+                        changed = true;
+                    }
+                });
+                if let Some(status) = &ui_state.profile_status {
+                    ui.label(status);
+                }
+                if let Some(profile) = &params.chamber_profile {
+                    ui.label(profile.label());
+                }
+            });
+
             ui.separator();
 
             // --- Inlet ---
@@ -99,16 +361,448 @@ pub fn draw_controls(
                 changed = true;
             }
 
+            // An air line takes over the outlet's load
+            ui.add_enabled_ui(params.air_line.is_none(), |ui| {
+                egui::ComboBox::from_label("Outlet End")
+                    .selected_text(params.outlet_termination.name())
+                    .show_ui(ui, |ui| {
+                        for termination in Termination::OUTLET {
+                            let name = termination.name();
+                            if ui
+                                .selectable_value(&mut params.outlet_termination, termination, name)
+                                .changed()
+                            {
+                                changed = true;
+                            }
+                        }
+                    });
+            });
+
+            // --- Predicted level at a listening distance ---
+            let mut has_listener = params.listener.is_some();
+            if ui.checkbox(&mut has_listener, "Predict SPL at a distance").changed() {
+                params.listener = has_listener.then(Listener::default);
+                changed = true;
+            }
+            let calibrated = params.pump_displacement.is_some();
+            if let Some(listener) = &mut params.listener {
+                ui.label("Pump Volume Velocity (mL/s peak)");
+                let mut strength_ml = (listener.source_strength * 1e6) as f32;
+                if ui
+                    .add_enabled(
+                        !calibrated,
+                        egui::Slider::new(&mut strength_ml, 0.1..=1000.0).logarithmic(true),
+                    )
+                    .on_disabled_hover_text("Set by the pump displacement")
+                    .changed()
+                {
+                    listener.source_strength = strength_ml as f64 / 1e6;
+                    changed = true;
+                }
+
+                ui.label("Listening Distance (m)");
+                let mut distance = listener.distance as f32;
+                if ui
+                    .add(egui::Slider::new(&mut distance, 0.1..=10.0).logarithmic(true))
+                    .changed()
+                {
+                    listener.distance = distance as f64;
+                    changed = true;
+                }
+            }
+
             ui.separator();
 
-            // --- Pump ---
-            ui.label("Pump RPM");
-            let mut rpm = params.rpm as f32;
+            // --- Pipe extensions into the chamber ---
+            // Together they must stop short of spanning the chamber; keep
+            // a millimetre between their ends
+            let free_mm = |other: f64| ((params.chamber_length - other) * 1000.0 - 1.0).max(0.0) as f32;
+            ui.label("Inlet Extension (mm)");
+            let mut inlet_ext_mm = (params.inlet_extension * 1000.0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut inlet_ext_mm, 0.0..=free_mm(params.outlet_extension)))
+                .changed()
+            {
+                params.inlet_extension = inlet_ext_mm as f64 / 1000.0;
+                changed = true;
+            }
+
+            ui.label("Outlet Extension (mm)");
+            let mut outlet_ext_mm = (params.outlet_extension * 1000.0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut outlet_ext_mm, 0.0..=free_mm(params.inlet_extension)))
+                .changed()
+            {
+                params.outlet_extension = outlet_ext_mm as f64 / 1000.0;
+                changed = true;
+            }
+
+            ui.separator();
+
+            // --- Inlet orifice ---
+            let mut has_orifice = params.orifice.is_some();
+            if ui.checkbox(&mut has_orifice, "Inlet orifice").changed() {
+                params.orifice = has_orifice.then(|| Orifice::new(params.inlet_diameter / 2.0, 1e-3));
+                changed = true;
+            }
+            if let Some(orifice) = &mut params.orifice {
+                ui.label("Hole Diameter (mm)");
+                let mut hole_mm = (orifice.hole_diameter * 1000.0) as f32;
+                let max_hole_mm = (params.inlet_diameter * 1000.0) as f32 * 0.95;
+                if ui
+                    .add(egui::Slider::new(&mut hole_mm, 0.5..=max_hole_mm))
+                    .changed()
+                {
+                    orifice.hole_diameter = hole_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Plate Thickness (mm)");
+                let mut thickness_mm = (orifice.thickness * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut thickness_mm, 0.2..=5.0))
+                    .changed()
+                {
+                    orifice.thickness = thickness_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                // Quasi-linear jet loss at the pump's pulsation level
+                let mut high_amplitude = params.source_pressure.is_some();
+                if ui.checkbox(&mut high_amplitude, "High-amplitude correction").changed() {
+                    params.source_pressure = high_amplitude.then_some(500.0);
+                    changed = true;
+                }
+                if let Some(pressure) = &mut params.source_pressure {
+                    ui.label("Pulsation Pressure (Pa peak)");
+                    let mut pressure_pa = *pressure as f32;
+                    if ui
+                        .add(egui::Slider::new(&mut pressure_pa, 10.0..=10_000.0).logarithmic(true))
+                        .changed()
+                    {
+                        *pressure = pressure_pa as f64;
+                        changed = true;
+                    }
+                }
+            }
+
+            ui.separator();
+
+            // --- Side branch on the outlet pipe ---
+            let mut has_branch = params.side_branch.is_some();
+            if ui.checkbox(&mut has_branch, "Damped side branch").changed() {
+                params.side_branch = has_branch.then(|| AbsorptiveBranch::new(0.1, params.outlet_diameter, 0.01, 20_000.0));
+                changed = true;
+            }
+            if let Some(branch) = &mut params.side_branch {
+                ui.label("Branch Length (mm)");
+                let mut length_mm = (branch.length * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut length_mm, 10.0..=300.0))
+                    .changed()
+                {
+                    branch.length = length_mm as f64 / 1000.0;
+                    branch.plug_thickness = branch.plug_thickness.min(0.9 * branch.length);
+                    changed = true;
+                }
+
+                ui.label("Branch Diameter (mm)");
+                let mut diameter_mm = (branch.diameter * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut diameter_mm, 2.0..=20.0))
+                    .changed()
+                {
+                    branch.diameter = diameter_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Plug Thickness (mm)");
+                let mut plug_mm = (branch.plug_thickness * 1000.0) as f32;
+                let max_plug_mm = (branch.length * 1000.0) as f32 * 0.9;
+                if ui
+                    .add(egui::Slider::new(&mut plug_mm, 0.0..=max_plug_mm))
+                    .changed()
+                {
+                    branch.plug_thickness = plug_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Plug Flow Resistivity (kPa·s/m²)");
+                let mut resistivity = (branch.flow_resistivity / 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut resistivity, 1.0..=100.0).logarithmic(true))
+                    .changed()
+                {
+                    branch.flow_resistivity = resistivity as f64 * 1000.0;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Baffle ---
+            let mut has_baffle = params.baffle.is_some();
+            if ui.checkbox(&mut has_baffle, "Baffle with connecting tube").changed() {
+                params.baffle = has_baffle.then(|| {
+                    let chamber_diameter = params.chamber_section().equivalent_diameter();
+                    Baffle::new(chamber_diameter, 1e-3, params.inlet_diameter, 5e-3)
+                });
+                if has_baffle {
+                    params.perforated_baffle = None;
+                }
+                changed = true;
+            }
+            let chamber_diameter = params.chamber_section().equivalent_diameter();
+            if let Some(baffle) = &mut params.baffle {
+                ui.label("Tube Diameter (mm)");
+                let mut tube_mm = (baffle.tube_diameter * 1000.0) as f32;
+                let max_tube_mm = (chamber_diameter * 1000.0) as f32 * 0.8;
+                if ui
+                    .add(egui::Slider::new(&mut tube_mm, 1.0..=max_tube_mm))
+                    .changed()
+                {
+                    baffle.tube_diameter = tube_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Tube Length (mm)");
+                let mut length_mm = (baffle.tube_length * 1000.0) as f32;
+                let min_length_mm = (baffle.thickness * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut length_mm, min_length_mm..=40.0))
+                    .changed()
+                {
+                    baffle.tube_length = length_mm as f64 / 1000.0;
+                    changed = true;
+                }
+            }
+
+            // A perforated plate replaces the baffle
+            let mut has_plate = params.perforated_baffle.is_some();
+            if ui.checkbox(&mut has_plate, "Perforated baffle plate").changed() {
+                params.perforated_baffle = has_plate.then(|| {
+                    let chamber_diameter = params.chamber_section().equivalent_diameter();
+                    PerforatedPlate::new(chamber_diameter, 0.1, 1e-3, 1e-3)
+                });
+                if has_plate {
+                    params.baffle = None;
+                }
+                changed = true;
+            }
+            if let Some(plate) = &mut params.perforated_baffle {
+                ui.label("Porosity (%)");
+                let mut porosity_pct = (plate.porosity * 100.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut porosity_pct, 1.0..=50.0))
+                    .changed()
+                {
+                    plate.porosity = porosity_pct as f64 / 100.0;
+                    changed = true;
+                }
+
+                ui.label("Hole Diameter (mm)");
+                let mut hole_mm = (plate.hole_diameter * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut hole_mm, 0.3..=5.0))
+                    .changed()
+                {
+                    plate.hole_diameter = hole_mm as f64 / 1000.0;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Port positions ---
+            let circular =
+                params.chamber_shape.is_none() && params.baffle.is_none() && params.perforated_baffle.is_none();
+            let mut has_offsets = params.port_offsets.is_some();
             if ui
-                .add(egui::Slider::new(&mut rpm, 500.0..=10000.0))
+                .add_enabled(circular, egui::Checkbox::new(&mut has_offsets, "Off-axis ports"))
+                .on_disabled_hover_text("Needs a circular chamber without a baffle")
                 .changed()
             {
-                params.rpm = rpm as f64;
+                params.port_offsets = has_offsets.then_some(PortOffsets {
+                    inlet: 0.0,
+                    outlet: 0.0,
+                    angle: std::f64::consts::PI,
+                });
+                changed = true;
+            }
+            let radius = params.chamber_diameter / 2.0;
+            let (inlet_max, outlet_max) = (
+                radius - params.inlet_diameter / 2.0,
+                radius - params.outlet_diameter / 2.0,
+            );
+            if let Some(offsets) = &mut params.port_offsets {
+                for (label, offset, max) in [
+                    ("Inlet Offset (mm)", &mut offsets.inlet, inlet_max),
+                    ("Outlet Offset (mm)", &mut offsets.outlet, outlet_max),
+                ] {
+                    ui.label(label);
+                    let mut offset_mm = (*offset * 1000.0) as f32;
+                    let max_mm = (max.max(0.0) * 1000.0) as f32;
+                    if ui
+                        .add(egui::Slider::new(&mut offset_mm, 0.0..=max_mm))
+                        .changed()
+                    {
+                        *offset = offset_mm as f64 / 1000.0;
+                        changed = true;
+                    }
+                }
+
+                ui.label("Angle Between Ports (°)");
+                let mut angle_deg = offsets.angle.to_degrees() as f32;
+                if ui
+                    .add(egui::Slider::new(&mut angle_deg, 0.0..=180.0))
+                    .changed()
+                {
+                    offsets.angle = (angle_deg as f64).to_radians();
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Shell ---
+            let mut has_shell = params.shell.is_some();
+            if ui.checkbox(&mut has_shell, "Shell breakout").changed() {
+                params.shell = has_shell.then_some(Shell {
+                    material: ShellMaterial::Pla,
+                    thickness: 1.2e-3,
+                });
+                changed = true;
+            }
+            if let Some(shell) = &mut params.shell {
+                egui::ComboBox::from_label("Shell Material")
+                    .selected_text(shell.material.name())
+                    .show_ui(ui, |ui| {
+                        for material in ShellMaterial::ALL {
+                            if ui
+                                .selectable_value(&mut shell.material, material, material.name())
+                                .changed()
+                            {
+                                changed = true;
+                            }
+                        }
+                    });
+
+                ui.label("Wall Thickness (mm)");
+                let mut thickness_mm = (shell.thickness * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut thickness_mm, 0.4..=5.0))
+                    .changed()
+                {
+                    shell.thickness = thickness_mm as f64 / 1000.0;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Walls ---
+            ui.label("Wall Roughness (µm)");
+            let mut roughness_um = (params.wall_roughness * 1e6) as f32;
+            if ui
+                .add(egui::Slider::new(&mut roughness_um, 0.0..=300.0))
+                .changed()
+            {
+                params.wall_roughness = roughness_um as f64 / 1e6;
+                changed = true;
+            }
+
+            let mut elastic = params.duct_wall.is_some();
+            if ui.checkbox(&mut elastic, "Elastic walls").changed() {
+                // Start from the shell's wall when one is modelled
+                params.duct_wall = elastic.then(|| {
+                    params.shell.unwrap_or(Shell {
+                        material: ShellMaterial::Pla,
+                        thickness: 1e-3,
+                    })
+                });
+                changed = true;
+            }
+            if let Some(wall) = &mut params.duct_wall {
+                egui::ComboBox::from_label("Duct Wall Material")
+                    .selected_text(wall.material.name())
+                    .show_ui(ui, |ui| {
+                        for material in ShellMaterial::ALL {
+                            if ui
+                                .selectable_value(&mut wall.material, material, material.name())
+                                .changed()
+                            {
+                                changed = true;
+                            }
+                        }
+                    });
+
+                ui.label("Duct Wall Thickness (mm)");
+                let mut thickness_mm = (wall.thickness * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut thickness_mm, 0.4..=5.0))
+                    .changed()
+                {
+                    wall.thickness = thickness_mm as f64 / 1000.0;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Pump ---
+            let drive_before = params.pump_drive;
+            let linear = PumpDrive::Linear {
+                mains_frequency: 50.0,
+                stroke: 1.0,
+            };
+            ui.horizontal(|ui| {
+                ui.label("Drive");
+                if ui
+                    .selectable_label(params.pump_drive == PumpDrive::Rotary, "Rotary")
+                    .clicked()
+                {
+                    params.pump_drive = PumpDrive::Rotary;
+                }
+                if ui
+                    .selectable_label(params.pump_drive != PumpDrive::Rotary, "Linear (mains)")
+                    .clicked()
+                    && params.pump_drive == PumpDrive::Rotary
+                {
+                    params.pump_drive = linear;
+                    // Pistons only come crank-driven
+                    params.piston = None;
+                }
+            });
+
+            match &mut params.pump_drive {
+                PumpDrive::Rotary => {
+                    ui.label("Pump RPM");
+                    let mut rpm = params.rpm as f32;
+                    if ui
+                        .add(egui::Slider::new(&mut rpm, 500.0..=10000.0))
+                        .changed()
+                    {
+                        params.rpm = rpm as f64;
+                        changed = true;
+                    }
+                }
+                PumpDrive::Linear {
+                    mains_frequency,
+                    stroke,
+                } => {
+                    ui.horizontal(|ui| {
+                        ui.label("Mains");
+                        ui.radio_value(mains_frequency, 50.0, "50 Hz");
+                        ui.radio_value(mains_frequency, 60.0, "60 Hz");
+                    });
+                    ui.label("Stroke");
+                    let mut s = *stroke as f32;
+                    if ui.add(egui::Slider::new(&mut s, 0.1..=1.5)).changed() {
+                        *stroke = s as f64;
+                    }
+                }
+            }
+            if params.pump_drive != drive_before {
                 changed = true;
             }
 
@@ -129,12 +823,532 @@ pub fn draw_controls(
                 .changed()
             {
                 params.duty_cycle = duty as f64;
+                params.valve_timing.suction_fraction =
+                    params.valve_timing.suction_fraction.min(1.0 - params.duty_cycle);
+                changed = true;
+            }
+
+            ui.label("Suction Stroke");
+            let mut suction = params.valve_timing.suction_fraction as f32;
+            let max_suction = (1.0 - params.duty_cycle) as f32;
+            if ui
+                .add(egui::Slider::new(&mut suction, 0.0..=max_suction))
+                .changed()
+            {
+                params.valve_timing.suction_fraction = suction as f64;
+                changed = true;
+            }
+
+            ui.label("Suction Level");
+            let mut suction_level = params.valve_timing.suction_level as f32;
+            if ui
+                .add(egui::Slider::new(&mut suction_level, 0.0..=1.0))
+                .changed()
+            {
+                params.valve_timing.suction_level = suction_level as f64;
+                changed = true;
+            }
+
+            ui.label("Valve Overlap Sharing");
+            let mut sharing = params.valve_timing.overlap_sharing as f32;
+            if ui
+                .add(egui::Slider::new(&mut sharing, 0.0..=1.0))
+                .changed()
+            {
+                params.valve_timing.overlap_sharing = sharing as f64;
+                changed = true;
+            }
+
+            let mut is_piston = params.piston.is_some();
+            if ui
+                .add_enabled(
+                    params.pump_drive == PumpDrive::Rotary,
+                    egui::Checkbox::new(&mut is_piston, "Piston compressor"),
+                )
+                .on_hover_text("Crank-driven pistons, one per valve, instead of diaphragm pulses")
+                .changed()
+            {
+                params.piston = is_piston.then(PistonCrank::default);
+                changed = true;
+            }
+            let (rpm, cylinders) = (params.pump_rpm(), params.num_valves);
+            if let Some(piston) = &mut params.piston {
+                ui.label("Bore (mm)");
+                let mut bore_mm = (piston.bore * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut bore_mm, 5.0..=80.0))
+                    .changed()
+                {
+                    piston.bore = bore_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Stroke (mm)");
+                let mut stroke_mm = (piston.stroke * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut stroke_mm, 2.0..=60.0))
+                    .changed()
+                {
+                    piston.stroke = stroke_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Con-rod Ratio");
+                let mut rod_ratio = piston.rod_ratio as f32;
+                if ui
+                    .add(egui::Slider::new(&mut rod_ratio, 2.0..=8.0))
+                    .changed()
+                {
+                    piston.rod_ratio = rod_ratio as f64;
+                    changed = true;
+                }
+                ui.label(format!(
+                    "Displacement {:.2} L/min",
+                    piston.mean_flow(rpm, cylinders) * 60_000.0
+                ));
+            }
+
+            let mut has_reed = params.reed_valve.is_some();
+            if ui
+                .checkbox(&mut has_reed, "Reed valves")
+                .on_hover_text("Model the flapper valves' lag, flutter and seat slap")
+                .changed()
+            {
+                params.reed_valve = has_reed.then(ReedValve::default);
                 changed = true;
             }
+            if let Some(reed) = &mut params.reed_valve {
+                ui.label("Reed Stiffness (N/m)");
+                let mut stiffness = reed.stiffness as f32;
+                if ui
+                    .add(egui::Slider::new(&mut stiffness, 20.0..=2000.0).logarithmic(true))
+                    .changed()
+                {
+                    reed.stiffness = stiffness as f64;
+                    changed = true;
+                }
+
+                ui.label("Reed Mass (mg)");
+                let mut mass_mg = (reed.mass * 1e6) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut mass_mg, 0.5..=50.0).logarithmic(true))
+                    .changed()
+                {
+                    reed.mass = mass_mg as f64 / 1e6;
+                    changed = true;
+                }
+
+                ui.label("Opening Pressure (%)");
+                let mut opening_pct = (reed.opening_pressure * 100.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut opening_pct, 0.0..=50.0))
+                    .changed()
+                {
+                    reed.opening_pressure = opening_pct as f64 / 100.0;
+                    changed = true;
+                }
+
+                ui.label("Slap");
+                let mut slap = reed.slap as f32;
+                if ui
+                    .add(egui::Slider::new(&mut slap, 0.0..=5.0))
+                    .changed()
+                {
+                    reed.slap = slap as f64;
+                    changed = true;
+                }
+                ui.label(format!("Natural frequency {:.0} Hz", reed.natural_frequency()));
+            }
+
+            // Imbalance of the first valve against the others
+            let variation = &mut params.valve_variation;
+            ui.label("Valve 1 Output");
+            let mut amplitude = variation.amplitude(0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut amplitude, 0.5..=1.5))
+                .changed()
+            {
+                variation.amplitudes = vec![amplitude as f64];
+                changed = true;
+            }
+
+            ui.label("Valve 1 Timing (% rev)");
+            let mut offset_pct = (variation.phase_offset(0) * 100.0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut offset_pct, -10.0..=10.0))
+                .changed()
+            {
+                variation.phase_offsets = vec![offset_pct as f64 / 100.0];
+                changed = true;
+            }
+
+            ui.label("Motor Jitter (% rev)");
+            let mut jitter_pct = (variation.jitter * 100.0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut jitter_pct, 0.0..=5.0))
+                .changed()
+            {
+                variation.jitter = jitter_pct as f64 / 100.0;
+                changed = true;
+            }
+
+            let mut has_wander = params.speed_wander.is_some();
+            if ui
+                .checkbox(&mut has_wander, "Speed wander")
+                .on_hover_text("Slow random hunting of the pump speed under load")
+                .changed()
+            {
+                params.speed_wander = has_wander.then(SpeedWander::default);
+                changed = true;
+            }
+            if let Some(wander) = &mut params.speed_wander {
+                ui.label("Wander Depth (% RMS)");
+                let mut depth_pct = (wander.depth * 100.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut depth_pct, 0.0..=10.0))
+                    .changed()
+                {
+                    wander.depth = depth_pct as f64 / 100.0;
+                    changed = true;
+                }
+
+                ui.label("Wander Bandwidth (Hz)");
+                let mut bandwidth = wander.bandwidth as f32;
+                if ui
+                    .add(egui::Slider::new(&mut bandwidth, 0.05..=10.0).logarithmic(true))
+                    .changed()
+                {
+                    wander.bandwidth = bandwidth as f64;
+                    changed = true;
+                }
+            }
+
+            egui::CollapsingHeader::new("Valve lift profile").show(ui, |ui| {
+                if let Some(points) = params.valve_lift.as_ref().map(|lift| lift.points.len()) {
+                    ui.horizontal(|ui| {
+                        ui.label(format!("{points} points loaded"));
+                        if ui.button("Clear").clicked() {
+                            params.valve_lift = None;
+                            changed = true;
+                        }
+                    });
+                }
+                ui.label("Open area against crank angle (angle°, area per line)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut ui_state.lift_text)
+                        .desired_rows(4)
+                        .code_editor(),
+                );
+                if ui
+                    .button("Load")
+                    .on_hover_text("Shape each valve's pressure stroke by its open area")
+                    .clicked()
+                {
+                    let loaded = ValveLift::from_table(&ui_state.lift_text)
+                        .and_then(|lift| lift.validate().map(|()| lift));
+                    match loaded {
+                        Ok(lift) => {
+                            params.valve_lift = Some(lift);
+                            ui_state.lift_status = None;
+                            changed = true;
+                        }
+                        Err(e) => ui_state.lift_status = Some(e),
+                    }
+                }
+                if let Some(status) = &ui_state.lift_status {
+                    ui.label(status);
+                }
+            });
+
+            egui::CollapsingHeader::new("Harmonic series").show(ui, |ui| {
+                let mut has_series = params.harmonic_series.is_some();
+                if ui
+                    .checkbox(&mut has_series, "Play harmonics directly")
+                    .on_hover_text("Define the pump by its order spectrum instead of the stroke model")
+                    .changed()
+                {
+                    // Start from the modelled pump's spectrum
+                    params.harmonic_series = has_series.then(|| {
+                        let mut pump = params.pump_source(SAMPLE_RATE);
+                        pump.set_series(None);
+                        HarmonicSeries::of(&pump, 8)
+                    });
+                    changed = true;
+                }
+                if let Some(series) = &mut params.harmonic_series {
+                    for (i, harmonic) in series.harmonics.iter_mut().enumerate() {
+                        ui.horizontal(|ui| {
+                            ui.label(format!("H{}", i + 1));
+                            let mut amplitude = harmonic.amplitude as f32;
+                            if ui.add(egui::Slider::new(&mut amplitude, 0.0..=1.0)).changed() {
+                                harmonic.amplitude = amplitude as f64;
+                                changed = true;
+                            }
+                            let mut phase_deg = harmonic.phase.to_degrees() as f32;
+                            if ui
+                                .add(egui::DragValue::new(&mut phase_deg).range(-180.0..=180.0).suffix("°"))
+                                .changed()
+                            {
+                                harmonic.phase = (phase_deg as f64).to_radians();
+                                changed = true;
+                            }
+                        });
+                    }
+                    ui.horizontal(|ui| {
+                        if ui.button("Add").clicked() && series.harmonics.len() < MAX_HARMONIC {
+                            series.harmonics.push(Harmonic {
+                                amplitude: 0.0,
+                                phase: 0.0,
+                            });
+                        }
+                        if ui.button("Remove").clicked() && series.harmonics.len() > 1 {
+                            series.harmonics.pop();
+                            changed = true;
+                        }
+                    });
+                }
+                ui.label("Order spectrum (harmonic, amplitude, phase° per line)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut ui_state.series_text)
+                        .desired_rows(4)
+                        .code_editor(),
+                );
+                if ui.button("Load").clicked() {
+                    let loaded = HarmonicSeries::from_table(&ui_state.series_text)
+                        .and_then(|series| series.validate().map(|()| series));
+                    match loaded {
+                        Ok(series) => {
+                            params.harmonic_series = Some(series);
+                            ui_state.series_status = None;
+                            changed = true;
+                        }
+                        Err(e) => ui_state.series_status = Some(e),
+                    }
+                }
+                if let Some(status) = &ui_state.series_status {
+                    ui.label(status);
+                }
+            });
+
+            egui::CollapsingHeader::new("Motor noise").show(ui, |ui| {
+                let mut has_motor = params.motor_noise.is_some();
+                if ui
+                    .checkbox(&mut has_motor, "Motor noise")
+                    .on_hover_text("Mains hum, PWM whine and bearing sidebands of the motor")
+                    .changed()
+                {
+                    params.motor_noise = has_motor.then(MotorNoise::default);
+                    changed = true;
+                }
+                let linear = params.pump_drive != PumpDrive::Rotary;
+                let Some(motor) = &mut params.motor_noise else {
+                    return;
+                };
+                ui.label("Mains (Hz)");
+                let mut mains = motor.mains_frequency as f32;
+                if ui
+                    .add_enabled(!linear, egui::Slider::new(&mut mains, 40.0..=70.0))
+                    .on_disabled_hover_text("Set by the linear drive")
+                    .changed()
+                {
+                    motor.mains_frequency = mains as f64;
+                    changed = true;
+                }
+
+                for (label, level, range) in [
+                    ("Hum", &mut motor.hum, 0.0..=0.2),
+                    ("PWM Whine", &mut motor.pwm_whine, 0.0..=0.05),
+                    ("Bearing Modulation", &mut motor.bearing, 0.0..=0.5),
+                ] {
+                    ui.label(label);
+                    let mut value = *level as f32;
+                    if ui.add(egui::Slider::new(&mut value, range)).changed() {
+                        *level = value as f64;
+                        changed = true;
+                    }
+                }
+
+                ui.label("PWM Frequency (kHz)");
+                let mut pwm_khz = (motor.pwm_frequency / 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut pwm_khz, 1.0..=40.0))
+                    .changed()
+                {
+                    motor.pwm_frequency = pwm_khz as f64 * 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Bearing Order");
+                let mut order = motor.bearing_order as f32;
+                if ui
+                    .add(egui::Slider::new(&mut order, 0.5..=10.0))
+                    .changed()
+                {
+                    motor.bearing_order = order as f64;
+                    changed = true;
+                }
+
+                ui.label("Bypassing the Muffler (%)");
+                let mut bypass_pct = (motor.bypass * 100.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut bypass_pct, 0.0..=100.0))
+                    .changed()
+                {
+                    motor.bypass = bypass_pct as f64 / 100.0;
+                    changed = true;
+                }
+            });
+
+            egui::CollapsingHeader::new("Second pump").show(ui, |ui| {
+                let mut has_second = params.second_pump.is_some();
+                if ui
+                    .checkbox(&mut has_second, "Second pump")
+                    .on_hover_text("Mix in a second pump of the same model, as in dual-pump aerators")
+                    .changed()
+                {
+                    params.second_pump = has_second.then(SecondPump::default);
+                    changed = true;
+                }
+                let Some(second) = &mut params.second_pump else {
+                    return;
+                };
+                ui.label("RPM");
+                if ui.add(egui::Slider::new(&mut second.rpm, 100.0..=20_000.0)).changed() {
+                    changed = true;
+                }
+                ui.label("Valves");
+                if ui.add(egui::Slider::new(&mut second.num_valves, 1..=8)).changed() {
+                    changed = true;
+                }
+                ui.label("Duty Cycle");
+                let mut duty = second.duty_cycle as f32;
+                if ui.add(egui::Slider::new(&mut duty, 0.05..=0.95)).changed() {
+                    second.duty_cycle = duty as f64;
+                    changed = true;
+                }
+                ui.label("Level (relative to the first pump)");
+                let mut level = second.level as f32;
+                if ui.add(egui::Slider::new(&mut level, 0.0..=2.0)).changed() {
+                    second.level = level as f64;
+                    changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut ui_state.second_own_muffler, "Own muffler")
+                        .on_hover_text("Keep a snapshot of the current design as the second pump's muffler");
+                    if ui_state.second_own_muffler && ui.button("Use current design").clicked() {
+                        ui_state.snapshot_second_muffler = true;
+                    }
+                });
+                if ui_state.beats.is_empty() {
+                    ui.label("No harmonics close enough to beat");
+                }
+                for beat in ui_state.beats.iter().take(5) {
+                    ui.label(format!(
+                        "H{} × H{}: {:.2} Hz beat at {:.0} Hz, {:.1} dB",
+                        beat.first_order, beat.second_order, beat.rate, beat.frequency, beat.level
+                    ));
+                }
+            });
+
+            ui.label("Pump Flow (L/min)");
+            let mut pump_flow_lpm = (params.pump_flow * 60_000.0) as f32;
+            if ui
+                .add_enabled(
+                    params.air_line.is_none() && params.pump_displacement.is_none(),
+                    egui::Slider::new(&mut pump_flow_lpm, 0.0..=60.0),
+                )
+                .on_disabled_hover_text("Set by the air line or the pump displacement")
+                .changed()
+            {
+                params.pump_flow = pump_flow_lpm as f64 / 60_000.0;
+                changed = true;
+            }
+
+            // Physical scale of the pump from its displacement
+            let mut calibrated = params.pump_displacement.is_some();
+            if ui
+                .checkbox(&mut calibrated, "Calibrate by displacement")
+                .on_hover_text("Give the pump physical units so predicted levels are absolute")
+                .changed()
+            {
+                let swept = params
+                    .piston
+                    .map_or(1e-6, |piston| piston.swept_volume() * params.num_valves as f64);
+                params.pump_displacement = calibrated.then_some(swept);
+                changed = true;
+            }
+            if let Some(displacement) = &mut params.pump_displacement {
+                ui.label("Displacement (cm³/rev)");
+                let mut displacement_cc = (*displacement * 1e6) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut displacement_cc, 0.1..=100.0).logarithmic(true))
+                    .changed()
+                {
+                    *displacement = displacement_cc as f64 / 1e6;
+                    changed = true;
+                }
+                let flow = params.displaced_flow().unwrap_or(0.0);
+                match params.pulse_pressure().filter(|_| params.source_pressure.is_none()) {
+                    Some(pressure) => ui.label(format!(
+                        "Flow {:.2} L/min, pulsation {:.0} Pa peak",
+                        flow * 60_000.0,
+                        pressure
+                    )),
+                    None => ui.label(format!("Flow {:.2} L/min", flow * 60_000.0)),
+                };
+            }
+
+            let pump = params.pump_source(SAMPLE_RATE);
+            let harmonics = pump.harmonic_levels(2);
+            let h2_db = 20.0 * (harmonics[1] / harmonics[0].max(1e-12)).max(1e-6).log10();
+            ui.label(format!(
+                "Overlap {:.0} %, H2/H1 {:.1} dB",
+                pump.valve_overlap() * 100.0,
+                h2_db
+            ));
 
             ui.separator();
 
             // --- Environment ---
+            egui::ComboBox::from_label("Gas")
+                .selected_text(params.gas.name())
+                .show_ui(ui, |ui| {
+                    let custom = Gas::Custom {
+                        molar_mass: params.gas.molar_mass(),
+                        gamma: params.gas.gamma(),
+                    };
+                    for gas in Gas::PRESETS.into_iter().chain([custom]) {
+                        let selected = params.gas.name() == gas.name();
+                        if ui.selectable_label(selected, gas.name()).clicked() && !selected {
+                            params.gas = gas;
+                            changed = true;
+                        }
+                    }
+                });
+            if let Gas::Custom { molar_mass, gamma } = &mut params.gas {
+                let mut molar_mass_g = *molar_mass * 1e3;
+                ui.horizontal(|ui| {
+                    ui.label("Molar mass (g/mol)");
+                    if ui
+                        .add(egui::DragValue::new(&mut molar_mass_g).range(1.0..=200.0).speed(0.1))
+                        .changed()
+                    {
+                        *molar_mass = molar_mass_g / 1e3;
+                        changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("γ");
+                    if ui
+                        .add(egui::DragValue::new(gamma).range(1.01..=5.0 / 3.0).speed(0.001))
+                        .changed()
+                    {
+                        changed = true;
+                    }
+                });
+            }
+
             ui.label("Temperature (°C)");
             let mut temp = params.temperature as f32;
             if ui
@@ -145,6 +1359,189 @@ pub fn draw_controls(
                 changed = true;
             }
 
+            ui.label("Relative Humidity (%)");
+            let mut humidity_pct = (params.relative_humidity * 100.0) as f32;
+            if ui
+                .add(egui::Slider::new(&mut humidity_pct, 0.0..=100.0))
+                .changed()
+            {
+                params.relative_humidity = humidity_pct as f64 / 100.0;
+                changed = true;
+            }
+
+            ui.label("Altitude (m)");
+            let mut altitude = params.altitude() as f32;
+            if ui
+                .add(egui::Slider::new(&mut altitude, 0.0..=3000.0))
+                .changed()
+            {
+                params.set_altitude(altitude as f64);
+                changed = true;
+            }
+            ui.label(format!("Static pressure {:.1} kPa", params.static_pressure / 1000.0));
+
+            ui.separator();
+
+            // --- Air line ---
+            let mut has_air_line = params.air_line.is_some();
+            if ui
+                .checkbox(&mut has_air_line, "Air line to aquarium")
+                .changed()
+            {
+                params.air_line = has_air_line.then(AirLine::default);
+                changed = true;
+            }
+            if let Some(line) = &mut params.air_line {
+                ui.label("Hose Length (m)");
+                let mut hose_len = line.hose_length as f32;
+                if ui
+                    .add(egui::Slider::new(&mut hose_len, 0.1..=5.0))
+                    .changed()
+                {
+                    line.hose_length = hose_len as f64;
+                    changed = true;
+                }
+
+                ui.label("Hose Diameter (mm)");
+                let mut hose_diam_mm = (line.hose_diameter * 1000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut hose_diam_mm, 2.0..=10.0))
+                    .changed()
+                {
+                    line.hose_diameter = hose_diam_mm as f64 / 1000.0;
+                    changed = true;
+                }
+
+                ui.label("Stone Depth (cm)");
+                let mut depth_cm = (line.stone.depth * 100.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut depth_cm, 0.0..=100.0))
+                    .changed()
+                {
+                    line.stone.depth = depth_cm as f64 / 100.0;
+                    changed = true;
+                }
+
+                ui.label("Air Flow (L/min)");
+                let mut flow_lpm = (line.stone.flow_rate * 60_000.0) as f32;
+                if ui
+                    .add(egui::Slider::new(&mut flow_lpm, 0.2..=10.0))
+                    .changed()
+                {
+                    line.stone.flow_rate = flow_lpm as f64 / 60_000.0;
+                    changed = true;
+                }
+            }
+
+            ui.separator();
+
+            // --- Numerics ---
+            egui::CollapsingHeader::new("Numerics").show(ui, |ui| {
+                let numerics = &mut params.numerics;
+                let before = numerics.clone();
+
+                egui::ComboBox::from_label("FFT size")
+                    .selected_text(numerics.fft_size.to_string())
+                    .show_ui(ui, |ui| {
+                        let (min, max) = numerics.fft_size_range();
+                        let sizes = std::iter::successors(Some(min), |&n| (n < max).then_some(n * 2));
+                        for size in sizes {
+                            ui.selectable_value(&mut numerics.fft_size, size, size.to_string());
+                        }
+                    });
+                ui.label(format!(
+                    "Resolution: {:.2} Hz",
+                    numerics.bin_width(SAMPLE_RATE)
+                ));
+
+                egui::ComboBox::from_label("IR window")
+                    .selected_text(numerics.ir_window.name())
+                    .show_ui(ui, |ui| {
+                        for window in IrWindow::ALL {
+                            if ui.selectable_label(numerics.ir_window.name() == window.name(), window.name()).clicked() {
+                                numerics.ir_window = window;
+                            }
+                        }
+                    });
+                match &mut numerics.ir_window {
+                    IrWindow::Tukey { taper } => {
+                        ui.label("Taper Fraction");
+                        ui.add(egui::Slider::new(taper, 0.0..=1.0));
+                    }
+                    IrWindow::Exponential { end_db } => {
+                        ui.label("Decay at End (dB)");
+                        ui.add(egui::Slider::new(end_db, 6.0..=120.0));
+                    }
+                    IrWindow::Rectangular | IrWindow::Hann => {}
+                }
+
+                // A longer IR keeps the tails of lightly damped chambers
+                let mut custom_length = numerics.ir_length.is_some();
+                if ui.checkbox(&mut custom_length, "Custom IR length").changed() {
+                    numerics.ir_length = custom_length.then_some(numerics.fft_size);
+                }
+                if let Some(length) = &mut numerics.ir_length {
+                    *length = (*length).min(numerics.fft_size);
+                    ui.label("IR Length (samples)");
+                    ui.add(egui::Slider::new(length, 64..=numerics.fft_size).logarithmic(true));
+                }
+
+                egui::ComboBox::from_label("Wall losses")
+                    .selected_text(format!("{:?}", numerics.wall_losses))
+                    .show_ui(ui, |ui| {
+                        for model in [
+                            WallLossModel::Lossless,
+                            WallLossModel::Viscothermal,
+                            WallLossModel::LowReducedFrequency,
+                        ] {
+                            ui.selectable_value(&mut numerics.wall_losses, model, format!("{model:?}"));
+                        }
+                    });
+
+                ui.checkbox(&mut numerics.end_corrections, "End corrections");
+
+                egui::ComboBox::from_label("Air line load")
+                    .selected_text(numerics.termination.name())
+                    .show_ui(ui, |ui| {
+                        for termination in Termination::AIR_LINE {
+                            let name = termination.name();
+                            ui.selectable_value(&mut numerics.termination, termination, name);
+                        }
+                    });
+
+                egui::ComboBox::from_label("Engine")
+                    .selected_text(format!("{:?}", numerics.engine))
+                    .show_ui(ui, |ui| {
+                        for engine in [Engine::FrequencyDomain, Engine::TimeDomain] {
+                            ui.selectable_value(&mut numerics.engine, engine, format!("{engine:?}"));
+                        }
+                    });
+                // The time-domain march only runs up to a smaller FFT size
+                numerics.fft_size = numerics.fft_size.min(numerics.fft_size_range().1);
+
+                let mut log_sweep = numerics.log_sweep.is_some();
+                if ui.checkbox(&mut log_sweep, "Log-spaced TL sweep").changed() {
+                    numerics.log_sweep = log_sweep.then(LogSweep::default);
+                }
+                if let Some(log) = &mut numerics.log_sweep {
+                    ui.label("Points per Octave");
+                    ui.add(egui::Slider::new(&mut log.points_per_octave, 3..=96));
+                }
+
+                let mut refine = numerics.refinement.is_some();
+                if ui.checkbox(&mut refine, "Refine around sharp features").changed() {
+                    numerics.refinement = refine.then(Refinement::default);
+                }
+                if let Some(refinement) = &mut numerics.refinement {
+                    ui.label("Max TL Step (dB)");
+                    ui.add(egui::Slider::new(&mut refinement.max_step_db, 0.1..=6.0));
+                }
+
+                if *numerics != before {
+                    changed = true;
+                }
+            });
+
             ui.separator();
 
             // --- Audio ---
@@ -161,6 +1558,301 @@ pub fn draw_controls(
 
             ui.label("Volume");
             ui.add(egui::Slider::new(&mut ui_state.volume, 0.0..=1.0));
+            let levels = ui_state.output_levels;
+            // Meter from -60 dBFS to full scale
+            let fraction = |db: f64| ((db + 60.0) / 60.0).clamp(0.0, 1.0) as f32;
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::ProgressBar::new(fraction(levels.rms_db()))
+                        .desired_width(140.0)
+                        .text(format!("{:.0} dBFS", levels.rms_db().max(-99.0))),
+                )
+                .on_hover_text(format!("RMS level, peak {:.1} dBFS", levels.peak_db().max(-99.0)));
+                let clip = egui::RichText::new("CLIP").strong();
+                let clip = if levels.clipped {
+                    clip.color(egui::Color32::RED)
+                } else {
+                    clip.weak()
+                };
+                if ui
+                    .add(egui::Label::new(clip).sense(egui::Sense::click()))
+                    .on_hover_text("Lights when the output would have clipped without the limiter; click to clear")
+                    .clicked()
+                {
+                    ui_state.reset_clip = true;
+                }
+            });
+            if levels.gain_reduction > 0.05 {
+                ui.label(format!("Limiting {:.1} dB", -levels.gain_reduction))
+                    .on_hover_text("The muffler's gain would clip at this volume; the limiter is turning it down");
+            }
+
+            ui.label("Dry / Wet");
+            ui.add(egui::Slider::new(&mut ui_state.dry_wet, 0.0..=1.0))
+                .on_hover_text("Blend in the unmuffled pump, matched to the muffled level");
+            ui.toggle_value(&mut ui_state.bypass_muffler, "Bypass muffler")
+                .on_hover_text("Hear the unmuffled pump at the muffled level until released");
+
+            ui.label("Speed glide (s)");
+            ui.add(egui::Slider::new(&mut ui_state.glide_time, 0.0..=2.0))
+                .on_hover_text("How smoothly playback follows changes to the RPM and duty cycle");
+
+            egui::CollapsingHeader::new("Playback health").show(ui, |ui| {
+                let stats = &ui_state.pipeline_stats;
+                ui.label(format!(
+                    "Underruns: {} of {} callbacks ({} frames of silence)",
+                    stats.underruns, stats.callbacks, stats.missing_samples
+                ))
+                .on_hover_text("Callbacks that found the buffer short and played silence");
+                ui.label(format!(
+                    "Feeder load: {:.0}% average, {:.0}% worst block",
+                    100.0 * stats.feeder_load(),
+                    100.0 * stats.peak_feeder_load()
+                ))
+                .on_hover_text("Time spent per block against its playing time; above 100% the feeder falls behind");
+                ui.label(format!("Buffered: {} of {} frames", stats.buffered, stats.buffer_target))
+                .on_hover_text("Underruns with a light feeder load mean the buffer is too small");
+                ui.label(format!(
+                    "Callback: {:.1} ms of audio, slowest took {:.2} ms",
+                    1e3 * stats.callback_duration,
+                    1e3 * stats.callback_time_max
+                ));
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut ui_state.ir_export_path).desired_width(140.0));
+                if ui
+                    .button("Export IR")
+                    .on_hover_text("Write the impulse response to a 32-bit float WAV file, with its provenance in the comment")
+                    .clicked()
+                {
+                    ui_state.export_ir = true;
+                }
+            });
+            if let Some(status) = &ui_state.ir_export_status {
+                ui.label(status);
+            }
+
+            ui.checkbox(&mut ui_state.match_loudness, "Match loudness between designs");
+            if ui_state.match_loudness {
+                egui::ComboBox::from_label("Level metric")
+                    .selected_text(ui_state.loudness_metric.name())
+                    .show_ui(ui, |ui| {
+                        for metric in LoudnessMetric::ALL {
+                            ui.selectable_value(&mut ui_state.loudness_metric, metric, metric.name());
+                        }
+                    });
+                ui.horizontal(|ui| {
+                    match ui_state.gain_offset_db {
+                        Some(offset) => ui.label(format!("Gain offset {offset:+.1} dB")),
+                        None => ui.label("Measuring…"),
+                    };
+                    if ui_state.gain_offset_limited {
+                        ui.label(egui::RichText::new("at limit").color(egui::Color32::YELLOW))
+                            .on_hover_text("The designs differ by more than this; levels are not matched");
+                    }
+                    if ui.button("Use as reference").clicked() {
+                        ui_state.reset_level_reference = true;
+                    }
+                });
+            }
+
+            let sources = [
+                None,
+                Some(TestSignal::Sine { frequency: 1000.0 }),
+                Some(TestSignal::LogSweep {
+                    start: 20.0,
+                    end: 20_000.0,
+                    duration: 2.0,
+                }),
+                Some(TestSignal::WhiteNoise),
+                Some(TestSignal::PinkNoise),
+                Some(TestSignal::Mls { order: 16 }),
+            ];
+            let source_name = |source: &Option<TestSignal>| match source {
+                None => "Pump",
+                Some(TestSignal::Sine { .. }) => "Sine",
+                Some(TestSignal::LogSweep { .. }) => "Log sweep",
+                Some(TestSignal::WhiteNoise) => "White noise",
+                Some(TestSignal::PinkNoise) => "Pink noise",
+                Some(TestSignal::Mls { .. }) => "MLS",
+            };
+            egui::ComboBox::from_label("Source")
+                .selected_text(source_name(&ui_state.test_signal))
+                .show_ui(ui, |ui| {
+                    for source in sources {
+                        let selected = source_name(&source) == source_name(&ui_state.test_signal);
+                        if ui.selectable_label(selected, source_name(&source)).clicked() && !selected {
+                            ui_state.test_signal = source;
+                        }
+                    }
+                });
+            match &mut ui_state.test_signal {
+                Some(TestSignal::Sine { frequency }) => {
+                    ui.label("Frequency (Hz)");
+                    ui.add(egui::Slider::new(frequency, 20.0..=20_000.0).logarithmic(true));
+                }
+                Some(TestSignal::LogSweep { duration, .. }) => {
+                    ui.label("Sweep duration (s)");
+                    ui.add(egui::Slider::new(duration, 0.5..=10.0));
+                }
+                _ => {}
+            }
+
+            egui::CollapsingHeader::new("RPM sweep").show(ui, |ui| {
+                ui.checkbox(&mut ui_state.rpm_sweep, "Sweep the pump speed")
+                    .on_hover_text("Spin the pump up and down during playback to hear resonances pass");
+                ui.label("Speed range (RPM)");
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut ui_state.sweep_low_rpm).range(100.0..=20_000.0));
+                    ui.label("to");
+                    ui.add(egui::DragValue::new(&mut ui_state.sweep_high_rpm).range(100.0..=20_000.0));
+                });
+                ui.label("Spin-up / spin-down (s)");
+                ui.add(egui::Slider::new(&mut ui_state.sweep_ramp, 0.5..=30.0));
+                ui.label("Hold at top speed (s)");
+                ui.add(egui::Slider::new(&mut ui_state.sweep_hold, 0.0..=30.0));
+                ui.checkbox(&mut ui_state.sweep_loop, "Repeat");
+                if let Some(rpm) = ui_state.sweep_rpm {
+                    ui.label(format!("Now at {rpm:.0} RPM"));
+                }
+            });
+
+            egui::CollapsingHeader::new("Pump recording").show(ui, |ui| {
+                ui.label("WAV of a real pump, looped in place of the model");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut ui_state.recording_path).desired_width(120.0));
+                    if ui.button("Load").clicked() {
+                        ui_state.load_recording = true;
+                    }
+                    if ui.button("Clear").clicked() {
+                        ui_state.clear_recording = true;
+                    }
+                });
+                if let Some(status) = &ui_state.recording_status {
+                    ui.label(status);
+                }
+                ui.checkbox(&mut ui_state.recording_follows_rpm, "Pitch follows RPM");
+                if ui_state.recording_follows_rpm {
+                    ui.label("Recorded at (RPM)");
+                    ui.add(egui::DragValue::new(&mut ui_state.recording_rpm).range(100.0..=20_000.0));
+                }
+            });
+
+            egui::CollapsingHeader::new("Live input").show(ui, |ui| {
+                ui.checkbox(&mut ui_state.live_input, "Play the input device through the muffler")
+                    .on_hover_text("A microphone by a real pump, or a loopback of a recording, in place of the model");
+                if let Some(status) = &ui_state.live_input_status {
+                    ui.label(status);
+                }
+            });
+
+            egui::CollapsingHeader::new("Room / headphone IR").show(ui, |ui| {
+                ui.label("WAV impulse response heard after the muffler, e.g. across a room");
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut ui_state.room_ir_path).desired_width(120.0));
+                    if ui.button("Load").clicked() {
+                        ui_state.load_room_ir = true;
+                    }
+                    if ui.button("Clear").clicked() {
+                        ui_state.clear_room_ir = true;
+                    }
+                });
+                if let Some(status) = &ui_state.room_ir_status {
+                    ui.label(status);
+                }
+            });
+
+            ui.checkbox(&mut ui_state.mute_band, "Mute band")
+                .on_hover_text("Notch a tone out of playback; click the TL plot to move it");
+            if ui_state.mute_band {
+                ui.horizontal(|ui| {
+                    ui.label("Harmonic");
+                    ui.add(egui::DragValue::new(&mut ui_state.mute_harmonic).range(1..=40));
+                    if ui.button("Select").clicked() {
+                        let fundamental = params.pump_source(44100.0).fundamental_frequency();
+                        ui_state.notch.frequency = ui_state.mute_harmonic as f64 * fundamental;
+                    }
+                });
+                ui.label("Notch frequency (Hz)");
+                ui.add(egui::Slider::new(&mut ui_state.notch.frequency, 20.0..=20_000.0).logarithmic(true));
+                ui.label("Notch bandwidth (Hz)");
+                ui.add(egui::Slider::new(&mut ui_state.notch.bandwidth, 5.0..=2000.0).logarithmic(true));
+            }
+
+            ui.checkbox(&mut ui_state.spatialize, "Position the outlet")
+                .on_hover_text("Place the outlet around you on headphones instead of inside your head");
+            if ui_state.spatialize {
+                ui.label("Azimuth (deg, right positive)");
+                ui.add(egui::Slider::new(&mut ui_state.position.azimuth, -180.0..=180.0));
+                ui.label("Distance (m)");
+                ui.add(egui::Slider::new(&mut ui_state.position.distance, 0.25..=10.0).logarithmic(true))
+                    .on_hover_text("Level falls 6 dB per doubling from its unpositioned level at 1 m");
+            }
+
+            ui.separator();
+
+            // --- View ---
+            ui.checkbox(&mut ui_state.show_schematic, "Show equivalent circuit");
+            ui.checkbox(&mut ui_state.show_pump_tones, "Show muffled pump harmonics")
+                .on_hover_text("Source level of each pump harmonic less the TL, relative to the unmuffled fundamental");
+
+            let mut show_wave = ui_state.standing_wave.is_some();
+            if ui.checkbox(&mut show_wave, "Show standing wave").changed() {
+                ui_state.standing_wave = show_wave.then_some(1000.0);
+            }
+            if let Some(frequency) = &mut ui_state.standing_wave {
+                ui.label("Standing Wave Frequency (Hz)");
+                ui.add(egui::Slider::new(frequency, 20.0..=20_000.0).logarithmic(true));
+            }
+
+            egui::CollapsingHeader::new("Validation").show(ui, |ui| {
+                ui.label("Measure the IR with a virtual log sweep and compare");
+                if ui.button("Run sweep").clicked() {
+                    ui_state.run_validation = true;
+                }
+                if let Some(error_db) = ui_state.validation_error_db {
+                    ui.label(format!("Sweep vs analytic IR: {error_db:.1} dB"));
+                }
+            });
+
+            egui::CollapsingHeader::new("Calibration").show(ui, |ui| {
+                ui.label("Measured TL of this design (frequency, dB per line)");
+                ui.add(
+                    egui::TextEdit::multiline(&mut ui_state.measurement_text)
+                        .desired_rows(4)
+                        .code_editor(),
+                );
+                ui.horizontal(|ui| {
+                    if ui.button("Fit").clicked() {
+                        let fitted = calibration::parse_tl_table(&ui_state.measurement_text)
+                            .and_then(|points| calibration::fit(params, &points));
+                        ui_state.calibration_status = Some(match fitted {
+                            Ok(fit) => {
+                                params.calibration = fit.calibration;
+                                changed = true;
+                                format!("RMS error {:.2} → {:.2} dB", fit.rms_before, fit.rms_after)
+                            }
+                            Err(e) => e,
+                        });
+                    }
+                    if ui.button("Reset").clicked() {
+                        params.calibration = Calibration::default();
+                        ui_state.calibration_status = None;
+                        changed = true;
+                    }
+                });
+                if let Some(status) = &ui_state.calibration_status {
+                    ui.label(status);
+                }
+                let cal = &params.calibration;
+                ui.label(format!(
+                    "End correction +{:.2} mm, loss factor {:.3}, length ×{:.3}",
+                    cal.end_correction * 1e3,
+                    cal.loss_factor,
+                    cal.length_scale
+                ));
+            });
         });
 
     changed